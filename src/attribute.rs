@@ -8,8 +8,10 @@
 
 use std::fmt;
 
+use writer;
 use {
     AttributeId,
+    AttributeType,
     AttributeQName,
     AttributeQNameRef,
     AttributeValue,
@@ -19,13 +21,36 @@ use {
 };
 
 
+/// A result of the [`Attribute::check_keyword`] method.
+///
+/// [`Attribute::check_keyword`]: struct.Attribute.html#method.check_keyword
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum KeywordCheck {
+    /// The value is an allowed keyword or `inherit`.
+    Valid,
+    /// The value is a string, which is not an allowed keyword for this attribute.
+    Invalid,
+    /// The attribute doesn't have a fixed set of keywords or the value is not a string.
+    NotKeyword,
+}
+
 /// Representation of the SVG attribute object.
-#[derive(Clone, PartialEq, Debug)]
+///
+/// The original source string, if any, is ignored during comparison.
+#[derive(Clone, Debug)]
 pub struct Attribute {
     /// Attribute name.
     pub name: AttributeQName,
     /// Attribute value.
     pub value: AttributeValue,
+    /// The source string and the value it was parsed into.
+    original: Option<(String, AttributeValue)>,
+}
+
+impl PartialEq for Attribute {
+    fn eq(&self, other: &Attribute) -> bool {
+        self.name == other.name && self.value == other.value
+    }
 }
 
 // TODO: fix docs
@@ -46,6 +71,7 @@ impl Attribute {
         Attribute {
             name: AttributeQNameRef::from(name).into(),
             value: AttributeValue::from(value),
+            original: None,
         }
     }
 
@@ -78,6 +104,84 @@ impl Attribute {
         }
     }
 
+    /// Checks that the attribute value is an allowed keyword.
+    ///
+    /// Useful for detecting typos, since an unknown keyword is stored as a string.
+    ///
+    /// See [`AttributeType::keywords`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Attribute, AttributeId as AId, AttributeValue, KeywordCheck};
+    ///
+    /// assert_eq!(Attribute::new(AId::FillRule, "evenodd").check_keyword(), KeywordCheck::Valid);
+    /// assert_eq!(Attribute::new(AId::FillRule, "even-odd").check_keyword(), KeywordCheck::Invalid);
+    /// assert_eq!(Attribute::new(AId::FillRule, AttributeValue::Inherit).check_keyword(),
+    ///            KeywordCheck::Valid);
+    /// assert_eq!(Attribute::new(AId::Class, "evenodd").check_keyword(), KeywordCheck::NotKeyword);
+    /// ```
+    ///
+    /// [`AttributeType::keywords`]: trait.AttributeType.html#tymethod.keywords
+    pub fn check_keyword(&self) -> KeywordCheck {
+        let list = match self.keywords() {
+            Some(list) => list,
+            None => return KeywordCheck::NotKeyword,
+        };
+
+        match self.value {
+            AttributeValue::Inherit => KeywordCheck::Valid,
+            AttributeValue::None if list.contains(&"none") => KeywordCheck::Valid,
+            AttributeValue::String(ref s) if list.contains(&s.as_str()) => KeywordCheck::Valid,
+            AttributeValue::String(_) => KeywordCheck::Invalid,
+            _ => KeywordCheck::NotKeyword,
+        }
+    }
+
+    /// Returns the source string of the attribute value.
+    ///
+    /// The source string is stored only when a document was parsed with
+    /// [`ParseOptions::preserve_source`] enabled and will be written back as is
+    /// instead of the value.
+    ///
+    /// Returns `None` when the source string is not stored or is stale,
+    /// i.e. the value was modified after parsing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ParseOptions, AttributeId as AId, Color};
+    ///
+    /// let mut opt = ParseOptions::default();
+    /// opt.preserve_source = true;
+    ///
+    /// let doc = Document::from_str_with_opt(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' fill='rgb(255, 0, 0)'/>", &opt).unwrap();
+    ///
+    /// let mut svg = doc.svg_element().unwrap();
+    /// assert_eq!(svg.attributes().get(AId::Fill).unwrap().original(), Some("rgb(255, 0, 0)"));
+    /// assert_eq!(doc.to_string(),
+    ///            "<svg xmlns=\"http://www.w3.org/2000/svg\" fill=\"rgb(255, 0, 0)\"/>\n");
+    ///
+    /// svg.set_attribute((AId::Fill, Color::new(0, 0, 255)));
+    /// assert_eq!(svg.attributes().get(AId::Fill).unwrap().original(), None);
+    /// assert_eq!(doc.to_string(),
+    ///            "<svg xmlns=\"http://www.w3.org/2000/svg\" fill=\"#0000ff\"/>\n");
+    /// ```
+    ///
+    /// [`ParseOptions::preserve_source`]: struct.ParseOptions.html#structfield.preserve_source
+    pub fn original(&self) -> Option<&str> {
+        match self.original {
+            Some((ref text, ref value)) if *value == self.value => Some(text.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Stores the source string of the current value.
+    pub(crate) fn set_original(&mut self, text: &str) {
+        self.original = Some((text.to_string(), self.value.clone()));
+    }
+
     impl_is_type!(is_none);
     impl_is_type!(is_inherit);
     impl_is_type!(is_current_color);
@@ -104,7 +208,16 @@ impl WriteBuffer for Attribute {
         buf.push(b'=');
         write_quote(opt, buf);
 
-        if self.has_id(AttributeId::Unicode) {
+        let formatter = match self.name {
+            QName::Name(ref name) => opt.attribute_formatters.get(name),
+            QName::Id(_) => None,
+        };
+
+        if let Some(f) = formatter {
+            AttributeValue::write_string(&f(&self.value), opt, buf);
+        } else if let Some(text) = self.original() {
+            AttributeValue::write_string(text, opt, buf);
+        } else if self.has_id(AttributeId::Unicode) {
             if let AttributeValue::String(ref s) = self.value {
                 write_escaped(s, buf);
             } else {
@@ -123,15 +236,11 @@ fn write_quote(opt: &WriteOptions, out: &mut Vec<u8>) {
 }
 
 fn write_escaped(unicode: &str, out: &mut Vec<u8>) {
-    use std::io::Write;
-
     if unicode.starts_with("&#") {
         out.extend_from_slice(unicode.as_bytes());
     } else {
         for c in unicode.chars() {
-            out.extend_from_slice(b"&#x");
-            write!(out, "{:x}", c as u32).unwrap();
-            out.push(b';');
+            writer::write_char_ref(c, out);
         }
     }
 }
@@ -61,6 +61,16 @@ pub trait AttributeType {
     ///
     /// This check is not defined by the SVG spec.
     fn is_stroke(&self) -> bool;
+
+    /// Returns a list of keywords allowed as the attribute value.
+    ///
+    /// Only attributes which value is a keyword from a fixed set are supported.
+    /// `inherit` is not included, since it's allowed for all presentation attributes.
+    ///
+    /// Returns `None` by default.
+    fn keywords(&self) -> Option<&'static [&'static str]> {
+        None
+    }
 }
 
 macro_rules! is_func {
@@ -85,6 +95,13 @@ impl AttributeType for Attribute {
     is_func!(is_core);
     is_func!(is_fill);
     is_func!(is_stroke);
+
+    fn keywords(&self) -> Option<&'static [&'static str]> {
+        match self.id() {
+            Some(id) => id.keywords(),
+            None => None,
+        }
+    }
 }
 
 impl AttributeType for AttributeId {
@@ -241,6 +258,66 @@ impl AttributeType for AttributeId {
             _ => false,
         }
     }
+
+    fn keywords(&self) -> Option<&'static [&'static str]> {
+        static RULE: &[&str] = &["nonzero", "evenodd"];
+        static COLOR_INTERPOLATION: &[&str] = &["auto", "sRGB", "linearRGB"];
+        static RENDERING: &[&str] = &["auto", "optimizeSpeed", "optimizeQuality"];
+
+        let list: &'static [&'static str] = match *self {
+            AttributeId::AlignmentBaseline => &[
+                "auto", "baseline", "before-edge", "text-before-edge", "middle", "central",
+                "after-edge", "text-after-edge", "ideographic", "alphabetic", "hanging",
+                "mathematical",
+            ],
+            AttributeId::ClipRule | AttributeId::FillRule => RULE,
+            AttributeId::ColorInterpolation
+            | AttributeId::ColorInterpolationFilters => COLOR_INTERPOLATION,
+            AttributeId::ColorRendering | AttributeId::ImageRendering => RENDERING,
+            AttributeId::Direction => &["ltr", "rtl"],
+            AttributeId::Display => &[
+                "inline", "block", "list-item", "run-in", "compact", "marker", "table",
+                "inline-table", "table-row-group", "table-header-group", "table-footer-group",
+                "table-row", "table-column-group", "table-column", "table-cell", "table-caption",
+                "none",
+            ],
+            AttributeId::DominantBaseline => &[
+                "auto", "use-script", "no-change", "reset-size", "ideographic", "alphabetic",
+                "hanging", "mathematical", "central", "middle", "text-after-edge",
+                "text-before-edge",
+            ],
+            AttributeId::FontStretch => &[
+                "normal", "wider", "narrower", "ultra-condensed", "extra-condensed", "condensed",
+                "semi-condensed", "semi-expanded", "expanded", "extra-expanded", "ultra-expanded",
+            ],
+            AttributeId::FontStyle => &["normal", "italic", "oblique"],
+            AttributeId::FontVariant => &["normal", "small-caps"],
+            AttributeId::FontWeight => &[
+                "normal", "bold", "bolder", "lighter",
+                "100", "200", "300", "400", "500", "600", "700", "800", "900",
+            ],
+            AttributeId::Overflow => &["visible", "hidden", "scroll", "auto"],
+            AttributeId::PointerEvents => &[
+                "visiblePainted", "visibleFill", "visibleStroke", "visible",
+                "painted", "fill", "stroke", "all", "none",
+            ],
+            AttributeId::ShapeRendering => &[
+                "auto", "optimizeSpeed", "crispEdges", "geometricPrecision",
+            ],
+            AttributeId::StrokeLinecap => &["butt", "round", "square"],
+            AttributeId::StrokeLinejoin => &["miter", "round", "bevel"],
+            AttributeId::TextAnchor => &["start", "middle", "end"],
+            AttributeId::TextRendering => &[
+                "auto", "optimizeSpeed", "optimizeLegibility", "geometricPrecision",
+            ],
+            AttributeId::UnicodeBidi => &["normal", "embed", "bidi-override"],
+            AttributeId::Visibility => &["visible", "hidden", "collapse"],
+            AttributeId::WritingMode => &["lr-tb", "rl-tb", "tb-rl", "lr", "rl", "tb"],
+            _ => return None,
+        };
+
+        Some(list)
+    }
 }
 
 // NOTE: `visibility` is marked as inheritable here: https://www.w3.org/TR/SVG/propidx.html,
@@ -17,16 +17,23 @@ use {
     LengthList,
     LengthUnit,
     Node,
+    NumberFormat,
     NumberList,
     PaintFallback,
     Path,
     Points,
     Transform,
+    TransformList,
     ValueWriteBuffer,
     ViewBox,
     WriteBuffer,
     WriteOptions,
 };
+use transform_ext;
+use writer::{
+    self,
+    round_value,
+};
 
 // TODO: custom debug
 
@@ -114,35 +121,46 @@ impl From<PaintFallback> for AttributeValue {
     }
 }
 
-// TODO: fix docs
-macro_rules! impl_is_type {
-    ($name:ident, $t:ident) => (
-        #[allow(missing_docs)]
-        pub fn $name(&self) -> bool {
-            match *self {
-                AttributeValue::$t(..) => true,
-                _ => false,
-            }
-        }
-    )
+/// A kind of the [`AttributeValue`].
+///
+/// [`AttributeValue`]: enum.AttributeValue.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[allow(missing_docs)]
+pub enum AttributeValueKind {
+    None,
+    Inherit,
+    CurrentColor,
+    AspectRatio,
+    Color,
+    FuncLink,
+    Paint,
+    Length,
+    LengthList,
+    Angle,
+    Link,
+    Number,
+    NumberList,
+    Path,
+    Points,
+    Transform,
+    ViewBox,
+    String,
 }
 
-macro_rules! impl_is_type_without_value {
+// TODO: fix docs
+macro_rules! impl_is_type {
     ($name:ident, $t:ident) => (
         #[allow(missing_docs)]
         pub fn $name(&self) -> bool {
-            match *self {
-                AttributeValue::$t => true,
-                _ => false,
-            }
+            self.kind() == AttributeValueKind::$t
         }
     )
 }
 
 impl AttributeValue {
-    impl_is_type_without_value!(is_none, None);
-    impl_is_type_without_value!(is_inherit, Inherit);
-    impl_is_type_without_value!(is_current_color, CurrentColor);
+    impl_is_type!(is_none, None);
+    impl_is_type!(is_inherit, Inherit);
+    impl_is_type!(is_current_color, CurrentColor);
     impl_is_type!(is_aspect_ratio, AspectRatio);
     impl_is_type!(is_color, Color);
     impl_is_type!(is_length, Length);
@@ -159,6 +177,39 @@ impl AttributeValue {
     impl_is_type!(is_transform, Transform);
     impl_is_type!(is_viewbox, ViewBox);
 
+    /// Returns the value kind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{AttributeValue, AttributeValueKind};
+    ///
+    /// assert_eq!(AttributeValue::from(1.0).kind(), AttributeValueKind::Number);
+    /// assert_eq!(AttributeValue::None.kind(), AttributeValueKind::None);
+    /// ```
+    pub fn kind(&self) -> AttributeValueKind {
+        match *self {
+            AttributeValue::None => AttributeValueKind::None,
+            AttributeValue::Inherit => AttributeValueKind::Inherit,
+            AttributeValue::CurrentColor => AttributeValueKind::CurrentColor,
+            AttributeValue::AspectRatio(_) => AttributeValueKind::AspectRatio,
+            AttributeValue::Color(_) => AttributeValueKind::Color,
+            AttributeValue::FuncLink(_) => AttributeValueKind::FuncLink,
+            AttributeValue::Paint(_, _) => AttributeValueKind::Paint,
+            AttributeValue::Length(_) => AttributeValueKind::Length,
+            AttributeValue::LengthList(_) => AttributeValueKind::LengthList,
+            AttributeValue::Angle(_) => AttributeValueKind::Angle,
+            AttributeValue::Link(_) => AttributeValueKind::Link,
+            AttributeValue::Number(_) => AttributeValueKind::Number,
+            AttributeValue::NumberList(_) => AttributeValueKind::NumberList,
+            AttributeValue::Path(_) => AttributeValueKind::Path,
+            AttributeValue::Points(_) => AttributeValueKind::Points,
+            AttributeValue::Transform(_) => AttributeValueKind::Transform,
+            AttributeValue::ViewBox(_) => AttributeValueKind::ViewBox,
+            AttributeValue::String(_) => AttributeValueKind::String,
+        }
+    }
+
     /// Checks that the current attribute value contains a `Node`.
     ///
     /// E.g. `Link`, `FuncLink` and `Paint`.
@@ -171,6 +222,50 @@ impl AttributeValue {
         }
     }
 
+    /// Returns an approximate size of the value in bytes, after serialization
+    /// with the specified options.
+    ///
+    /// Keywords, strings and links are calculated directly, without serialization.
+    /// Numeric values are still written into a temporary buffer,
+    /// since their size depends on the number formatting.
+    ///
+    /// Useful to check that a transformation will actually reduce the file size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{AttributeValue, WriteOptions};
+    ///
+    /// let value = AttributeValue::from("text");
+    /// assert_eq!(value.approx_byte_size(&WriteOptions::default()), 4);
+    /// ```
+    pub fn approx_byte_size(&self, opt: &WriteOptions) -> usize {
+        fn link_size(node: &Node) -> usize {
+            // `url(#` + `)`
+            node.id().len() + 6
+        }
+
+        match *self {
+            AttributeValue::None => 4,
+            AttributeValue::Inherit => 7,
+            AttributeValue::CurrentColor => 12,
+            AttributeValue::String(ref s) => {
+                let quote = if opt.use_single_quote { b'\'' } else { b'"' };
+                // `&quot;` and `&apos;` are 6 bytes long.
+                let quotes = s.as_bytes().iter().filter(|c| **c == quote).count();
+                s.len() + quotes * 5
+            }
+            AttributeValue::Link(ref n) => n.id().len() + 1,
+            AttributeValue::FuncLink(ref n) => link_size(n),
+            AttributeValue::Paint(ref n, None) => link_size(n),
+            _ => {
+                let mut buf = Vec::new();
+                self.write_buf_opt(opt, &mut buf);
+                buf.len()
+            }
+        }
+    }
+
     /// Constructs a new attribute value with a default value, if it's known.
     pub fn default_value(id: AttributeId) -> Option<AttributeValue> {
         macro_rules! some {
@@ -247,6 +342,30 @@ impl AttributeValue {
 
 impl WriteBuffer for AttributeValue {
     fn write_buf_opt(&self, opt: &WriteOptions, buf: &mut Vec<u8>) {
+        if let NumberFormat::Fixed(precision) = opt.number_format {
+            if let Some(v) = round_value(self, precision) {
+                v.write_value(opt, buf);
+                return;
+            }
+        }
+
+        self.write_value(opt, buf);
+    }
+}
+
+impl AttributeValue {
+    /// Writes a string value, escaping quotes.
+    pub(crate) fn write_string(s: &str, opt: &WriteOptions, buf: &mut Vec<u8>) {
+        for c in s.chars() {
+            match c {
+                '"' if !opt.use_single_quote => buf.extend_from_slice(b"&quot;"),
+                '\'' if opt.use_single_quote => buf.extend_from_slice(b"&apos;"),
+                _ => writer::write_char(c, opt, buf),
+            }
+        }
+    }
+
+    fn write_value(&self, opt: &WriteOptions, buf: &mut Vec<u8>) {
         match *self {
             AttributeValue::None => {
                 buf.extend_from_slice(b"none");
@@ -258,13 +377,7 @@ impl WriteBuffer for AttributeValue {
                 buf.extend_from_slice(b"currentColor");
             }
             AttributeValue::String(ref s) => {
-                for c in s.as_bytes() {
-                    match *c {
-                        b'"' if !opt.use_single_quote => buf.extend_from_slice(b"&quot;"),
-                        b'\'' if opt.use_single_quote => buf.extend_from_slice(b"&apos;"),
-                        _ => buf.push(*c),
-                    }
-                }
+                AttributeValue::write_string(s, opt, buf);
             }
             AttributeValue::Number(ref n) => {
                 n.write_buf_opt(&opt.values, buf);
@@ -282,7 +395,16 @@ impl WriteBuffer for AttributeValue {
                 a.write_buf_opt(&opt.values, buf);
             }
             AttributeValue::Transform(ref t) => {
-                t.write_buf_opt(&opt.values, buf);
+                let rotate = if opt.simplify_rotations {
+                    transform_ext::to_rotate_around(t)
+                } else {
+                    None
+                };
+
+                match rotate {
+                    Some(item) => TransformList(vec![item]).write_buf_opt(&opt.values, buf),
+                    None => t.write_buf_opt(&opt.values, buf),
+                }
             }
             AttributeValue::Path(ref p) => {
                 p.write_buf_opt(&opt.values, buf);
@@ -11,12 +11,17 @@ use std::str;
 use std::mem;
 use std::iter::FilterMap;
 use std::slice::{Iter, IterMut};
+use std::vec::IntoIter;
 
+use writer;
 use {
     Attribute,
     AttributeId,
+    AttributeQName,
     AttributeQNameRef,
     AttributeValue,
+    AttributesOrder,
+    ElementId,
     QName,
     WriteBuffer,
 };
@@ -200,12 +205,108 @@ impl Attributes {
         self.0.iter_mut()
     }
 
+    /// Returns an iterator over attributes in the specified order.
+    ///
+    /// Uses the same rules as [`WriteOptions::attributes_order`], but doesn't modify
+    /// the storage order. The element ID is required by the `Specification` order,
+    /// since it contains element-specific rules.
+    ///
+    /// [`WriteOptions::attributes_order`]: struct.WriteOptions.html#structfield.attributes_order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{AttributeId as AId, AttributesOrder, Document, ElementId as EId};
+    ///
+    /// let mut doc = Document::new();
+    /// let mut rect = doc.create_element(EId::Rect);
+    /// rect.set_attribute((AId::Width, 10.0));
+    /// rect.set_attribute(("data-name", "rect"));
+    /// rect.set_attribute((AId::Fill, "red"));
+    /// rect.set_attribute((AId::X, 5.0));
+    ///
+    /// let attrs = rect.attributes();
+    /// let names: Vec<_> = attrs.iter_sorted(AttributesOrder::Alphabetical, rect.tag_id())
+    ///     .map(|a| a.name.to_string()).collect();
+    /// assert_eq!(names, ["fill", "width", "x", "data-name"]);
+    ///
+    /// let names: Vec<_> = attrs.iter_sorted(AttributesOrder::Specification, rect.tag_id())
+    ///     .map(|a| a.name.to_string()).collect();
+    /// assert_eq!(names, ["fill", "x", "width", "data-name"]);
+    /// ```
+    pub fn iter_sorted(&self, order: AttributesOrder, eid: Option<ElementId>) -> IntoIter<&Attribute> {
+        writer::sort_attributes(self, order, eid).into_iter()
+    }
+
+    /// Returns an iterator over attributes with the specified namespace prefix.
+    ///
+    /// The prefix can be set with or without a trailing colon.
+    ///
+    /// `xlink:href` has the `xlink` prefix and `xml:space`, `xml:base`
+    /// and `xml:lang` have the `xml` one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId};
+    ///
+    /// let mut doc = Document::new();
+    /// let mut rect = doc.create_element(ElementId::Rect);
+    /// rect.set_attribute(("inkscape:label", "Layer"));
+    /// rect.set_attribute(("inkscape:groupmode", "layer"));
+    /// rect.set_attribute(("sodipodi:nodetypes", "cc"));
+    ///
+    /// assert_eq!(rect.attributes().iter_by_prefix("inkscape").count(), 2);
+    /// assert_eq!(rect.attributes().iter_by_prefix("sodipodi:").count(), 1);
+    /// ```
+    pub fn iter_by_prefix<'a, 'b>(&'a self, prefix: &'b str) -> AttributesByPrefix<'a, 'b> {
+        // `trim_end_matches` requires Rust 1.30.
+        #[allow(deprecated)]
+        let prefix = prefix.trim_right_matches(':');
+
+        AttributesByPrefix {
+            iter: self.0.iter(),
+            prefix,
+        }
+    }
+
     /// Clears the attributes list, removing all values.
     pub(crate) fn clear(&mut self) {
         self.0.clear();
     }
 }
 
+/// An iterator over attributes with the specified namespace prefix.
+///
+/// Created by [`Attributes::iter_by_prefix`].
+///
+/// [`Attributes::iter_by_prefix`]: struct.Attributes.html#method.iter_by_prefix
+pub struct AttributesByPrefix<'a, 'b> {
+    iter: Iter<'a, Attribute>,
+    prefix: &'b str,
+}
+
+impl<'a, 'b> Iterator for AttributesByPrefix<'a, 'b> {
+    type Item = &'a Attribute;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prefix = self.prefix;
+        self.iter.find(|a| attribute_prefix(&a.name) == Some(prefix))
+    }
+}
+
+/// Returns a namespace prefix of the attribute name.
+pub(crate) fn attribute_prefix(name: &AttributeQName) -> Option<&str> {
+    match *name {
+        QName::Id(AttributeId::Href) => Some("xlink"),
+          QName::Id(AttributeId::Space)
+        | QName::Id(AttributeId::Base)
+        | QName::Id(AttributeId::Lang) => Some("xml"),
+        QName::Id(_) => None,
+        QName::Name(ref name) => name.find(':').map(|idx| &name[..idx]),
+    }
+}
+
 impl IntoIterator for Attributes {
     type Item = Attribute;
     type IntoIter = ::std::vec::IntoIter<Self::Item>;
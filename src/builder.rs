@@ -0,0 +1,225 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    Attribute,
+    Document,
+    Node,
+    NodeType,
+    TagNameRef,
+};
+
+/// A builder for an element with attributes and children.
+///
+/// Created by [`Document::element`].
+///
+/// All methods are thin wrappers around the [`Document`] and [`Node`] methods,
+/// so the same rules apply. For example, a link attribute must reference
+/// an element with a non-empty ID, otherwise [`attr`] will panic.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, ElementId as EId, AttributeId as AId};
+///
+/// let mut doc = Document::new();
+/// let mut svg = doc.element(EId::Svg)
+///     .child(EId::LinearGradient, |lg| lg.id("lg1"))
+///     .finish();
+/// let gradient = svg.first_child().unwrap();
+///
+/// let rect = doc.element(EId::Rect)
+///     .attr((AId::X, 10.0))
+///     .attr((AId::Fill, gradient))
+///     .finish();
+/// doc.root().append(svg.clone());
+/// svg.append(rect);
+///
+/// assert_eq!(doc.to_string(),
+/// "<svg xmlns=\"http://www.w3.org/2000/svg\">
+///     <linearGradient id=\"lg1\"/>
+///     <rect fill=\"url(#lg1)\" x=\"10\"/>
+/// </svg>
+/// ");
+/// ```
+///
+/// [`Document`]: struct.Document.html
+/// [`Document::element`]: struct.Document.html#method.element
+/// [`Node`]: type.Node.html
+/// [`attr`]: #method.attr
+pub struct ElementBuilder<'a> {
+    doc: &'a mut Document,
+    node: Node,
+}
+
+impl<'a> ElementBuilder<'a> {
+    pub(crate) fn new<'b, T>(doc: &'a mut Document, tag_name: T) -> Self
+        where TagNameRef<'b>: From<T>, T: Copy
+    {
+        let node = doc.create_element(tag_name);
+        ElementBuilder { doc, node }
+    }
+
+    /// Sets the element ID.
+    pub fn id<S: Into<String>>(mut self, id: S) -> Self {
+        self.node.set_id(id);
+        self
+    }
+
+    /// Sets an attribute.
+    ///
+    /// # Panics
+    ///
+    /// Will panic on any error produced by the [`Node::set_attribute_checked`] method.
+    ///
+    /// [`Node::set_attribute_checked`]: type.Node.html#method.set_attribute_checked
+    pub fn attr<T>(mut self, v: T) -> Self
+        where T: Into<Attribute>
+    {
+        self.node.set_attribute(v);
+        self
+    }
+
+    /// Appends a new child element, which is configured by the closure.
+    pub fn child<'b, T, F>(mut self, tag_name: T, f: F) -> Self
+        where TagNameRef<'b>: From<T>, T: Copy,
+              F: for<'c> FnOnce(ElementBuilder<'c>) -> ElementBuilder<'c>
+    {
+        let child = f(ElementBuilder::new(self.doc, tag_name)).finish();
+        self.node.append(child);
+        self
+    }
+
+    /// Appends an existing node.
+    ///
+    /// The node will be detached from its current parent.
+    pub fn append(mut self, node: Node) -> Self {
+        self.node.append(node);
+        self
+    }
+
+    /// Appends a text node.
+    pub fn text<S: Into<String>>(mut self, text: S) -> Self {
+        let node = self.doc.create_node(NodeType::Text, text);
+        self.node.append(node);
+        self
+    }
+
+    /// Returns the constructed element.
+    ///
+    /// The element is not added to the document tree.
+    pub fn finish(self) -> Node {
+        self.node
+    }
+}
+
+/// Builds an element tree using a markup-like syntax.
+///
+/// Expands into the [`ElementBuilder`] calls and returns the root element,
+/// which is not added to the document tree.
+///
+/// Syntax:
+///
+/// - an element is written as `Tag(attributes) { children }`,
+///   where `Tag` is an `ElementId` variant. Both parts are optional;
+/// - an attribute is written as `Name = value`, where `Name` is an `AttributeId` variant
+///   and `value` is anything that can be converted into an attribute value.
+///   A string literal can be used for a non-SVG attribute name;
+/// - `id = value` sets the element ID;
+/// - a string literal or an expression in parentheses inside the children block
+///   is added as a text node.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate svgdom;
+///
+/// use svgdom::{Document, AttributeId as AId};
+///
+/// fn main() {
+///     let mut doc = Document::new();
+///     let name = "World";
+///     let svg = svg!(doc, Svg(Width = 100.0, "data-name" = "test") {
+///         G(id = "g1", Opacity = 0.5) {
+///             Rect(X = 10.0, Width = 20.0)
+///             Circle
+///         }
+///         Text { "Hello, " (name) }
+///     });
+///     doc.root().append(svg.clone());
+///
+///     assert_eq!(svg.attributes().get_value(AId::Width).unwrap().to_string(), "100");
+///     assert_eq!(doc.to_string(),
+/// "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" data-name=\"test\">
+///     <g id=\"g1\" opacity=\"0.5\">
+///         <rect width=\"20\" x=\"10\"/>
+///         <circle/>
+///     </g>
+///     <text>Hello, World</text>
+/// </svg>
+/// ");
+/// }
+/// ```
+///
+/// [`ElementBuilder`]: struct.ElementBuilder.html
+#[macro_export]
+macro_rules! svg {
+    (@attrs $b:expr;) => { $b };
+    (@attrs $b:expr; id = $v:expr) => { $b.id($v) };
+    (@attrs $b:expr; id = $v:expr, $($rest:tt)*) => {
+        svg!(@attrs $b.id($v); $($rest)*)
+    };
+    (@attrs $b:expr; $n:ident = $v:expr) => {
+        $b.attr(($crate::AttributeId::$n, $v))
+    };
+    (@attrs $b:expr; $n:ident = $v:expr, $($rest:tt)*) => {
+        svg!(@attrs $b.attr(($crate::AttributeId::$n, $v)); $($rest)*)
+    };
+    (@attrs $b:expr; $n:tt = $v:expr) => {
+        $b.attr(($n, $v))
+    };
+    (@attrs $b:expr; $n:tt = $v:expr, $($rest:tt)*) => {
+        svg!(@attrs $b.attr(($n, $v)); $($rest)*)
+    };
+
+    (@children $b:expr;) => { $b };
+    (@children $b:expr; $tag:ident ($($a:tt)*) { $($c:tt)* } $($rest:tt)*) => {
+        svg!(@children $b.child($crate::ElementId::$tag, |e| {
+            svg!(@children svg!(@attrs e; $($a)*); $($c)*)
+        }); $($rest)*)
+    };
+    (@children $b:expr; $tag:ident ($($a:tt)*) $($rest:tt)*) => {
+        svg!(@children $b.child($crate::ElementId::$tag, |e| {
+            svg!(@attrs e; $($a)*)
+        }); $($rest)*)
+    };
+    (@children $b:expr; $tag:ident { $($c:tt)* } $($rest:tt)*) => {
+        svg!(@children $b.child($crate::ElementId::$tag, |e| {
+            svg!(@children e; $($c)*)
+        }); $($rest)*)
+    };
+    (@children $b:expr; $tag:ident $($rest:tt)*) => {
+        svg!(@children $b.child($crate::ElementId::$tag, |e| e); $($rest)*)
+    };
+    (@children $b:expr; $text:tt $($rest:tt)*) => {
+        svg!(@children $b.text($text); $($rest)*)
+    };
+
+    ($doc:expr, $tag:ident ($($a:tt)*) { $($c:tt)* }) => {
+        svg!(@children svg!(@attrs $doc.element($crate::ElementId::$tag); $($a)*); $($c)*).finish()
+    };
+    ($doc:expr, $tag:ident ($($a:tt)*)) => {
+        svg!(@attrs $doc.element($crate::ElementId::$tag); $($a)*).finish()
+    };
+    ($doc:expr, $tag:ident { $($c:tt)* }) => {
+        svg!(@children $doc.element($crate::ElementId::$tag); $($c)*).finish()
+    };
+    ($doc:expr, $tag:ident) => {
+        $doc.element($crate::ElementId::$tag).finish()
+    };
+}
@@ -0,0 +1,131 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A list of class names.
+///
+/// Represents the `class` attribute value.
+/// Names are unique and preserve the insertion order.
+///
+/// See [`Node::class_list`] and [`Node::set_class_list`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::ClassList;
+///
+/// let mut list: ClassList = "icon  big".parse().unwrap();
+/// list.add("red");
+/// list.remove("big");
+/// assert!(list.toggle("active"));
+/// assert!(list.contains("icon"));
+/// assert_eq!(list.to_string(), "icon red active");
+/// ```
+///
+/// [`Node::class_list`]: type.Node.html#method.class_list
+/// [`Node::set_class_list`]: type.Node.html#method.set_class_list
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct ClassList(Vec<String>);
+
+impl ClassList {
+    /// Constructs a new, empty `ClassList`.
+    pub fn new() -> Self {
+        ClassList::default()
+    }
+
+    /// Adds a class name. Does nothing if the name is already present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is empty or contains spaces.
+    pub fn add(&mut self, name: &str) {
+        assert!(is_valid_name(name), "invalid class name: '{}'", name);
+
+        if !self.contains(name) {
+            self.0.push(name.to_string());
+        }
+    }
+
+    /// Removes a class name. Does nothing if the name is not present.
+    pub fn remove(&mut self, name: &str) {
+        self.0.retain(|n| n != name);
+    }
+
+    /// Checks that the list contains a class name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.iter().any(|n| n == name)
+    }
+
+    /// Removes a class name if it's present and adds it otherwise.
+    ///
+    /// Returns `true` if the name was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is empty or contains spaces.
+    pub fn toggle(&mut self, name: &str) -> bool {
+        if self.contains(name) {
+            self.remove(name);
+            false
+        } else {
+            self.add(name);
+            true
+        }
+    }
+
+    /// Returns an iterator over class names.
+    pub fn iter(&self) -> ::std::slice::Iter<String> {
+        self.0.iter()
+    }
+
+    /// Returns the number of class names.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks that the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromStr for ClassList {
+    type Err = ();
+
+    /// Parses a space-separated list of class names.
+    ///
+    /// Duplicates are removed. Never fails.
+    fn from_str(text: &str) -> Result<Self, ()> {
+        let mut list = ClassList::new();
+        for name in text.split_whitespace() {
+            list.add(name);
+        }
+
+        Ok(list)
+    }
+}
+
+impl fmt::Display for ClassList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, name) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+
+            write!(f, "{}", name)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(char::is_whitespace)
+}
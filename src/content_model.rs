@@ -0,0 +1,247 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use {
+    ElementId as EId,
+    ElementId,
+    Node,
+};
+
+/// This trait contains methods that describe the SVG content model.
+///
+/// Details: <https://www.w3.org/TR/SVG11/intro.html#TermContentModel>
+pub trait ContentModel {
+    /// Returns a list of elements that are allowed as children of the current one.
+    ///
+    /// Returns `None` when any element is allowed, like inside `metadata`
+    /// and `foreignObject`.
+    ///
+    /// The `a` element is treated as a container, even though the spec allows
+    /// only the content of its parent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{ContentModel, ElementId};
+    ///
+    /// assert!(ElementId::LinearGradient.allowed_children().unwrap().contains(&ElementId::Stop));
+    /// assert_eq!(ElementId::Rect.allowed_children().unwrap().contains(&ElementId::Rect), false);
+    /// assert_eq!(ElementId::Metadata.allowed_children(), None);
+    /// ```
+    fn allowed_children(&self) -> Option<&'static [ElementId]>;
+
+    /// Checks that an element is allowed as a child of the current one.
+    fn is_allowed_child(&self, child: ElementId) -> bool {
+        match self.allowed_children() {
+            Some(list) => list.contains(&child),
+            None => true,
+        }
+    }
+}
+
+impl ContentModel for ElementId {
+    fn allowed_children(&self) -> Option<&'static [ElementId]> {
+        let list = match *self {
+            EId::A
+            | EId::Defs
+            | EId::G
+            | EId::Glyph
+            | EId::Marker
+            | EId::Mask
+            | EId::MissingGlyph
+            | EId::Pattern
+            | EId::Svg
+            | EId::Symbol => CONTAINER,
+            EId::AltGlyph
+            | EId::FontFaceFormat
+            | EId::FontFaceName
+            | EId::GlyphRef
+            | EId::Hkern
+            | EId::Script
+            | EId::Style
+            | EId::Vkern => &[],
+            EId::AltGlyphDef => &[EId::AltGlyphItem, EId::GlyphRef],
+            EId::AltGlyphItem => &[EId::GlyphRef],
+            EId::Animate
+            | EId::AnimateColor
+            | EId::AnimateTransform
+            | EId::ColorProfile
+            | EId::Cursor
+            | EId::Mpath
+            | EId::Set
+            | EId::View => DESCRIPTIVE,
+            EId::AnimateMotion => &[EId::Desc, EId::Metadata, EId::Title, EId::Mpath],
+            EId::Circle
+            | EId::Ellipse
+            | EId::Image
+            | EId::Line
+            | EId::Path
+            | EId::Polygon
+            | EId::Polyline
+            | EId::Rect
+            | EId::Use => GRAPHIC,
+            EId::ClipPath => CLIP_PATH,
+            EId::Desc | EId::Metadata | EId::Title | EId::ForeignObject => return None,
+            EId::FeBlend
+            | EId::FeColorMatrix
+            | EId::FeComposite
+            | EId::FeConvolveMatrix
+            | EId::FeDisplacementMap
+            | EId::FeDistantLight
+            | EId::FeFuncA
+            | EId::FeFuncB
+            | EId::FeFuncG
+            | EId::FeFuncR
+            | EId::FeGaussianBlur
+            | EId::FeMergeNode
+            | EId::FeMorphology
+            | EId::FeOffset
+            | EId::FePointLight
+            | EId::FeSpotLight
+            | EId::FeTile
+            | EId::FeTurbulence => &[EId::Animate, EId::Set],
+            EId::FeComponentTransfer => &[EId::FeFuncA, EId::FeFuncB, EId::FeFuncG, EId::FeFuncR],
+            EId::FeDiffuseLighting | EId::FeSpecularLighting => LIGHTING,
+            EId::FeFlood | EId::Stop => &[EId::Animate, EId::AnimateColor, EId::Set],
+            EId::FeImage => &[EId::Animate, EId::AnimateTransform, EId::Set],
+            EId::FeMerge => &[EId::FeMergeNode],
+            EId::Filter => FILTER,
+            EId::Font => FONT,
+            EId::FontFace => &[EId::Desc, EId::Metadata, EId::Title, EId::FontFaceSrc],
+            EId::FontFaceSrc => &[EId::FontFaceName, EId::FontFaceUri],
+            EId::FontFaceUri => &[EId::FontFaceFormat],
+            EId::LinearGradient | EId::RadialGradient => GRADIENT,
+            EId::Switch => SWITCH,
+            EId::Text => TEXT,
+            EId::TextPath | EId::Tspan => TEXT_CHILD,
+            EId::Tref => &[EId::Desc, EId::Metadata, EId::Title,
+                           EId::Animate, EId::AnimateColor, EId::Set],
+        };
+
+        Some(list)
+    }
+}
+
+static DESCRIPTIVE: &[ElementId] = &[EId::Desc, EId::Metadata, EId::Title];
+
+// Animation and descriptive elements.
+static GRAPHIC: &[ElementId] = &[
+    EId::Animate, EId::AnimateColor, EId::AnimateMotion, EId::AnimateTransform, EId::Set,
+    EId::Desc, EId::Metadata, EId::Title,
+];
+
+static CONTAINER: &[ElementId] = &[
+    EId::Animate, EId::AnimateColor, EId::AnimateMotion, EId::AnimateTransform, EId::Set,
+    EId::Desc, EId::Metadata, EId::Title,
+    EId::Circle, EId::Ellipse, EId::Line, EId::Path, EId::Polygon, EId::Polyline, EId::Rect,
+    EId::Defs, EId::G, EId::Svg, EId::Symbol, EId::Use,
+    EId::LinearGradient, EId::RadialGradient,
+    EId::A, EId::AltGlyphDef, EId::ClipPath, EId::ColorProfile, EId::Cursor, EId::Filter,
+    EId::Font, EId::FontFace, EId::ForeignObject, EId::Image, EId::Marker, EId::Mask,
+    EId::Pattern, EId::Script, EId::Style, EId::Switch, EId::Text, EId::View,
+];
+
+static CLIP_PATH: &[ElementId] = &[
+    EId::Animate, EId::AnimateColor, EId::AnimateMotion, EId::AnimateTransform, EId::Set,
+    EId::Desc, EId::Metadata, EId::Title,
+    EId::Circle, EId::Ellipse, EId::Line, EId::Path, EId::Polygon, EId::Polyline, EId::Rect,
+    EId::Text, EId::Use,
+];
+
+static SWITCH: &[ElementId] = &[
+    EId::Animate, EId::AnimateColor, EId::AnimateMotion, EId::AnimateTransform, EId::Set,
+    EId::Desc, EId::Metadata, EId::Title,
+    EId::Circle, EId::Ellipse, EId::Line, EId::Path, EId::Polygon, EId::Polyline, EId::Rect,
+    EId::A, EId::ForeignObject, EId::G, EId::Image, EId::Svg, EId::Switch, EId::Text, EId::Use,
+];
+
+static GRADIENT: &[ElementId] = &[
+    EId::Desc, EId::Metadata, EId::Title,
+    EId::Animate, EId::AnimateTransform, EId::Set, EId::Stop,
+];
+
+static FILTER: &[ElementId] = &[
+    EId::Desc, EId::Metadata, EId::Title,
+    EId::Animate, EId::Set,
+    EId::FeBlend, EId::FeColorMatrix, EId::FeComponentTransfer, EId::FeComposite,
+    EId::FeConvolveMatrix, EId::FeDiffuseLighting, EId::FeDisplacementMap, EId::FeFlood,
+    EId::FeGaussianBlur, EId::FeImage, EId::FeMerge, EId::FeMorphology, EId::FeOffset,
+    EId::FeSpecularLighting, EId::FeTile, EId::FeTurbulence,
+];
+
+static LIGHTING: &[ElementId] = &[
+    EId::Desc, EId::Metadata, EId::Title,
+    EId::FeDistantLight, EId::FePointLight, EId::FeSpotLight,
+];
+
+static FONT: &[ElementId] = &[
+    EId::Desc, EId::Metadata, EId::Title,
+    EId::FontFace, EId::Glyph, EId::Hkern, EId::MissingGlyph, EId::Vkern,
+];
+
+static TEXT: &[ElementId] = &[
+    EId::Animate, EId::AnimateColor, EId::AnimateMotion, EId::AnimateTransform, EId::Set,
+    EId::Desc, EId::Metadata, EId::Title,
+    EId::AltGlyph, EId::TextPath, EId::Tref, EId::Tspan, EId::A,
+];
+
+static TEXT_CHILD: &[ElementId] = &[
+    EId::Desc, EId::Metadata, EId::Title,
+    EId::Animate, EId::AnimateColor, EId::Set,
+    EId::AltGlyph, EId::A, EId::Tref, EId::Tspan,
+];
+
+/// A content model violation.
+///
+/// See [`Document::validate_content_model`] for details.
+///
+/// [`Document::validate_content_model`]: struct.Document.html#method.validate_content_model
+#[derive(Clone, Debug)]
+pub struct ValidationError {
+    /// An element that is not allowed inside its parent.
+    pub node: Node,
+    /// A parent element.
+    pub parent: Node,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "element '{}' is not allowed inside '{}'",
+               *self.node.tag_name(), *self.parent.tag_name())
+    }
+}
+
+pub fn validate_content_model(root: &Node) -> Vec<ValidationError> {
+    let mut list = Vec::new();
+
+    for node in root.descendants() {
+        let id = match node.tag_id() {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let parent = match node.parent() {
+            Some(parent) => parent,
+            None => continue,
+        };
+
+        // Non-SVG parents and the root node are ignored.
+        let parent_id = match parent.tag_id() {
+            Some(id) => id,
+            None => continue,
+        };
+
+        if !parent_id.is_allowed_child(id) {
+            list.push(ValidationError { node: node.clone(), parent });
+        }
+    }
+
+    list
+}
@@ -0,0 +1,695 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt::Write;
+
+use xpath;
+use {
+    Attribute,
+    AttributeId,
+    AttributeQName,
+    AttributeValue,
+    Document,
+    Error,
+    FuzzyEq,
+    Node,
+    NodeType,
+    PathSegment,
+    WriteBuffer,
+    WriteOptions,
+};
+
+/// A single difference between two documents.
+///
+/// See [`Document::diff`] for details.
+///
+/// [`Document::diff`]: struct.Document.html#method.diff
+#[derive(Clone, PartialEq, Debug)]
+pub enum DomChange {
+    /// A node was added.
+    NodeAdded {
+        /// A parent node from the original document.
+        parent: Node,
+        /// An index of the node in the new parent's children list.
+        index: usize,
+        /// An added node from the new document.
+        node: Node,
+    },
+    /// A node was removed.
+    NodeRemoved {
+        /// A removed node from the original document.
+        node: Node,
+    },
+    /// An attribute was added.
+    AttributeAdded {
+        /// A node from the original document.
+        node: Node,
+        /// An added attribute.
+        ///
+        /// Links point to nodes from the new document.
+        attribute: Attribute,
+    },
+    /// An attribute was removed.
+    AttributeRemoved {
+        /// A node from the original document.
+        node: Node,
+        /// A removed attribute name.
+        name: AttributeQName,
+    },
+    /// An attribute value was changed.
+    AttributeChanged {
+        /// A node from the original document.
+        node: Node,
+        /// An original attribute.
+        old: Attribute,
+        /// A new attribute.
+        ///
+        /// Links point to nodes from the new document.
+        new: Attribute,
+    },
+    /// A text of the text or comment node was changed.
+    TextChanged {
+        /// A node from the original document.
+        node: Node,
+        /// An original text.
+        old: String,
+        /// A new text.
+        new: String,
+    },
+}
+
+pub fn diff(doc1: &Document, doc2: &Document) -> Vec<DomChange> {
+    let mut changes = Vec::new();
+    diff_children(&doc1.root(), &doc2.root(), &mut changes);
+    changes
+}
+
+pub fn diff_text(doc1: &Document, doc2: &Document, opt: &WriteOptions) -> String {
+    let changes = diff(doc1, doc2);
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut text = String::from("--- original\n+++ modified\n");
+    let mut context: Option<Node> = None;
+    let mut paths = xpath::PathCache::default();
+    for change in &changes {
+        let node = match *change {
+            DomChange::NodeAdded { ref parent, .. } => parent.clone(),
+            DomChange::NodeRemoved { ref node } => node.parent().unwrap(),
+            DomChange::AttributeAdded { ref node, .. }
+            | DomChange::AttributeRemoved { ref node, .. }
+            | DomChange::AttributeChanged { ref node, .. }
+            | DomChange::TextChanged { ref node, .. } => node.clone(),
+        };
+
+        // Consecutive changes of the same node are grouped.
+        if context.as_ref() != Some(&node) {
+            writeln!(text, "@@ {} @@", paths.path(&node)).unwrap();
+            context = Some(node);
+        }
+
+        match *change {
+            DomChange::NodeAdded { ref node, .. } => {
+                writeln!(text, "+ {}", node_to_string(node, opt)).unwrap();
+            }
+            DomChange::NodeRemoved { ref node } => {
+                writeln!(text, "- {}", node_to_string(node, opt)).unwrap();
+            }
+            DomChange::AttributeAdded { ref attribute, .. } => {
+                writeln!(text, "+ {}", attribute.with_write_opt(opt)).unwrap();
+            }
+            DomChange::AttributeRemoved { ref node, ref name } => {
+                let attr = if *name == AttributeQName::Id(AttributeId::Id) {
+                    Attribute::new(AttributeId::Id, node.id().clone())
+                } else {
+                    node.attributes().get(name.as_ref()).unwrap().clone()
+                };
+
+                writeln!(text, "- {}", attr.with_write_opt(opt)).unwrap();
+            }
+            DomChange::AttributeChanged { ref old, ref new, .. } => {
+                writeln!(text, "- {}", old.with_write_opt(opt)).unwrap();
+                writeln!(text, "+ {}", new.with_write_opt(opt)).unwrap();
+            }
+            DomChange::TextChanged { ref old, ref new, .. } => {
+                writeln!(text, "- {:?}", old).unwrap();
+                writeln!(text, "+ {:?}", new).unwrap();
+            }
+        }
+    }
+
+    text
+}
+
+// Returns a single line representation of the node.
+fn node_to_string(node: &Node, opt: &WriteOptions) -> String {
+    match node.node_type() {
+        NodeType::Element => {
+            let mut text = format!("<{}", *node.tag_name());
+            if node.has_id() {
+                let attr = Attribute::new(AttributeId::Id, node.id().clone());
+                write!(text, " {}", attr.with_write_opt(opt)).unwrap();
+            }
+
+            let attrs = node.attributes();
+            for attr in attrs.iter_sorted(opt.attributes_order, node.tag_id()) {
+                write!(text, " {}", attr.with_write_opt(opt)).unwrap();
+            }
+
+            if node.has_children() {
+                write!(text, ">...</{}>", *node.tag_name()).unwrap();
+            } else {
+                text.push_str("/>");
+            }
+
+            text
+        }
+        NodeType::Text => format!("{:?}", *node.text()),
+        NodeType::Comment => format!("<!--{}-->", *node.text()),
+        NodeType::ProcessingInstruction => format!("<?{}?>", *node.text()),
+        NodeType::Root => String::new(),
+    }
+}
+
+pub fn apply_patch(doc: &mut Document, changes: &[DomChange]) -> Result<(), Error> {
+    // Check everything first, so the document stays untouched on error.
+    for change in changes {
+        if !is_applicable(doc, change) {
+            return Err(Error::InvalidPatch);
+        }
+    }
+
+    check_links(doc, changes)?;
+
+    // Removing a node will remove all links to it, so we have to remember them
+    // to restore later if an element with the same ID will be added.
+    let mut dangling_links = Vec::new();
+    for change in changes {
+        if let DomChange::NodeRemoved { ref node } = *change {
+            for n in node.descendants() {
+                for linked in n.linked_nodes().iter() {
+                    for attr in linked.attributes().iter() {
+                        if attr.value.is_link_container() && link_target(&attr.value) == Some(n.clone()) {
+                            dangling_links.push((linked.clone(), attr.clone()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for change in changes {
+        if let DomChange::NodeRemoved { ref node } = *change {
+            doc.remove_node(node.clone());
+        }
+    }
+
+    // IDs must be updated before any links will be resolved.
+    for change in changes {
+        match *change {
+            DomChange::AttributeAdded { ref node, ref attribute }
+            | DomChange::AttributeChanged { ref node, new: ref attribute, .. }
+                if attribute.name == AttributeQName::Id(AttributeId::Id) => {
+                node.clone().set_id(attribute.value.to_string());
+            }
+            DomChange::AttributeRemoved { ref node, ref name }
+                if *name == AttributeQName::Id(AttributeId::Id) => {
+                node.clone().set_id(String::new());
+            }
+            _ => {}
+        }
+    }
+
+    // Added nodes and their origins.
+    let mut added = Vec::new();
+    for change in changes {
+        if let DomChange::NodeAdded { ref parent, index, ref node } = *change {
+            let new_node = copy_subtree(doc, node, &mut added);
+            match parent.children().nth(index) {
+                Some(mut n) => n.insert_before(new_node),
+                None => parent.clone().append(new_node),
+            }
+        }
+    }
+
+    for (mut node, attr) in dangling_links {
+        if !is_in_doc(doc, &node) || node.has_attribute(attr.name.as_ref()) {
+            continue;
+        }
+
+        // Unlike other links, the missing one is not an error.
+        if let Ok(value) = resolve_links(doc, &attr.value) {
+            node.set_attribute_checked(Attribute::new(attr.name.as_ref(), value))?;
+        }
+    }
+
+    for &(ref new_node, ref node) in &added {
+        for attr in node.attributes().iter() {
+            let value = resolve_links(doc, &attr.value)?;
+            new_node.clone().set_attribute_checked(Attribute::new(attr.name.as_ref(), value))?;
+        }
+    }
+
+    for change in changes {
+        match *change {
+            DomChange::AttributeAdded { ref node, ref attribute }
+            | DomChange::AttributeChanged { ref node, new: ref attribute, .. }
+                if attribute.name != AttributeQName::Id(AttributeId::Id) => {
+                let value = resolve_links(doc, &attribute.value)?;
+                let attr = Attribute::new(attribute.name.as_ref(), value);
+                node.clone().set_attribute_checked(attr)?;
+            }
+            DomChange::AttributeRemoved { ref node, ref name }
+                if *name != AttributeQName::Id(AttributeId::Id) => {
+                node.clone().remove_attribute(name.as_ref());
+            }
+            DomChange::TextChanged { ref node, ref new, .. } => {
+                node.clone().set_text(new);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn is_applicable(doc: &Document, change: &DomChange) -> bool {
+    match *change {
+        DomChange::NodeAdded { ref parent, .. } => {
+            is_in_doc(doc, parent) && parent.node_type() != NodeType::Text
+        }
+        DomChange::NodeRemoved { ref node } => {
+            is_in_doc(doc, node) && *node != doc.root()
+        }
+        DomChange::AttributeAdded { ref node, ref attribute } => {
+               is_in_doc(doc, node)
+            && node.is_element()
+            && !has_attribute(node, &attribute.name)
+        }
+        DomChange::AttributeRemoved { ref node, ref name } => {
+            is_in_doc(doc, node) && has_attribute(node, name)
+        }
+        DomChange::AttributeChanged { ref node, ref old, .. } => {
+            if !is_in_doc(doc, node) {
+                return false;
+            }
+
+            if old.name == AttributeQName::Id(AttributeId::Id) {
+                return *node.id() == old.value.to_string();
+            }
+
+            match node.attributes().get(old.name.as_ref()) {
+                Some(attr) => values_fuzzy_eq(&attr.value, &old.value),
+                None => false,
+            }
+        }
+        DomChange::TextChanged { ref node, ref old, .. } => {
+            is_in_doc(doc, node) && *node.text() == *old
+        }
+    }
+}
+
+// Checks that links will be resolved and will not form a crosslink after the patch is applied.
+fn check_links(doc: &Document, changes: &[DomChange]) -> Result<(), Error> {
+    let mut removed = Vec::new();
+    let mut new_ids = Vec::new();
+    // Attributes that are changed or removed by the patch.
+    let mut replaced = Vec::new();
+    // Links that will be set by the patch: a source ID and a target node.
+    let mut links = Vec::new();
+    for change in changes {
+        match *change {
+            DomChange::NodeRemoved { ref node } => {
+                removed.push(node.clone());
+            }
+            DomChange::AttributeAdded { ref node, ref attribute }
+            | DomChange::AttributeChanged { ref node, new: ref attribute, .. } => {
+                if attribute.name == AttributeQName::Id(AttributeId::Id) {
+                    new_ids.push((node.clone(), attribute.value.to_string()));
+                } else {
+                    replaced.push((node.clone(), attribute.name.clone()));
+                    if let Some(target) = link_target(&attribute.value) {
+                        links.push((node.clone(), target));
+                    }
+                }
+            }
+            DomChange::AttributeRemoved { ref node, ref name } => {
+                if *name == AttributeQName::Id(AttributeId::Id) {
+                    new_ids.push((node.clone(), String::new()));
+                } else {
+                    replaced.push((node.clone(), name.clone()));
+                }
+            }
+            DomChange::NodeAdded { .. } | DomChange::TextChanged { .. } => {}
+        }
+    }
+
+    let is_removed = |node: &Node| node.ancestors().any(|n| removed.contains(&n));
+    let final_id = |node: &Node| -> String {
+        match new_ids.iter().rev().find(|&&(ref n, _)| n == node) {
+            Some(&(_, ref id)) => id.clone(),
+            None => node.id().clone(),
+        }
+    };
+
+    let mut ids: Vec<String> = Vec::new();
+    for node in doc.root().descendants().filter(|n| n.is_element() && !is_removed(n)) {
+        ids.push(final_id(&node));
+    }
+
+    for change in changes {
+        if let DomChange::NodeAdded { ref node, .. } = *change {
+            for n in node.descendants().filter(|n| n.is_element()) {
+                ids.push(n.id().clone());
+                for attr in n.attributes().iter() {
+                    if let Some(target) = link_target(&attr.value) {
+                        links.push((n.clone(), target));
+                    }
+                }
+            }
+        }
+    }
+
+    // Pairs of source and target IDs of all links after the patch.
+    let mut pairs = Vec::new();
+    for &(ref source, ref target) in &links {
+        let source_id = if is_in_doc(doc, source) { final_id(source) } else { source.id().clone() };
+        let target_id = target.id().clone();
+        if target_id.is_empty() {
+            return Err(Error::ElementMustHaveAnId);
+        }
+
+        if !ids.contains(&target_id) {
+            return Err(Error::InvalidPatch);
+        }
+
+        if source_id == target_id {
+            return Err(Error::ElementCrosslink);
+        }
+
+        pairs.push((source_id, target_id));
+    }
+
+    for node in doc.root().descendants().filter(|n| n.is_element() && !is_removed(n)) {
+        for attr in node.attributes().iter() {
+            if replaced.iter().any(|&(ref n, ref name)| *n == node && *name == attr.name) {
+                continue;
+            }
+
+            if let Some(target) = link_target(&attr.value) {
+                // Links to removed nodes are restored by ID when possible.
+                let target_id = if is_removed(&target) { target.id().clone() } else { final_id(&target) };
+                if ids.contains(&target_id) {
+                    pairs.push((final_id(&node), target_id));
+                }
+            }
+        }
+    }
+
+    for &(ref source_id, ref target_id) in &pairs {
+        if source_id.is_empty() {
+            continue;
+        }
+
+        if pairs.iter().any(|&(ref s, ref t)| s == target_id && t == source_id) {
+            return Err(Error::ElementCrosslink);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_in_doc(doc: &Document, node: &Node) -> bool {
+    node.ancestors().last() == Some(doc.root())
+}
+
+fn has_attribute(node: &Node, name: &AttributeQName) -> bool {
+    if *name == AttributeQName::Id(AttributeId::Id) {
+        node.has_id()
+    } else {
+        node.has_attribute(name.as_ref())
+    }
+}
+
+fn link_target(value: &AttributeValue) -> Option<Node> {
+    match *value {
+          AttributeValue::Link(ref link)
+        | AttributeValue::FuncLink(ref link)
+        | AttributeValue::Paint(ref link, _) => Some(link.clone()),
+        _ => None,
+    }
+}
+
+// Links can point to nodes from another document, so they are resolved by ID.
+fn resolve_links(doc: &Document, value: &AttributeValue) -> Result<AttributeValue, Error> {
+    let target = match link_target(value) {
+        Some(target) => target,
+        None => return Ok(value.clone()),
+    };
+
+    let id = target.id().clone();
+    let node = match doc.root().descendants().find(|n| *n.id() == id) {
+        Some(node) => node,
+        None => return Err(Error::InvalidPatch),
+    };
+
+    Ok(match *value {
+        AttributeValue::Link(_) => AttributeValue::Link(node),
+        AttributeValue::FuncLink(_) => AttributeValue::FuncLink(node),
+        AttributeValue::Paint(_, fallback) => AttributeValue::Paint(node, fallback),
+        _ => unreachable!(),
+    })
+}
+
+fn copy_subtree(doc: &mut Document, node: &Node, added: &mut Vec<(Node, Node)>) -> Node {
+    let mut new_node = if node.is_element() {
+        let mut elem = doc.create_element(node.tag_name().as_ref());
+        elem.set_id(node.id().clone());
+        added.push((elem.clone(), node.clone()));
+        elem
+    } else {
+        doc.create_node(node.node_type(), node.text().clone())
+    };
+
+    for child in node.children() {
+        let new_child = copy_subtree(doc, &child, added);
+        new_node.append(new_child);
+    }
+
+    new_node
+}
+
+fn diff_children(parent1: &Node, parent2: &Node, changes: &mut Vec<DomChange>) {
+    let children1: Vec<Node> = parent1.children().collect();
+    let children2: Vec<Node> = parent2.children().collect();
+
+    // Index of the matched node from `children1` for each node from `children2`.
+    //
+    // Matched nodes must preserve their order, otherwise the result
+    // can't be expressed without a move operation.
+    let mut matched: Vec<Option<usize>> = vec![None; children2.len()];
+    let mut is_used = vec![false; children1.len()];
+    let mut next = 0;
+    for (i2, n2) in children2.iter().enumerate() {
+        let has_id = |n: &Node| n.has_id() && children2.iter().any(|n2| *n2.id() == *n.id());
+
+        let pos = if n2.has_id() {
+            // Match by ID.
+            (next..children1.len()).find(|i1| {
+                *children1[*i1].id() == *n2.id() && is_same_kind(&children1[*i1], n2)
+            })
+        } else if    next < children1.len()
+                  && !has_id(&children1[next])
+                  && is_same_kind(&children1[next], n2) {
+            // Match by position.
+            Some(next)
+        } else {
+            None
+        };
+
+        if let Some(i1) = pos {
+            matched[i2] = Some(i1);
+            is_used[i1] = true;
+            next = i1 + 1;
+        }
+    }
+
+    for (i1, n1) in children1.iter().enumerate() {
+        if !is_used[i1] {
+            changes.push(DomChange::NodeRemoved { node: n1.clone() });
+        }
+    }
+
+    for (i2, n2) in children2.iter().enumerate() {
+        match matched[i2] {
+            Some(i1) => {
+                diff_nodes(&children1[i1], n2, changes);
+            }
+            None => {
+                changes.push(DomChange::NodeAdded {
+                    parent: parent1.clone(),
+                    index: i2,
+                    node: n2.clone(),
+                });
+            }
+        }
+    }
+}
+
+fn is_same_kind(n1: &Node, n2: &Node) -> bool {
+    if n1.node_type() != n2.node_type() {
+        return false;
+    }
+
+    if n1.is_element() {
+        *n1.tag_name() == *n2.tag_name()
+    } else {
+        true
+    }
+}
+
+fn diff_nodes(n1: &Node, n2: &Node, changes: &mut Vec<DomChange>) {
+    match n1.node_type() {
+        NodeType::Element => {
+            diff_attributes(n1, n2, changes);
+            diff_children(n1, n2, changes);
+        }
+        NodeType::Text | NodeType::Comment | NodeType::ProcessingInstruction => {
+            if *n1.text() != *n2.text() {
+                changes.push(DomChange::TextChanged {
+                    node: n1.clone(),
+                    old: n1.text().clone(),
+                    new: n2.text().clone(),
+                });
+            }
+        }
+        NodeType::Root => {}
+    }
+}
+
+fn diff_attributes(n1: &Node, n2: &Node, changes: &mut Vec<DomChange>) {
+    // ID is not stored in attributes, so we have to check it separately.
+    if *n1.id() != *n2.id() {
+        let old = Attribute::new(AttributeId::Id, n1.id().clone());
+        let new = Attribute::new(AttributeId::Id, n2.id().clone());
+        if !n1.has_id() {
+            changes.push(DomChange::AttributeAdded { node: n1.clone(), attribute: new });
+        } else if !n2.has_id() {
+            changes.push(DomChange::AttributeRemoved { node: n1.clone(), name: old.name });
+        } else {
+            changes.push(DomChange::AttributeChanged { node: n1.clone(), old, new });
+        }
+    }
+
+    let attrs1 = n1.attributes();
+    let attrs2 = n2.attributes();
+
+    for attr1 in attrs1.iter() {
+        match attrs2.get(attr1.name.as_ref()) {
+            Some(attr2) => {
+                if !values_fuzzy_eq(&attr1.value, &attr2.value) {
+                    changes.push(DomChange::AttributeChanged {
+                        node: n1.clone(),
+                        old: attr1.clone(),
+                        new: attr2.clone(),
+                    });
+                }
+            }
+            None => {
+                changes.push(DomChange::AttributeRemoved {
+                    node: n1.clone(),
+                    name: attr1.name.clone(),
+                });
+            }
+        }
+    }
+
+    for attr2 in attrs2.iter() {
+        if !attrs1.contains(attr2.name.as_ref()) {
+            changes.push(DomChange::AttributeAdded {
+                node: n1.clone(),
+                attribute: attr2.clone(),
+            });
+        }
+    }
+}
+
+// Compares values from different documents, so links are compared by ID
+// and numbers are compared using fuzzy comparison.
+fn values_fuzzy_eq(v1: &AttributeValue, v2: &AttributeValue) -> bool {
+    use AttributeValue as AV;
+
+    match (v1, v2) {
+        (&AV::Number(n1), &AV::Number(n2)) => n1.fuzzy_eq(&n2),
+        (&AV::Length(l1), &AV::Length(l2)) => l1.fuzzy_eq(&l2),
+        (&AV::Angle(a1), &AV::Angle(a2)) => a1.unit == a2.unit && a1.num.fuzzy_eq(&a2.num),
+        (&AV::Transform(ts1), &AV::Transform(ts2)) => ts1.fuzzy_eq(&ts2),
+        (&AV::ViewBox(vb1), &AV::ViewBox(vb2)) => vb1.fuzzy_eq(&vb2),
+        (&AV::NumberList(ref list1), &AV::NumberList(ref list2)) => {
+               list1.len() == list2.len()
+            && list1.iter().zip(list2.iter()).all(|(n1, n2)| n1.fuzzy_eq(n2))
+        }
+        (&AV::LengthList(ref list1), &AV::LengthList(ref list2)) => {
+               list1.len() == list2.len()
+            && list1.iter().zip(list2.iter()).all(|(l1, l2)| l1.fuzzy_eq(l2))
+        }
+        (&AV::Points(ref list1), &AV::Points(ref list2)) => {
+               list1.len() == list2.len()
+            && list1.iter().zip(list2.iter()).all(|(p1, p2)| {
+                p1.0.fuzzy_eq(&p2.0) && p1.1.fuzzy_eq(&p2.1)
+            })
+        }
+        (&AV::Path(ref p1), &AV::Path(ref p2)) => {
+               p1.len() == p2.len()
+            && p1.iter().zip(p2.iter()).all(|(s1, s2)| segments_fuzzy_eq(s1, s2))
+        }
+        (&AV::Link(ref l1), &AV::Link(ref l2))
+        | (&AV::FuncLink(ref l1), &AV::FuncLink(ref l2)) => {
+            *l1.id() == *l2.id()
+        }
+        (&AV::Paint(ref l1, f1), &AV::Paint(ref l2, f2)) => {
+            *l1.id() == *l2.id() && f1 == f2
+        }
+        _ => v1 == v2,
+    }
+}
+
+fn segments_fuzzy_eq(seg1: &PathSegment, seg2: &PathSegment) -> bool {
+    if seg1.cmd() != seg2.cmd() || seg1.is_absolute() != seg2.is_absolute() {
+        return false;
+    }
+
+    if let (PathSegment::EllipticalArc { large_arc: la1, sweep: s1, .. },
+            PathSegment::EllipticalArc { large_arc: la2, sweep: s2, .. }) = (*seg1, *seg2) {
+        if la1 != la2 || s1 != s2 {
+            return false;
+        }
+    }
+
+    let c1 = segment_coords(seg1);
+    let c2 = segment_coords(seg2);
+    c1.iter().zip(c2.iter()).all(|(n1, n2)| n1.fuzzy_eq(n2))
+}
+
+fn segment_coords(seg: &PathSegment) -> [f64; 6] {
+    match *seg {
+          PathSegment::MoveTo { x, y, .. }
+        | PathSegment::LineTo { x, y, .. }
+        | PathSegment::SmoothQuadratic { x, y, .. } => [x, y, 0.0, 0.0, 0.0, 0.0],
+        PathSegment::HorizontalLineTo { x, .. } => [x, 0.0, 0.0, 0.0, 0.0, 0.0],
+        PathSegment::VerticalLineTo { y, .. } => [0.0, y, 0.0, 0.0, 0.0, 0.0],
+        PathSegment::CurveTo { x1, y1, x2, y2, x, y, .. } => [x1, y1, x2, y2, x, y],
+        PathSegment::SmoothCurveTo { x2, y2, x, y, .. } => [x2, y2, x, y, 0.0, 0.0],
+        PathSegment::Quadratic { x1, y1, x, y, .. } => [x1, y1, x, y, 0.0, 0.0],
+        PathSegment::EllipticalArc { rx, ry, x_axis_rotation, x, y, .. } => {
+            [rx, ry, x_axis_rotation, x, y, 0.0]
+        }
+        PathSegment::ClosePath { .. } => [0.0; 6],
+    }
+}
@@ -6,30 +6,58 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::collections::HashSet;
 use std::fmt;
 
 use slab::Slab;
 
-use parser::parse_svg;
+#[cfg(feature = "parsing")]
+use parser::{
+    parse_svg,
+    parse_svg_fragment,
+};
+#[cfg(feature = "parsing")]
 use {
     ParseOptions,
+    ParserError,
 };
 
+use content_model;
+use diff;
+use external;
+use features;
+use flat;
+use geometry;
+use merge;
 use writer;
+use xpath;
 use {
+    AttributeId,
     AttributeQName,
     Attributes,
     AttributeValue,
+    Descendants,
+    DomChange,
+    ElementBuilder,
     ElementId,
+    ElementType,
+    Error,
+    ExternalReference,
+    FeatureSet,
     FilterSvg,
     FilterSvgAttrs,
+    FlatDom,
     Node,
     NodeData,
+    NodeId,
     NodeType,
-    ParserError,
     QName,
     QNameRef,
+    Snapshot,
     TagNameRef,
+    Transform,
+    TranslateMode,
+    ValidationError,
     WriteBuffer,
     WriteOptions,
 };
@@ -72,29 +100,36 @@ use {
 pub struct Document {
     root: Node,
     storage: Slab<Node>,
+    // The number of created nodes. Used by `NodeId`.
+    generation: u64,
+    name_validator: Option<fn(&str) -> bool>,
 }
 
 impl Document {
     /// Constructs a new `Document`.
     pub fn new() -> Document {
-        let mut storage = Slab::new();
-        let mut root = Node::new(NodeData {
+        let root = Node::new(NodeData {
             storage_key: None,
+            generation: 0,
+            name_validator: None,
             node_type: NodeType::Root,
             tag_name: QName::Name(String::new()),
             id: String::new(),
             attributes: Attributes::new(),
             linked_nodes: Vec::new(),
             text: String::new(),
+            source_pos: None,
         });
 
-        let key = storage.insert(root.clone());
-        root.borrow_mut().storage_key = Some(key);
+        let mut doc = Document {
+            root: root.clone(),
+            storage: Slab::new(),
+            generation: 0,
+            name_validator: None,
+        };
+        doc.insert_node(root);
 
-        Document {
-            root,
-            storage,
-        }
+        doc
     }
 
     /// Constructs a new `Document` from the text using a default [`ParseOptions`].
@@ -102,6 +137,7 @@ impl Document {
     /// [`ParseOptions`]: struct.ParseOptions.html
     ///
     /// **Note:** only SVG elements and attributes will be parsed.
+    #[cfg(feature = "parsing")]
     pub fn from_str(text: &str) -> Result<Document, ParserError> {
         Document::from_str_with_opt(text, &ParseOptions::default())
     }
@@ -111,20 +147,64 @@ impl Document {
     /// [`ParseOptions`]: struct.ParseOptions.html
     ///
     /// **Note:** only SVG elements and attributes will be parsed.
+    #[cfg(feature = "parsing")]
     pub fn from_str_with_opt(text: &str, opt: &ParseOptions) -> Result<Document, ParserError> {
         parse_svg(text, opt)
     }
 
+    /// Constructs a new `Document` from an SVG fragment using a default [`ParseOptions`].
+    ///
+    /// Unlike [`from_str`], the text can contain any number of top-level elements,
+    /// which will be added directly to the root node. E.g. `<rect/><circle/>`.
+    /// Prefixes `xlink` and `xml` are predefined, so the fragment doesn't need
+    /// namespace declarations, but they can still be set on elements.
+    ///
+    /// Whitespace-only text between elements is ignored. Any other top-level text
+    /// is preserved as a text node of the root.
+    ///
+    /// Since the fragment is parsed inside a temporary `svg` element,
+    /// it can't contain an XML declaration or a DOCTYPE, and error positions
+    /// in the first line are shifted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_fragment_str("<rect/>\n<circle/>").unwrap();
+    /// assert_eq!(doc.root().children().count(), 2);
+    /// ```
+    ///
+    /// [`ParseOptions`]: struct.ParseOptions.html
+    /// [`from_str`]: #method.from_str
+    #[cfg(feature = "parsing")]
+    pub fn from_fragment_str(text: &str) -> Result<Document, ParserError> {
+        Document::from_fragment_str_with_opt(text, &ParseOptions::default())
+    }
+
+    /// Constructs a new `Document` from an SVG fragment using a supplied [`ParseOptions`].
+    ///
+    /// See [`from_fragment_str`] for details.
+    ///
+    /// [`ParseOptions`]: struct.ParseOptions.html
+    /// [`from_fragment_str`]: #method.from_fragment_str
+    #[cfg(feature = "parsing")]
+    pub fn from_fragment_str_with_opt(text: &str, opt: &ParseOptions) -> Result<Document, ParserError> {
+        parse_svg_fragment(text, opt)
+    }
+
     /// Constructs a new [`Node`] with [`NodeType`]::Element type.
     ///
     /// Constructed node do belong to this document, but not added to it tree structure.
     ///
     /// # Panics
     ///
-    /// Panics if a string tag name is empty.
+    /// - Panics if a string tag name is empty.
+    /// - Panics if a string tag name was rejected by the [name validator].
     ///
     /// [`Node`]: type.Node.html
     /// [`NodeType`]: enum.NodeType.html
+    /// [name validator]: #method.set_name_validator
     pub fn create_element<'a, T>(&mut self, tag_name: T) -> Node
         where TagNameRef<'a>: From<T>, T: Copy
     {
@@ -133,20 +213,26 @@ impl Document {
             if name.is_empty() {
                 panic!("supplied tag name is empty");
             }
+
+            if !self.is_valid_name(name) {
+                panic!("supplied tag name '{}' is invalid", name);
+            }
         }
 
-        let mut node = Node::new(NodeData {
+        let node = Node::new(NodeData {
             storage_key: None,
+            generation: 0,
+            name_validator: None,
             node_type: NodeType::Element,
             tag_name: QNameRef::from(tag_name).into(),
             id: String::new(),
             attributes: Attributes::new(),
             linked_nodes: Vec::new(),
             text: String::new(),
+            source_pos: None,
         });
 
-        let key = self.storage.insert(node.clone());
-        node.borrow_mut().storage_key = Some(key);
+        self.insert_node(node.clone());
 
         node
     }
@@ -164,22 +250,99 @@ impl Document {
     pub fn create_node<S: Into<String>>(&mut self, node_type: NodeType, text: S) -> Node {
         assert!(node_type != NodeType::Element && node_type != NodeType::Root);
 
-        let mut node = Node::new(NodeData {
+        let node = Node::new(NodeData {
             storage_key: None,
+            generation: 0,
+            name_validator: None,
             node_type,
             tag_name: QName::Name(String::new()),
             id: String::new(),
             attributes: Attributes::new(),
             linked_nodes: Vec::new(),
             text: text.into(),
+            source_pos: None,
         });
 
-        let key = self.storage.insert(node.clone());
-        node.borrow_mut().storage_key = Some(key);
+        self.insert_node(node.clone());
 
         node
     }
 
+    /// Returns a builder for a new element.
+    ///
+    /// See [`ElementBuilder`] for details.
+    ///
+    /// [`ElementBuilder`]: struct.ElementBuilder.html
+    pub fn element<'a, T>(&mut self, tag_name: T) -> ElementBuilder
+        where TagNameRef<'a>: From<T>, T: Copy
+    {
+        ElementBuilder::new(self, tag_name)
+    }
+
+    // Adds a new node to the storage.
+    fn insert_node(&mut self, mut node: Node) {
+        self.generation += 1;
+        let key = self.storage.insert(node.clone());
+
+        let mut data = node.borrow_mut();
+        data.storage_key = Some(key);
+        data.generation = self.generation;
+        data.name_validator = self.name_validator;
+    }
+
+    /// Sets a function that validates string names of elements and attributes.
+    ///
+    /// Names of SVG elements and attributes are always valid, so only custom names
+    /// are checked, like in `<my:element my:attribute=""/>`.
+    ///
+    /// The validator is used by:
+    ///
+    /// - [`create_element`] and [`Node::set_tag_name`], which will panic on invalid names;
+    /// - [`Node::set_tag_name_checked`] and [`Node::set_attribute_checked`],
+    ///   which will return `Error::InvalidName`;
+    /// - [`Node::set_attribute`] and other attribute setters, which will panic.
+    ///
+    /// The validator applies to all nodes of the document, including the existing ones.
+    /// The parser doesn't use it.
+    ///
+    /// By default, any non-empty name is allowed.
+    /// Use [`is_valid_xml_name`] to allow only valid XML names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, Error, is_valid_xml_name};
+    ///
+    /// let mut doc = Document::new();
+    /// doc.set_name_validator(Some(is_valid_xml_name));
+    ///
+    /// let mut node = doc.create_element("my:element");
+    /// assert_eq!(node.set_tag_name_checked("my element"), Err(Error::InvalidName));
+    /// assert_eq!(node.set_attribute_checked(("1st", "value")), Err(Error::InvalidName));
+    /// assert!(node.set_attribute_checked(("my:attribute", "value")).is_ok());
+    /// ```
+    ///
+    /// [`create_element`]: #method.create_element
+    /// [`Node::set_tag_name`]: type.Node.html#method.set_tag_name
+    /// [`Node::set_tag_name_checked`]: type.Node.html#method.set_tag_name_checked
+    /// [`Node::set_attribute_checked`]: type.Node.html#method.set_attribute_checked
+    /// [`Node::set_attribute`]: type.Node.html#method.set_attribute
+    /// [`is_valid_xml_name`]: fn.is_valid_xml_name.html
+    pub fn set_name_validator(&mut self, validator: Option<fn(&str) -> bool>) {
+        self.name_validator = validator;
+
+        for (_, node) in self.storage.iter_mut() {
+            node.borrow_mut().name_validator = validator;
+        }
+    }
+
+    fn is_valid_name(&self, name: &str) -> bool {
+        match self.name_validator {
+            Some(f) => f(name),
+            None => true,
+        }
+    }
+
     /// Returns the root [`Node`].
     ///
     /// [`Node`]: type.Node.html
@@ -187,6 +350,22 @@ impl Document {
         self.root.clone()
     }
 
+    /// Returns a node by its handle.
+    ///
+    /// Returns `None` if the node was removed or the handle belongs to another document.
+    /// Since handles don't contain a document reference, a handle from another document
+    /// can still point to a random node.
+    ///
+    /// See [`NodeId`] for details.
+    ///
+    /// [`NodeId`]: struct.NodeId.html
+    pub fn node(&self, id: NodeId) -> Option<Node> {
+        match self.storage.get(id.key) {
+            Some(node) if node.borrow().generation == id.generation => Some(node.clone()),
+            _ => None,
+        }
+    }
+
     /// Returns the first child with `svg` tag name of the root [`Node`].
     ///
     /// In most of the cases result of this method and `first_element_child()` will be the same,
@@ -252,6 +431,56 @@ impl Document {
         self._remove(node.clone(), &mut ids);
     }
 
+    /// Removes multiple nodes and their children from the tree.
+    ///
+    /// Works like [`remove_node`], but nodes that are descendants of other nodes
+    /// in the list are skipped, since they will be removed along with their ancestors.
+    /// Already removed nodes and duplicates are skipped too.
+    ///
+    /// Returns the number of removed nodes from the list, including descendants
+    /// that were removed along with their ancestors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the nodes or their adjoining nodes or any children node is currently borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId};
+    ///
+    /// let mut doc = Document::new();
+    /// let mut g = doc.create_element(ElementId::G);
+    /// let rect = doc.create_element(ElementId::Rect);
+    /// g.append(rect.clone());
+    /// doc.root().append(g.clone());
+    ///
+    /// assert_eq!(doc.remove_nodes(&[rect.clone(), g.clone(), g.clone()]), 2);
+    /// assert_eq!(doc.remove_nodes(&[rect]), 0);
+    /// assert!(!doc.root().has_children());
+    /// ```
+    ///
+    /// [`remove_node`]: #method.remove_node
+    pub fn remove_nodes(&mut self, nodes: &[Node]) -> usize {
+        let ids: HashSet<NodeId> = nodes.iter().filter_map(|n| n.node_id()).collect();
+
+        let mut attrs = Vec::with_capacity(16);
+        for node in nodes {
+            // Skip removed nodes and duplicates.
+            if node.is_detached() {
+                continue;
+            }
+
+            let is_nested = node.ancestors().skip(1)
+                .any(|n| n.node_id().map(|id| ids.contains(&id)) == Some(true));
+            if !is_nested {
+                self._remove(node.clone(), &mut attrs);
+            }
+        }
+
+        ids.len()
+    }
+
     fn _remove(&mut self, mut node: Node, ids: &mut Vec<AttributeQName>) {
         ids.clear();
 
@@ -377,6 +606,1186 @@ impl Document {
             }
         }
     }
+
+    /// Wraps a node into a new element of the specified type.
+    ///
+    /// The new element takes the position of the node in the tree
+    /// and the node becomes its only child.
+    ///
+    /// Returns the new element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node doesn't have a parent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId};
+    ///
+    /// let mut doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'><rect/></svg>").unwrap();
+    /// let rect = doc.svg_element().unwrap().first_child().unwrap();
+    /// doc.wrap_node(rect, ElementId::G);
+    /// assert_eq!(doc.to_string(),
+    ///            "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    <g>\n        <rect/>\n    </g>\n</svg>\n");
+    /// ```
+    pub fn wrap_node(&mut self, node: Node, tag: ElementId) -> Node {
+        self.group_siblings(&[node], tag)
+    }
+
+    /// Wraps consecutive sibling nodes into a new `g` element.
+    ///
+    /// The new element takes the position of the first node in the tree.
+    ///
+    /// Returns the new element.
+    ///
+    /// # Panics
+    ///
+    /// - If the list is empty.
+    /// - If the nodes are not consecutive siblings in the document order.
+    /// - If the nodes don't have a parent.
+    pub fn group_nodes(&mut self, nodes: &[Node]) -> Node {
+        self.group_siblings(nodes, ElementId::G)
+    }
+
+    fn group_siblings(&mut self, nodes: &[Node], tag: ElementId) -> Node {
+        assert!(!nodes.is_empty(), "the list of nodes is empty");
+        assert!(nodes[0].parent().is_some(), "the node doesn't have a parent");
+        for pair in nodes.windows(2) {
+            assert!(pair[0].next_sibling().as_ref() == Some(&pair[1]),
+                    "nodes are not consecutive siblings");
+        }
+
+        let wrapper = self.create_element(tag);
+        let mut first = nodes[0].clone();
+        first.insert_before(wrapper.clone());
+
+        for node in nodes {
+            let mut node = node.clone();
+            node.detach();
+            wrapper.clone().append(node);
+        }
+
+        wrapper
+    }
+
+    /// Splits a text node at the specified character offset.
+    ///
+    /// The current node keeps the text before the offset and a new text node
+    /// with the rest is inserted right after it.
+    ///
+    /// Returns the new node.
+    ///
+    /// # Panics
+    ///
+    /// - If the node is not a `Text` node.
+    /// - If the offset is greater than the number of characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let mut doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'><text>Текст</text></svg>").unwrap();
+    /// let text = doc.svg_element().unwrap().first_child().unwrap().first_child().unwrap();
+    /// let new_text = doc.split_text(text.clone(), 2);
+    /// assert_eq!(*text.text(), "Те");
+    /// assert_eq!(*new_text.text(), "кст");
+    /// ```
+    pub fn split_text(&mut self, mut node: Node, offset: usize) -> Node {
+        assert_eq!(node.node_type(), NodeType::Text);
+
+        let idx = {
+            let text = node.text();
+            match text.char_indices().nth(offset) {
+                Some((idx, _)) => idx,
+                None => {
+                    assert_eq!(text.chars().count(), offset, "offset is out of bounds");
+                    text.len()
+                }
+            }
+        };
+
+        let tail = node.text_mut().split_off(idx);
+        let new_node = self.create_node(NodeType::Text, tail);
+        node.insert_after(new_node.clone());
+
+        new_node
+    }
+
+    /// Merges adjacent text nodes and removes empty ones.
+    ///
+    /// Processes all descendants of the specified node. Only sibling nodes are merged,
+    /// so text inside different elements, and therefore with a different `xml:space`,
+    /// is never joined.
+    ///
+    /// The parser doesn't call this method, so text nodes are kept as they were parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, NodeType};
+    ///
+    /// let mut doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'><text>Text</text></svg>").unwrap();
+    /// let text = doc.svg_element().unwrap().first_child().unwrap();
+    /// doc.split_text(text.first_child().unwrap(), 2);
+    /// assert_eq!(text.children().count(), 2);
+    ///
+    /// doc.normalize(text.clone());
+    /// assert_eq!(text.children().count(), 1);
+    /// ```
+    pub fn normalize(&mut self, node: Node) {
+        let mut child = node.first_child();
+        while let Some(mut n) = child {
+            child = n.next_sibling();
+
+            if n.is_text() {
+                while let Some(next) = n.next_sibling() {
+                    if !next.is_text() {
+                        break;
+                    }
+
+                    n.text_mut().push_str(&next.text());
+                    child = next.next_sibling();
+                    self.remove_node(next);
+                }
+
+                if n.text().is_empty() {
+                    self.remove_node(n);
+                }
+            } else if n.has_children() {
+                self.normalize(n);
+            }
+        }
+    }
+
+    /// Returns all elements that have the specified attribute.
+    ///
+    /// See [`nodes_with_attribute_iter`] for a lazy variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, AttributeId as AId};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect transform='scale(2)'/>
+    ///         <rect/>
+    ///         <g display='none'>
+    ///             <rect transform='scale(2)'/>
+    ///         </g>
+    ///      </svg>").unwrap();
+    ///
+    /// assert_eq!(doc.nodes_with_attribute(AId::Transform).len(), 2);
+    /// assert_eq!(doc.nodes_with_attribute_iter(AId::Transform).only_visible().count(), 1);
+    /// ```
+    ///
+    /// [`nodes_with_attribute_iter`]: #method.nodes_with_attribute_iter
+    pub fn nodes_with_attribute(&self, id: AttributeId) -> Vec<Node> {
+        self.nodes_with_attribute_iter(id).collect()
+    }
+
+    /// Returns an iterator over elements that have the specified attribute.
+    pub fn nodes_with_attribute_iter(&self, id: AttributeId) -> NodesWithAttribute {
+        NodesWithAttribute {
+            iter: self.root.descendants(),
+            id,
+            only_visible: false,
+        }
+    }
+
+    /// Returns all nodes that match the predicate.
+    ///
+    /// Nodes are checked in the document order. The root node is checked too.
+    ///
+    /// The predicate runs under a shared borrow of the document, so it must not
+    /// mutate the tree. Modifying nodes inside the predicate may lead to skipped nodes
+    /// or a panic.
+    ///
+    /// See [`find_nodes_iter`] for a lazy variant.
+    ///
+    /// # Examples
+    ///
+    /// Find all red-filled shapes:
+    ///
+    /// ```
+    /// use svgdom::{Document, AttributeId as AId, AttributeValue, Color, ElementType};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect fill='red'/>
+    ///         <circle fill='#0000ff'/>
+    ///         <g fill='red'>
+    ///             <path fill='#ff0000'/>
+    ///         </g>
+    ///      </svg>").unwrap();
+    ///
+    /// let red = AttributeValue::Color(Color::new(255, 0, 0));
+    /// let nodes = doc.find_nodes(|n| {
+    ///     n.is_shape() && n.attributes().get_value(AId::Fill) == Some(&red)
+    /// });
+    /// assert_eq!(nodes.len(), 2);
+    /// ```
+    ///
+    /// [`find_nodes_iter`]: #method.find_nodes_iter
+    pub fn find_nodes<F>(&self, pred: F) -> Vec<Node>
+        where F: Fn(&Node) -> bool
+    {
+        self.find_nodes_iter(pred).collect()
+    }
+
+    /// Returns an iterator over nodes that match the predicate.
+    ///
+    /// See [`find_nodes`] for details.
+    ///
+    /// [`find_nodes`]: #method.find_nodes
+    pub fn find_nodes_iter<F>(&self, pred: F) -> FindNodes<F>
+        where F: Fn(&Node) -> bool
+    {
+        FindNodes {
+            iter: self.root.descendants(),
+            pred,
+        }
+    }
+
+    /// Returns all elements with the specified tag name.
+    ///
+    /// Intended for definition elements, like gradients, patterns, filters and markers.
+    ///
+    /// The whole tree is searched, not just `defs` elements, since definitions
+    /// can be placed anywhere. Nodes are returned in the document order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <defs>
+    ///             <linearGradient id='lg1'/>
+    ///         </defs>
+    ///         <g>
+    ///             <linearGradient id='lg2'/>
+    ///             <radialGradient id='rg1'/>
+    ///         </g>
+    ///      </svg>").unwrap();
+    ///
+    /// let ids: Vec<_> = doc.defs_of(ElementId::LinearGradient).iter().map(|n| n.id().clone()).collect();
+    /// assert_eq!(ids, vec!["lg1".to_string(), "lg2".to_string()]);
+    /// ```
+    pub fn defs_of(&self, id: ElementId) -> Vec<Node> {
+        self.find_nodes(|n| n.is_tag_name(id))
+    }
+
+    /// Returns an iterator over all links in the document.
+    ///
+    /// Each item is a `(source, attribute, target)` tuple, in the document order.
+    /// `Link`, `FuncLink` and `Paint` values are included.
+    ///
+    /// References that were not resolved during parsing are stored as strings
+    /// and are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, AttributeId as AId};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    ///         <linearGradient id='lg1'/>
+    ///         <linearGradient id='lg2' xlink:href='#lg1'/>
+    ///         <rect fill='url(#lg2)'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let links: Vec<_> = doc.links().map(|(src, aid, dst)| (src.id().clone(), aid, dst.id().clone())).collect();
+    /// assert_eq!(links, vec![
+    ///     ("lg2".to_string(), AId::Href, "lg1".to_string()),
+    ///     ("".to_string(), AId::Fill, "lg2".to_string()),
+    /// ]);
+    /// ```
+    pub fn links(&self) -> Links {
+        Links {
+            iter: self.root.descendants(),
+            node: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns a list of SVG features used by the document.
+    ///
+    /// Useful to check that a document can be handled by a limited renderer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <linearGradient id='lg1'/>
+    ///         <rect fill='url(#lg1)' onclick='alert(1)'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let features = doc.features_used();
+    /// assert!(features.gradients);
+    /// assert!(features.scripting);
+    /// assert!(!features.filters);
+    /// ```
+    pub fn features_used(&self) -> FeatureSet {
+        features::features_used(&self.root)
+    }
+
+    /// Returns the number of nodes in the document tree.
+    ///
+    /// The root node and nodes that are not attached to the tree are not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <!--comment-->
+    ///         <text>Text</text>
+    ///      </svg>").unwrap();
+    ///
+    /// assert_eq!(doc.node_count(), 4);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.root.descendants().count() - 1
+    }
+
+    /// Returns the number of attributes of all elements in the document tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect width='10' height='10'/>
+    ///      </svg>").unwrap();
+    ///
+    /// assert_eq!(doc.attribute_count(), 2);
+    /// ```
+    pub fn attribute_count(&self) -> usize {
+        self.root.descendants().filter(|n| n.is_element()).map(|n| n.attributes().len()).sum()
+    }
+
+    /// Returns the size of the serialized document in bytes.
+    ///
+    /// The result is the same as the length of the string produced
+    /// using the same `WriteOptions`, but the whole document is never stored in memory.
+    ///
+    /// The cost is the same as of the serialization itself, since the document
+    /// is still fully written, just to a counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, WriteBuffer, WriteOptions};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect width='10' height='10'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let opt = WriteOptions::default();
+    /// assert_eq!(doc.len_bytes(&opt), doc.with_write_opt(&opt).to_string().len());
+    /// ```
+    pub fn len_bytes(&self, opt: &WriteOptions) -> usize {
+        writer::serialized_len(self, opt)
+    }
+
+    /// Checks that SVG elements are nested according to the SVG content model.
+    ///
+    /// Returns a list of elements that are not allowed inside their parents,
+    /// in the document order. Non-SVG elements and their children are not checked.
+    ///
+    /// See [`ContentModel`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <g><stop/></g>
+    ///         <linearGradient><stop/></linearGradient>
+    ///      </svg>").unwrap();
+    ///
+    /// let errors = doc.validate_content_model();
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].to_string(), "element 'stop' is not allowed inside 'g'");
+    /// ```
+    ///
+    /// [`ContentModel`]: trait.ContentModel.html
+    pub fn validate_content_model(&self) -> Vec<ValidationError> {
+        content_model::validate_content_model(&self.root)
+    }
+
+    /// Returns a list of references to resources outside the document.
+    ///
+    /// Checks `xlink:href` attributes and `url()` references in other attributes.
+    /// Resolved links always point to elements inside the document, so only string values
+    /// are checked. Local references, like `#id`, are ignored.
+    ///
+    /// Each reference is returned only once, in the document order.
+    ///
+    /// Note that `style` elements are not preserved after parsing,
+    /// so `@import` rules cannot be detected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ExternalReference};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    ///         <image xlink:href='data:image/png;base64,iVBORw0KGgo='/>
+    ///         <use xlink:href='icons.svg#icon1'/>
+    ///         <rect cursor='url(pointer.cur), auto'/>
+    ///      </svg>").unwrap();
+    ///
+    /// assert_eq!(doc.external_references(), vec![
+    ///     ExternalReference::Data("image/png".to_string()),
+    ///     ExternalReference::Url("icons.svg#icon1".to_string()),
+    ///     ExternalReference::Url("pointer.cur".to_string()),
+    /// ]);
+    /// ```
+    pub fn external_references(&self) -> Vec<ExternalReference> {
+        external::external_references(&self.root)
+    }
+
+    /// Replaces references to external files with the resolved content.
+    ///
+    /// The `resolver` receives a URL and returns the file content.
+    /// When it returns `None`, the reference is left intact.
+    ///
+    /// - `image` and `feImage` links are replaced with base64 `data:` URLs.
+    ///   The MIME type is detected from the data or from the file extension.
+    /// - `use` links, like `icons.svg#icon1`, are resolved by parsing the file and copying
+    ///   the referenced element and the elements it links to into the `defs` element.
+    ///   The `use` element is linked to the copy. Colliding IDs are renamed.
+    ///
+    /// Each URL is resolved only once. References inside the imported content
+    /// are not resolved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let mut doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    ///         <image xlink:href='image.png'/>
+    ///         <image xlink:href='missing.png'/>
+    ///      </svg>").unwrap();
+    ///
+    /// doc.inline_references(|url| {
+    ///     if url == "image.png" { Some(vec![0x89, 0x50, 0x4E, 0x47]) } else { None }
+    /// });
+    ///
+    /// assert_eq!(doc.to_string(),
+    /// "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">
+    ///     <image xlink:href=\"data:image/png;base64,iVBORw==\"/>
+    ///     <image xlink:href=\"missing.png\"/>
+    /// </svg>
+    /// ");
+    /// ```
+    #[cfg(feature = "parsing")]
+    pub fn inline_references<F>(&mut self, resolver: F)
+        where F: FnMut(&str) -> Option<Vec<u8>>
+    {
+        external::inline_references(self, resolver)
+    }
+
+    /// Prepends a transform to the transform of each specified element.
+    ///
+    /// The resulting transform is `ts * current`, so `ts` is applied
+    /// in the parent coordinate system. The attribute is created when absent.
+    ///
+    /// Gradients and patterns use the `gradientTransform` and `patternTransform`
+    /// attributes respectively. Non-element nodes are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, Transform};
+    ///
+    /// let mut doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect transform='scale(2)'/>
+    ///         <rect/>
+    ///      </svg>").unwrap();
+    ///
+    /// let nodes: Vec<_> = doc.svg_element().unwrap().children().collect();
+    /// doc.apply_transform_to(&nodes, &Transform::new_translate(10.0, 20.0));
+    ///
+    /// assert_eq!(doc.to_string(),
+    /// "<svg xmlns=\"http://www.w3.org/2000/svg\">
+    ///     <rect transform=\"matrix(2 0 0 2 10 20)\"/>
+    ///     <rect transform=\"matrix(1 0 0 1 10 20)\"/>
+    /// </svg>
+    /// ");
+    /// ```
+    pub fn apply_transform_to(&mut self, nodes: &[Node], ts: &Transform) {
+        for node in nodes {
+            if !node.is_element() {
+                continue;
+            }
+
+            let aid = if node.is_gradient() {
+                AttributeId::GradientTransform
+            } else if node.is_tag_name(ElementId::Pattern) {
+                AttributeId::PatternTransform
+            } else {
+                AttributeId::Transform
+            };
+
+            let mut new_ts = *ts;
+            if let Some(&AttributeValue::Transform(ref curr)) = node.attributes().get_value(aid) {
+                new_ts.append(curr);
+            }
+
+            let mut node = node.clone();
+            node.set_attribute((aid, new_ts));
+        }
+    }
+
+    /// Calls a closure for each SVG attribute value in the document.
+    ///
+    /// Elements are visited in the document order.
+    /// Link values (`Link`, `FuncLink` and `Paint`) and non-SVG attributes are skipped.
+    ///
+    /// The closure receives a copy of the value, which is written back afterwards.
+    /// So the node can be accessed inside the closure, but any changes to the visited
+    /// attribute made via the node will be overwritten.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the closure sets a link value, since links are tracked by the nodes
+    /// and must be set via [`Node::set_attribute`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, AttributeValue};
+    ///
+    /// let mut doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect x='10.4' y='20.6' opacity='0.5'/>
+    ///      </svg>").unwrap();
+    ///
+    /// doc.visit_values_mut(|_, _, value| {
+    ///     if let AttributeValue::Length(ref mut len) = *value {
+    ///         len.num = len.num.round();
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(doc.to_string(),
+    /// "<svg xmlns=\"http://www.w3.org/2000/svg\">
+    ///     <rect opacity=\"0.5\" x=\"10\" y=\"21\"/>
+    /// </svg>
+    /// ");
+    /// ```
+    ///
+    /// [`Node::set_attribute`]: type.Node.html#method.set_attribute
+    pub fn visit_values_mut<F>(&mut self, mut f: F)
+        where F: FnMut(&Node, AttributeId, &mut AttributeValue)
+    {
+        for mut node in self.root.descendants().filter(|n| n.is_element()) {
+            let ids: Vec<AttributeId> = node.attributes().iter()
+                .filter(|a| !a.is_link_container())
+                .filter_map(|a| a.id())
+                .collect();
+
+            for aid in ids {
+                let mut value = match node.attributes().get_value(aid) {
+                    Some(v) => v.clone(),
+                    None => continue,
+                };
+
+                f(&node, aid, &mut value);
+
+                assert!(!value.is_link_container(),
+                        "a link value cannot be set by visit_values_mut");
+
+                if let Some(v) = node.attributes_mut().get_value_mut(aid) {
+                    *v = value;
+                }
+            }
+        }
+    }
+
+    /// Scales the document by the specified factor.
+    ///
+    /// All coordinates and lengths are multiplied by the factor, including
+    /// the root element size, `viewBox`, paths, stroke widths and font sizes.
+    /// Transforms are adjusted accordingly.
+    ///
+    /// Percentages, `em` and `ex` units, numbers like opacity and values defined
+    /// relative to the object bounding box, like gradients with the default
+    /// `gradientUnits`, are left untouched. The same applies to markers with
+    /// the default `markerUnits`, since they are scaled by the stroke width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let mut doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='50%' viewBox='0 0 100 100'>
+    ///         <rect x='10' width='20mm' stroke-width='2' opacity='0.5' transform='translate(5 0)'/>
+    ///      </svg>").unwrap();
+    ///
+    /// doc.scale(2.0);
+    ///
+    /// assert_eq!(doc.to_string(),
+    /// "<svg xmlns=\"http://www.w3.org/2000/svg\" height=\"50%\" viewBox=\"0 0 200 200\" width=\"200\">
+    ///     <rect opacity=\"0.5\" stroke-width=\"4\" transform=\"matrix(1 0 0 1 10 0)\" width=\"40mm\" x=\"20\"/>
+    /// </svg>
+    /// ");
+    /// ```
+    pub fn scale(&mut self, factor: f64) {
+        geometry::scale_document(self, factor);
+    }
+
+    /// Translates the document content by the specified offset.
+    ///
+    /// Only the root `svg` element content is affected. The offset is defined
+    /// in the user space of the root element.
+    ///
+    /// `TranslateMode::Wrap` is the safest method. It simply wraps the content into
+    /// a new group. `TranslateMode::Rewrite` produces a cleaner output by offsetting
+    /// coordinates of shapes, images, `use`, `text` and nested `svg` elements directly.
+    /// Groups without a transform are processed recursively.
+    /// Elements that have a transform or reference other elements, like gradients,
+    /// will get the `translate` transform instead. `use` elements that reference
+    /// moved elements are adjusted, so the offset would not be applied twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, TranslateMode};
+    ///
+    /// let mut doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect x='10' width='20' height='20'/>
+    ///      </svg>").unwrap();
+    ///
+    /// doc.translate(5.0, 10.0, TranslateMode::Rewrite);
+    ///
+    /// assert_eq!(doc.to_string(),
+    /// "<svg xmlns=\"http://www.w3.org/2000/svg\">
+    ///     <rect height=\"20\" width=\"20\" x=\"15\" y=\"10\"/>
+    /// </svg>
+    /// ");
+    /// ```
+    pub fn translate(&mut self, dx: f64, dy: f64, mode: TranslateMode) {
+        geometry::translate_document(self, dx, dy, mode);
+    }
+
+    /// Returns the size of the root `svg` element in pixels.
+    ///
+    /// This is the size that renderers should use for the canvas.
+    ///
+    /// - Absolute `width` and `height` are converted into pixels using 96 DPI.
+    /// - Percentages are resolved against `default_size`.
+    /// - When only one of them is set, the other one is computed using
+    ///   the `viewBox` aspect ratio.
+    /// - When both are missing, the `viewBox` size is used.
+    /// - Otherwise, missing values are taken from `default_size`.
+    ///
+    /// Negative values and `em`/`ex` units are treated as missing.
+    /// `preserveAspectRatio` doesn't affect the size, only how the `viewBox` is fitted into it.
+    ///
+    /// Returns `default_size` when there is no root `svg` element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' width='1in' viewBox='0 0 20 10'/>").unwrap();
+    /// assert_eq!(doc.resolve_size((100.0, 100.0)), (96.0, 48.0));
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' width='50%' height='10'/>").unwrap();
+    /// assert_eq!(doc.resolve_size((100.0, 100.0)), (50.0, 10.0));
+    /// ```
+    pub fn resolve_size(&self, default_size: (f64, f64)) -> (f64, f64) {
+        geometry::document_size(self, default_size)
+    }
+
+    /// Imports the content of another document.
+    ///
+    /// The root `svg` element content of the `other` document is copied into a new `g` element,
+    /// which is appended to the root `svg` element of the current document.
+    /// An `svg` element will be created when the current document doesn't have one.
+    /// The `offset` is set as the `translate` transform of the new group.
+    ///
+    /// The root `svg` element attributes of the `other` document, like `viewBox`,
+    /// are not preserved.
+    ///
+    /// IDs that are already present in the current document are renamed
+    /// to the shortest unused IDs, like [`postproc::minify_ids`] does.
+    /// Links inside the imported content are preserved.
+    ///
+    /// Returns the new group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let mut doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <linearGradient id='lg1'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let other = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <linearGradient id='lg1'/>
+    ///         <rect fill='url(#lg1)'/>
+    ///      </svg>").unwrap();
+    ///
+    /// doc.merge(other, Some((10.0, 0.0)));
+    ///
+    /// assert_eq!(doc.to_string(),
+    /// "<svg xmlns=\"http://www.w3.org/2000/svg\">
+    ///     <linearGradient id=\"lg1\"/>
+    ///     <g transform=\"matrix(1 0 0 1 10 0)\">
+    ///         <linearGradient id=\"a\"/>
+    ///         <rect fill=\"url(#a)\"/>
+    ///     </g>
+    /// </svg>
+    /// ");
+    /// ```
+    ///
+    /// [`postproc::minify_ids`]: postproc/fn.minify_ids.html
+    pub fn merge(&mut self, other: Document, offset: Option<(f64, f64)>) -> Node {
+        merge::merge_document(self, other, offset)
+    }
+
+    /// Imports the content of another document as a `symbol` element.
+    ///
+    /// The root `svg` element content of the `other` document is copied into a new `symbol`
+    /// element with the specified ID, which is appended to the first `defs` element
+    /// of the root `svg` element. Missing `svg` and `defs` elements will be created.
+    ///
+    /// The `viewBox` and `preserveAspectRatio` attributes are copied from the root `svg`
+    /// element of the `other` document. When `viewBox` is not set, it will be created
+    /// from the `width` and `height` attributes, if possible.
+    ///
+    /// IDs inside the symbol are handled the same way as in [`merge`].
+    ///
+    /// Returns the new symbol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ID is empty or already used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let icon = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' width='24' height='24'>
+    ///         <circle r='10'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let mut doc = Document::new();
+    /// doc.add_as_symbol(&icon, "icon1");
+    /// doc.add_as_symbol(&icon, "icon2");
+    ///
+    /// assert_eq!(doc.to_string(),
+    /// "<svg xmlns=\"http://www.w3.org/2000/svg\">
+    ///     <defs>
+    ///         <symbol id=\"icon1\" viewBox=\"0 0 24 24\">
+    ///             <circle r=\"10\"/>
+    ///         </symbol>
+    ///         <symbol id=\"icon2\" viewBox=\"0 0 24 24\">
+    ///             <circle r=\"10\"/>
+    ///         </symbol>
+    ///     </defs>
+    /// </svg>
+    /// ");
+    /// ```
+    ///
+    /// [`merge`]: #method.merge
+    pub fn add_as_symbol(&mut self, other: &Document, id: &str) -> Node {
+        merge::add_as_symbol(self, other, id)
+    }
+
+    /// Returns an immutable, thread-safe copy of the document tree.
+    ///
+    /// See [`Snapshot`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str("<svg xmlns='http://www.w3.org/2000/svg'/>").unwrap();
+    /// let snapshot = Arc::new(doc.snapshot());
+    ///
+    /// let s = snapshot.clone();
+    /// let count = thread::spawn(move || s.len()).join().unwrap();
+    /// assert_eq!(count, 2);
+    /// ```
+    ///
+    /// [`Snapshot`]: struct.Snapshot.html
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::from(self.to_flat())
+    }
+
+    /// Converts the document into an index-based representation.
+    ///
+    /// See [`FlatDom`] for details.
+    ///
+    /// [`FlatDom`]: struct.FlatDom.html
+    pub fn to_flat(&self) -> FlatDom {
+        flat::to_flat(self)
+    }
+
+    /// Constructs a new `Document` from an index-based representation.
+    ///
+    /// # Errors
+    ///
+    /// - [`InvalidNodeType`] if the first node is not a root node
+    ///   or if there are other root nodes.
+    /// - [`ElementMustHaveAnId`]
+    /// - [`ElementCrosslink`]
+    ///
+    /// # Panics
+    ///
+    /// - If a node index is out of bounds.
+    /// - If an element has an empty string tag name.
+    ///
+    /// [`InvalidNodeType`]: enum.Error.html
+    /// [`ElementMustHaveAnId`]: enum.Error.html
+    /// [`ElementCrosslink`]: enum.Error.html
+    pub fn from_flat(flat: &FlatDom) -> Result<Document, Error> {
+        flat::from_flat(flat)
+    }
+
+    /// Returns a list of differences between two documents.
+    ///
+    /// Child nodes are matched by ID where possible and by position otherwise.
+    /// Nodes that were matched are compared recursively.
+    ///
+    /// Matching is forward-only, since matched nodes must keep their order:
+    /// when children with IDs were reordered, only the ones that kept their
+    /// relative order are matched and the rest are reported as removed and added.
+    ///
+    /// Attribute values are compared semantically: numbers are compared using
+    /// fuzzy comparison and links are compared by the ID of the referenced element.
+    ///
+    /// All nodes in the returned changes belong to `self`, except the added nodes
+    /// and links inside the added or changed attributes, which belong to `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, DomChange};
+    ///
+    /// let doc1 = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'><rect width='10'/></svg>").unwrap();
+    /// let doc2 = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'><rect width='10.0'/></svg>").unwrap();
+    /// assert!(doc1.diff(&doc2).is_empty());
+    ///
+    /// let doc3 = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'><rect width='20'/></svg>").unwrap();
+    /// match doc1.diff(&doc3)[0] {
+    ///     DomChange::AttributeChanged { .. } => {}
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn diff(&self, other: &Document) -> Vec<DomChange> {
+        diff::diff(self, other)
+    }
+
+    /// Returns a human-readable report of differences between two documents.
+    ///
+    /// The report is based on [`diff`], so semantically equal documents,
+    /// like ones with reordered attributes, produce an empty string.
+    ///
+    /// Changes are grouped by the node they belong to, which is printed as a locator
+    /// produced by [`Node::xpath`]. Attribute values and nodes are written using
+    /// the specified `WriteOptions`, but added and removed elements are shown
+    /// without their children.
+    ///
+    /// Useful for printing a failed comparison in tests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, WriteOptions};
+    ///
+    /// let doc1 = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect width='10' height='20'/>
+    ///         <circle id='circle1'/>
+    ///      </svg>").unwrap();
+    /// let doc2 = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect height='20' width='15'/>
+    ///         <ellipse/>
+    ///      </svg>").unwrap();
+    ///
+    /// let mut opt = WriteOptions::default();
+    /// opt.use_single_quote = true;
+    /// assert_eq!(doc1.diff_text(&doc2, &opt),
+    /// "--- original
+    /// +++ modified
+    /// @@ /svg @@
+    /// - <circle id='circle1'/>
+    /// @@ /svg/rect[1] @@
+    /// - width='10'
+    /// + width='15'
+    /// @@ /svg @@
+    /// + <ellipse/>
+    /// ");
+    /// ```
+    ///
+    /// [`diff`]: #method.diff
+    /// [`Node::xpath`]: type.Node.html#method.xpath
+    pub fn diff_text(&self, other: &Document, opt: &WriteOptions) -> String {
+        diff::diff_text(self, other, opt)
+    }
+
+    /// Applies changes produced by [`diff`] to the document.
+    ///
+    /// The changes must be computed against this document, because nodes
+    /// are matched by reference and not by value. Links inside the added
+    /// or changed attributes are resolved by ID.
+    ///
+    /// The order of children is preserved, so after applying `a.diff(&b)` to `a`
+    /// it will be equal to `b`.
+    ///
+    /// # Errors
+    ///
+    /// - [`InvalidPatch`] if a referenced node is not a part of this document,
+    ///   an attribute to change or remove is missing, an attribute to add is already set,
+    ///   a current attribute value or text doesn't match the expected one
+    ///   or a link can't be resolved.
+    /// - [`ElementMustHaveAnId`]
+    /// - [`ElementCrosslink`]
+    ///
+    /// All errors are detected before any changes are made,
+    /// so the document is never modified on error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let mut doc1 = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'><rect width='10'/></svg>").unwrap();
+    /// let doc2 = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'><circle r='5'/></svg>").unwrap();
+    ///
+    /// let changes = doc1.diff(&doc2);
+    /// doc1.apply_patch(&changes).unwrap();
+    /// assert_eq!(doc1.to_string(), doc2.to_string());
+    ///
+    /// // The same patch can't be applied twice.
+    /// assert!(doc1.apply_patch(&changes).is_err());
+    /// ```
+    ///
+    /// [`diff`]: #method.diff
+    /// [`InvalidPatch`]: enum.Error.html
+    /// [`ElementMustHaveAnId`]: enum.Error.html
+    /// [`ElementCrosslink`]: enum.Error.html
+    pub fn apply_patch(&mut self, changes: &[DomChange]) -> Result<(), Error> {
+        diff::apply_patch(self, changes)
+    }
+
+    /// Returns a node by an XPath-like locator produced by [`Node::xpath`].
+    ///
+    /// Only absolute paths of child steps with optional positional predicates are supported,
+    /// like `/svg/g[2]/rect[1]`. A step without a predicate selects the first matching node.
+    ///
+    /// Returns `None` if the path is malformed or doesn't point to a node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <g/>
+    ///         <g><rect id='rect1'/></g>
+    ///     </svg>").unwrap();
+    ///
+    /// let rect = doc.node_at_xpath("/svg/g[2]/rect[1]").unwrap();
+    /// assert_eq!(*rect.id(), "rect1");
+    /// assert_eq!(doc.node_at_xpath(&rect.xpath()), Some(rect));
+    ///
+    /// assert_eq!(doc.node_at_xpath("/svg/g[3]"), None);
+    /// ```
+    ///
+    /// [`Node::xpath`]: struct.Node.html#method.xpath
+    pub fn node_at_xpath(&self, path: &str) -> Option<Node> {
+        xpath::resolve(self, path)
+    }
+}
+
+/// An iterator over elements that have the specified attribute.
+///
+/// Created by [`Document::nodes_with_attribute_iter`].
+///
+/// [`Document::nodes_with_attribute_iter`]: struct.Document.html#method.nodes_with_attribute_iter
+pub struct NodesWithAttribute {
+    iter: Descendants<NodeData>,
+    id: AttributeId,
+    only_visible: bool,
+}
+
+impl NodesWithAttribute {
+    /// Skips elements that are not rendered because of
+    /// the `display` or `visibility` attributes.
+    pub fn only_visible(mut self) -> Self {
+        self.only_visible = true;
+        self
+    }
+}
+
+impl Iterator for NodesWithAttribute {
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = try_opt!(self.iter.next());
+
+            if !node.is_element() || !node.has_attribute(self.id) {
+                continue;
+            }
+
+            if self.only_visible && !is_visible(&node) {
+                continue;
+            }
+
+            return Some(node);
+        }
+    }
+}
+
+/// An iterator over all links in the document.
+///
+/// Created by [`Document::links`].
+///
+/// [`Document::links`]: struct.Document.html#method.links
+pub struct Links {
+    iter: Descendants<NodeData>,
+    node: Option<Node>,
+    // Links of the current node in the reversed order.
+    pending: Vec<(AttributeId, Node)>,
+}
+
+impl Iterator for Links {
+    type Item = (Node, AttributeId, Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((aid, target)) = self.pending.pop() {
+                if let Some(ref node) = self.node {
+                    return Some((node.clone(), aid, target));
+                }
+            }
+
+            let node = try_opt!(self.iter.next());
+
+            if !node.is_element() {
+                continue;
+            }
+
+            for attr in node.attributes().iter().rev() {
+                let aid = match attr.id() {
+                    Some(aid) => aid,
+                    None => continue,
+                };
+
+                match attr.value {
+                    AttributeValue::Link(ref link) |
+                    AttributeValue::FuncLink(ref link) |
+                    AttributeValue::Paint(ref link, _) => {
+                        self.pending.push((aid, link.clone()));
+                    }
+                    _ => {}
+                }
+            }
+
+            self.node = Some(node);
+        }
+    }
+}
+
+/// An iterator over nodes that match a predicate.
+///
+/// Created by [`Document::find_nodes_iter`].
+///
+/// [`Document::find_nodes_iter`]: struct.Document.html#method.find_nodes_iter
+pub struct FindNodes<F> {
+    iter: Descendants<NodeData>,
+    pred: F,
+}
+
+impl<F> Iterator for FindNodes<F>
+    where F: Fn(&Node) -> bool
+{
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = try_opt!(self.iter.next());
+
+            if (self.pred)(&node) {
+                return Some(node);
+            }
+        }
+    }
+}
+
+fn is_visible(node: &Node) -> bool {
+    // `display` is not inheritable, but affects all children.
+    let is_display_none = node.ancestors().any(|n| {
+        match n.attributes().get_value(AttributeId::Display) {
+            Some(&AttributeValue::None) => true,
+            Some(&AttributeValue::String(ref v)) => v == "none",
+            _ => false,
+        }
+    });
+
+    if is_display_none {
+        return false;
+    }
+
+    // `visibility` is inheritable, so the closest defined value is used.
+    for n in node.ancestors() {
+        match n.attributes().get_value(AttributeId::Visibility) {
+            Some(&AttributeValue::Inherit) | None => continue,
+            Some(&AttributeValue::String(ref v)) => return v != "hidden" && v != "collapse",
+            Some(_) => return true,
+        }
+    }
+
+    true
 }
 
 impl WriteBuffer for Document {
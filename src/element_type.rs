@@ -14,12 +14,24 @@ use {
 /// This trait contains methods that check element's type according to the
 /// [SVG spec](https://www.w3.org/TR/SVG/intro.html#Definitions).
 ///
-/// Note that all methods works with `Node` type and will return `false`
-/// if node's type is not equal to `NodeType::Element`.
+/// It's implemented for `ElementId` and `Node`.
+///
+/// Note that methods of the `Node` type will return `false`
+/// if node's type is not equal to `NodeType::Element` or if the element is unknown.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{ElementId, ElementType};
+///
+/// assert!(ElementId::G.is_container());
+/// assert!(ElementId::Rect.is_graphic());
+/// assert!(!ElementId::Rect.is_container());
+/// ```
 ///
 /// # Panics
 ///
-/// All methods panics if the node is currently mutability borrowed.
+/// All methods of the `Node` type panics if the node is currently mutability borrowed.
 pub trait ElementType {
     /// Returns true if the current node is referenced.
     ///
@@ -115,19 +127,15 @@ pub trait ElementType {
 macro_rules! is_func {
     ($name:ident, $($pattern:tt)+) => (
         fn $name(&self) -> bool {
-            if let Some(id) = self.tag_id() {
-                match id {
-                    $($pattern)+ => true,
-                    _ => false
-                }
-            } else {
-                false
+            match *self {
+                $($pattern)+ => true,
+                _ => false
             }
         }
     )
 }
 
-impl ElementType for Node {
+impl ElementType for ElementId {
     is_func!(is_referenced,
           ElementId::AltGlyphDef
         | ElementId::ClipPath
@@ -222,3 +230,29 @@ impl ElementType for Node {
         | ElementId::FeTile
         | ElementId::FeTurbulence);
 }
+
+macro_rules! node_is_func {
+    ($($name:ident),+) => ($(
+        fn $name(&self) -> bool {
+            match self.tag_id() {
+                Some(id) => id.$name(),
+                None => false,
+            }
+        }
+    )+)
+}
+
+impl ElementType for Node {
+    node_is_func!(
+        is_referenced,
+        is_basic_shape,
+        is_shape,
+        is_container,
+        is_text_content,
+        is_text_content_child,
+        is_graphic,
+        is_gradient,
+        is_paint_server,
+        is_filter_primitive
+    );
+}
@@ -9,10 +9,11 @@
 use std::error;
 use std::fmt;
 
+#[cfg(feature = "parsing")]
 use roxmltree::{self, TextPos};
 
 /// SVG DOM errors.
-#[derive(Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Error {
     /// If you want to use referenced element inside link attribute,
     /// such element must have a non-empty ID.
@@ -33,6 +34,24 @@ pub enum Error {
     /// <linearGradient id="lg1" xlink:href="#lg1"/>
     /// ```
     ElementCrosslink,
+
+    /// A patch can't be applied to the document.
+    ///
+    /// Either a referenced node or attribute is missing, or a current value
+    /// doesn't match the expected one.
+    InvalidPatch,
+
+    /// The operation is not supported by the node type.
+    ///
+    /// For example, element and root nodes can't contain text data.
+    InvalidNodeType,
+
+    /// A name was rejected by the document name validator.
+    ///
+    /// See [`Document::set_name_validator`] for details.
+    ///
+    /// [`Document::set_name_validator`]: struct.Document.html#method.set_name_validator
+    InvalidName,
 }
 
 impl fmt::Display for Error {
@@ -44,6 +63,15 @@ impl fmt::Display for Error {
             Error::ElementCrosslink => {
                 write!(f, "element crosslink")
             }
+            Error::InvalidPatch => {
+                write!(f, "the patch can't be applied to the document")
+            }
+            Error::InvalidNodeType => {
+                write!(f, "the operation is not supported by the node type")
+            }
+            Error::InvalidName => {
+                write!(f, "invalid name")
+            }
         }
     }
 }
@@ -56,6 +84,7 @@ impl error::Error for Error {
 
 
 /// SVG parsing errors.
+#[cfg(feature = "parsing")]
 #[derive(Debug)]
 pub enum ParserError {
     /// Parsed document must have an `svg` element.
@@ -72,8 +101,93 @@ pub enum ParserError {
 
     /// A `roxmltree` error.
     RoXmlError(roxmltree::Error),
+
+    /// The document has more nodes than allowed by [`ParseOptions::max_nodes`].
+    ///
+    /// [`ParseOptions::max_nodes`]: struct.ParseOptions.html#structfield.max_nodes
+    LimitExceeded,
 }
 
+#[cfg(feature = "parsing")]
+impl ParserError {
+    /// Returns the error position in the source text, when available.
+    pub fn pos(&self) -> Option<TextPos> {
+        match *self {
+            ParserError::UnsupportedCSS(pos) |
+            ParserError::InvalidAttributeValue(pos) => Some(pos),
+            ParserError::RoXmlError(ref e) => Some(e.pos()),
+            ParserError::NoSvgElement |
+            ParserError::DomError(_) |
+            ParserError::LimitExceeded => None,
+        }
+    }
+
+    /// Returns an object that implements `fmt::Display` with the offending
+    /// line of the source text and a caret under the error column.
+    ///
+    /// `text` must be the same text that was passed to the parser.
+    ///
+    /// The error doesn't keep the source text, so its own `Display` and `Debug`
+    /// implementations don't show the source context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let text = "<svg xmlns='http://www.w3.org/2000/svg'>\n    <rect width='qwe'/>\n</svg>";
+    /// let err = Document::from_str(text).err().unwrap();
+    /// assert_eq!(err.with_source(text).to_string(),
+    ///            "invalid attribute value at 2:18\n    <rect width='qwe'/>\n                 ^");
+    /// ```
+    pub fn with_source<'a>(&'a self, text: &'a str) -> DisplayParserError<'a> {
+        DisplayParserError { error: self, text }
+    }
+}
+
+/// A parsing error with the source context.
+///
+/// Created by [`ParserError::with_source`].
+///
+/// [`ParserError::with_source`]: enum.ParserError.html#method.with_source
+#[cfg(feature = "parsing")]
+pub struct DisplayParserError<'a> {
+    error: &'a ParserError,
+    text: &'a str,
+}
+
+#[cfg(feature = "parsing")]
+impl<'a> fmt::Display for DisplayParserError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+
+        let pos = match self.error.pos() {
+            Some(pos) => pos,
+            None => return Ok(()),
+        };
+
+        // Rows and columns are 1-based.
+        if pos.row == 0 || pos.col == 0 {
+            return Ok(());
+        }
+
+        let line = match self.text.lines().nth(pos.row as usize - 1) {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+
+        write!(f, "\n{}\n", line)?;
+
+        // Tabs are preserved, so the caret is aligned regardless of the tab width.
+        for c in line.chars().take(pos.col as usize - 1) {
+            write!(f, "{}", if c == '\t' { '\t' } else { ' ' })?;
+        }
+
+        write!(f, "^")
+    }
+}
+
+#[cfg(feature = "parsing")]
 impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -92,22 +206,28 @@ impl fmt::Display for ParserError {
             ParserError::RoXmlError(ref e) => {
                 write!(f, "{}", e)
             }
+            ParserError::LimitExceeded => {
+                write!(f, "the document has too many nodes")
+            }
         }
     }
 }
 
+#[cfg(feature = "parsing")]
 impl error::Error for ParserError {
     fn description(&self) -> &str {
         "an SVG parsing error"
     }
 }
 
+#[cfg(feature = "parsing")]
 impl From<Error> for ParserError {
     fn from(value: Error) -> Self {
         ParserError::DomError(value)
     }
 }
 
+#[cfg(feature = "parsing")]
 impl From<roxmltree::Error> for ParserError {
     fn from(value: roxmltree::Error) -> Self {
         ParserError::RoXmlError(value)
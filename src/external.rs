@@ -0,0 +1,214 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[cfg(feature = "parsing")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "parsing")]
+use std::str;
+
+#[cfg(feature = "parsing")]
+use merge;
+#[cfg(feature = "parsing")]
+use postproc;
+use {
+    AttributeId,
+    AttributeValue,
+    Node,
+};
+#[cfg(feature = "parsing")]
+use {
+    Document,
+    ElementId,
+};
+
+/// A reference to a resource outside the document.
+///
+/// See [`Document::external_references`] for details.
+///
+/// [`Document::external_references`]: struct.Document.html#method.external_references
+#[derive(Clone, PartialEq, Debug)]
+pub enum ExternalReference {
+    /// A URL of an external file, like `image.png` or `http://example.com/image.svg#id`.
+    Url(String),
+    /// A self-contained `data:` URL.
+    ///
+    /// Contains only the MIME type, like `image/png`, since the data itself can be large.
+    /// The MIME type is empty when not set.
+    Data(String),
+}
+
+pub fn external_references(root: &Node) -> Vec<ExternalReference> {
+    let mut list = Vec::new();
+
+    for node in root.descendants().filter(|n| n.is_element()) {
+        for attr in node.attributes().iter() {
+            let value = match attr.value {
+                AttributeValue::String(ref value) => value,
+                _ => continue,
+            };
+
+            if attr.has_id(AttributeId::Href) {
+                push_reference(value, &mut list);
+            } else {
+                let mut text = value.as_str();
+                while let Some(start) = text.find("url(") {
+                    text = &text[start + 4..];
+                    let end = text.find(')').unwrap_or(text.len());
+                    push_reference(&text[..end], &mut list);
+                    text = &text[end..];
+                }
+            }
+        }
+    }
+
+    list
+}
+
+fn push_reference(url: &str, list: &mut Vec<ExternalReference>) {
+    let url = url.trim().trim_matches(|c| c == '\'' || c == '"').trim();
+    if url.is_empty() || url.starts_with('#') {
+        return;
+    }
+
+    let is_data = url.len() >= 5 && url.is_char_boundary(5) && url[..5].to_lowercase() == "data:";
+    let reference = if is_data {
+        let mime = &url[5..];
+        let end = mime.find(&[';', ','][..]).unwrap_or(mime.len());
+        ExternalReference::Data(mime[..end].trim().to_string())
+    } else {
+        ExternalReference::Url(url.to_string())
+    };
+
+    if !list.contains(&reference) {
+        list.push(reference);
+    }
+}
+
+#[cfg(feature = "parsing")]
+pub fn inline_references<F>(doc: &mut Document, mut resolver: F)
+    where F: FnMut(&str) -> Option<Vec<u8>>
+{
+    // Parsed external documents by file name.
+    let mut documents: HashMap<String, Option<Document>> = HashMap::new();
+    // Imported elements by the original reference.
+    let mut imported: HashMap<String, Node> = HashMap::new();
+
+    let nodes: Vec<Node> = doc.root().descendants().filter(|n| n.is_element()).collect();
+    for mut node in nodes {
+        let href = match node.attributes().get_value(AttributeId::Href) {
+            Some(&AttributeValue::String(ref href)) => href.trim().to_string(),
+            _ => continue,
+        };
+
+        if href.is_empty() || href.starts_with('#') || href.starts_with("data:") {
+            continue;
+        }
+
+        if node.is_tag_name(ElementId::Image) || node.is_tag_name(ElementId::FeImage) {
+            if let Some(data) = resolver(&href) {
+                let mime = guess_mime(&href, &data);
+                node.set_image_data(mime, &data);
+            }
+        } else if node.is_tag_name(ElementId::Use) {
+            let elem = match imported.get(&href) {
+                Some(elem) => Some(elem.clone()),
+                None => import_element(doc, &href, &mut resolver, &mut documents),
+            };
+
+            if let Some(elem) = elem {
+                imported.insert(href, elem.clone());
+                node.set_attribute((AttributeId::Href, elem));
+            }
+        }
+    }
+}
+
+// Copies an element referenced via `file.svg#id` into the `defs` element.
+#[cfg(feature = "parsing")]
+fn import_element<F>(
+    doc: &mut Document,
+    href: &str,
+    resolver: &mut F,
+    documents: &mut HashMap<String, Option<Document>>,
+) -> Option<Node>
+    where F: FnMut(&str) -> Option<Vec<u8>>
+{
+    let idx = try_opt!(href.find('#'));
+    let (file, id) = (&href[..idx], &href[idx + 1..]);
+
+    if file.is_empty() || id.is_empty() {
+        return None;
+    }
+
+    if !documents.contains_key(file) {
+        let other = resolver(file).and_then(|data| {
+            let text = match str::from_utf8(&data) {
+                Ok(text) => text,
+                Err(_) => {
+                    warn!("'{}' is not an UTF-8 file.", file);
+                    return None;
+                }
+            };
+
+            match Document::from_str(text) {
+                Ok(other) => Some(other),
+                Err(e) => {
+                    warn!("Failed to parse '{}': {}.", file, e);
+                    None
+                }
+            }
+        });
+
+        documents.insert(file.to_string(), other);
+    }
+
+    let other = try_opt!(documents[file].as_ref());
+    let elem = try_opt!(other.root().descendants().find(|n| *n.id() == id));
+
+    let mut used_ids: HashSet<String> = doc.root().descendants()
+        .filter(|n| n.has_id())
+        .map(|n| n.id().clone())
+        .collect();
+
+    // Elements referenced by the imported element, like gradients, are copied too.
+    let mut nodes = merge::dependencies(&elem);
+    nodes.push(elem);
+
+    let mut defs = merge::defs_element(doc);
+    merge::import_nodes(doc, &nodes, &mut defs);
+
+    let copies: Vec<Node> = defs.children().skip(defs.children().count() - nodes.len()).collect();
+    postproc::rename_colliding_ids(&copies, &mut used_ids);
+
+    copies.last().cloned()
+}
+
+#[cfg(feature = "parsing")]
+fn guess_mime(href: &str, data: &[u8]) -> &'static str {
+    if data.starts_with(b"\x89PNG") {
+        return "image/png";
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return "image/jpeg";
+    } else if data.starts_with(b"GIF8") {
+        return "image/gif";
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+
+    // Fallback to the file extension.
+    let path = href.split(&['?', '#'][..]).next().unwrap_or(href).to_lowercase();
+    if path.ends_with(".svg") || path.ends_with(".svgz") {
+        "image/svg+xml"
+    } else if path.ends_with(".png") {
+        "image/png"
+    } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
+        "image/jpeg"
+    } else {
+        "application/octet-stream"
+    }
+}
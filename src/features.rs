@@ -0,0 +1,96 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    AttributeId,
+    ElementId as EId,
+    Node,
+};
+
+/// A list of SVG features used by a document.
+///
+/// See [`Document::features_used`] for details.
+///
+/// [`Document::features_used`]: struct.Document.html#method.features_used
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct FeatureSet {
+    /// `linearGradient` or `radialGradient` elements are present.
+    pub gradients: bool,
+    /// `pattern` elements are present.
+    pub patterns: bool,
+    /// `filter` elements are present.
+    pub filters: bool,
+    /// `clipPath` elements are present.
+    pub clipping: bool,
+    /// `mask` elements are present.
+    pub masking: bool,
+    /// `marker` elements are present.
+    pub markers: bool,
+    /// `text` elements are present.
+    pub text: bool,
+    /// `textPath` elements are present.
+    pub text_on_path: bool,
+    /// `image` elements are present.
+    pub images: bool,
+    /// `animate`, `animateColor`, `animateMotion`, `animateTransform`
+    /// or `set` elements are present.
+    pub animation: bool,
+    /// `script` elements or event attributes, like `onclick`, are present.
+    pub scripting: bool,
+    /// `foreignObject` elements are present.
+    pub foreign_object: bool,
+    /// `style` elements or `class` attributes are present.
+    ///
+    /// The parser resolves CSS into attributes, so it's usually `false` for a parsed document.
+    pub css: bool,
+}
+
+pub fn features_used(root: &Node) -> FeatureSet {
+    let mut features = FeatureSet::default();
+
+    for node in root.descendants() {
+        if !node.is_element() {
+            continue;
+        }
+
+        if let Some(id) = node.tag_id() {
+            match id {
+                EId::LinearGradient | EId::RadialGradient => features.gradients = true,
+                EId::Pattern => features.patterns = true,
+                EId::Filter => features.filters = true,
+                EId::ClipPath => features.clipping = true,
+                EId::Mask => features.masking = true,
+                EId::Marker => features.markers = true,
+                EId::Text => features.text = true,
+                EId::TextPath => features.text_on_path = true,
+                EId::Image => features.images = true,
+                EId::Animate
+                | EId::AnimateColor
+                | EId::AnimateMotion
+                | EId::AnimateTransform
+                | EId::Set => features.animation = true,
+                EId::Script => features.scripting = true,
+                EId::ForeignObject => features.foreign_object = true,
+                EId::Style => features.css = true,
+                _ => {}
+            }
+        }
+
+        for attr in node.attributes().iter() {
+            if let Some(id) = attr.id() {
+                if id == AttributeId::Class {
+                    features.css = true;
+                } else if id.as_str().starts_with("on") {
+                    features.scripting = true;
+                }
+            }
+        }
+    }
+
+    features
+}
@@ -0,0 +1,349 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::ops::{Deref, Index};
+
+use {
+    Angle,
+    AspectRatio,
+    Attribute,
+    AttributeQName,
+    AttributeValue,
+    Color,
+    Document,
+    Error,
+    Length,
+    LengthList,
+    Node,
+    NodeType,
+    NumberList,
+    PaintFallback,
+    Path,
+    Points,
+    TagName,
+    Transform,
+    ViewBox,
+};
+
+/// Value of the [`FlatAttribute`].
+///
+/// Same as [`AttributeValue`], but links are stored as node indexes
+/// and not as [`Node`]s.
+///
+/// [`AttributeValue`]: enum.AttributeValue.html
+/// [`FlatAttribute`]: struct.FlatAttribute.html
+/// [`Node`]: type.Node.html
+#[derive(Clone, PartialEq, Debug)]
+#[allow(missing_docs)]
+pub enum FlatValue {
+    None,
+    Inherit,
+    CurrentColor,
+    AspectRatio(AspectRatio),
+    Color(Color),
+    /// FuncIRI
+    FuncLink(usize),
+    Paint(usize, Option<PaintFallback>),
+    Length(Length),
+    LengthList(LengthList),
+    Angle(Angle),
+    /// IRI
+    Link(usize),
+    Number(f64),
+    NumberList(NumberList),
+    Path(Path),
+    Points(Points),
+    Transform(Transform),
+    ViewBox(ViewBox),
+    String(String),
+}
+
+/// An attribute of the [`FlatNode`].
+///
+/// [`FlatNode`]: struct.FlatNode.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct FlatAttribute {
+    /// Attribute name.
+    pub name: AttributeQName,
+    /// Attribute value.
+    pub value: FlatValue,
+}
+
+/// A node of the [`Snapshot`].
+///
+/// [`Snapshot`]: struct.Snapshot.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct FlatNode {
+    /// Node type.
+    pub node_type: NodeType,
+    /// Tag name. Empty for non-element nodes.
+    pub tag_name: TagName,
+    /// Element ID.
+    pub id: String,
+    /// Element attributes.
+    pub attributes: Vec<FlatAttribute>,
+    /// Text of the comment and text nodes.
+    pub text: String,
+    /// Index of the parent node. `None` only for the root node.
+    pub parent: Option<usize>,
+    /// Indexes of the children nodes.
+    pub children: Vec<usize>,
+}
+
+/// An index-based representation of the [`Document`].
+///
+/// Nodes are stored in a plain `Vec` and reference each other by indexes
+/// instead of `Rc` pointers, which makes it cheaper to traverse and easier to serialize.
+/// All attribute values are kept typed.
+///
+/// Nodes are stored in the document order. The root node always has the index `0`.
+///
+/// Can be created via [`Document::to_flat`] and converted back via [`Document::from_flat`].
+///
+/// [`Document`]: struct.Document.html
+/// [`Document::to_flat`]: struct.Document.html#method.to_flat
+/// [`Document::from_flat`]: struct.Document.html#method.from_flat
+#[derive(Clone, PartialEq, Debug)]
+pub struct FlatDom {
+    /// A list of nodes.
+    pub nodes: Vec<FlatNode>,
+}
+
+impl FlatDom {
+    /// Returns the root node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the nodes list is empty.
+    pub fn root(&self) -> &FlatNode {
+        &self.nodes[0]
+    }
+
+    /// Returns a node by index.
+    pub fn get(&self, idx: usize) -> Option<&FlatNode> {
+        self.nodes.get(idx)
+    }
+
+    /// Returns an index of the element with the specified ID.
+    pub fn index_by_id(&self, id: &str) -> Option<usize> {
+        self.nodes.iter().position(|n| n.node_type == NodeType::Element && n.id == id)
+    }
+
+    /// Returns nodes count.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if there are no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl Index<usize> for FlatDom {
+    type Output = FlatNode;
+
+    fn index(&self, idx: usize) -> &FlatNode {
+        &self.nodes[idx]
+    }
+}
+
+/// An immutable copy of the [`Document`].
+///
+/// Unlike the [`Document`], which is built from the `Rc<RefCell>` nodes,
+/// the `Snapshot` is `Send` and `Sync`, so it can be shared between threads
+/// (via `Arc`) and analyzed in parallel.
+///
+/// It's a read-only wrapper around the [`FlatDom`].
+///
+/// Tradeoffs compared to the [`Document`]:
+///
+/// - A snapshot is a copy. It requires a full tree traversal to build and doesn't
+///   reflect any changes made to the [`Document`] afterwards.
+/// - A snapshot is read-only. There is no way to modify it.
+/// - Links are stored as node indexes. Links to nodes that are not part
+///   of the document tree are stored as strings.
+/// - There are no [`ElementType`] and [`AttributeType`] helpers.
+///   Only raw data is available.
+///
+/// [`AttributeType`]: trait.AttributeType.html
+/// [`Document`]: struct.Document.html
+/// [`ElementType`]: trait.ElementType.html
+/// [`FlatDom`]: struct.FlatDom.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct Snapshot(FlatDom);
+
+impl Snapshot {
+    /// Returns all nodes in the document order.
+    pub fn nodes(&self) -> &[FlatNode] {
+        &self.0.nodes
+    }
+}
+
+impl Deref for Snapshot {
+    type Target = FlatDom;
+
+    fn deref(&self) -> &FlatDom {
+        &self.0
+    }
+}
+
+impl From<FlatDom> for Snapshot {
+    fn from(dom: FlatDom) -> Self {
+        Snapshot(dom)
+    }
+}
+
+pub fn to_flat(doc: &Document) -> FlatDom {
+    // Node's storage key -> index in the flat tree.
+    let mut indexes = HashMap::new();
+    let mut nodes: Vec<FlatNode> = Vec::new();
+
+    for node in doc.root().descendants() {
+        let idx = nodes.len();
+        indexes.insert(storage_key(&node), idx);
+
+        let parent = node.parent().and_then(|p| indexes.get(&storage_key(&p)).cloned());
+        if let Some(parent) = parent {
+            nodes[parent].children.push(idx);
+        }
+
+        nodes.push(FlatNode {
+            node_type: node.node_type(),
+            tag_name: node.tag_name().clone(),
+            id: node.id().clone(),
+            attributes: Vec::new(),
+            text: node.text().clone(),
+            parent,
+            children: Vec::new(),
+        });
+    }
+
+    // Links can be resolved only when all indexes are known.
+    for (node, flat) in doc.root().descendants().zip(nodes.iter_mut()) {
+        flat.attributes = node.attributes().iter().map(|attr| {
+            FlatAttribute {
+                name: attr.name.clone(),
+                value: flat_value(&attr.value, &indexes),
+            }
+        }).collect();
+    }
+
+    FlatDom { nodes }
+}
+
+pub fn from_flat(flat: &FlatDom) -> Result<Document, Error> {
+    // Only the first node can be the root one.
+    for (idx, flat_node) in flat.nodes.iter().enumerate() {
+        if (flat_node.node_type == NodeType::Root) != (idx == 0) {
+            return Err(Error::InvalidNodeType);
+        }
+    }
+
+    let mut doc = Document::new();
+
+    let mut nodes = Vec::with_capacity(flat.len());
+    for flat_node in &flat.nodes {
+        let node = match flat_node.node_type {
+            NodeType::Root => doc.root(),
+            NodeType::Element => {
+                let mut node = doc.create_element(flat_node.tag_name.as_ref());
+                node.set_id(flat_node.id.clone());
+                node
+            }
+            _ => {
+                doc.create_node(flat_node.node_type, flat_node.text.clone())
+            }
+        };
+
+        nodes.push(node);
+    }
+
+    for (idx, flat_node) in flat.nodes.iter().enumerate() {
+        for child in &flat_node.children {
+            let child = nodes[*child].clone();
+            nodes[idx].append(child);
+        }
+    }
+
+    for (idx, flat_node) in flat.nodes.iter().enumerate() {
+        for attr in &flat_node.attributes {
+            let value = match attr.value {
+                FlatValue::None => AttributeValue::None,
+                FlatValue::Inherit => AttributeValue::Inherit,
+                FlatValue::CurrentColor => AttributeValue::CurrentColor,
+                FlatValue::AspectRatio(v) => AttributeValue::AspectRatio(v),
+                FlatValue::Color(v) => AttributeValue::Color(v),
+                FlatValue::FuncLink(link) => AttributeValue::FuncLink(nodes[link].clone()),
+                FlatValue::Paint(link, fallback) => {
+                    AttributeValue::Paint(nodes[link].clone(), fallback)
+                }
+                FlatValue::Length(v) => AttributeValue::Length(v),
+                FlatValue::LengthList(ref v) => AttributeValue::LengthList(v.clone()),
+                FlatValue::Angle(v) => AttributeValue::Angle(v),
+                FlatValue::Link(link) => AttributeValue::Link(nodes[link].clone()),
+                FlatValue::Number(v) => AttributeValue::Number(v),
+                FlatValue::NumberList(ref v) => AttributeValue::NumberList(v.clone()),
+                FlatValue::Path(ref v) => AttributeValue::Path(v.clone()),
+                FlatValue::Points(ref v) => AttributeValue::Points(v.clone()),
+                FlatValue::Transform(v) => AttributeValue::Transform(v),
+                FlatValue::ViewBox(v) => AttributeValue::ViewBox(v),
+                FlatValue::String(ref v) => AttributeValue::String(v.clone()),
+            };
+
+            nodes[idx].set_attribute_checked(Attribute::new(attr.name.as_ref(), value))?;
+        }
+    }
+
+    Ok(doc)
+}
+
+fn flat_value(value: &AttributeValue, indexes: &HashMap<usize, usize>) -> FlatValue {
+    let link_idx = |node: &Node| indexes.get(&storage_key(node)).cloned();
+
+    match *value {
+        AttributeValue::None => FlatValue::None,
+        AttributeValue::Inherit => FlatValue::Inherit,
+        AttributeValue::CurrentColor => FlatValue::CurrentColor,
+        AttributeValue::AspectRatio(v) => FlatValue::AspectRatio(v),
+        AttributeValue::Color(v) => FlatValue::Color(v),
+        AttributeValue::FuncLink(ref node) => {
+            match link_idx(node) {
+                Some(idx) => FlatValue::FuncLink(idx),
+                None => FlatValue::String(value.to_string()),
+            }
+        }
+        AttributeValue::Paint(ref node, fallback) => {
+            match link_idx(node) {
+                Some(idx) => FlatValue::Paint(idx, fallback),
+                None => FlatValue::String(value.to_string()),
+            }
+        }
+        AttributeValue::Length(v) => FlatValue::Length(v),
+        AttributeValue::LengthList(ref v) => FlatValue::LengthList(v.clone()),
+        AttributeValue::Angle(v) => FlatValue::Angle(v),
+        AttributeValue::Link(ref node) => {
+            match link_idx(node) {
+                Some(idx) => FlatValue::Link(idx),
+                None => FlatValue::String(value.to_string()),
+            }
+        }
+        AttributeValue::Number(v) => FlatValue::Number(v),
+        AttributeValue::NumberList(ref v) => FlatValue::NumberList(v.clone()),
+        AttributeValue::Path(ref v) => FlatValue::Path(v.clone()),
+        AttributeValue::Points(ref v) => FlatValue::Points(v.clone()),
+        AttributeValue::Transform(v) => FlatValue::Transform(v),
+        AttributeValue::ViewBox(v) => FlatValue::ViewBox(v),
+        AttributeValue::String(ref v) => FlatValue::String(v.clone()),
+    }
+}
+
+fn storage_key(node: &Node) -> usize {
+    node.borrow().storage_key.expect("node without a storage key")
+}
@@ -0,0 +1,1073 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::str::FromStr;
+
+use {
+    Align,
+    AspectRatio,
+    AttributeId,
+    AttributeValue,
+    Attributes,
+    Document,
+    ElementId,
+    ElementType,
+    Length,
+    LengthExt,
+    LengthUnit,
+    Node,
+    NumberList,
+    Path,
+    PathExt,
+    PathSegment,
+    Transform,
+    ViewBox,
+};
+use path_ext::{
+    visit_segment_numbers,
+    SegmentNumber,
+};
+
+// Lengths with absolute units are converted using the CSS DPI.
+const DPI: f64 = 96.0;
+
+pub fn ctm(node: &Node) -> Transform {
+    let mut ancestors: Vec<Node> = node.ancestors().collect();
+    ancestors.reverse();
+
+    let mut ts = Transform::default();
+    for n in &ancestors {
+        if let Some(&AttributeValue::Transform(ref t)) = n.attributes().get_value(AttributeId::Transform) {
+            ts.append(t);
+        }
+
+        // The viewport transform is applied only to the `svg` element content.
+        if n != node && n.is_tag_name(ElementId::Svg) {
+            ts.append(&viewport_transform(n));
+        }
+    }
+
+    ts
+}
+
+pub fn to_root_coordinates(node: &Node) -> Option<Path> {
+    let mut path = try_opt!(shape_to_path(node));
+
+    path.transform(&ctm(node));
+    Some(path)
+}
+
+pub fn nearest_viewport(node: &Node) -> Option<Node> {
+    node.ancestors().skip(1).find(is_viewport)
+}
+
+fn is_viewport(node: &Node) -> bool {
+    match node.tag_id() {
+        Some(ElementId::Svg) => true,
+        Some(ElementId::Symbol) | Some(ElementId::Pattern) | Some(ElementId::Marker) => {
+            node.has_attribute(AttributeId::ViewBox)
+        }
+        _ => false,
+    }
+}
+
+pub fn viewport_size(node: &Node) -> Option<(f64, f64)> {
+    if !is_viewport(node) {
+        return None;
+    }
+
+    if let Some(&AttributeValue::ViewBox(vb)) = node.attributes().get_value(AttributeId::ViewBox) {
+        if vb.w > 0.0 && vb.h > 0.0 {
+            return Some((vb.w, vb.h));
+        }
+    }
+
+    // Only the `svg` element can be a viewport without a `viewBox`.
+    let w = try_opt!(resolve_size(node, AttributeId::Width));
+    let h = try_opt!(resolve_size(node, AttributeId::Height));
+    Some((w, h))
+}
+
+// Resolves the `svg` element `width` or `height`. Percentages are relative to the parent viewport.
+fn resolve_size(svg: &Node, aid: AttributeId) -> Option<f64> {
+    let parent_size = || {
+        let parent_vp = try_opt!(nearest_viewport(svg));
+        let (w, h) = try_opt!(viewport_size(&parent_vp));
+        Some(if aid == AttributeId::Width { w } else { h })
+    };
+
+    // The default value is `100%`.
+    let default = AttributeValue::Length(Length::new(100.0, LengthUnit::Percent));
+    let attrs = svg.attributes();
+    let value = match attrs.get_value(aid) {
+        Some(v @ &AttributeValue::Length(_)) | Some(v @ &AttributeValue::Number(_)) => v,
+        _ => &default,
+    };
+
+    length_to_px(value, parent_size)
+}
+
+// Converts a length or a number into pixels.
+//
+// `percent_base` is called only for percentages. Font-relative units are not supported.
+fn length_to_px<F>(value: &AttributeValue, percent_base: F) -> Option<f64>
+    where F: FnOnce() -> Option<f64>
+{
+    match *value {
+        AttributeValue::Length(len) if len.unit == LengthUnit::Percent => {
+            percent_base().map(|base| base * len.num / 100.0)
+        }
+        AttributeValue::Length(len) => len.to_px(DPI),
+        AttributeValue::Number(n) => Some(n),
+        _ => None,
+    }
+}
+
+pub fn document_size(doc: &Document, default_size: (f64, f64)) -> (f64, f64) {
+    let svg = match doc.svg_element() {
+        Some(svg) => svg,
+        None => return default_size,
+    };
+
+    let attrs = svg.attributes();
+
+    // Negative values and font-relative units are treated as missing.
+    let resolve = |aid: AttributeId, base: f64| {
+        let n = attrs.get_value(aid).and_then(|v| length_to_px(v, || Some(base)));
+        n.and_then(|n| if n >= 0.0 { Some(n) } else { None })
+    };
+
+    let w = resolve(AttributeId::Width, default_size.0);
+    let h = resolve(AttributeId::Height, default_size.1);
+
+    let vb = match attrs.get_value(AttributeId::ViewBox) {
+        Some(&AttributeValue::ViewBox(vb)) if vb.w > 0.0 && vb.h > 0.0 => Some(vb),
+        _ => None,
+    };
+
+    match (w, h, vb) {
+        (Some(w), Some(h), _) => (w, h),
+        (Some(w), None, Some(vb)) => (w, w * vb.h / vb.w),
+        (None, Some(h), Some(vb)) => (h * vb.w / vb.h, h),
+        (None, None, Some(vb)) => (vb.w, vb.h),
+        (w, h, None) => (w.unwrap_or(default_size.0), h.unwrap_or(default_size.1)),
+    }
+}
+
+pub fn shape_to_path(node: &Node) -> Option<Path> {
+    let id = try_opt!(node.tag_id());
+
+    let attrs = node.attributes();
+    let num = |aid: AttributeId| get_number(&attrs, aid).unwrap_or(0.0);
+
+    let mut path = Path::new();
+    match id {
+        ElementId::Path => {
+            if let Some(&AttributeValue::Path(ref p)) = attrs.get_value(AttributeId::D) {
+                path = p.clone();
+            }
+        }
+        ElementId::Rect => {
+            let (x, y) = (num(AttributeId::X), num(AttributeId::Y));
+            let w = try_opt!(get_number(&attrs, AttributeId::Width));
+            let h = try_opt!(get_number(&attrs, AttributeId::Height));
+
+            // A missing radius is equal to the other one.
+            let rx = get_number(&attrs, AttributeId::Rx);
+            let ry = get_number(&attrs, AttributeId::Ry);
+            let (rx, ry) = match (rx, ry) {
+                (Some(rx), Some(ry)) => (rx, ry),
+                (Some(rx), None) => (rx, rx),
+                (None, Some(ry)) => (ry, ry),
+                (None, None) => (0.0, 0.0),
+            };
+            let rx = rx.max(0.0).min(w / 2.0);
+            let ry = ry.max(0.0).min(h / 2.0);
+
+            if rx > 0.0 && ry > 0.0 {
+                let arc = |x, y| PathSegment::EllipticalArc {
+                    abs: true, rx, ry, x_axis_rotation: 0.0, large_arc: false, sweep: true, x, y
+                };
+
+                path.push(PathSegment::MoveTo { abs: true, x: x + rx, y });
+                path.push(PathSegment::LineTo { abs: true, x: x + w - rx, y });
+                path.push(arc(x + w, y + ry));
+                path.push(PathSegment::LineTo { abs: true, x: x + w, y: y + h - ry });
+                path.push(arc(x + w - rx, y + h));
+                path.push(PathSegment::LineTo { abs: true, x: x + rx, y: y + h });
+                path.push(arc(x, y + h - ry));
+                path.push(PathSegment::LineTo { abs: true, x, y: y + ry });
+                path.push(arc(x + rx, y));
+            } else {
+                path.push(PathSegment::MoveTo { abs: true, x, y });
+                path.push(PathSegment::LineTo { abs: true, x: x + w, y });
+                path.push(PathSegment::LineTo { abs: true, x: x + w, y: y + h });
+                path.push(PathSegment::LineTo { abs: true, x, y: y + h });
+            }
+
+            path.push(PathSegment::ClosePath { abs: true });
+        }
+        ElementId::Circle | ElementId::Ellipse => {
+            let (cx, cy) = (num(AttributeId::Cx), num(AttributeId::Cy));
+            let (rx, ry) = if id == ElementId::Circle {
+                let r = try_opt!(get_number(&attrs, AttributeId::R));
+                (r, r)
+            } else {
+                let rx = try_opt!(get_number(&attrs, AttributeId::Rx));
+                let ry = try_opt!(get_number(&attrs, AttributeId::Ry));
+                (rx, ry)
+            };
+
+            let arc = |x, y| PathSegment::EllipticalArc {
+                abs: true, rx, ry, x_axis_rotation: 0.0, large_arc: false, sweep: true, x, y
+            };
+
+            path.push(PathSegment::MoveTo { abs: true, x: cx + rx, y: cy });
+            path.push(arc(cx, cy + ry));
+            path.push(arc(cx - rx, cy));
+            path.push(arc(cx, cy - ry));
+            path.push(arc(cx + rx, cy));
+            path.push(PathSegment::ClosePath { abs: true });
+        }
+        ElementId::Line => {
+            path.push(PathSegment::MoveTo { abs: true, x: num(AttributeId::X1), y: num(AttributeId::Y1) });
+            path.push(PathSegment::LineTo { abs: true, x: num(AttributeId::X2), y: num(AttributeId::Y2) });
+        }
+        ElementId::Polyline | ElementId::Polygon => {
+            if let Some(&AttributeValue::Points(ref points)) = attrs.get_value(AttributeId::Points) {
+                for (i, &(x, y)) in points.iter().enumerate() {
+                    if i == 0 {
+                        path.push(PathSegment::MoveTo { abs: true, x, y });
+                    } else {
+                        path.push(PathSegment::LineTo { abs: true, x, y });
+                    }
+                }
+
+                if id == ElementId::Polygon && !path.is_empty() {
+                    path.push(PathSegment::ClosePath { abs: true });
+                }
+            }
+        }
+        _ => return None,
+    }
+
+    Some(path)
+}
+
+fn viewport_transform(svg: &Node) -> Transform {
+    let mut ts = Transform::default();
+
+    let attrs = svg.attributes();
+
+    // `x` and `y` are ignored on the outermost `svg` element.
+    let is_nested = match svg.parent() {
+        Some(parent) => !parent.is_root(),
+        None => false,
+    };
+
+    if is_nested {
+        let x = get_number(&attrs, AttributeId::X).unwrap_or(0.0);
+        let y = get_number(&attrs, AttributeId::Y).unwrap_or(0.0);
+        ts.translate(x, y);
+    }
+
+    let vb = match attrs.get_value(AttributeId::ViewBox) {
+        Some(&AttributeValue::ViewBox(vb)) if vb.w > 0.0 && vb.h > 0.0 => vb,
+        _ => return ts,
+    };
+
+    let width = get_number(&attrs, AttributeId::Width).unwrap_or(vb.w);
+    let height = get_number(&attrs, AttributeId::Height).unwrap_or(vb.h);
+
+    let aspect = match attrs.get_value(AttributeId::PreserveAspectRatio) {
+        Some(&AttributeValue::AspectRatio(aspect)) => aspect,
+        _ => AspectRatio { defer: false, align: Align::XMidYMid, slice: false },
+    };
+
+    ts.append(&view_box_transform(vb, aspect, width, height));
+    ts
+}
+
+// Only absolute lengths are supported.
+fn get_number(attrs: &Attributes, id: AttributeId) -> Option<f64> {
+    match attrs.get_value(id) {
+        Some(&AttributeValue::Length(ref len)) => len.to_px(DPI),
+        Some(&AttributeValue::Number(n)) => Some(n),
+        _ => None,
+    }
+}
+
+fn view_box_transform(vb: ViewBox, aspect: AspectRatio, width: f64, height: f64) -> Transform {
+    let sx = width / vb.w;
+    let sy = height / vb.h;
+
+    if aspect.align == Align::None {
+        return Transform::new(sx, 0.0, 0.0, sy, -vb.x * sx, -vb.y * sy);
+    }
+
+    let s = if aspect.slice { sx.max(sy) } else { sx.min(sy) };
+
+    let (x_align, y_align) = match aspect.align {
+        Align::None => unreachable!(),
+        Align::XMinYMin => (0.0, 0.0),
+        Align::XMidYMin => (0.5, 0.0),
+        Align::XMaxYMin => (1.0, 0.0),
+        Align::XMinYMid => (0.0, 0.5),
+        Align::XMidYMid => (0.5, 0.5),
+        Align::XMaxYMid => (1.0, 0.5),
+        Align::XMinYMax => (0.0, 1.0),
+        Align::XMidYMax => (0.5, 1.0),
+        Align::XMaxYMax => (1.0, 1.0),
+    };
+
+    let tx = (width - vb.w * s) * x_align - vb.x * s;
+    let ty = (height - vb.h * s) * y_align - vb.y * s;
+
+    Transform::new(s, 0.0, 0.0, s, tx, ty)
+}
+
+pub fn scale_document(doc: &mut Document, factor: f64) {
+    doc.visit_values_mut(|node, aid, value| {
+        if !is_scalable(node, aid) {
+            return;
+        }
+
+        if let Some(list) = parse_number_string(node, aid, value) {
+            *value = AttributeValue::NumberList(list);
+        }
+
+        match *value {
+            AttributeValue::Length(ref mut len) => {
+                scale_length(len, factor);
+            }
+            AttributeValue::LengthList(ref mut list) => {
+                for len in list.iter_mut() {
+                    scale_length(len, factor);
+                }
+            }
+            AttributeValue::Number(ref mut n) if is_scalable_number(node, aid) => {
+                *n *= factor;
+            }
+            AttributeValue::NumberList(ref mut list) if is_scalable_number(node, aid) => {
+                for n in list.iter_mut() {
+                    *n *= factor;
+                }
+            }
+            AttributeValue::Path(ref mut path) => {
+                for seg in path.iter_mut() {
+                    visit_segment_numbers(seg, |n, kind| {
+                        if kind != SegmentNumber::Angle {
+                            *n *= factor;
+                        }
+                    });
+                }
+            }
+            AttributeValue::Points(ref mut points) => {
+                for p in points.iter_mut() {
+                    p.0 *= factor;
+                    p.1 *= factor;
+                }
+            }
+            AttributeValue::Transform(ref mut ts) => {
+                ts.e *= factor;
+                ts.f *= factor;
+            }
+            AttributeValue::ViewBox(ref mut vb) => {
+                vb.x *= factor;
+                vb.y *= factor;
+                vb.w *= factor;
+                vb.h *= factor;
+            }
+            _ => {}
+        }
+    });
+}
+
+// Checks that an attribute is defined in the user space and not relative to the bounding box.
+fn is_scalable(node: &Node, aid: AttributeId) -> bool {
+    // A gradient stop offset is a ratio.
+    if aid == AttributeId::Offset {
+        return false;
+    }
+
+    let is_region = match aid {
+        AttributeId::X | AttributeId::Y | AttributeId::Width | AttributeId::Height => true,
+        _ => false,
+    };
+
+    let is_user_space = match node.tag_id() {
+        Some(ElementId::LinearGradient) | Some(ElementId::RadialGradient) => {
+            has_user_space_units(node, AttributeId::GradientUnits, false)
+        }
+        Some(ElementId::Pattern) if is_region => {
+            has_user_space_units(node, AttributeId::PatternUnits, false)
+        }
+        Some(ElementId::Mask) if is_region => {
+            has_user_space_units(node, AttributeId::MaskUnits, false)
+        }
+        Some(ElementId::Filter) if is_region => {
+            has_user_space_units(node, AttributeId::FilterUnits, false)
+        }
+        _ => true,
+    };
+
+    if !is_user_space {
+        return false;
+    }
+
+    // Markers with `markerUnits=strokeWidth` are scaled by the stroke width,
+    // which is scaled already.
+    if node.is_tag_name(ElementId::Marker) && !has_user_space_units(node, AttributeId::MarkerUnits, false) {
+        return false;
+    }
+
+    // Check that the element content is not defined relative to the bounding box.
+    for parent in node.ancestors().skip(1) {
+        let units = match parent.tag_id() {
+            Some(ElementId::Marker) => {
+                if has_user_space_units(&parent, AttributeId::MarkerUnits, false) {
+                    continue;
+                }
+
+                return false;
+            }
+            Some(ElementId::ClipPath) => AttributeId::ClipPathUnits,
+            Some(ElementId::Mask) => AttributeId::MaskContentUnits,
+            Some(ElementId::Filter) => AttributeId::PrimitiveUnits,
+            // `patternContentUnits` is ignored when `viewBox` is set.
+            Some(ElementId::Pattern) if !parent.has_attribute(AttributeId::ViewBox) => {
+                AttributeId::PatternContentUnits
+            }
+            _ => continue,
+        };
+
+        return has_user_space_units(&parent, units, true);
+    }
+
+    true
+}
+
+// Checks that a plain number attribute of a filter primitive is a user space length.
+fn is_scalable_number(node: &Node, aid: AttributeId) -> bool {
+    match node.tag_id() {
+        Some(ElementId::FeOffset) => aid == AttributeId::Dx || aid == AttributeId::Dy,
+        Some(ElementId::FeGaussianBlur) => aid == AttributeId::StdDeviation,
+        Some(ElementId::FeMorphology) => aid == AttributeId::Radius,
+        Some(ElementId::FeDisplacementMap) => aid == AttributeId::Scale,
+        Some(ElementId::FePointLight) | Some(ElementId::FeSpotLight) => {
+            match aid {
+                AttributeId::X | AttributeId::Y | AttributeId::Z
+                | AttributeId::PointsAtX | AttributeId::PointsAtY | AttributeId::PointsAtZ => true,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+// Some filter primitive numbers, like `radius`, are stored as strings by the parser.
+fn parse_number_string(node: &Node, aid: AttributeId, value: &AttributeValue) -> Option<NumberList> {
+    match *value {
+        AttributeValue::String(ref s) if is_scalable_number(node, aid) => {
+            NumberList::from_str(s).ok()
+        }
+        _ => None,
+    }
+}
+
+fn has_user_space_units(node: &Node, aid: AttributeId, default: bool) -> bool {
+    match node.attributes().get_value(aid) {
+        Some(&AttributeValue::String(ref s)) => s == "userSpaceOnUse",
+        _ => default,
+    }
+}
+
+// Relative units are left untouched, since `em` and `ex` depend on the font size,
+// which is scaled too.
+fn scale_length(len: &mut Length, factor: f64) {
+    match len.unit {
+        LengthUnit::Percent | LengthUnit::Em | LengthUnit::Ex => {}
+        _ => *len = len.scale(factor),
+    }
+}
+
+/// A document translation method.
+///
+/// See [`Document::translate`] for details.
+///
+/// [`Document::translate`]: struct.Document.html#method.translate
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TranslateMode {
+    /// Wrap the root element content into a `g` element with the `translate` transform.
+    Wrap,
+    /// Offset element coordinates directly.
+    ///
+    /// Elements that can't be offset, like the ones with transforms or
+    /// with lengths in non-user units, get the `translate` transform instead.
+    Rewrite,
+}
+
+pub fn translate_document(doc: &mut Document, dx: f64, dy: f64, mode: TranslateMode) {
+    let svg = match doc.svg_element() {
+        Some(svg) => svg,
+        None => return,
+    };
+
+    let ts = Transform::new_translate(dx, dy);
+
+    match mode {
+        TranslateMode::Wrap => {
+            let children: Vec<Node> = svg.children().collect();
+            if !children.is_empty() {
+                let mut g = doc.group_nodes(&children);
+                g.set_attribute((AttributeId::Transform, ts));
+            }
+        }
+        TranslateMode::Rewrite => {
+            let mut tr = Translation::default();
+            for child in svg.children() {
+                translate_element(&child, dx, dy, &mut tr);
+            }
+
+            let Translation { moved, values, fallback } = tr;
+            let is_moved = |node: &Node| node.ancestors().any(|n| moved.contains(&n));
+            let has_moved_target = |node: &Node| {
+                match use_target(node) {
+                    Some(target) => is_moved(&target),
+                    None => false,
+                }
+            };
+
+            // A `use` element instance is already moved with the referenced element.
+            let mut skipped = Vec::new();
+            for (mut node, aid, value) in values {
+                if node.is_tag_name(ElementId::Use) && has_moved_target(&node) {
+                    if !skipped.contains(&node) {
+                        skipped.push(node);
+                    }
+
+                    continue;
+                }
+
+                node.set_attribute((aid, value));
+            }
+
+            let fallback: Vec<Node> = fallback.into_iter().filter(|node| {
+                let is_moved_use = node.is_tag_name(ElementId::Use)
+                                && !node.has_attribute(AttributeId::Transform)
+                                && has_moved_target(node);
+                if is_moved_use {
+                    skipped.push(node.clone());
+                }
+
+                !is_moved_use
+            }).collect();
+
+            // Other `use` elements, like ones with a transform or outside the translated
+            // content, have to be moved back, otherwise the offset would be applied twice.
+            let uses: Vec<Node> = svg.descendants()
+                .filter(|n| n.is_tag_name(ElementId::Use) && !skipped.contains(n))
+                .filter(|n| has_moved_target(n))
+                .collect();
+
+            for mut node in uses {
+                match (offset_value(&node, AttributeId::X, -dx), offset_value(&node, AttributeId::Y, -dy)) {
+                    (Some(x), Some(y)) => {
+                        node.set_attribute((AttributeId::X, x));
+                        node.set_attribute((AttributeId::Y, y));
+                    }
+                    _ => {
+                        let mut use_ts = match node.attributes().get_value(AttributeId::Transform) {
+                            Some(&AttributeValue::Transform(ts)) => ts,
+                            _ => Transform::default(),
+                        };
+                        use_ts.translate(-dx, -dy);
+                        node.set_attribute((AttributeId::Transform, use_ts));
+                    }
+                }
+            }
+
+            doc.apply_transform_to(&fallback, &ts);
+        }
+    }
+}
+
+// Planned changes of the `TranslateMode::Rewrite` mode.
+#[derive(Default)]
+struct Translation {
+    // Elements that are moved along with their descendants.
+    moved: Vec<Node>,
+    // New coordinate attributes.
+    values: Vec<(Node, AttributeId, AttributeValue)>,
+    // Elements that can't be offset and will get a `translate` transform instead.
+    fallback: Vec<Node>,
+}
+
+fn use_target(node: &Node) -> Option<Node> {
+    match node.attributes().get_value(AttributeId::Href) {
+        Some(&AttributeValue::Link(ref link)) => Some(link.clone()),
+        _ => None,
+    }
+}
+
+// Plans the element coordinates offset. Elements that can't be offset are added to the `fallback`.
+fn translate_element(node: &Node, dx: f64, dy: f64, tr: &mut Translation) {
+    let id = match node.tag_id() {
+        Some(id) => id,
+        None => return,
+    };
+
+    // Referenced elements, like gradients with `userSpaceOnUse` units,
+    // are defined in the user space of the current element, so we can't offset it.
+    let has_links = node.attributes().iter().any(|a| {
+        a.is_link_container() && !a.has_id(AttributeId::Href)
+    });
+
+    if has_links || node.has_attribute(AttributeId::Transform) {
+        // A nested `svg` element can't have a transform.
+        if id != ElementId::Svg {
+            tr.fallback.push(node.clone());
+            tr.moved.push(node.clone());
+        }
+
+        return;
+    }
+
+    match id {
+        ElementId::G | ElementId::A | ElementId::Switch => {
+            tr.moved.push(node.clone());
+            for child in node.children() {
+                translate_element(&child, dx, dy, tr);
+            }
+        }
+        _ if id == ElementId::Svg || node.is_graphic() => {
+            match offset_coordinates(node, id, dx, dy) {
+                Some(values) => tr.values.extend(values),
+                None => tr.fallback.push(node.clone()),
+            }
+
+            tr.moved.push(node.clone());
+        }
+        _ => {}
+    }
+}
+
+// Returns new coordinate attributes or `None` when the element can't be offset.
+fn offset_coordinates(
+    node: &Node,
+    id: ElementId,
+    dx: f64,
+    dy: f64,
+) -> Option<Vec<(Node, AttributeId, AttributeValue)>> {
+    let mut values = Vec::new();
+
+    macro_rules! offset {
+        ($n:expr, $aid:expr, $d:expr) => {
+            values.push(($n.clone(), $aid, try_opt!(offset_value($n, $aid, $d))))
+        };
+    }
+
+    match id {
+        ElementId::Rect | ElementId::Image | ElementId::Use | ElementId::Svg => {
+            offset!(node, AttributeId::X, dx);
+            offset!(node, AttributeId::Y, dy);
+        }
+        ElementId::Circle | ElementId::Ellipse => {
+            offset!(node, AttributeId::Cx, dx);
+            offset!(node, AttributeId::Cy, dy);
+        }
+        ElementId::Line => {
+            offset!(node, AttributeId::X1, dx);
+            offset!(node, AttributeId::Y1, dy);
+            offset!(node, AttributeId::X2, dx);
+            offset!(node, AttributeId::Y2, dy);
+        }
+        ElementId::Path => {
+            if let Some(&AttributeValue::Path(ref path)) = node.attributes().get_value(AttributeId::D) {
+                values.push((node.clone(), AttributeId::D, offset_path(path, dx, dy).into()));
+            }
+        }
+        ElementId::Polyline | ElementId::Polygon => {
+            let attrs = node.attributes();
+            if let Some(&AttributeValue::Points(ref points)) = attrs.get_value(AttributeId::Points) {
+                let mut points = points.clone();
+                for p in points.iter_mut() {
+                    p.0 += dx;
+                    p.1 += dy;
+                }
+
+                values.push((node.clone(), AttributeId::Points, points.into()));
+            }
+        }
+        ElementId::Text => {
+            // A text path position is defined by the referenced path.
+            if node.descendants().any(|n| n.is_tag_name(ElementId::TextPath)) {
+                return None;
+            }
+
+            offset!(node, AttributeId::X, dx);
+            offset!(node, AttributeId::Y, dy);
+
+            // Absolutely positioned text chunks.
+            for child in node.descendants().skip(1) {
+                if child.has_attribute(AttributeId::X) {
+                    offset!(&child, AttributeId::X, dx);
+                }
+
+                if child.has_attribute(AttributeId::Y) {
+                    offset!(&child, AttributeId::Y, dy);
+                }
+            }
+        }
+        _ => return None,
+    }
+
+    Some(values)
+}
+
+// Offsets a coordinate or a list of coordinates. Only user units are supported.
+fn offset_value(node: &Node, aid: AttributeId, d: f64) -> Option<AttributeValue> {
+    fn offset_length(len: Length, d: f64) -> Option<Length> {
+        match len.unit {
+            LengthUnit::None | LengthUnit::Px => Some(Length::new(len.num + d, len.unit)),
+            _ => None,
+        }
+    }
+
+    match node.attributes().get_value(aid) {
+        Some(&AttributeValue::Length(len)) => offset_length(len, d).map(AttributeValue::from),
+        Some(&AttributeValue::LengthList(ref list)) => {
+            let mut list = list.clone();
+            for len in list.iter_mut() {
+                *len = try_opt!(offset_length(*len, d));
+            }
+
+            Some(list.into())
+        }
+        Some(&AttributeValue::Number(n)) => Some((n + d).into()),
+        Some(_) => None,
+        // The default coordinate value is zero.
+        None => Some(Length::new_number(d).into()),
+    }
+}
+
+// Offsets absolute segments. The first segment is always offset,
+// since it's relative to the origin.
+fn offset_path(path: &Path, dx: f64, dy: f64) -> Path {
+    let mut path = path.clone();
+    for (i, seg) in path.iter_mut().enumerate() {
+        if i != 0 && !seg.is_absolute() {
+            continue;
+        }
+
+        visit_segment_numbers(seg, |n, kind| {
+            match kind {
+                SegmentNumber::X => *n += dx,
+                SegmentNumber::Y => *n += dy,
+                SegmentNumber::Radius | SegmentNumber::Angle => {}
+            }
+        });
+    }
+
+    path
+}
+
+/// A rectangle.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[allow(missing_docs)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+// Min and max points of a bounding box.
+#[derive(Clone, Copy)]
+struct Bounds(Option<(f64, f64, f64, f64)>);
+
+impl Bounds {
+    fn add(&mut self, x: f64, y: f64) {
+        self.0 = Some(match self.0 {
+            Some((x1, y1, x2, y2)) => (x1.min(x), y1.min(y), x2.max(x), y2.max(y)),
+            None => (x, y, x, y),
+        });
+    }
+
+    fn expand(&mut self, d: f64) {
+        if let Some((x1, y1, x2, y2)) = self.0 {
+            self.0 = Some((x1 - d, y1 - d, x2 + d, y2 + d));
+        }
+    }
+
+    fn to_rect(self) -> Option<Rect> {
+        self.0.map(|(x1, y1, x2, y2)| Rect { x: x1, y: y1, width: x2 - x1, height: y2 - y1 })
+    }
+}
+
+pub fn bounding_box(node: &Node) -> Option<Rect> {
+    match shape_to_canonical_path(node) {
+        Some(path) => path_bounds(&path).to_rect(),
+        None => None,
+    }
+}
+
+fn shape_to_canonical_path(node: &Node) -> Option<Path> {
+    let mut path = try_opt!(shape_to_path(node));
+
+    path.to_canonical();
+    Some(path)
+}
+
+// Expects a canonical path.
+fn path_bounds(path: &Path) -> Bounds {
+    let mut bounds = Bounds(None);
+    let mut prev = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    for seg in path.iter() {
+        match *seg {
+            PathSegment::MoveTo { x, y, .. } => {
+                bounds.add(x, y);
+                start = (x, y);
+                prev = (x, y);
+            }
+            PathSegment::LineTo { x, y, .. } => {
+                bounds.add(x, y);
+                prev = (x, y);
+            }
+            PathSegment::CurveTo { x1, y1, x2, y2, x, y, .. } => {
+                bounds.add(x, y);
+                for t in curve_extremes(prev.0, x1, x2, x).into_iter()
+                    .chain(curve_extremes(prev.1, y1, y2, y))
+                {
+                    bounds.add(eval_curve(prev.0, x1, x2, x, t), eval_curve(prev.1, y1, y2, y, t));
+                }
+
+                prev = (x, y);
+            }
+            PathSegment::ClosePath { .. } => {
+                prev = start;
+            }
+            _ => {}
+        }
+    }
+
+    bounds
+}
+
+// Returns `t` values in the (0, 1) range, where a cubic curve coordinate has an extremum.
+fn curve_extremes(p0: f64, p1: f64, p2: f64, p3: f64) -> Vec<f64> {
+    // The curve derivative divided by 3: `a*t^2 + b*t + c`.
+    let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+    let b = 2.0 * (p0 - 2.0 * p1 + p2);
+    let c = p1 - p0;
+
+    let mut list = Vec::new();
+    if a.abs() < 1e-12 {
+        if b.abs() > 1e-12 {
+            list.push(-c / b);
+        }
+    } else {
+        let d = b * b - 4.0 * a * c;
+        if d >= 0.0 {
+            let d = d.sqrt();
+            list.push((-b + d) / (2.0 * a));
+            list.push((-b - d) / (2.0 * a));
+        }
+    }
+
+    list.retain(|t| *t > 0.0 && *t < 1.0);
+    list
+}
+
+fn eval_curve(p0: f64, p1: f64, p2: f64, p3: f64, t: f64) -> f64 {
+    let mt = 1.0 - t;
+    mt * mt * mt * p0 + 3.0 * mt * mt * t * p1 + 3.0 * mt * t * t * p2 + t * t * t * p3
+}
+
+// A non-degenerate segment of a canonical path.
+struct Segment {
+    start: (f64, f64),
+    start_dir: (f64, f64),
+    end: (f64, f64),
+    end_dir: (f64, f64),
+}
+
+pub fn stroke_bounding_box(node: &Node) -> Option<Rect> {
+    let path = try_opt!(shape_to_canonical_path(node));
+
+    let mut bounds = path_bounds(&path);
+
+    let style = node.computed_style();
+    match style.get(&AttributeId::Stroke) {
+        None | Some(&AttributeValue::None) => return bounds.to_rect(),
+        _ => {}
+    }
+
+    let hw = try_opt!(resolve_stroke_width(node, style.get(&AttributeId::StrokeWidth))) / 2.0;
+
+    if hw <= 0.0 {
+        return bounds.to_rect();
+    }
+
+    // Round joins and caps, as well as butt caps and bevel joins,
+    // never go further than the half of the stroke width from the path.
+    bounds.expand(hw);
+
+    let is_keyword = |aid: AttributeId, name: &str| {
+        match style.get(&aid) {
+            Some(&AttributeValue::String(ref s)) => s == name,
+            _ => false,
+        }
+    };
+
+    let is_square_cap = is_keyword(AttributeId::StrokeLinecap, "square");
+    // `miter` is the default value.
+    let is_miter_join = !is_keyword(AttributeId::StrokeLinejoin, "round")
+                     && !is_keyword(AttributeId::StrokeLinejoin, "bevel");
+    let miter_limit = match style.get(&AttributeId::StrokeMiterlimit) {
+        Some(&AttributeValue::Number(n)) => n.max(1.0),
+        _ => 4.0,
+    };
+
+    for (segments, is_closed) in split_subpaths(&path) {
+        let (first, last) = match (segments.first(), segments.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => continue,
+        };
+
+        if is_miter_join {
+            for pair in segments.windows(2) {
+                add_miter(&mut bounds, pair[0].end, pair[0].end_dir, pair[1].start_dir, hw, miter_limit);
+            }
+
+            if is_closed {
+                add_miter(&mut bounds, last.end, last.end_dir, first.start_dir, hw, miter_limit);
+            }
+        }
+
+        if is_square_cap && !is_closed {
+            let dir = (-first.start_dir.0, -first.start_dir.1);
+            add_square_cap(&mut bounds, first.start, dir, hw);
+            add_square_cap(&mut bounds, last.end, last.end_dir, hw);
+        }
+    }
+
+    bounds.to_rect()
+}
+
+fn resolve_stroke_width(node: &Node, value: Option<&AttributeValue>) -> Option<f64> {
+    let value = match value {
+        Some(value) => value,
+        None => return Some(1.0),
+    };
+
+    // Percentages are relative to the normalized viewport diagonal.
+    length_to_px(value, || {
+        let vp = try_opt!(nearest_viewport(node));
+        let (w, h) = try_opt!(viewport_size(&vp));
+        Some(((w * w + h * h) / 2.0).sqrt())
+    })
+}
+
+// Splits a canonical path into subpaths of non-degenerate segments.
+fn split_subpaths(path: &Path) -> Vec<(Vec<Segment>, bool)> {
+    let mut list = Vec::new();
+    let mut segments = Vec::new();
+    let mut prev = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    for seg in path.iter() {
+        let (p, curve) = match *seg {
+            PathSegment::MoveTo { x, y, .. } => {
+                if !segments.is_empty() {
+                    list.push((segments, false));
+                    segments = Vec::new();
+                }
+
+                start = (x, y);
+                prev = (x, y);
+                continue;
+            }
+            PathSegment::LineTo { x, y, .. } => ((x, y), None),
+            PathSegment::CurveTo { x1, y1, x2, y2, x, y, .. } => ((x, y), Some(((x1, y1), (x2, y2)))),
+            PathSegment::ClosePath { .. } => {
+                if let Some(dir) = direction(prev, start) {
+                    segments.push(Segment { start: prev, start_dir: dir, end: start, end_dir: dir });
+                }
+
+                if !segments.is_empty() {
+                    list.push((segments, true));
+                    segments = Vec::new();
+                }
+
+                prev = start;
+                continue;
+            }
+            _ => continue,
+        };
+
+        let (start_dir, end_dir) = match curve {
+            Some((p1, p2)) => {
+                let start_dir = direction(prev, p1).or(direction(prev, p2)).or(direction(prev, p));
+                let end_dir = direction(p2, p).or(direction(p1, p)).or(direction(prev, p));
+                (start_dir, end_dir)
+            }
+            None => (direction(prev, p), direction(prev, p)),
+        };
+
+        if let (Some(start_dir), Some(end_dir)) = (start_dir, end_dir) {
+            segments.push(Segment { start: prev, start_dir, end: p, end_dir });
+        }
+
+        prev = p;
+    }
+
+    if !segments.is_empty() {
+        list.push((segments, false));
+    }
+
+    list
+}
+
+// Returns a unit vector from `p1` to `p2`.
+fn direction(p1: (f64, f64), p2: (f64, f64)) -> Option<(f64, f64)> {
+    let (dx, dy) = (p2.0 - p1.0, p2.1 - p1.1);
+    let len = (dx * dx + dy * dy).sqrt();
+    if len < 1e-12 {
+        None
+    } else {
+        Some((dx / len, dy / len))
+    }
+}
+
+// Adds the outer point of a miter join, unless it exceeds the miter limit.
+fn add_miter(bounds: &mut Bounds, p: (f64, f64), a: (f64, f64), b: (f64, f64), hw: f64, limit: f64) {
+    let cross = a.0 * b.1 - a.1 * b.0;
+    if cross.abs() < 1e-12 {
+        // Straight or fully reversed segments don't have a miter.
+        return;
+    }
+
+    // A sum of the left normals, which points to the inner side of the turn for a left turn.
+    let s = (-a.1 - b.1, a.0 + b.0);
+    let len2 = s.0 * s.0 + s.1 * s.1;
+    if 2.0 / len2.sqrt() > limit {
+        // Converted into a bevel join.
+        return;
+    }
+
+    let k = if cross > 0.0 { -1.0 } else { 1.0 } * 2.0 * hw / len2;
+    bounds.add(p.0 + s.0 * k, p.1 + s.1 * k);
+}
+
+fn add_square_cap(bounds: &mut Bounds, p: (f64, f64), dir: (f64, f64), hw: f64) {
+    let (tx, ty) = (dir.0 * hw, dir.1 * hw);
+    let (nx, ny) = (-ty, tx);
+    bounds.add(p.0 + tx + nx, p.1 + ty + ny);
+    bounds.add(p.0 + tx - nx, p.1 + ty - ny);
+}
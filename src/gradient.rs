@@ -0,0 +1,120 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    AttributeId,
+    AttributeValue,
+    Color,
+    ElementId,
+    ElementType,
+    LengthUnit,
+    Node,
+};
+
+/// A resolved gradient stop.
+///
+/// See [`Node::gradient_stops`] for details.
+///
+/// [`Node::gradient_stops`]: type.Node.html#method.gradient_stops
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GradientStop {
+    /// Stop offset in a 0..1 range.
+    pub offset: f64,
+    /// Stop color.
+    pub color: Color,
+    /// Stop opacity in a 0..1 range.
+    pub opacity: f64,
+}
+
+pub fn gradient_stops(node: &Node) -> Vec<GradientStop> {
+    if !node.is_gradient() {
+        return Vec::new();
+    }
+
+    let node = match find_stops_owner(node) {
+        Some(node) => node,
+        None => return Vec::new(),
+    };
+
+    let mut stops = Vec::new();
+    let mut prev_offset = 0.0;
+    for stop in node.children().filter(|n| n.is_tag_name(ElementId::Stop)) {
+        let offset = match stop.attributes().get_value(AttributeId::Offset) {
+            Some(&AttributeValue::Length(len)) => {
+                if len.unit == LengthUnit::Percent { len.num / 100.0 } else { len.num }
+            }
+            Some(&AttributeValue::Number(n)) => n,
+            _ => 0.0,
+        };
+
+        // Each offset must be equal or greater than the previous one.
+        let offset = f64_max(prev_offset, f64_bound(0.0, offset, 1.0));
+        prev_offset = offset;
+
+        let style = stop.computed_style();
+
+        let color = match style.get(&AttributeId::StopColor) {
+            Some(&AttributeValue::Color(c)) => c,
+            Some(&AttributeValue::CurrentColor) => {
+                match style.get(&AttributeId::Color) {
+                    Some(&AttributeValue::Color(c)) => c,
+                    _ => Color::new(0, 0, 0),
+                }
+            }
+            _ => Color::new(0, 0, 0),
+        };
+
+        let opacity = match style.get(&AttributeId::StopOpacity) {
+            Some(&AttributeValue::Number(n)) => f64_bound(0.0, n, 1.0),
+            _ => 1.0,
+        };
+
+        stops.push(GradientStop { offset, color, opacity });
+    }
+
+    stops
+}
+
+// Returns a gradient with stops, following `xlink:href` links.
+fn find_stops_owner(node: &Node) -> Option<Node> {
+    let mut node = node.clone();
+    // Protects from the recursive links.
+    let mut visited: Vec<Node> = Vec::new();
+    loop {
+        if node.children().any(|n| n.is_tag_name(ElementId::Stop)) {
+            return Some(node);
+        }
+
+        visited.push(node.clone());
+
+        let link = match node.attributes().get_value(AttributeId::Href) {
+            Some(&AttributeValue::Link(ref link)) if link.is_gradient() => link.clone(),
+            _ => return None,
+        };
+
+        if visited.contains(&link) {
+            return None;
+        }
+
+        node = link;
+    }
+}
+
+fn f64_bound(min: f64, val: f64, max: f64) -> f64 {
+    if val > max {
+        max
+    } else if val < min {
+        min
+    } else {
+        val
+    }
+}
+
+fn f64_max(a: f64, b: f64) -> f64 {
+    if a > b { a } else { b }
+}
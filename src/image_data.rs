@@ -0,0 +1,163 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// An `image` element data.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ImageData {
+    /// Data embedded via the `data:` URI.
+    Embedded {
+        /// A MIME type, like `image/png`.
+        ///
+        /// `text/plain` when not set.
+        mime: String,
+        /// Decoded data.
+        data: Vec<u8>,
+    },
+    /// A link to an external resource. Not fetched.
+    External(String),
+}
+
+impl ImageData {
+    /// Parses an `xlink:href` attribute value.
+    ///
+    /// Returns `None` on an invalid `data:` URI.
+    pub fn from_href(href: &str) -> Option<ImageData> {
+        let href = href.trim();
+        if !href.starts_with("data:") {
+            return Some(ImageData::External(href.to_string()));
+        }
+
+        let href = &href[5..];
+        let comma = try_opt!(href.find(','));
+
+        let (header, data) = (&href[..comma], &href[comma + 1..]);
+
+        let mut is_base64 = false;
+        let mut mime = String::new();
+        for (i, part) in header.split(';').enumerate() {
+            if part == "base64" {
+                is_base64 = true;
+            } else if i == 0 {
+                mime = part.trim().to_string();
+            }
+        }
+
+        if mime.is_empty() {
+            mime = "text/plain".to_string();
+        }
+
+        // Base64 data can contain percent-encoded characters too.
+        let data = try_opt!(percent_decode(data));
+
+        let data = if is_base64 {
+            try_opt!(base64_decode(&data))
+        } else {
+            data
+        };
+
+        Some(ImageData::Embedded { mime, data })
+    }
+
+    /// Converts data into an `xlink:href` attribute value.
+    ///
+    /// Embedded data is always encoded as base64.
+    pub fn to_href(&self) -> String {
+        match *self {
+            ImageData::Embedded { ref mime, ref data } => {
+                format!("data:{};base64,{}", mime, base64_encode(data))
+            }
+            ImageData::External(ref url) => url.clone(),
+        }
+    }
+}
+
+const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut s = String::with_capacity(data.chunks(3).len() * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            if chunk.len() > 1 { chunk[1] } else { 0 },
+            if chunk.len() > 2 { chunk[2] } else { 0 },
+        ];
+
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(BASE64_CHARS[(n >> (18 - i * 6) & 0x3F) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+
+    s
+}
+
+fn base64_decode(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() / 4 * 3);
+    let mut n = 0u32;
+    let mut bits = 0;
+    for &c in data {
+        let v = if c >= b'A' && c <= b'Z' {
+            c - b'A'
+        } else if c >= b'a' && c <= b'z' {
+            c - b'a' + 26
+        } else if c >= b'0' && c <= b'9' {
+            c - b'0' + 52
+        } else {
+            match c {
+                b'+' | b'-' => 62,
+                b'/' | b'_' => 63,
+                b'=' => break,
+                b' ' | b'\t' | b'\n' | b'\r' => continue,
+                _ => return None,
+            }
+        };
+
+        n = (n << 6 | v as u32) & 0xFFFFFF;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((n >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+fn percent_decode(data: &str) -> Option<Vec<u8>> {
+    let bytes = data.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if i + 2 >= bytes.len() {
+                return None;
+            }
+
+            let hex = match ::std::str::from_utf8(&bytes[i + 1..i + 3]) {
+                Ok(hex) => hex,
+                Err(_) => return None,
+            };
+
+            match u8::from_str_radix(hex, 16) {
+                Ok(b) => out.push(b),
+                Err(_) => return None,
+            }
+
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    Some(out)
+}
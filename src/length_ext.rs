@@ -0,0 +1,152 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    FuzzyEq,
+    Length,
+    LengthUnit,
+};
+
+/// Additional methods for the [`Length`].
+///
+/// [`Length`]: struct.Length.html
+pub trait LengthExt {
+    /// Converts a length into user units (px) using a specified DPI.
+    ///
+    /// Returns `None` for relative units: `em`, `ex` and `%`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Length, LengthExt, LengthUnit};
+    ///
+    /// assert_eq!(Length::new(1.0, LengthUnit::In).to_px(96.0), Some(96.0));
+    /// assert_eq!(Length::new(1.0, LengthUnit::Em).to_px(96.0), None);
+    /// ```
+    fn to_px(&self, dpi: f64) -> Option<f64>;
+
+    /// Adds two lengths.
+    ///
+    /// Lengths with the same unit are added as is. Otherwise, both lengths must have
+    /// absolute units and `other` will be converted into the unit of `self`
+    /// using a specified DPI.
+    ///
+    /// Returns `None` when units are different and at least one of them is relative.
+    ///
+    /// Lengths are stored in `svgtypes`, so the `Add` trait cannot be implemented here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Length, LengthExt, LengthUnit};
+    ///
+    /// let len = Length::new(1.0, LengthUnit::In);
+    /// assert_eq!(len.checked_add(&Length::new(1.0, LengthUnit::In), 96.0),
+    ///            Some(Length::new(2.0, LengthUnit::In)));
+    /// assert_eq!(len.checked_add(&Length::new(48.0, LengthUnit::Px), 96.0),
+    ///            Some(Length::new(1.5, LengthUnit::In)));
+    /// assert_eq!(len.checked_add(&Length::new(50.0, LengthUnit::Percent), 96.0), None);
+    /// ```
+    fn checked_add(&self, other: &Length, dpi: f64) -> Option<Length>;
+
+    /// Subtracts one length from another.
+    ///
+    /// See [`checked_add`] for details.
+    ///
+    /// [`checked_add`]: #tymethod.checked_add
+    fn checked_sub(&self, other: &Length, dpi: f64) -> Option<Length>;
+
+    /// Multiplies a length by a number, preserving the unit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Length, LengthExt, LengthUnit};
+    ///
+    /// assert_eq!(Length::new(10.0, LengthUnit::Percent).scale(2.0),
+    ///            Length::new(20.0, LengthUnit::Percent));
+    /// ```
+    fn scale(&self, k: f64) -> Length;
+
+    /// Checks that two lengths are equal after converting them into user units
+    /// using a specified DPI.
+    ///
+    /// Useful for deduplication, since `1in` and `96px` are the same length at 96 DPI.
+    /// Unlike fuzzy equality, only the units conversion is taken into account
+    /// and numbers must be equal up to a rounding error.
+    ///
+    /// Relative units, like `%` and `em`, cannot be converted without an element context,
+    /// so such lengths are equal only to lengths with the same unit and number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Length, LengthExt, LengthUnit};
+    ///
+    /// let len = Length::new(1.0, LengthUnit::In);
+    /// assert!(len.equivalent_to(&Length::new(96.0, LengthUnit::Px), 96.0));
+    /// assert!(len.equivalent_to(&Length::new(2.54, LengthUnit::Cm), 96.0));
+    /// assert!(!len.equivalent_to(&Length::new(96.0, LengthUnit::Px), 72.0));
+    ///
+    /// let len = Length::new(50.0, LengthUnit::Percent);
+    /// assert!(len.equivalent_to(&Length::new(50.0, LengthUnit::Percent), 96.0));
+    /// assert!(!len.equivalent_to(&Length::new(50.0, LengthUnit::Px), 96.0));
+    /// ```
+    fn equivalent_to(&self, other: &Length, dpi: f64) -> bool;
+}
+
+impl LengthExt for Length {
+    fn to_px(&self, dpi: f64) -> Option<f64> {
+        let n = self.num;
+        match self.unit {
+            LengthUnit::None | LengthUnit::Px => Some(n),
+            LengthUnit::In => Some(n * dpi),
+            LengthUnit::Cm => Some(n * dpi / 2.54),
+            LengthUnit::Mm => Some(n * dpi / 25.4),
+            LengthUnit::Pt => Some(n * dpi / 72.0),
+            LengthUnit::Pc => Some(n * dpi / 6.0),
+            LengthUnit::Em | LengthUnit::Ex | LengthUnit::Percent => None,
+        }
+    }
+
+    fn checked_add(&self, other: &Length, dpi: f64) -> Option<Length> {
+        let n = try_opt!(convert(other, self.unit, dpi));
+        Some(Length::new(self.num + n, self.unit))
+    }
+
+    fn checked_sub(&self, other: &Length, dpi: f64) -> Option<Length> {
+        let n = try_opt!(convert(other, self.unit, dpi));
+        Some(Length::new(self.num - n, self.unit))
+    }
+
+    fn scale(&self, k: f64) -> Length {
+        Length::new(self.num * k, self.unit)
+    }
+
+    fn equivalent_to(&self, other: &Length, dpi: f64) -> bool {
+        if self.unit == other.unit {
+            return self.num.fuzzy_eq(&other.num);
+        }
+
+        match (self.to_px(dpi), other.to_px(dpi)) {
+            (Some(n1), Some(n2)) => n1.fuzzy_eq(&n2),
+            _ => false,
+        }
+    }
+}
+
+// Converts a length number into the specified unit.
+fn convert(len: &Length, unit: LengthUnit, dpi: f64) -> Option<f64> {
+    if len.unit == unit {
+        return Some(len.num);
+    }
+
+    let px = try_opt!(len.to_px(dpi));
+    let unit_px = try_opt!(Length::new(1.0, unit).to_px(dpi));
+    Some(px / unit_px)
+}
@@ -54,6 +54,7 @@ doc for details.
 - Only SVG elements and attributes will be parsed.
 - Attribute values, CDATA with CSS, DOCTYPE, text data and whitespaces will not be preserved.
 - UTF-8 only.
+- Only entities from the internal DTD subset are expanded. See `ParseOptions::keep_unknown_entities`.
 - Only most popular attributes are parsed, other stored as strings.
 - No compressed SVG (.svgz). You should decompress it by yourself.
 - CSS support is minimal.
@@ -74,21 +75,54 @@ doc for details.
 #![warn(missing_docs)]
 
 #[macro_use] extern crate log;
-extern crate simplecss;
+#[cfg(feature = "parsing")] extern crate simplecss;
 extern crate slab;
 extern crate svgtypes;
-extern crate roxmltree;
+#[cfg(feature = "parsing")] extern crate roxmltree;
+#[cfg(feature = "serde")] extern crate serde;
 
 
+macro_rules! try_opt {
+    ($expr: expr) => {
+        match $expr {
+            Some(value) => value,
+            None => return None
+        }
+    }
+}
+
 mod attribute;
+mod builder;
+mod class_list;
+mod content_model;
+mod diff;
 mod document;
 mod node;
 mod tree;
 mod element_type;
 mod error;
+mod external;
+mod features;
+mod flat;
+mod geometry;
+mod gradient;
+mod image_data;
+mod length_ext;
+mod merge;
 mod name;
+#[cfg(feature = "parsing")]
 mod parser;
+mod path_ext;
+pub mod postproc;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod tokens;
+mod transform_ext;
+mod transform_list;
 mod writer;
+mod xpath;
 mod attribute_type;
 mod attribute_value;
 mod attributes;
@@ -96,14 +130,60 @@ mod attributes;
 
 pub use attribute::*;
 pub use attribute_type::AttributeType;
-pub use attribute_value::AttributeValue;
+pub use attribute_value::{
+    AttributeValue,
+    AttributeValueKind,
+};
 pub use attributes::*;
-pub use document::Document;
+pub use builder::ElementBuilder;
+pub use class_list::ClassList;
+pub use content_model::{
+    ContentModel,
+    ValidationError,
+};
+pub use diff::DomChange;
+pub use document::{
+    Document,
+    FindNodes,
+    Links,
+    NodesWithAttribute,
+};
 pub use element_type::ElementType;
 pub use error::*;
+pub use external::ExternalReference;
+pub use features::FeatureSet;
+pub use flat::{
+    FlatAttribute,
+    FlatDom,
+    FlatNode,
+    FlatValue,
+    Snapshot,
+};
+pub use geometry::{
+    Rect,
+    TranslateMode,
+};
+pub use gradient::GradientStop;
+pub use image_data::ImageData;
+pub use length_ext::LengthExt;
 pub use name::*;
 pub use node::*;
-pub use parser::ParseOptions;
+#[cfg(feature = "parsing")]
+pub use parser::{
+    DuplicateAttributes,
+    ParseOptions,
+};
+pub use path_ext::PathExt;
+pub use tokens::{
+    PathTokens,
+    PointsTokens,
+    TransformTokens,
+};
+pub use transform_ext::TransformExt;
+pub use transform_list::{
+    TransformItem,
+    TransformList,
+};
 pub use tree::iterator::*;
 pub use writer::*;
 
@@ -115,6 +195,7 @@ pub use svgtypes::{
     AttributeId,
     Color,
     ElementId,
+    Error as ValueError,
     FuzzyEq,
     FuzzyZero,
     Length,
@@ -161,16 +242,27 @@ pub enum NodeType {
     Comment,
     /// A text node.
     Text,
+    /// A processing instruction node, like `<?xml-stylesheet href="style.css"?>`.
+    ///
+    /// The node text contains the target and data separated by a space,
+    /// like `xml-stylesheet href="style.css"`.
+    ProcessingInstruction,
 }
 
 
 /// Node's data.
 pub struct NodeData {
     storage_key: Option<usize>,
+    // A document-wide unique number, since storage keys are reused.
+    generation: u64,
+    // A copy of the document name validator.
+    name_validator: Option<fn(&str) -> bool>,
     node_type: NodeType,
     tag_name: TagName,
     id: String,
     attributes: Attributes,
     linked_nodes: Vec<Node>,
     text: String,
+    // A row and a column in the source text. Set only by `ParseOptions::track_positions`.
+    source_pos: Option<(u32, u32)>,
 }
@@ -0,0 +1,354 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{HashMap, HashSet};
+
+use postproc;
+use {
+    Attribute,
+    AttributeId,
+    AttributeType,
+    AttributeValue,
+    Document,
+    ElementId,
+    Node,
+    NodeType,
+    Transform,
+    ViewBox,
+};
+
+pub fn merge_document(doc: &mut Document, other: Document, offset: Option<(f64, f64)>) -> Node {
+    let mut used_ids: HashSet<String> = doc.root().descendants()
+        .filter(|n| n.has_id())
+        .map(|n| n.id().clone())
+        .collect();
+    postproc::rename_colliding_ids(&[other.root()], &mut used_ids);
+
+    let mut svg = match doc.svg_element() {
+        Some(svg) => svg,
+        None => {
+            let svg = doc.create_element(ElementId::Svg);
+            doc.root().append(svg.clone());
+            svg
+        }
+    };
+
+    let mut g = doc.create_element(ElementId::G);
+    if let Some((dx, dy)) = offset {
+        g.set_attribute((AttributeId::Transform, Transform::new_translate(dx, dy)));
+    }
+    svg.append(g.clone());
+
+    if let Some(other_svg) = other.svg_element() {
+        let children: Vec<Node> = other_svg.children().collect();
+        import_nodes(doc, &children, &mut g);
+    }
+
+    g
+}
+
+pub fn add_as_symbol(doc: &mut Document, other: &Document, id: &str) -> Node {
+    assert!(!id.is_empty(), "symbol ID must not be empty");
+
+    let mut used_ids: HashSet<String> = doc.root().descendants()
+        .filter(|n| n.has_id())
+        .map(|n| n.id().clone())
+        .collect();
+    assert!(!used_ids.contains(id), "ID '{}' is already used", id);
+    used_ids.insert(id.to_string());
+
+    let mut defs = defs_element(doc);
+
+    let mut symbol = doc.create_element(ElementId::Symbol);
+    symbol.set_id(id);
+    defs.append(symbol.clone());
+
+    if let Some(other_svg) = other.svg_element() {
+        let attrs = other_svg.attributes();
+        match attrs.get_value(AttributeId::ViewBox) {
+            Some(&AttributeValue::ViewBox(vb)) => {
+                symbol.set_attribute((AttributeId::ViewBox, vb));
+            }
+            _ => {
+                if let Some((w, h)) = other_svg.viewport_size() {
+                    symbol.set_attribute((AttributeId::ViewBox, ViewBox::new(0.0, 0.0, w, h)));
+                }
+            }
+        }
+
+        if let Some(value) = attrs.get_value(AttributeId::PreserveAspectRatio) {
+            symbol.set_attribute((AttributeId::PreserveAspectRatio, value.clone()));
+        }
+
+        let children: Vec<Node> = other_svg.children().collect();
+        import_nodes(doc, &children, &mut symbol);
+    }
+
+    // Rename only the imported elements.
+    let children: Vec<Node> = symbol.children().collect();
+    postproc::rename_colliding_ids(&children, &mut used_ids);
+
+    symbol
+}
+
+pub fn subtree_to_document(node: &Node) -> Document {
+    let mut doc = Document::new();
+
+    if node.node_type() == NodeType::Root {
+        let children: Vec<Node> = node.children().collect();
+        let mut root = doc.root();
+        import_nodes(&mut doc, &children, &mut root);
+        return doc;
+    }
+
+    let is_svg = node.is_tag_name(ElementId::Svg);
+    let viewport = node.ancestors().skip(1).find(|n| n.is_tag_name(ElementId::Svg));
+
+    // Attributes inherited from ancestors.
+    let mut inherited: Vec<Attribute> = Vec::new();
+    if node.is_element() {
+        for ancestor in node.ancestors().skip(1) {
+            for attr in ancestor.attributes().iter() {
+                let id = match attr.id() {
+                    Some(id) => id,
+                    None => continue,
+                };
+
+                if id.is_inheritable()
+                    && !node.has_attribute(id)
+                    && !inherited.iter().any(|a| a.has_id(id))
+                {
+                    inherited.push(attr.clone());
+                }
+            }
+        }
+    }
+
+    // Elements referenced by the subtree or by inherited attributes.
+    let mut nodes = dependencies(node);
+    for attr in &inherited {
+        if let Some(link) = link_value(&attr.value) {
+            if nodes.contains(&link) || link.ancestors().any(|n| n == *node) {
+                continue;
+            }
+
+            let link_deps = dependencies(&link);
+            nodes.push(link);
+            for dep in link_deps {
+                if !nodes.contains(&dep) && !dep.ancestors().any(|n| n == *node) {
+                    nodes.push(dep);
+                }
+            }
+        }
+    }
+    let deps_count = nodes.len();
+    nodes.push(node.clone());
+
+    let mut parent = if is_svg {
+        doc.root()
+    } else {
+        let mut svg = doc.create_element(ElementId::Svg);
+        if let Some(ref viewport) = viewport {
+            for id in &[AttributeId::Width, AttributeId::Height,
+                        AttributeId::ViewBox, AttributeId::PreserveAspectRatio] {
+                if let Some(value) = viewport.attributes().get_value(*id) {
+                    svg.set_attribute((*id, value.clone()));
+                }
+            }
+        }
+
+        doc.root().append(svg.clone());
+        svg
+    };
+
+    import_nodes(&mut doc, &nodes, &mut parent);
+
+    let copies: Vec<Node> = parent.children().collect();
+    let mut copy = copies[copies.len() - 1].clone();
+
+    // Referenced elements are moved into `defs`.
+    if deps_count != 0 {
+        let mut defs = defs_element(&mut doc);
+        for mut dep in copies.into_iter().take(deps_count) {
+            dep.detach();
+            defs.append(dep);
+        }
+    }
+
+    for attr in inherited {
+        let value = match link_value(&attr.value) {
+            Some(link) => {
+                let link_copy = doc.root().descendants().find(|n| *n.id() == *link.id());
+                match (link_copy, attr.value) {
+                    (Some(n), AttributeValue::Link(_)) => AttributeValue::Link(n),
+                    (Some(n), AttributeValue::FuncLink(_)) => AttributeValue::FuncLink(n),
+                    (Some(n), AttributeValue::Paint(_, fallback)) => AttributeValue::Paint(n, fallback),
+                    _ => continue,
+                }
+            }
+            None => attr.value,
+        };
+
+        copy.set_attribute(Attribute::new(attr.name.as_ref(), value));
+    }
+
+    // Transforms of ancestors inside the viewport are applied to the copy.
+    if !is_svg && node.is_element() {
+        let mut ts = Transform::default();
+        let mut ancestors: Vec<Node> = node.ancestors().skip(1)
+            .take_while(|n| Some(n) != viewport.as_ref())
+            .collect();
+        ancestors.reverse();
+        ancestors.push(node.clone());
+
+        for n in &ancestors {
+            if let Some(&AttributeValue::Transform(ref t)) = n.attributes().get_value(AttributeId::Transform) {
+                ts.append(t);
+            }
+        }
+
+        if !ts.is_default() {
+            copy.set_attribute((AttributeId::Transform, ts));
+        }
+    }
+
+    doc
+}
+
+fn link_value(value: &AttributeValue) -> Option<Node> {
+    match *value {
+        AttributeValue::Link(ref link)
+        | AttributeValue::FuncLink(ref link)
+        | AttributeValue::Paint(ref link, _) => Some(link.clone()),
+        _ => None,
+    }
+}
+
+// Collects elements outside the `elem` subtree that are referenced from it, recursively.
+pub fn dependencies(elem: &Node) -> Vec<Node> {
+    let mut list: Vec<Node> = Vec::new();
+    let mut stack = vec![elem.clone()];
+    while let Some(root) = stack.pop() {
+        for node in root.descendants() {
+            for attr in node.attributes().iter() {
+                let link = match attr.value {
+                    AttributeValue::Link(ref link)
+                    | AttributeValue::FuncLink(ref link)
+                    | AttributeValue::Paint(ref link, _) => link.clone(),
+                    _ => continue,
+                };
+
+                let is_imported = link.ancestors()
+                    .any(|n| n == *elem || list.contains(&n));
+                if !is_imported {
+                    list.push(link.clone());
+                    stack.push(link);
+                }
+            }
+        }
+    }
+
+    list
+}
+
+// Returns the first `defs` element of the root `svg` element.
+//
+// Both elements are created when missing.
+pub fn defs_element(doc: &mut Document) -> Node {
+    let mut svg = match doc.svg_element() {
+        Some(svg) => svg,
+        None => {
+            let svg = doc.create_element(ElementId::Svg);
+            doc.root().append(svg.clone());
+            svg
+        }
+    };
+
+    match svg.children().find(|n| n.is_tag_name(ElementId::Defs)) {
+        Some(defs) => defs,
+        None => {
+            let defs = doc.create_element(ElementId::Defs);
+            svg.prepend(defs.clone());
+            defs
+        }
+    }
+}
+
+// Copies nodes from another document and appends them to the `parent`.
+//
+// Unlike `Document::copy_node_deep`, IDs are preserved and links between copied nodes
+// are redirected to the copies. Links to other nodes are stored as strings.
+pub fn import_nodes(doc: &mut Document, nodes: &[Node], parent: &mut Node) {
+    // Source node's storage key -> copied node.
+    let mut map: HashMap<usize, Node> = HashMap::new();
+    let mut pairs: Vec<(Node, Node)> = Vec::new();
+
+    for node in nodes {
+        let copy = import_tree(doc, node, &mut map, &mut pairs);
+        parent.append(copy);
+    }
+
+    // Links can be resolved only when all nodes are copied.
+    for (src, mut dst) in pairs {
+        for attr in src.attributes().iter() {
+            let value = match attr.value {
+                AttributeValue::Link(ref n) => {
+                    match map.get(&storage_key(n)) {
+                        Some(n) => AttributeValue::Link(n.clone()),
+                        None => AttributeValue::String(attr.value.to_string()),
+                    }
+                }
+                AttributeValue::FuncLink(ref n) => {
+                    match map.get(&storage_key(n)) {
+                        Some(n) => AttributeValue::FuncLink(n.clone()),
+                        None => AttributeValue::String(attr.value.to_string()),
+                    }
+                }
+                AttributeValue::Paint(ref n, fallback) => {
+                    match map.get(&storage_key(n)) {
+                        Some(n) => AttributeValue::Paint(n.clone(), fallback),
+                        None => AttributeValue::String(attr.value.to_string()),
+                    }
+                }
+                _ => attr.value.clone(),
+            };
+
+            dst.set_attribute(Attribute::new(attr.name.as_ref(), value));
+        }
+    }
+}
+
+fn import_tree(
+    doc: &mut Document,
+    node: &Node,
+    map: &mut HashMap<usize, Node>,
+    pairs: &mut Vec<(Node, Node)>,
+) -> Node {
+    let mut copy = match node.node_type() {
+        NodeType::Element => {
+            let mut elem = doc.create_element(node.tag_name().as_ref());
+            elem.set_id(node.id().clone());
+            pairs.push((node.clone(), elem.clone()));
+            elem
+        }
+        _ => doc.create_node(node.node_type(), node.text().clone()),
+    };
+
+    map.insert(storage_key(node), copy.clone());
+
+    for child in node.children() {
+        let child_copy = import_tree(doc, &child, map, pairs);
+        copy.append(child_copy);
+    }
+
+    copy
+}
+
+fn storage_key(node: &Node) -> usize {
+    node.borrow().storage_key.expect("node without a storage key")
+}
@@ -59,18 +59,32 @@ impl<T: SvgId> QName<T> {
 }
 
 impl WriteBuffer for QName<AttributeId> {
-    fn write_buf_opt(&self, _opt: &WriteOptions, buf: &mut Vec<u8>) {
+    fn write_buf_opt(&self, opt: &WriteOptions, buf: &mut Vec<u8>) {
         match *self {
             QName::Id(id) => {
-                if self.has_id(AttributeId::Href) {
-                    buf.extend_from_slice(b"xlink:");
-                } else if self.has_id(AttributeId::Space) {
-                    buf.extend_from_slice(b"xml:");
+                match id {
+                    AttributeId::Href => {
+                        buf.extend_from_slice(b"xlink:");
+                    }
+                    AttributeId::Space => {
+                        buf.extend_from_slice(b"xml:");
+                    }
+                    AttributeId::Base | AttributeId::Lang
+                        if opt.canonicalize_attribute_prefixes => {
+                        buf.extend_from_slice(b"xml:");
+                    }
+                    _ => {}
                 }
 
                 buf.extend_from_slice(id.name().as_bytes());
             }
             QName::Name(ref name) => {
+                let name = if opt.canonicalize_attribute_prefixes && name.starts_with("svg:") {
+                    &name[4..]
+                } else {
+                    name
+                };
+
                 buf.extend_from_slice(name.as_bytes());
             }
         }
@@ -141,3 +155,73 @@ impl<'a, T: SvgId> From<QNameRef<'a, T>> for QName<T> {
         }
     }
 }
+
+/// Checks that the string is a valid XML name.
+///
+/// Namespace prefixes are allowed, so `xlink:href` is a valid name.
+///
+/// Details: <https://www.w3.org/TR/xml/#NT-Name>
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::is_valid_xml_name;
+///
+/// assert!(is_valid_xml_name("rect"));
+/// assert!(is_valid_xml_name("inkscape:label"));
+/// assert!(!is_valid_xml_name(""));
+/// assert!(!is_valid_xml_name("1rect"));
+/// assert!(!is_valid_xml_name("my rect"));
+/// ```
+pub fn is_valid_xml_name(name: &str) -> bool {
+    let mut iter = name.chars();
+    match iter.next() {
+        Some(c) if is_name_start_char(c) => {}
+        _ => return false,
+    }
+
+    iter.all(is_name_char)
+}
+
+fn is_name_start_char(c: char) -> bool {
+    let c = c as u32;
+    match c {
+        0x3A | 0x5F => true, // ':' | '_'
+        _ => {
+               in_range(c, 0x41, 0x5A) // A-Z
+            || in_range(c, 0x61, 0x7A) // a-z
+            || in_range(c, 0xC0, 0xD6)
+            || in_range(c, 0xD8, 0xF6)
+            || in_range(c, 0xF8, 0x2FF)
+            || in_range(c, 0x370, 0x37D)
+            || in_range(c, 0x37F, 0x1FFF)
+            || in_range(c, 0x200C, 0x200D)
+            || in_range(c, 0x2070, 0x218F)
+            || in_range(c, 0x2C00, 0x2FEF)
+            || in_range(c, 0x3001, 0xD7FF)
+            || in_range(c, 0xF900, 0xFDCF)
+            || in_range(c, 0xFDF0, 0xFFFD)
+            || in_range(c, 0x10000, 0xEFFFF)
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    if is_name_start_char(c) {
+        return true;
+    }
+
+    let c = c as u32;
+    match c {
+        0x2D | 0x2E | 0xB7 => true, // '-' | '.' | '·'
+        _ => {
+               in_range(c, 0x30, 0x39) // 0-9
+            || in_range(c, 0x300, 0x36F)
+            || in_range(c, 0x203F, 0x2040)
+        }
+    }
+}
+
+fn in_range(c: u32, start: u32, end: u32) -> bool {
+    c >= start && c <= end
+}
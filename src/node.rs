@@ -6,26 +6,46 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::collections::HashMap;
 use std::iter::FilterMap;
 use std::cell::{Ref, RefMut};
 
+#[cfg(feature = "parsing")]
+use roxmltree::TextPos;
+
 use {
+    geometry,
+    gradient,
+    merge,
     tree,
+    xpath,
     Attribute,
     AttributeId,
     AttributeQName,
     AttributeQNameRef,
     Attributes,
+    AttributeType,
     AttributeValue,
+    ClassList,
+    Document,
     ElementId,
+    ElementType,
     Error,
+    FilterSvgAttrs,
+    FilterSvgAttrsMut,
+    FuzzyEq,
+    GradientStop,
+    ImageData,
     NodeData,
     NodeType,
     PaintFallback,
+    Path,
     QName,
     QNameRef,
+    Rect,
     TagName,
     TagNameRef,
+    Transform,
 };
 
 impl<'a, N, V> From<(N, V)> for Attribute
@@ -78,12 +98,51 @@ impl<'a, N> From<(N, (Node, Option<PaintFallback>))> for Attribute
 /// - List of linked nodes. [Details.](#method.set_attribute_checked)
 /// - Text data, which is used by non-element nodes. Empty by default.
 ///
+/// # Borrowing
+///
+/// The node data is stored inside a `RefCell`, so most of the methods will panic
+/// if the node is currently mutably borrowed, e.g. when a reference returned by
+/// [`attributes_mut`] is still alive. To avoid this:
+///
+/// - Don't keep references returned by accessors, like [`attributes`] or [`text`],
+///   longer than needed. Clone the required data instead.
+/// - Collect nodes into a `Vec` before modifying them during a traversal.
+/// - Use the `try_` variants, like [`try_text`], [`try_tag_name`] and [`try_attributes`],
+///   when exclusive access cannot be guaranteed. They will return `None` instead of panicking.
+/// - Use [`Document::snapshot`] for long read-only passes. A snapshot is an immutable copy
+///   of the tree, which cannot be borrowed at all.
+///
 /// [`Attribute`]: struct.Attribute.html
 /// [`Attributes`]: struct.Attributes.html
 /// [`NodeType`]: enum.NodeType.html
 /// [`TagName`]: type.TagName.html
+/// [`attributes`]: #method.attributes
+/// [`attributes_mut`]: #method.attributes_mut
+/// [`text`]: #method.text
+/// [`try_text`]: #method.try_text
+/// [`try_tag_name`]: #method.try_tag_name
+/// [`try_attributes`]: #method.try_attributes
+/// [`Document::snapshot`]: struct.Document.html#method.snapshot
 pub type Node = tree::Node<NodeData>;
 
+/// A lightweight handle to a [`Node`].
+///
+/// Unlike [`Node`], it doesn't keep the node alive, so it can be stored
+/// in an external data structure, like an editor selection.
+/// Use [`Document::node`] to get the node back.
+///
+/// An ID is valid only for the document that contains the node
+/// and only until the node is removed from it. IDs of removed nodes
+/// are never reused by the same document. Detached nodes are still valid.
+///
+/// [`Node`]: type.Node.html
+/// [`Document::node`]: struct.Document.html#method.node
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct NodeId {
+    pub(crate) key: usize,
+    pub(crate) generation: u64,
+}
+
 impl Node {
     /// Returns `true` if the node has a parent node.
     ///
@@ -114,6 +173,61 @@ impl Node {
         }
     }
 
+    /// Returns the next sibling element, skipping text and comment nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node or any of its siblings are currently mutably borrowed.
+    ///
+    /// # Examples
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    /// "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///     <rect/>
+    ///     <!--comment-->
+    ///     <circle/>
+    /// </svg>").unwrap();
+    ///
+    /// let svg = doc.root().first_child().unwrap();
+    /// let rect = svg.first_child().unwrap();
+    /// let circle = svg.last_child().unwrap();
+    /// assert_eq!(rect.next_element_sibling(), Some(circle.clone()));
+    /// assert_eq!(circle.next_element_sibling(), None);
+    /// assert_eq!(circle.prev_element_sibling(), Some(rect.clone()));
+    /// ```
+    pub fn next_element_sibling(&self) -> Option<Node> {
+        let mut curr = self.next_sibling();
+        while let Some(node) = curr {
+            if node.is_element() {
+                return Some(node);
+            }
+
+            curr = node.next_sibling();
+        }
+
+        None
+    }
+
+    /// Returns the previous sibling element, skipping text and comment nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node or any of its siblings are currently mutably borrowed.
+    pub fn prev_element_sibling(&self) -> Option<Node> {
+        let mut curr = self.previous_sibling();
+        while let Some(node) = curr {
+            if node.is_element() {
+                return Some(node);
+            }
+
+            curr = node.previous_sibling();
+        }
+
+        None
+    }
+
     /// Returns node's type.
     ///
     /// You can't change the type of the node. Only create a new one.
@@ -125,6 +239,15 @@ impl Node {
         self.borrow().node_type
     }
 
+    /// Returns node's type or `None` if the node is currently mutably borrowed.
+    ///
+    /// Non-panicking version of the [`node_type`] method.
+    ///
+    /// [`node_type`]: #method.node_type
+    pub fn try_node_type(&self) -> Option<NodeType> {
+        self.try_borrow().map(|d| d.node_type)
+    }
+
     /// Returns `true` if current node is a Root node.
     ///
     /// # Panics
@@ -161,6 +284,15 @@ impl Node {
         self.node_type() == NodeType::Text
     }
 
+    /// Returns `true` if current node is a ProcessingInstruction node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    pub fn is_processing_instruction(&self) -> bool {
+        self.node_type() == NodeType::ProcessingInstruction
+    }
+
     /// Checks that node belongs to any `Document`.
     ///
     /// # Panics
@@ -170,6 +302,69 @@ impl Node {
         self.borrow().storage_key.is_none()
     }
 
+    /// Returns a node handle.
+    ///
+    /// Returns `None` if the node was removed from the document.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId};
+    ///
+    /// let mut doc = Document::new();
+    /// let rect = doc.create_element(ElementId::Rect);
+    /// let id = rect.node_id().unwrap();
+    /// assert_eq!(doc.node(id), Some(rect.clone()));
+    ///
+    /// doc.remove_node(rect.clone());
+    /// assert_eq!(rect.node_id(), None);
+    /// assert_eq!(doc.node(id), None);
+    /// ```
+    pub fn node_id(&self) -> Option<NodeId> {
+        let data = self.borrow();
+        data.storage_key.map(|key| NodeId { key, generation: data.generation })
+    }
+
+    /// Returns the node position in the source text.
+    ///
+    /// The position is set only when the document was parsed with
+    /// [`ParseOptions::track_positions`] enabled. Nodes created afterwards,
+    /// including copies, have no position.
+    ///
+    /// Elements point to the start of their tag. The position refers to the text
+    /// after entities escaping, see [`ParseOptions::keep_unknown_entities`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ParseOptions};
+    ///
+    /// let mut opt = ParseOptions::default();
+    /// opt.track_positions = true;
+    ///
+    /// let doc = Document::from_str_with_opt(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>\n    <rect/>\n</svg>", &opt).unwrap();
+    ///
+    /// let rect = doc.svg_element().unwrap().first_child().unwrap();
+    /// let pos = rect.source_pos().unwrap();
+    /// assert_eq!((pos.row, pos.col), (2, 5));
+    /// ```
+    ///
+    /// [`ParseOptions::track_positions`]: struct.ParseOptions.html#structfield.track_positions
+    /// [`ParseOptions::keep_unknown_entities`]: struct.ParseOptions.html#structfield.keep_unknown_entities
+    #[cfg(feature = "parsing")]
+    pub fn source_pos(&self) -> Option<TextPos> {
+        self.borrow().source_pos.map(|(row, col)| TextPos::new(row, col))
+    }
+
     /// Returns a text data of the node.
     ///
     /// Nodes with `Element` type can't contain text data.
@@ -181,6 +376,29 @@ impl Node {
         Ref::map(self.borrow(), |d| &d.text)
     }
 
+    /// Returns a text data of the node or `None` if the node is currently mutably borrowed.
+    ///
+    /// Non-panicking version of the [`text`] method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, NodeType};
+    ///
+    /// let mut doc = Document::new();
+    /// let mut node = doc.create_node(NodeType::Text, "text");
+    /// assert_eq!(node.try_text().map(|t| t.clone()), Some("text".to_string()));
+    ///
+    /// let node2 = node.clone();
+    /// let _text = node.text_mut();
+    /// assert!(node2.try_text().is_none());
+    /// ```
+    ///
+    /// [`text`]: #method.text
+    pub fn try_text(&self) -> Option<Ref<String>> {
+        self.try_borrow().map(|d| Ref::map(d, |d| &d.text))
+    }
+
     /// Returns a mutable text data of the node.
     ///
     /// Nodes with `Element` type can't contain text data.
@@ -192,16 +410,146 @@ impl Node {
         RefMut::map(self.borrow_mut(), |d| &mut d.text)
     }
 
+    /// Returns a mutable text data of the node or `None` if the node is currently borrowed.
+    ///
+    /// Non-panicking version of the [`text_mut`] method.
+    ///
+    /// [`text_mut`]: #method.text_mut
+    pub fn try_text_mut(&mut self) -> Option<RefMut<String>> {
+        self.try_borrow_mut().map(|d| RefMut::map(d, |d| &mut d.text))
+    }
+
     /// Sets a text data to the node.
     ///
+    /// Unchecked version of the [`set_text_checked`] method.
+    /// The node type is checked only in debug builds.
+    ///
     /// # Panics
     ///
     /// Panics if the node is currently mutably borrowed.
+    ///
+    /// [`set_text_checked`]: #method.set_text_checked
     pub fn set_text(&mut self, text: &str) {
         debug_assert_ne!(self.node_type(), NodeType::Element);
         self.borrow_mut().text = text.to_owned();
     }
 
+    /// Sets a text data to the node.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidNodeType` for `Element` and `Root` nodes,
+    /// since they can't contain text data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId, Error, NodeType};
+    ///
+    /// let mut doc = Document::new();
+    /// let mut text = doc.create_node(NodeType::Text, "text");
+    /// assert!(text.set_text_checked("new text").is_ok());
+    /// assert_eq!(*text.text(), "new text");
+    ///
+    /// let mut rect = doc.create_element(ElementId::Rect);
+    /// assert_eq!(rect.set_text_checked("text"), Err(Error::InvalidNodeType));
+    /// ```
+    pub fn set_text_checked(&mut self, text: &str) -> Result<(), Error> {
+        match self.node_type() {
+            NodeType::Element | NodeType::Root => Err(Error::InvalidNodeType),
+            _ => {
+                self.borrow_mut().text = text.to_owned();
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns a target of the processing instruction node, like `xml-stylesheet`.
+    ///
+    /// Returns `None` for other node types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<?xml-stylesheet href='style.css'?>
+    ///      <svg xmlns='http://www.w3.org/2000/svg'/>").unwrap();
+    ///
+    /// let pi = doc.root().first_child().unwrap();
+    /// assert_eq!(&*pi.pi_target().unwrap(), "xml-stylesheet");
+    /// assert_eq!(&*pi.pi_data().unwrap(), "href='style.css'");
+    /// ```
+    pub fn pi_target(&self) -> Option<Ref<str>> {
+        if !self.is_processing_instruction() {
+            return None;
+        }
+
+        Some(Ref::map(self.borrow(), |d| split_pi(&d.text).0))
+    }
+
+    /// Returns a data of the processing instruction node, like `href="style.css"`.
+    ///
+    /// Returns `None` for other node types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    pub fn pi_data(&self) -> Option<Ref<str>> {
+        if !self.is_processing_instruction() {
+            return None;
+        }
+
+        Some(Ref::map(self.borrow(), |d| split_pi(&d.text).1))
+    }
+
+    /// Sets a data of the processing instruction node, preserving its target.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidNodeType` for other node types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, NodeType};
+    ///
+    /// let mut doc = Document::new();
+    /// let mut pi = doc.create_node(NodeType::ProcessingInstruction, "xml-stylesheet");
+    /// pi.set_pi_data("href='style.css'").unwrap();
+    /// assert_eq!(&*pi.text(), "xml-stylesheet href='style.css'");
+    /// ```
+    pub fn set_pi_data(&mut self, data: &str) -> Result<(), Error> {
+        if !self.is_processing_instruction() {
+            return Err(Error::InvalidNodeType);
+        }
+
+        let text = {
+            let target = self.pi_target().unwrap();
+            if data.is_empty() {
+                target.to_string()
+            } else {
+                format!("{} {}", &*target, data)
+            }
+        };
+
+        self.borrow_mut().text = text;
+        Ok(())
+    }
+
     /// Returns an ID of the element node.
     ///
     /// # Panics
@@ -211,6 +559,15 @@ impl Node {
         Ref::map(self.borrow(), |d| &d.id)
     }
 
+    /// Returns an ID of the element node or `None` if the node is currently mutably borrowed.
+    ///
+    /// Non-panicking version of the [`id`] method.
+    ///
+    /// [`id`]: #method.id
+    pub fn try_id(&self) -> Option<Ref<String>> {
+        self.try_borrow().map(|d| Ref::map(d, |d| &d.id))
+    }
+
     /// Returns `true` if node has a not empty ID.
     ///
     /// # Panics
@@ -234,6 +591,60 @@ impl Node {
 
     }
 
+    /// Returns a list of classes from the `class` attribute.
+    ///
+    /// Classes resolved by the parser are already applied as attributes,
+    /// so only unresolved classes are present here.
+    /// See [`ParseOptions::skip_unresolved_classes`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ParseOptions};
+    ///
+    /// let mut opt = ParseOptions::default();
+    /// opt.skip_unresolved_classes = false;
+    /// let doc = Document::from_str_with_opt(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect class='icon big'/>
+    ///      </svg>", &opt).unwrap();
+    ///
+    /// let mut rect = doc.svg_element().unwrap().first_child().unwrap();
+    /// let mut list = rect.class_list();
+    /// assert!(list.contains("icon"));
+    /// list.toggle("big");
+    /// rect.set_class_list(&list);
+    /// assert_eq!(doc.to_string(),
+    ///     "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    <rect class=\"icon\"/>\n</svg>\n");
+    /// ```
+    ///
+    /// [`ParseOptions::skip_unresolved_classes`]: struct.ParseOptions.html#structfield.skip_unresolved_classes
+    pub fn class_list(&self) -> ClassList {
+        match self.attributes().get_value(AttributeId::Class) {
+            Some(&AttributeValue::String(ref text)) => text.parse().unwrap(),
+            _ => ClassList::new(),
+        }
+    }
+
+    /// Sets the `class` attribute from a list of classes.
+    ///
+    /// The attribute will be removed if the list is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed.
+    pub fn set_class_list(&mut self, list: &ClassList) {
+        if list.is_empty() {
+            self.remove_attribute(AttributeId::Class);
+        } else {
+            self.set_attribute((AttributeId::Class, list.to_string()));
+        }
+    }
+
     /// Returns `true` if node has an `Element` type and an SVG tag name.
     ///
     /// # Panics
@@ -259,6 +670,30 @@ impl Node {
         Ref::map(self.borrow(), |d| &d.tag_name)
     }
 
+    /// Returns a tag name of the element node
+    /// or `None` if the node is currently mutably borrowed.
+    ///
+    /// Non-panicking version of the [`tag_name`] method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId, QName};
+    ///
+    /// let mut doc = Document::new();
+    /// let mut rect = doc.create_element(ElementId::Rect);
+    /// assert_eq!(*rect.try_tag_name().unwrap(), QName::Id(ElementId::Rect));
+    ///
+    /// let rect2 = rect.clone();
+    /// let _attrs = rect.attributes_mut();
+    /// assert!(rect2.try_tag_name().is_none());
+    /// ```
+    ///
+    /// [`tag_name`]: #method.tag_name
+    pub fn try_tag_name(&self) -> Option<Ref<TagName>> {
+        self.try_borrow().map(|d| Ref::map(d, |d| &d.tag_name))
+    }
+
     /// Returns a tag name id of the SVG element node.
     ///
     /// # Panics
@@ -289,66 +724,377 @@ impl Node {
     /// # Panics
     ///
     /// - Panics if the node is currently borrowed.
+    /// - Panics if the node is not an element.
     /// - Panics if a string tag name is empty.
+    /// - Panics if a string tag name was rejected by the [name validator].
+    ///
+    /// [name validator]: struct.Document.html#method.set_name_validator
     pub fn set_tag_name<'a, T>(&mut self, tag_name: T)
         where TagNameRef<'a>: From<T>
     {
-        debug_assert_eq!(self.node_type(), NodeType::Element);
+        assert_eq!(self.node_type(), NodeType::Element);
 
         let tn = TagNameRef::from(tag_name);
         if let QNameRef::Name(name) = tn {
-            if name.is_empty() {
-                panic!("supplied tag name is empty");
+            if !name.is_empty() && !self.is_valid_name(name) {
+                panic!("supplied tag name '{}' is invalid", name);
             }
         }
 
-        self.borrow_mut().tag_name = TagName::from(tn);
+        self.set_tag_name_impl(tn);
     }
 
-    /// Returns a reference to the `Attributes` of the current node.
+    /// Sets a tag name of the element node.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if the node is currently mutably borrowed.
-    pub fn attributes(&self) -> Ref<Attributes> {
-        Ref::map(self.borrow(), |d| &d.attributes)
-    }
-
-    /// Returns a mutable reference to the `Attributes` of the current node.
+    /// - Returns `Error::InvalidNodeType` for non-element nodes,
+    ///   since only element nodes can contain tag name.
+    /// - Returns `Error::InvalidName` if a string tag name was rejected
+    ///   by the [name validator].
     ///
     /// # Panics
     ///
-    /// Panics if the node is currently borrowed.
-    pub fn attributes_mut(&mut self) -> RefMut<Attributes> {
-        RefMut::map(self.borrow_mut(), |d| &mut d.attributes)
-    }
-
-    /// Returns `true` if the node has an attribute with such `id`.
-    ///
-    /// # Panics
+    /// - Panics if the node is currently borrowed.
+    /// - Panics if a string tag name is empty.
     ///
-    /// Panics if the node is currently mutably borrowed.
-    #[inline]
-    pub fn has_attribute<'a, N>(&self, name: N) -> bool
-        where AttributeQNameRef<'a>: From<N>
-    {
-        self.borrow().attributes.contains(name)
-    }
-
-    /// Inserts a new attribute into attributes list.
+    /// # Examples
     ///
-    /// Unwrapped version of the [`set_attribute_checked`] method.
+    /// ```
+    /// use svgdom::{Document, ElementId, Error, NodeType};
     ///
-    /// # Panics
+    /// let mut doc = Document::new();
+    /// let mut node = doc.create_element(ElementId::Rect);
+    /// assert!(node.set_tag_name_checked(ElementId::Circle).is_ok());
+    /// assert!(node.is_tag_name(ElementId::Circle));
     ///
-    /// Will panic on any error produced by the [`set_attribute_checked`] method.
+    /// let mut text = doc.create_node(NodeType::Text, "text");
+    /// assert_eq!(text.set_tag_name_checked(ElementId::Circle), Err(Error::InvalidNodeType));
+    /// ```
     ///
-    /// [`set_attribute_checked`]: #method.set_attribute_checked
-    pub fn set_attribute<T>(&mut self, v: T)
-        where T: Into<Attribute>
+    /// [name validator]: struct.Document.html#method.set_name_validator
+    pub fn set_tag_name_checked<'a, T>(&mut self, tag_name: T) -> Result<(), Error>
+        where TagNameRef<'a>: From<T>
     {
-        self.set_attribute_checked(v).unwrap();
-    }
+        if self.node_type() != NodeType::Element {
+            return Err(Error::InvalidNodeType);
+        }
+
+        let tn = TagNameRef::from(tag_name);
+        if let QNameRef::Name(name) = tn {
+            if !name.is_empty() && !self.is_valid_name(name) {
+                return Err(Error::InvalidName);
+            }
+        }
+
+        self.set_tag_name_impl(tn);
+        Ok(())
+    }
+
+    // Checks the name using the document name validator.
+    fn is_valid_name(&self, name: &str) -> bool {
+        match self.borrow().name_validator {
+            Some(f) => f(name),
+            None => true,
+        }
+    }
+
+    fn set_tag_name_impl(&mut self, tn: TagNameRef) {
+        if let QNameRef::Name(name) = tn {
+            if name.is_empty() {
+                panic!("supplied tag name is empty");
+            }
+        }
+
+        self.borrow_mut().tag_name = TagName::from(tn);
+    }
+
+    /// Converts the `polyline` element into the `polygon` one.
+    ///
+    /// A polygon is always closed, so its outline gets a segment from the last point
+    /// to the first one. The last point is removed when it's equal to the first one,
+    /// since it's redundant now. The fill is not affected.
+    ///
+    /// Does nothing if the node is not a `polyline` element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, AttributeId, ElementId};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <polyline points='10 20 30 40 50 60 10 20'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let mut node = doc.svg_element().unwrap().first_child().unwrap();
+    /// node.polyline_to_polygon();
+    /// assert!(node.is_tag_name(ElementId::Polygon));
+    /// assert_eq!(node.attributes().get_value(AttributeId::Points).unwrap().to_string(),
+    ///            "10 20 30 40 50 60");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed.
+    pub fn polyline_to_polygon(&mut self) {
+        if !self.is_tag_name(ElementId::Polyline) {
+            return;
+        }
+
+        self.set_tag_name(ElementId::Polygon);
+
+        let mut attrs = self.attributes_mut();
+        if let Some(&mut AttributeValue::Points(ref mut points)) =
+            attrs.get_value_mut(AttributeId::Points)
+        {
+            if points.len() > 1 && is_same_point(points[0], points[points.len() - 1]) {
+                points.pop();
+            }
+        }
+    }
+
+    /// Converts the `polygon` element into the `polyline` one.
+    ///
+    /// The first point is appended to the end of the list, so the outline stays closed.
+    /// The fill is not affected.
+    ///
+    /// Does nothing if the node is not a `polygon` element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed.
+    pub fn polygon_to_polyline(&mut self) {
+        if !self.is_tag_name(ElementId::Polygon) {
+            return;
+        }
+
+        self.set_tag_name(ElementId::Polyline);
+
+        let mut attrs = self.attributes_mut();
+        if let Some(&mut AttributeValue::Points(ref mut points)) =
+            attrs.get_value_mut(AttributeId::Points)
+        {
+            if points.len() > 1 && !is_same_point(points[0], points[points.len() - 1]) {
+                let first = points[0];
+                points.push(first);
+            }
+        }
+    }
+
+    /// Returns a reference to the `Attributes` of the current node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    pub fn attributes(&self) -> Ref<Attributes> {
+        Ref::map(self.borrow(), |d| &d.attributes)
+    }
+
+    /// Returns a reference to the `Attributes` of the current node
+    /// or `None` if the node is currently mutably borrowed.
+    ///
+    /// Non-panicking version of the [`attributes`] method.
+    ///
+    /// [`attributes`]: #method.attributes
+    pub fn try_attributes(&self) -> Option<Ref<Attributes>> {
+        self.try_borrow().map(|d| Ref::map(d, |d| &d.attributes))
+    }
+
+    /// Returns a mutable reference to the `Attributes` of the current node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed.
+    pub fn attributes_mut(&mut self) -> RefMut<Attributes> {
+        RefMut::map(self.borrow_mut(), |d| &mut d.attributes)
+    }
+
+    /// Returns a mutable reference to the `Attributes` of the current node
+    /// or `None` if the node is currently borrowed.
+    ///
+    /// Non-panicking version of the [`attributes_mut`] method.
+    ///
+    /// [`attributes_mut`]: #method.attributes_mut
+    pub fn try_attributes_mut(&mut self) -> Option<RefMut<Attributes>> {
+        self.try_borrow_mut().map(|d| RefMut::map(d, |d| &mut d.attributes))
+    }
+
+    /// Calls a closure for each SVG attribute value of the node.
+    ///
+    /// Unlike [`Document::visit_values_mut`], the values are modified in place,
+    /// using a single borrow of the node.
+    ///
+    /// Link values (`Link`, `FuncLink` and `Paint`) and non-SVG attributes are skipped,
+    /// because links are tracked by the nodes and must be changed via [`set_attribute`].
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the node is currently borrowed.
+    /// - Panics if the closure sets a link value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, AttributeValue};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect x='10.4' y='20.6' opacity='0.5'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let mut rect = doc.svg_element().unwrap().first_child().unwrap();
+    /// rect.update_values(|_, value| {
+    ///     if let AttributeValue::Length(ref mut len) = *value {
+    ///         len.num = len.num.round();
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(doc.to_string(),
+    /// "<svg xmlns=\"http://www.w3.org/2000/svg\">
+    ///     <rect opacity=\"0.5\" x=\"10\" y=\"21\"/>
+    /// </svg>
+    /// ");
+    /// ```
+    ///
+    /// [`Document::visit_values_mut`]: struct.Document.html#method.visit_values_mut
+    /// [`set_attribute`]: #method.set_attribute
+    pub fn update_values<F>(&mut self, mut f: F)
+        where F: FnMut(AttributeId, &mut AttributeValue)
+    {
+        let mut attrs = self.attributes_mut();
+        for (aid, attr) in attrs.iter_mut().svg() {
+            if attr.is_link_container() {
+                continue;
+            }
+
+            f(aid, &mut attr.value);
+
+            assert!(!attr.value.is_link_container(),
+                    "a link value cannot be set by update_values");
+        }
+    }
+
+    /// Returns `true` if the node has an attribute with such `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    #[inline]
+    pub fn has_attribute<'a, N>(&self, name: N) -> bool
+        where AttributeQNameRef<'a>: From<N>
+    {
+        self.borrow().attributes.contains(name)
+    }
+
+    /// Returns a presentation attribute value by its CSS property name.
+    ///
+    /// Returns `None` when the attribute is not set or when the name is unknown
+    /// or isn't a presentation attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, AttributeValue, Color};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <rect width='10' style='fill:red'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let rect = doc.svg_element().unwrap().first_child().unwrap();
+    /// assert_eq!(rect.get_property("fill"), Some(AttributeValue::Color(Color::new(255, 0, 0))));
+    /// assert_eq!(rect.get_property("width"), None);
+    /// assert_eq!(rect.get_property("unknown"), None);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    pub fn get_property(&self, name: &str) -> Option<AttributeValue> {
+        match AttributeId::from_str(name) {
+            Some(id) if id.is_presentation() => self.attributes().get_value(id).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the `d` attribute value.
+    ///
+    /// Returns `None` if the attribute is not set or isn't a [`Path`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, PathSegment};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <path d='M 10 20 L 30 40'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let mut path = doc.svg_element().unwrap().first_child().unwrap();
+    /// assert_eq!(path.path_data().unwrap().len(), 2);
+    ///
+    /// path.path_data_mut().unwrap().push(PathSegment::ClosePath { abs: true });
+    /// assert_eq!(path.path_data().unwrap().to_string(), "M 10 20 L 30 40 Z");
+    ///
+    /// assert!(doc.svg_element().unwrap().path_data().is_none());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    ///
+    /// [`Path`]: struct.Path.html
+    pub fn path_data(&self) -> Option<Ref<Path>> {
+        if !self.attributes().get_value(AttributeId::D).map(|v| v.is_path()).unwrap_or(false) {
+            return None;
+        }
+
+        Some(Ref::map(self.attributes(), |attrs| {
+            match attrs.get_value(AttributeId::D) {
+                Some(&AttributeValue::Path(ref path)) => path,
+                _ => unreachable!(),
+            }
+        }))
+    }
+
+    /// Returns a mutable reference to the `d` attribute value.
+    ///
+    /// Returns `None` if the attribute is not set or isn't a [`Path`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed.
+    ///
+    /// [`Path`]: struct.Path.html
+    pub fn path_data_mut(&mut self) -> Option<RefMut<Path>> {
+        if !self.attributes().get_value(AttributeId::D).map(|v| v.is_path()).unwrap_or(false) {
+            return None;
+        }
+
+        Some(RefMut::map(self.attributes_mut(), |attrs| {
+            match attrs.get_value_mut(AttributeId::D) {
+                Some(&mut AttributeValue::Path(ref mut path)) => path,
+                _ => unreachable!(),
+            }
+        }))
+    }
+
+    /// Inserts a new attribute into attributes list.
+    ///
+    /// Unwrapped version of the [`set_attribute_checked`] method.
+    ///
+    /// # Panics
+    ///
+    /// Will panic on any error produced by the [`set_attribute_checked`] method.
+    ///
+    /// [`set_attribute_checked`]: #method.set_attribute_checked
+    pub fn set_attribute<T>(&mut self, v: T)
+        where T: Into<Attribute>
+    {
+        self.set_attribute_checked(v).unwrap();
+    }
 
     /// Inserts a new attribute into attributes list.
     ///
@@ -369,6 +1115,7 @@ impl Node {
     ///
     /// - [`ElementMustHaveAnId`]
     /// - [`ElementCrosslink`]
+    /// - [`InvalidName`], if a string attribute name was rejected by the [name validator]
     ///
     /// # Panics
     ///
@@ -457,6 +1204,8 @@ impl Node {
     ///
     /// [`ElementMustHaveAnId`]: enum.Error.html
     /// [`ElementCrosslink`]: enum.Error.html
+    /// [`InvalidName`]: enum.Error.html
+    /// [name validator]: struct.Document.html#method.set_name_validator
     pub fn set_attribute_checked<T>(&mut self, v: T) -> Result<(), Error>
         where T: Into<Attribute>
     {
@@ -466,6 +1215,12 @@ impl Node {
     fn set_attribute_checked_impl(&mut self, attr: Attribute) -> Result<(), Error> {
         debug_assert!(self.node_type() == NodeType::Element);
 
+        if let QName::Name(ref name) = attr.name {
+            if !self.is_valid_name(name) {
+                return Err(Error::InvalidName);
+            }
+        }
+
         match attr.value {
               AttributeValue::Link(ref iri)
             | AttributeValue::FuncLink(ref iri) => {
@@ -594,6 +1349,41 @@ impl Node {
         self.attributes_mut().remove(name);
     }
 
+    /// Removes all attributes with the specified namespace prefix.
+    ///
+    /// Links will be unlinked, just like in [`remove_attribute`].
+    ///
+    /// See [`Attributes::iter_by_prefix`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId};
+    ///
+    /// let mut doc = Document::new();
+    /// let mut rect = doc.create_element(ElementId::Rect);
+    /// rect.set_attribute(("inkscape:label", "Layer"));
+    /// rect.set_attribute(("width", 10.0));
+    ///
+    /// rect.remove_attributes_by_prefix("inkscape");
+    /// assert_eq!(rect.attributes().len(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed.
+    ///
+    /// [`remove_attribute`]: #method.remove_attribute
+    /// [`Attributes::iter_by_prefix`]: struct.Attributes.html#method.iter_by_prefix
+    pub fn remove_attributes_by_prefix(&mut self, prefix: &str) {
+        let names: Vec<AttributeQName> = self.attributes().iter_by_prefix(prefix)
+                                             .map(|a| a.name.clone()).collect();
+
+        for name in &names {
+            self.remove_attribute(name.as_ref());
+        }
+    }
+
     /// Returns an iterator over linked nodes.
     ///
     /// See [Node::set_attribute()](#method.set_attribute) for details.
@@ -637,6 +1427,616 @@ impl Node {
     pub fn uses_count(&self) -> usize {
         self.linked_nodes().len()
     }
+
+    /// Checks that the node is rendered as part of the document.
+    ///
+    /// A node is reachable when, walking up from the node itself:
+    ///
+    /// - the root `svg` element is reached;
+    /// - or an element referenced by a reachable `use` element is reached.
+    ///
+    /// The walk stops with `false` on `defs`, `symbol` and other [referenced] elements,
+    /// like gradients, patterns, clip paths, masks, markers and filters,
+    /// since they are never rendered directly. A `symbol` is rendered only
+    /// when referenced by a `use` element. Elements used as gradients, clip paths, etc.
+    /// are not considered reachable, since they are only used to render other elements.
+    ///
+    /// Conditional processing, `display`, `visibility` and invalid `use` cycles are not
+    /// taken into account. Nodes outside the root `svg` element, like comments before it,
+    /// and the root node itself are not reachable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    ///         <defs>
+    ///             <rect id='rect1'/>
+    ///             <rect id='rect2'/>
+    ///         </defs>
+    ///         <use xlink:href='#rect1'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let defs = doc.svg_element().unwrap().first_child().unwrap();
+    /// assert!(defs.first_child().unwrap().is_reachable_from_root());
+    /// assert!(!defs.last_child().unwrap().is_reachable_from_root());
+    /// assert!(!defs.is_reachable_from_root());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node, any of its ancestors or their `use` elements are currently
+    /// mutably borrowed.
+    ///
+    /// [referenced]: trait.ElementType.html#tymethod.is_referenced
+    pub fn is_reachable_from_root(&self) -> bool {
+        is_reachable(self, &mut Vec::new())
+    }
+
+    /// Returns an element referenced by the `clip-path` attribute.
+    ///
+    /// Returns `None` when the attribute is not set, is `none` or isn't a link.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <clipPath id='clip1'/>
+    ///         <rect clip-path='url(#clip1)'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let clip = doc.svg_element().unwrap().first_child().unwrap();
+    /// let rect = clip.next_sibling().unwrap();
+    /// assert_eq!(rect.clip_path(), Some(clip.clone()));
+    /// assert_eq!(clip.clip_path(), None);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    pub fn clip_path(&self) -> Option<Node> {
+        self.func_link(AttributeId::ClipPath)
+    }
+
+    /// Returns an element referenced by the `mask` attribute.
+    ///
+    /// Returns `None` when the attribute is not set, is `none` or isn't a link.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    pub fn mask(&self) -> Option<Node> {
+        self.func_link(AttributeId::Mask)
+    }
+
+    fn func_link(&self, aid: AttributeId) -> Option<Node> {
+        match self.attributes().get_value(aid) {
+            Some(&AttributeValue::FuncLink(ref link)) => Some(link.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns resolved stops of the gradient element.
+    ///
+    /// - Stops are collected from the `stop` children.
+    ///   When there are none, they will be taken from the gradient referenced
+    ///   via `xlink:href`, recursively.
+    /// - `offset` is clamped to the 0..1 range and can't be smaller than the previous one.
+    /// - `stop-color` and `stop-opacity` are resolved via [`computed_style`],
+    ///   so `inherit` and `currentColor` are supported.
+    ///   Black and `1` are used by default.
+    ///
+    /// Returns an empty list for non-gradient elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, Color, GradientStop};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    ///         <linearGradient id='lg1'>
+    ///             <stop offset='0.2' stop-color='#ff0000'/>
+    ///             <stop offset='10%' stop-opacity='0.5'/>
+    ///         </linearGradient>
+    ///         <linearGradient id='lg2' xlink:href='#lg1'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let lg2 = doc.svg_element().unwrap().last_child().unwrap();
+    /// assert_eq!(lg2.gradient_stops(), vec![
+    ///     GradientStop { offset: 0.2, color: Color::new(255, 0, 0), opacity: 1.0 },
+    ///     GradientStop { offset: 0.2, color: Color::new(0, 0, 0), opacity: 0.5 },
+    /// ]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the involved nodes are currently mutably borrowed.
+    ///
+    /// [`computed_style`]: #method.computed_style
+    pub fn gradient_stops(&self) -> Vec<GradientStop> {
+        gradient::gradient_stops(self)
+    }
+
+    /// Returns the `image` element data.
+    ///
+    /// Decodes the `data:` URI from the `xlink:href` attribute.
+    /// Both base64 and percent-encoded data is supported.
+    /// External URLs are returned as is.
+    ///
+    /// Returns `None` when the `xlink:href` attribute is not set,
+    /// isn't a string or contains an invalid `data:` URI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ImageData};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    ///         <image xlink:href='data:image/png;base64,iVBORw=='/>
+    ///      </svg>").unwrap();
+    ///
+    /// let image = doc.svg_element().unwrap().first_child().unwrap();
+    /// assert_eq!(image.image_data(), Some(ImageData::Embedded {
+    ///     mime: "image/png".to_string(),
+    ///     data: vec![0x89, 0x50, 0x4E, 0x47],
+    /// }));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    pub fn image_data(&self) -> Option<ImageData> {
+        match self.attributes().get_value(AttributeId::Href) {
+            Some(&AttributeValue::String(ref href)) => ImageData::from_href(href),
+            _ => None,
+        }
+    }
+
+    /// Sets the `image` element data.
+    ///
+    /// Data will be stored in the `xlink:href` attribute as a base64 `data:` URI.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed.
+    pub fn set_image_data(&mut self, mime: &str, data: &[u8]) {
+        let data = ImageData::Embedded { mime: mime.to_string(), data: data.to_vec() };
+        self.set_attribute((AttributeId::Href, data.to_href()));
+    }
+
+    /// Returns computed presentation attributes of the node.
+    ///
+    /// The parser already resolves CSS and `style` attributes into
+    /// presentation attributes according to the cascade order,
+    /// so this method only applies the inheritance:
+    ///
+    /// - inheritable attributes are copied from the parent's computed values;
+    /// - own presentation attributes override inherited ones;
+    /// - the `inherit` value is replaced with the parent's computed value
+    ///   or removed when the parent doesn't have one.
+    ///
+    /// Non-presentation and non-SVG attributes are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, AttributeId as AId, AttributeValue, Color};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' fill='#ff0000' opacity='0.5'>
+    ///         <rect fill-opacity='inherit' fill-rule='evenodd'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let rect = doc.svg_element().unwrap().first_child().unwrap();
+    /// let style = rect.computed_style();
+    /// assert_eq!(style[&AId::Fill], AttributeValue::Color(Color::new(255, 0, 0)));
+    /// assert_eq!(style.contains_key(&AId::Opacity), false);
+    /// assert_eq!(style.contains_key(&AId::FillOpacity), false);
+    /// assert_eq!(style.len(), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node or any of its ancestors are currently mutably borrowed.
+    pub fn computed_style(&self) -> HashMap<AttributeId, AttributeValue> {
+        let mut ancestors: Vec<Node> = self.ancestors().collect();
+        ancestors.reverse();
+
+        let mut style: HashMap<AttributeId, AttributeValue> = HashMap::new();
+        for node in ancestors {
+            let parent_style = style;
+            style = parent_style.iter()
+                .filter(|&(id, _)| id.is_inheritable())
+                .map(|(id, value)| (*id, value.clone()))
+                .collect();
+
+            for (id, attr) in node.attributes().iter().svg() {
+                if !id.is_presentation() {
+                    continue;
+                }
+
+                if attr.value == AttributeValue::Inherit {
+                    match parent_style.get(&id) {
+                        Some(value) => { style.insert(id, value.clone()); }
+                        None => { style.remove(&id); }
+                    }
+                } else {
+                    style.insert(id, attr.value.clone());
+                }
+            }
+        }
+
+        style
+    }
+
+    /// Returns the current transformation matrix of the node.
+    ///
+    /// The matrix maps the node's coordinates into the root `svg` element viewport coordinates
+    /// and includes:
+    ///
+    /// - the `transform` attribute of the node and all its ancestors;
+    /// - the `viewBox`, `preserveAspectRatio` and size of all ancestor `svg` elements;
+    /// - the `x` and `y` attributes of nested `svg` elements.
+    ///
+    /// Only absolute units are supported for `svg` size. Otherwise the `viewBox` size is used.
+    ///
+    /// Nothing is cached, so it takes O(depth) time on each call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, Transform};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' width='200' height='200' viewBox='0 0 100 100'>
+    ///         <g transform='translate(10 20)'>
+    ///             <rect transform='scale(2)'/>
+    ///         </g>
+    ///      </svg>").unwrap();
+    ///
+    /// let rect = doc.svg_element().unwrap().first_child().unwrap().first_child().unwrap();
+    /// assert_eq!(rect.ctm(), Transform::new(4.0, 0.0, 0.0, 4.0, 20.0, 40.0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node or any of its ancestors are currently mutably borrowed.
+    pub fn ctm(&self) -> Transform {
+        geometry::ctm(self)
+    }
+
+    /// Returns the shape geometry in the root `svg` element viewport coordinates.
+    ///
+    /// Supports `path`, `rect`, `circle`, `ellipse`, `line`, `polyline` and `polygon`.
+    /// The shape is converted into a path and transformed using the [`ctm`].
+    /// The result contains only absolute segments.
+    ///
+    /// Returns `None` for other elements and for shapes with a missing size
+    /// or with relative units.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <g transform='translate(10 20)'>
+    ///             <rect x='5' width='10' height='20'/>
+    ///         </g>
+    ///      </svg>").unwrap();
+    ///
+    /// let g = doc.svg_element().unwrap().first_child().unwrap();
+    /// let rect = g.first_child().unwrap();
+    /// assert_eq!(rect.to_root_coordinates().unwrap().to_string(),
+    ///            "M 15 20 L 25 20 L 25 40 L 15 40 Z");
+    /// assert_eq!(g.to_root_coordinates(), None);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node or any of its ancestors are currently mutably borrowed.
+    ///
+    /// [`ctm`]: #method.ctm
+    pub fn to_root_coordinates(&self) -> Option<Path> {
+        geometry::to_root_coordinates(self)
+    }
+
+    /// Returns the closest ancestor that establishes a new viewport.
+    ///
+    /// Such elements are `svg` and `symbol`, `pattern` or `marker` with a `viewBox`.
+    /// Percentage lengths of the node are resolved against this viewport.
+    ///
+    /// See [`viewport_size`] for the viewport size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::Document;
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' width='200' height='100'>
+    ///         <pattern viewBox='0 0 10 20'>
+    ///             <rect width='50%'/>
+    ///         </pattern>
+    ///      </svg>").unwrap();
+    ///
+    /// let svg = doc.svg_element().unwrap();
+    /// let pattern = svg.first_child().unwrap();
+    /// let rect = pattern.first_child().unwrap();
+    /// assert_eq!(rect.nearest_viewport(), Some(pattern.clone()));
+    /// assert_eq!(pattern.nearest_viewport(), Some(svg.clone()));
+    /// assert_eq!(svg.nearest_viewport(), None);
+    /// assert_eq!(pattern.viewport_size(), Some((10.0, 20.0)));
+    /// assert_eq!(svg.viewport_size(), Some((200.0, 100.0)));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the node ancestors are currently mutably borrowed.
+    ///
+    /// [`viewport_size`]: #method.viewport_size
+    pub fn nearest_viewport(&self) -> Option<Node> {
+        geometry::nearest_viewport(self)
+    }
+
+    /// Returns the size of the viewport established by this element.
+    ///
+    /// The `viewBox` size is used when present. Otherwise, the `svg` element
+    /// `width` and `height` are used, resolving percentages against the parent viewport.
+    ///
+    /// Returns `None` if the element doesn't establish a viewport (see [`nearest_viewport`])
+    /// or the size cannot be resolved, like the root `svg` element with a percentage size
+    /// and without a `viewBox`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node or any of its ancestors are currently mutably borrowed.
+    ///
+    /// [`nearest_viewport`]: #method.nearest_viewport
+    pub fn viewport_size(&self) -> Option<(f64, f64)> {
+        geometry::viewport_size(self)
+    }
+
+    /// Returns the bounding box of the shape's geometry.
+    ///
+    /// The box is exact, including curve extremes, and is in the element's user space,
+    /// i.e. the `transform` attribute of the element itself is not applied.
+    /// The stroke, markers and the `visibility` are ignored.
+    ///
+    /// Returns `None` for non-shape elements, for empty shapes
+    /// and for shapes with a missing size or with relative units.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, Rect};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <path d='M 10 10 C 10 30 30 30 30 10'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let path = doc.svg_element().unwrap().first_child().unwrap();
+    /// assert_eq!(path.bounding_box(), Some(Rect { x: 10.0, y: 10.0, width: 20.0, height: 15.0 }));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        geometry::bounding_box(self)
+    }
+
+    /// Returns the bounding box of the shape including its stroke.
+    ///
+    /// Uses the computed `stroke`, `stroke-width`, `stroke-linecap`, `stroke-linejoin`
+    /// and `stroke-miterlimit` values. When there is no stroke,
+    /// the result is the same as [`bounding_box`].
+    ///
+    /// The geometry box is expanded by the half of the `stroke-width`,
+    /// which covers round and butt caps and round and bevel joins.
+    /// The corners of `square` caps and the tips of `miter` joins, that are within
+    /// the `stroke-miterlimit`, are added exactly. Percentage `stroke-width`
+    /// is resolved against the [`nearest_viewport`].
+    ///
+    /// This is an approximation:
+    ///
+    /// - the box around curves and butt caps can be slightly larger than the stroke itself;
+    /// - `stroke-dasharray` is ignored, so the box of a dashed stroke can be larger too;
+    /// - arcs are approximated with curves;
+    /// - markers are ignored.
+    ///
+    /// Returns `None` in the same cases as [`bounding_box`] and when
+    /// the `stroke-width` uses `em` or `ex` units.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, Rect};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <path stroke='black' stroke-width='4' stroke-linecap='square' d='M 10 10 L 30 10'/>
+    ///      </svg>").unwrap();
+    ///
+    /// let path = doc.svg_element().unwrap().first_child().unwrap();
+    /// assert_eq!(path.stroke_bounding_box(), Some(Rect { x: 8.0, y: 8.0, width: 24.0, height: 4.0 }));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node or any of its ancestors are currently mutably borrowed.
+    ///
+    /// [`bounding_box`]: #method.bounding_box
+    /// [`nearest_viewport`]: #method.nearest_viewport
+    pub fn stroke_bounding_box(&self) -> Option<Rect> {
+        geometry::stroke_bounding_box(self)
+    }
+
+    /// Copies the subtree rooted at this node into a new standalone document.
+    ///
+    /// Elements outside the subtree that it references, like gradients, clip paths
+    /// or `use` targets, are copied into the `defs` element, so the result renders the same
+    /// way in isolation.
+    ///
+    /// If the node is not an `svg` element, it is wrapped into a new `svg` element
+    /// that inherits `width`, `height`, `viewBox` and `preserveAspectRatio` from the nearest
+    /// `svg` ancestor. Transforms of the ancestors inside that `svg` element are applied to
+    /// the copy and inheritable attributes, like `fill`, are copied from the ancestors.
+    ///
+    /// The root node is copied as a whole.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, WriteBuffer, WriteOptions};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 10 10'>
+    ///         <linearGradient id='lg1'/>
+    ///         <g fill='url(#lg1)' transform='translate(10 20)'>
+    ///             <rect id='rect1' width='5' height='5'/>
+    ///         </g>
+    ///      </svg>").unwrap();
+    ///
+    /// let rect = doc.root().descendants().find(|n| *n.id() == "rect1").unwrap();
+    /// let new_doc = rect.to_document();
+    ///
+    /// let mut opt = WriteOptions::default();
+    /// opt.use_single_quote = true;
+    /// assert_eq!(new_doc.with_write_opt(&opt).to_string(),
+    /// "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 10 10'>
+    ///     <defs>
+    ///         <linearGradient id='lg1'/>
+    ///     </defs>
+    ///     <rect id='rect1' fill='url(#lg1)' height='5' transform='matrix(1 0 0 1 10 20)' width='5'/>
+    /// </svg>
+    /// ");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the copied nodes are currently mutably borrowed.
+    pub fn to_document(&self) -> Document {
+        merge::subtree_to_document(self)
+    }
+
+    /// Returns an XPath-like locator that identifies the node by its position.
+    ///
+    /// The locator consists of child steps with positional predicates only,
+    /// like `/svg/g[2]/rect[1]`. A step is either a tag name, `text()`, `comment()`
+    /// or `processing-instruction()`, and the predicate is a 1-based index among
+    /// the siblings with the same step name. The predicate is omitted for the root element
+    /// when it's the only element of the document. The root node itself is `/`.
+    ///
+    /// The locator is computed on each call, so it reflects the current tree.
+    ///
+    /// Use [`Document::node_at_xpath`] to resolve it back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId as EId};
+    ///
+    /// let doc = Document::from_str(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg'>
+    ///         <g/>
+    ///         <g><rect/><text>Text</text></g>
+    ///     </svg>").unwrap();
+    ///
+    /// let rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    /// assert_eq!(rect.xpath(), "/svg/g[2]/rect[1]");
+    ///
+    /// let text = rect.next_sibling().unwrap().first_child().unwrap();
+    /// assert_eq!(text.xpath(), "/svg/g[2]/text[1]/text()[1]");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node, any of its ancestors or their children are currently mutably borrowed.
+    ///
+    /// [`Document::node_at_xpath`]: struct.Document.html#method.node_at_xpath
+    pub fn xpath(&self) -> String {
+        xpath::node_path(self)
+    }
+}
+
+// Splits a processing instruction text into a target and data.
+fn split_pi(text: &str) -> (&str, &str) {
+    match text.find(|c: char| c.is_whitespace()) {
+        Some(idx) => {
+            let data = &text[idx..];
+            let start = data.find(|c: char| !c.is_whitespace()).unwrap_or(data.len());
+            (&text[..idx], &data[start..])
+        }
+        None => (text, ""),
+    }
+}
+
+// `visited` contains already checked `use` elements, which prevents infinite recursion.
+fn is_reachable(node: &Node, visited: &mut Vec<Node>) -> bool {
+    for n in node.ancestors() {
+        if !n.is_element() {
+            continue;
+        }
+
+        let is_symbol = n.is_tag_name(ElementId::Symbol);
+        let is_hidden = n.is_tag_name(ElementId::Defs) || n.is_referenced();
+
+        // A `use` element can reference only renderable elements and symbols.
+        if (!is_hidden || is_symbol) && is_instantiated(&n, visited) {
+            return true;
+        }
+
+        if is_hidden {
+            return false;
+        }
+
+        if n.is_tag_name(ElementId::Svg) && n.parent().map(|p| p.is_root()).unwrap_or(false) {
+            return true;
+        }
+    }
+
+    false
+}
+
+// Checks that the element is referenced by any reachable `use` element.
+fn is_instantiated(node: &Node, visited: &mut Vec<Node>) -> bool {
+    let uses: Vec<Node> = node.linked_nodes().iter().filter(|n| {
+        if !n.is_tag_name(ElementId::Use) {
+            return false;
+        }
+
+        match n.attributes().get_value(AttributeId::Href) {
+            Some(&AttributeValue::Link(ref link)) => link == node,
+            _ => false,
+        }
+    }).cloned().collect();
+
+    for u in uses {
+        if visited.contains(&u) {
+            continue;
+        }
+
+        visited.push(u.clone());
+        if is_reachable(&u, visited) {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn is_same_point(p1: (f64, f64), p2: (f64, f64)) -> bool {
+    p1.0.fuzzy_eq(&p2.0) && p1.1.fuzzy_eq(&p2.1)
 }
 
 /// An iterator over SVG elements.
@@ -41,6 +41,28 @@ enum CssSelector<'a> {
     Class(&'a str),
 }
 
+impl<'a> CssSelector<'a> {
+    // Simple selectors have only one component,
+    // so a single number is enough to represent the specificity.
+    fn specificity(&self) -> u8 {
+        match *self {
+            CssSelector::Universal => 0,
+            CssSelector::Type(_) => 1,
+            CssSelector::Class(_) => 2,
+            CssSelector::Id(_) => 3,
+        }
+    }
+}
+
+struct CssRule<'a> {
+    selector: CssSelector<'a>,
+    declarations: Vec<(&'a str, &'a str)>,
+    // Declarations with `!important` are stored in a separate rule.
+    important: bool,
+    // Position of the 'style' element text. Used for errors.
+    pos: TextPos,
+}
+
 pub fn resolve_css(
     ro_doc: &roxmltree::Document,
     doc: &Document,
@@ -50,6 +72,7 @@ pub fn resolve_css(
     // remember all resolved classes
     let mut resolved_classes: Vec<String> = Vec::with_capacity(16);
 
+    let mut rules = Vec::new();
     for node in ro_doc.descendants().filter(|n| n.has_tag_name("style")) {
         match node.attribute("type") {
             Some("text/css") => {}
@@ -62,33 +85,156 @@ pub fn resolve_css(
             None => continue,
         };
 
-        if let Err(_) = parse_style(ro_doc, style, doc, post_data, &mut resolved_classes, opt) {
+        // If an error occurred then use the text node position.
+        let pos = ro_doc.text_pos_from(node.first_child().unwrap().pos());
+
+        if parse_style(style, pos, &mut rules).is_err() {
             if opt.skip_invalid_css {
                 warn!("Document contains an unsupported CSS.");
             } else {
-                // If an error occurred then use the text node position.
-                let text_node = node.first_child().unwrap();
-                let pos = ro_doc.text_pos_from(text_node.pos());
                 return Err(ParserError::UnsupportedCSS(pos));
             }
         }
     }
 
+    sort_rules(&mut rules);
+
+    // Style attributes override all declarations, except the important ones.
+    let important_idx = rules.iter().position(|r| r.important).unwrap_or(rules.len());
+
+    for rule in &rules[..important_idx] {
+        apply_rule_checked(ro_doc, rule, doc, post_data, &mut resolved_classes, opt)?;
+    }
+
+    for d in &mut post_data.style_attrs {
+        super::parse_style_attribute(ro_doc, &d.text, d.value_pos, opt,
+                                     &mut d.node, &mut post_data.links)?;
+    }
+
+    for rule in &rules[important_idx..] {
+        apply_rule_checked(ro_doc, rule, doc, post_data, &mut resolved_classes, opt)?;
+    }
+
     postprocess_class_selector(&resolved_classes, &mut post_data.class_attrs, opt);
 
     Ok(())
 }
 
-fn parse_style(
+fn apply_rule_checked(
     ro_doc: &roxmltree::Document,
-    style: &str,
+    rule: &CssRule,
     doc: &Document,
     post_data: &mut PostData,
     resolved_classes: &mut Vec<String>,
     opt: &ParseOptions,
+) -> Result<(), ParserError> {
+    if apply_rule(ro_doc, rule, doc, post_data, resolved_classes, opt).is_err() {
+        if opt.skip_invalid_css {
+            warn!("Document contains an unsupported CSS.");
+        } else {
+            return Err(ParserError::UnsupportedCSS(rule.pos));
+        }
+    }
+
+    Ok(())
+}
+
+// Rules should be applied in the cascade order:
+// an important declaration always wins,
+// then the one with a higher specificity wins and
+// the latest one wins when specificity is the same.
+// The sort is stable, so the document order is preserved.
+fn sort_rules(rules: &mut Vec<CssRule>) {
+    rules.sort_by_key(|r| (r.important, r.selector.specificity()));
+}
+
+// Applies `style` elements that are present in the DOM, which is not the case after parsing.
+pub fn flatten_css(doc: &mut Document) {
+    let opt = ParseOptions {
+        skip_invalid_attributes: true,
+        skip_invalid_css: true,
+        ..ParseOptions::default()
+    };
+
+    let style_nodes: Vec<Node> = doc.root().descendants()
+        .filter(|n| n.is_tag_name(ElementId::Style))
+        .collect();
+
+    let mut texts = Vec::new();
+    for node in &style_nodes {
+        match node.attributes().get_value(AttributeId::Type) {
+            Some(&AttributeValue::String(ref t)) if t != "text/css" => continue,
+            _ => {}
+        }
+
+        let text: String = node.children().filter(|n| n.is_text()).map(|n| n.text().clone()).collect();
+        texts.push(text);
+    }
+
+    let mut rules = Vec::new();
+    for text in &texts {
+        if parse_style(text, TextPos::new(1, 1), &mut rules).is_err() {
+            warn!("Document contains an unsupported CSS.");
+        }
+    }
+
+    sort_rules(&mut rules);
+
+    let mut post_data = PostData {
+        links: Links {
+            list: Vec::new(),
+        },
+        class_attrs: Vec::new(),
+        style_attrs: Vec::new(),
+        nodes_count: 0,
+        positions: Vec::new(),
+    };
+
+    let mut class_nodes = Vec::new();
+    for node in doc.root().descendants().filter(|n| n.has_attribute(AttributeId::Class)) {
+        for name in node.class_list().iter() {
+            post_data.class_attrs.push(NodeStringData {
+                node: node.clone(),
+                text: name.clone(),
+                value_pos: 0,
+            });
+        }
+
+        class_nodes.push(node);
+    }
+
+    // Attribute values are parsed without the source text, so positions are meaningless
+    // and errors are ignored anyway.
+    let ro_doc = roxmltree::Document::parse("<svg/>").unwrap();
+
+    let mut resolved_classes = Vec::new();
+    for rule in &rules {
+        if apply_rule(&ro_doc, rule, doc, &mut post_data, &mut resolved_classes, &opt).is_err() {
+            warn!("Document contains an unsupported CSS.");
+        }
+    }
+
+    if let Err(e) = super::resolve_links(doc, &mut post_data.links) {
+        warn!("Failed to resolve links: {}.", e);
+    }
+
+    for mut node in class_nodes {
+        node.remove_attribute(AttributeId::Class);
+    }
+
+    for node in style_nodes {
+        doc.remove_node(node);
+    }
+}
+
+fn parse_style<'a>(
+    style: &'a str,
+    pos: TextPos,
+    rules: &mut Vec<CssRule<'a>>,
 ) -> Result<(), ParserError> {
     let mut selectors: Vec<CssSelector> = Vec::new();
     let mut values: Vec<(&str,&str)> = Vec::with_capacity(16);
+    let mut important_values: Vec<(&str,&str)> = Vec::new();
 
     // Position doesn't matter, because we ignore this errors anyway.
     macro_rules! gen_err {
@@ -109,9 +255,13 @@ fn parse_style(
         simplecss::Tokenizer::new(style)
     };
 
+    let important_flags = important_flags(style);
+    let mut decl_idx = 0;
+
     'root: loop {
         selectors.clear();
         values.clear();
+        important_values.clear();
 
         // get list of selectors
         loop {
@@ -148,49 +298,155 @@ fn parse_style(
         // get list of declarations
         loop {
             match tokenizer.parse_next().map_err(|_| gen_err!())? {
-                CssToken::Declaration(name, value) => values.push((name, value)),
+                CssToken::Declaration(name, value) => {
+                    let (value, has_suffix) = strip_important(value);
+                    let important = has_suffix
+                                 || important_flags.get(decl_idx).cloned().unwrap_or(false);
+                    decl_idx += 1;
+
+                    if important {
+                        important_values.push((name, value));
+                    } else {
+                        values.push((name, value));
+                    }
+                }
                 CssToken::BlockEnd => break,
                 CssToken::EndOfStream => break 'root,
                 _ => return Err(gen_err!()),
             }
         }
 
-        // process selectors
         for selector in &selectors {
-            match *selector {
-                CssSelector::Universal => {
-                    for (_, mut node) in doc.root().descendants().svg() {
-                        apply_css_attributes(ro_doc, &values, opt,
-                                             &mut node, &mut post_data.links)?;
+            if !values.is_empty() {
+                rules.push(CssRule {
+                    selector: *selector,
+                    declarations: values.clone(),
+                    important: false,
+                    pos,
+                });
+            }
+
+            if !important_values.is_empty() {
+                rules.push(CssRule {
+                    selector: *selector,
+                    declarations: important_values.clone(),
+                    important: true,
+                    pos,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Removes `!important` from the declaration value.
+fn strip_important(value: &str) -> (&str, bool) {
+    match value.rfind('!') {
+        Some(idx) if value[idx + 1..].trim() == "important" => (value[..idx].trim(), true),
+        _ => (value, false),
+    }
+}
+
+// Returns `!important` flags of all declarations in the style text, in order.
+//
+// `simplecss` strips `!important` from declaration values,
+// so we have to check the source text separately.
+fn important_flags(style: &str) -> Vec<bool> {
+    let mut flags = Vec::new();
+    let mut decl = String::new();
+    let mut in_block = false;
+
+    let mut chars = style.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                // Skip a comment.
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
                     }
+
+                    prev = c;
                 }
-                CssSelector::Type(name) => {
-                    if let Some(eid) = ElementId::from_str(name) {
-                        for (id, mut node) in doc.root().descendants().svg() {
-                            if id == eid {
-                                apply_css_attributes(ro_doc, &values, opt,
-                                                     &mut node, &mut post_data.links)?;
-                            }
-                        }
-                    } else {
-                        warn!("CSS styles for a non-SVG element ('{}') are ignored.", name);
+            }
+            '"' | '\'' if in_block => {
+                // Quoted strings can contain any characters.
+                decl.push(c);
+                for c2 in chars.by_ref() {
+                    decl.push(c2);
+                    if c2 == c {
+                        break;
                     }
                 }
-                CssSelector::Id(name) => {
-                    if let Some(mut node) = doc.root().descendants().find(|n| *n.id() == name) {
-                        apply_css_attributes(ro_doc, &values, opt,
-                                             &mut node, &mut post_data.links)?;
-                    }
+            }
+            '{' if !in_block => {
+                in_block = true;
+                decl.clear();
+            }
+            ';' | '}' if in_block => {
+                if decl.contains(':') {
+                    flags.push(strip_important(decl.trim()).1);
                 }
-                CssSelector::Class(name) => {
-                    // we use already collected list of 'class' attributes
-                    for d in post_data.class_attrs.iter_mut().filter(|n| n.text == name) {
-                        apply_css_attributes(ro_doc, &values, opt,
-                                             &mut d.node, &mut post_data.links)?;
 
-                        resolved_classes.push(name.to_string());
+                decl.clear();
+                in_block = c == ';';
+            }
+            _ if in_block => decl.push(c),
+            _ => {}
+        }
+    }
+
+    if in_block && decl.contains(':') {
+        flags.push(strip_important(decl.trim()).1);
+    }
+
+    flags
+}
+
+fn apply_rule(
+    ro_doc: &roxmltree::Document,
+    rule: &CssRule,
+    doc: &Document,
+    post_data: &mut PostData,
+    resolved_classes: &mut Vec<String>,
+    opt: &ParseOptions,
+) -> Result<(), ParserError> {
+    let values = &rule.declarations;
+    match rule.selector {
+        CssSelector::Universal => {
+            for (_, mut node) in doc.root().descendants().svg() {
+                apply_css_attributes(ro_doc, values, opt,
+                                     &mut node, &mut post_data.links)?;
+            }
+        }
+        CssSelector::Type(name) => {
+            if let Some(eid) = ElementId::from_str(name) {
+                for (id, mut node) in doc.root().descendants().svg() {
+                    if id == eid {
+                        apply_css_attributes(ro_doc, values, opt,
+                                             &mut node, &mut post_data.links)?;
                     }
                 }
+            } else {
+                warn!("CSS styles for a non-SVG element ('{}') are ignored.", name);
+            }
+        }
+        CssSelector::Id(name) => {
+            if let Some(mut node) = doc.root().descendants().find(|n| *n.id() == name) {
+                apply_css_attributes(ro_doc, values, opt,
+                                     &mut node, &mut post_data.links)?;
+            }
+        }
+        CssSelector::Class(name) => {
+            // we use already collected list of 'class' attributes
+            for d in post_data.class_attrs.iter_mut().filter(|n| n.text == name) {
+                apply_css_attributes(ro_doc, values, opt,
+                                     &mut d.node, &mut post_data.links)?;
+
+                resolved_classes.push(name.to_string());
             }
         }
     }
@@ -261,7 +517,9 @@ fn apply_css_attributes<'a>(
                 }
             }
             None => {
-                node.set_attribute((aname, avalue));
+                if opt.allowed_attributes.is_none() {
+                    node.set_attribute((aname, avalue));
+                }
             }
         }
     }
@@ -0,0 +1,202 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::borrow::Cow;
+
+use roxmltree;
+
+use {
+    DuplicateAttributes,
+    ParserError,
+};
+
+/// Removes duplicated attributes from the text according to the policy.
+///
+/// `roxmltree` rejects such documents, so when it reports a duplicate,
+/// all start tags are scanned once and all duplicates are removed at once.
+/// Other errors are ignored here.
+pub fn remove_duplicated_attributes(
+    text: Cow<str>,
+    policy: DuplicateAttributes,
+) -> Result<Cow<str>, ParserError> {
+    let err = match roxmltree::Document::parse(&text) {
+        Err(e @ roxmltree::Error::DuplicatedAttribute(..)) => e,
+        _ => return Ok(text),
+    };
+
+    let ranges = duplicated_ranges(&text, policy);
+    if ranges.is_empty() {
+        return Err(err.into());
+    }
+
+    let mut new_text = String::with_capacity(text.len());
+    let mut pos = TextPosCounter::new();
+    let mut offset = 0;
+    for (start, end) in ranges {
+        let (row, col) = pos.advance(&text, start);
+        warn!("Duplicated attribute '{}' at {}:{} is ignored.",
+              attribute_name(&text[start..]), row, col);
+
+        // Remove the leading whitespace too.
+        let spaces: usize = text[offset..start].chars().rev()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| c.len_utf8())
+            .sum();
+
+        new_text.push_str(&text[offset..start - spaces]);
+        offset = end;
+    }
+    new_text.push_str(&text[offset..]);
+
+    Ok(Cow::Owned(new_text))
+}
+
+// Returns sorted ranges of all attributes that should be removed.
+fn duplicated_ranges(text: &str, policy: DuplicateAttributes) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut attrs: Vec<(&str, (usize, usize))> = Vec::new();
+
+    let mut pos = 0;
+    while let Some(idx) = text[pos..].find('<') {
+        let start = pos + idx;
+        let tail = &text[start..];
+
+        // Skip everything that can contain `<` and is not a start tag.
+        let skip_to = if tail.starts_with("<!--") {
+            Some("-->")
+        } else if tail.starts_with("<![CDATA[") {
+            Some("]]>")
+        } else if tail.starts_with("<?") {
+            Some("?>")
+        } else {
+            None
+        };
+
+        if let Some(end_str) = skip_to {
+            pos = match tail.find(end_str) {
+                Some(end) => start + end + end_str.len(),
+                None => break,
+            };
+            continue;
+        }
+
+        if tail.starts_with("<!") {
+            // A DTD with an optional internal subset.
+            let end = match (tail.find('['), tail.find('>')) {
+                (Some(open), Some(close)) if open < close => {
+                    tail[open..].find(']').and_then(|i| tail[open + i..].find('>').map(|j| open + i + j))
+                }
+                (_, close) => close,
+            };
+
+            pos = match end {
+                Some(end) => start + end + 1,
+                None => break,
+            };
+            continue;
+        }
+
+        if tail.starts_with("</") {
+            pos = start + 2;
+            continue;
+        }
+
+        // Skip the tag name.
+        pos = start + 1 + tail[1..].find(|c: char| c == '>' || c == '/' || c.is_whitespace())
+                                   .unwrap_or(tail.len() - 1);
+
+        attrs.clear();
+        loop {
+            pos = skip_spaces(text, pos);
+            match attribute_range(text, pos) {
+                Some((attr_start, attr_end)) => {
+                    attrs.push((attribute_name(&text[attr_start..]), (attr_start, attr_end)));
+                    pos = attr_end;
+                }
+                None => break,
+            }
+        }
+
+        for (i, &(name, range)) in attrs.iter().enumerate() {
+            let is_duplicate = if policy == DuplicateAttributes::KeepLast {
+                attrs[i + 1..].iter().any(|a| a.0 == name)
+            } else {
+                attrs[..i].iter().any(|a| a.0 == name)
+            };
+
+            if is_duplicate {
+                ranges.push(range);
+            }
+        }
+    }
+
+    ranges
+}
+
+// Converts byte positions into rows and columns incrementally.
+struct TextPosCounter {
+    row: u32,
+    col: u32,
+    offset: usize,
+}
+
+impl TextPosCounter {
+    fn new() -> Self {
+        TextPosCounter { row: 1, col: 1, offset: 0 }
+    }
+
+    // `pos` must not be smaller than the previous one.
+    fn advance(&mut self, text: &str, pos: usize) -> (u32, u32) {
+        for c in text[self.offset..pos].chars() {
+            if c == '\n' {
+                self.row += 1;
+                self.col = 1;
+            } else {
+                self.col += 1;
+            }
+        }
+
+        self.offset = pos;
+        (self.row, self.col)
+    }
+}
+
+fn attribute_name(text: &str) -> &str {
+    let end = text.find(|c: char| c == '=' || c.is_whitespace()).unwrap_or(text.len());
+    &text[..end]
+}
+
+// Returns the range of the attribute that starts at `start`.
+fn attribute_range(text: &str, start: usize) -> Option<(usize, usize)> {
+    let tail = &text[start..];
+    let name = attribute_name(tail);
+    if name.is_empty() || name.starts_with('>') || name.starts_with('/') {
+        return None;
+    }
+
+    let eq = skip_spaces(tail, name.len());
+    if !tail[eq..].starts_with('=') {
+        return None;
+    }
+
+    let value_start = skip_spaces(tail, eq + 1);
+    let quote = match tail[value_start..].chars().next() {
+        Some(c) if c == '"' || c == '\'' => c,
+        _ => return None,
+    };
+
+    let value_start = value_start + 1;
+    tail[value_start..].find(quote).map(|end| (start, start + value_start + end + 1))
+}
+
+fn skip_spaces(text: &str, pos: usize) -> usize {
+    match text[pos..].find(|c: char| !c.is_whitespace()) {
+        Some(i) => pos + i,
+        None => text.len(),
+    }
+}
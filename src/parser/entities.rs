@@ -0,0 +1,131 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::borrow::Cow;
+
+// Entities that are always defined by XML.
+static PREDEFINED: &[&str] = &["lt", "gt", "amp", "apos", "quot"];
+
+/// Escapes references to undeclared entities, so they will be parsed as a plain text.
+///
+/// Entities declared in the internal DTD subset are left as is,
+/// since they will be expanded by the XML parser.
+/// Comments, CDATA, processing instructions and the DOCTYPE itself are not modified.
+pub fn escape_unknown_entities(text: &str) -> Cow<str> {
+    let declared = declared_entities(text);
+    let bytes = text.as_bytes();
+
+    let mut escaped = String::new();
+    let mut last = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if bytes[pos] == b'<' {
+            pos = skip_markup(text, pos);
+            continue;
+        }
+
+        if bytes[pos] == b'&' {
+            if let Some(name) = entity_name(&text[pos + 1..]) {
+                if !PREDEFINED.contains(&name) && !declared.contains(&name) {
+                    warn!("Unknown entity '{}' is preserved as text.", name);
+
+                    escaped.push_str(&text[last..pos + 1]);
+                    escaped.push_str("amp;");
+                    last = pos + 1;
+                }
+            }
+        }
+
+        pos += 1;
+    }
+
+    if last == 0 {
+        return Cow::Borrowed(text);
+    }
+
+    escaped.push_str(&text[last..]);
+    Cow::Owned(escaped)
+}
+
+/// Checks that the text contains an entity reference, like `&name;`.
+pub fn contains_entity_reference(text: &str) -> bool {
+    text.match_indices('&').any(|(i, _)| entity_name(&text[i + 1..]).is_some())
+}
+
+// Returns the position right after the markup that can't contain entity references.
+//
+// Element tags are skipped only by one char, since attributes can contain references.
+fn skip_markup(text: &str, pos: usize) -> usize {
+    let tail = &text[pos..];
+    let end = if tail.starts_with("<!--") {
+        find_end(tail, "-->")
+    } else if tail.starts_with("<![CDATA[") {
+        find_end(tail, "]]>")
+    } else if tail.starts_with("<?") {
+        find_end(tail, "?>")
+    } else if tail.starts_with("<!DOCTYPE") {
+        // The internal subset can contain '>' inside declarations.
+        match (tail.find('['), tail.find('>')) {
+            (Some(open), Some(close)) if open < close => {
+                match tail[open..].find(']') {
+                    Some(i) => open + i + find_end(&tail[open + i..], ">"),
+                    None => tail.len(),
+                }
+            }
+            _ => find_end(tail, ">"),
+        }
+    } else {
+        1
+    };
+
+    pos + end
+}
+
+fn find_end(text: &str, end: &str) -> usize {
+    match text.find(end) {
+        Some(i) => i + end.len(),
+        None => text.len(),
+    }
+}
+
+// Collects names of the general entities declared in the internal DTD subset.
+fn declared_entities(text: &str) -> Vec<&str> {
+    let mut list = Vec::new();
+
+    let mut tail = text;
+    while let Some(i) = tail.find("<!ENTITY") {
+        tail = &tail[i + 8..];
+        tail = &tail[tail.find(|c: char| !c.is_whitespace()).unwrap_or(tail.len())..];
+
+        // Parameter entities can't be referenced from the document.
+        if !tail.starts_with('%') {
+            let len = tail.find(|c: char| !is_name_char(c)).unwrap_or(tail.len());
+            if len != 0 {
+                list.push(&tail[..len]);
+            }
+        }
+    }
+
+    list
+}
+
+// Returns the entity name if the text starts with `name;`.
+//
+// Character references, like `&#160;`, are ignored.
+fn entity_name(text: &str) -> Option<&str> {
+    let len = text.find(|c: char| !is_name_char(c)).unwrap_or(text.len());
+    if len == 0 || !text[len..].starts_with(';') {
+        return None;
+    }
+
+    Some(&text[..len])
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ':'
+}
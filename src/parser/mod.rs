@@ -8,6 +8,7 @@
 
 use std::str::{self, FromStr};
 
+pub use self::css::flatten_css;
 pub use self::options::*;
 
 use roxmltree::{
@@ -26,6 +27,8 @@ use svgtypes::{
 use super::*;
 
 mod css;
+mod duplicates;
+mod entities;
 mod options;
 mod text;
 
@@ -71,10 +74,29 @@ pub struct PostData {
     pub class_attrs: Vec<NodeStringData>,
     // List of style attributes.
     pub style_attrs: Vec<NodeStringData>,
+    // Number of created nodes. Used to check `ParseOptions::max_nodes`.
+    pub nodes_count: usize,
+    // Nodes with their byte positions in the source text.
+    // Used only by `ParseOptions::track_positions`.
+    pub positions: Vec<(Node, usize)>,
 }
 
 pub fn parse_svg(text: &str, opt: &ParseOptions) -> Result<Document, ParserError> {
-    let ro_doc = roxmltree::Document::parse(text)?;
+    if let Some(max) = opt.max_nodes {
+        check_nodes_limit(text, max)?;
+    }
+
+    let mut text = if opt.keep_unknown_entities {
+        entities::escape_unknown_entities(text)
+    } else {
+        text.into()
+    };
+
+    if opt.duplicate_attributes != DuplicateAttributes::Error {
+        text = duplicates::remove_duplicated_attributes(text, opt.duplicate_attributes)?;
+    }
+
+    let ro_doc = roxmltree::Document::parse(&text)?;
 
     // Since we not only parsing, but also converting an SVG structure,
     // we can't do everything in one take.
@@ -87,6 +109,8 @@ pub fn parse_svg(text: &str, opt: &ParseOptions) -> Result<Document, ParserError
         },
         class_attrs: Vec::new(),
         style_attrs: Vec::new(),
+        nodes_count: 0,
+        positions: Vec::new(),
     };
 
     let mut doc = Document::new();
@@ -97,6 +121,8 @@ pub fn parse_svg(text: &str, opt: &ParseOptions) -> Result<Document, ParserError
         process_node(&ro_doc, child, opt, &mut post_data, &mut doc, &mut parent)?;
     }
 
+    set_source_positions(&text, &mut post_data.positions);
+
     // First element must be an 'svg' element.
     if doc.svg_element().is_none() {
         return Err(ParserError::NoSvgElement);
@@ -106,17 +132,42 @@ pub fn parse_svg(text: &str, opt: &ParseOptions) -> Result<Document, ParserError
     // is stored separately and will be processed later.
     doc.drain(root.clone(), |n| n.is_tag_name(ElementId::Style));
 
+    // Resolve CSS and styles.
     css::resolve_css(&ro_doc, &doc, &mut post_data, opt)?;
 
-    // Resolve styles.
-    for d in &mut post_data.style_attrs {
-        parse_style_attribute(&ro_doc, &d.text, d.value_pos, opt,
-                              &mut d.node, &mut post_data.links)?;
+    resolve_links(&doc, &mut post_data.links)?;
+
+    text::prepare_text(&mut doc, &opt.preserve_whitespace_elements);
+
+    Ok(doc)
+}
+
+// Fragments are parsed inside a temporary `svg` element, which is removed afterwards.
+pub fn parse_svg_fragment(text: &str, opt: &ParseOptions) -> Result<Document, ParserError> {
+    let prefix = "<svg xmlns='http://www.w3.org/2000/svg' \
+                       xmlns:xlink='http://www.w3.org/1999/xlink'>";
+    let text = format!("{}{}</svg>", prefix, text);
+    let mut doc = parse_svg(&text, opt)?;
+
+    let mut root = doc.root();
+    let svg = doc.svg_element().unwrap();
+    let children: Vec<Node> = svg.children().collect();
+    for mut child in children {
+        child.detach();
+        root.append(child);
     }
 
-    resolve_links(&doc, &mut post_data.links);
+    doc.remove_node(svg);
 
-    text::prepare_text(&mut doc);
+    // Positions on the first line are shifted by the wrapper.
+    if opt.track_positions {
+        for mut node in doc.root().descendants() {
+            let pos = node.borrow().source_pos;
+            if let Some((1, col)) = pos {
+                node.borrow_mut().source_pos = Some((1, col - prefix.len() as u32));
+            }
+        }
+    }
 
     Ok(doc)
 }
@@ -132,6 +183,11 @@ fn process_node(
     match xml_node.node_type() {
         roxmltree::NodeType::Element => {
             if xml_node.tag_name().namespace() != Some("http://www.w3.org/2000/svg") {
+                if opt.keep_foreign_content && opt.allowed_elements.is_none()
+                   && is_foreign_parent(parent) {
+                    return process_foreign_node(ro_doc, xml_node, opt, post_data, doc, parent);
+                }
+
                 return Ok(());
             }
 
@@ -139,6 +195,10 @@ fn process_node(
             let local = tag_name.name();
             let mut e = match ElementId::from_str(local) {
                 Some(eid) => {
+                    if !is_allowed_element(opt, eid) {
+                        return Ok(());
+                    }
+
                     doc.create_element(eid)
                 }
                 None => {
@@ -163,6 +223,7 @@ fn process_node(
                 }
             }
 
+            count_node(&e, xml_node, post_data, opt)?;
             parent.append(e.clone());
 
             if xml_node.is_element() && xml_node.has_children() {
@@ -174,13 +235,18 @@ fn process_node(
         roxmltree::NodeType::Text => {
             let text = xml_node.text().unwrap();
             if text.trim().is_empty() {
-                // Whitespaces inside text elements are important.
-                if let Some(id) = parent.tag_id() {
+                // Whitespaces inside text elements and foreign content are important.
+                if parent.is_element() && !parent.is_svg_element() {
+                    let n = doc.create_node(NodeType::Text, text);
+                    count_node(&n, xml_node, post_data, opt)?;
+                    parent.append(n);
+                } else if let Some(id) = parent.tag_id() {
                     match id {
                           ElementId::Text
                         | ElementId::Tspan
                         | ElementId::Tref => {
                             let n = doc.create_node(NodeType::Text, text);
+                            count_node(&n, xml_node, post_data, opt)?;
                             parent.append(n);
                         }
                         _ => {}
@@ -188,11 +254,24 @@ fn process_node(
                 }
             } else {
                 let n = doc.create_node(NodeType::Text, xml_node.text().unwrap());
+                count_node(&n, xml_node, post_data, opt)?;
                 parent.append(n);
             }
         }
         roxmltree::NodeType::Comment => {
             let n = doc.create_node(NodeType::Comment, xml_node.text().unwrap());
+            count_node(&n, xml_node, post_data, opt)?;
+            parent.append(n);
+        }
+        roxmltree::NodeType::PI => {
+            let pi = xml_node.pi().unwrap();
+            let text = match pi.value {
+                Some(value) => format!("{} {}", pi.target, value),
+                None => pi.target.to_string(),
+            };
+
+            let n = doc.create_node(NodeType::ProcessingInstruction, text);
+            count_node(&n, xml_node, post_data, opt)?;
             parent.append(n);
         }
         _ => {}
@@ -212,6 +291,170 @@ fn process_node(
     Ok(())
 }
 
+fn count_node(
+    node: &Node,
+    xml_node: roxmltree::Node,
+    post_data: &mut PostData,
+    opt: &ParseOptions,
+) -> Result<(), ParserError> {
+    post_data.nodes_count += 1;
+
+    if opt.track_positions {
+        post_data.positions.push((node.clone(), xml_node.pos()));
+    }
+
+    match opt.max_nodes {
+        Some(max) if post_data.nodes_count > max => Err(ParserError::LimitExceeded),
+        _ => Ok(()),
+    }
+}
+
+// Counts elements and comments in the source text, so an input that is obviously
+// too big is rejected before the XML tree is built.
+//
+// This is only a quick estimation. The precise limit is checked by `count_node`.
+fn check_nodes_limit(text: &str, max: usize) -> Result<(), ParserError> {
+    let mut count = 0;
+    let mut s = text.as_bytes();
+    while let Some(idx) = s.iter().position(|c| *c == b'<') {
+        s = &s[idx + 1..];
+
+        // Skip the content that can contain `<`.
+        let end: &[u8] = if s.starts_with(b"!--") {
+            count += 1;
+            b"-->"
+        } else if s.starts_with(b"![CDATA[") {
+            b"]]>"
+        } else if s.starts_with(b"?") {
+            b"?>"
+        } else {
+            if !s.starts_with(b"/") && !s.starts_with(b"!") {
+                count += 1;
+            }
+
+            b""
+        };
+
+        if count > max {
+            return Err(ParserError::LimitExceeded);
+        }
+
+        if !end.is_empty() {
+            match s.windows(end.len()).position(|w| w == end) {
+                Some(idx) => s = &s[idx + end.len()..],
+                None => break,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Converts byte positions into rows and columns in a single pass.
+fn set_source_positions(text: &str, positions: &mut [(Node, usize)]) {
+    // Positions are mostly in the document order already, but nodes created
+    // from entities point back into the DTD.
+    positions.sort_by_key(|p| p.1);
+
+    let mut row = 1;
+    let mut col = 1;
+    let mut offset = 0;
+    for &mut (ref mut node, pos) in positions {
+        for c in text[offset..pos].chars() {
+            if c == '\n' {
+                row += 1;
+                col = 1;
+            } else {
+                col += 1;
+            }
+        }
+        offset = pos;
+
+        node.borrow_mut().source_pos = Some((row, col));
+    }
+}
+
+fn is_allowed_element(opt: &ParseOptions, id: ElementId) -> bool {
+    match opt.allowed_elements {
+        Some(ref list) => list.contains(&id),
+        None => true,
+    }
+}
+
+fn is_allowed_attribute(opt: &ParseOptions, id: AttributeId) -> bool {
+    match opt.allowed_attributes {
+        Some(ref list) => list.contains(&id),
+        None => true,
+    }
+}
+
+fn is_local_reference(value: &str, node: &Node) -> bool {
+    let value = value.trim();
+    if value.starts_with('#') {
+        return true;
+    }
+
+    if node.is_tag_name(ElementId::Image) || node.is_tag_name(ElementId::FeImage) {
+        let value = value.to_lowercase();
+        return value.starts_with("data:image/") && !value.starts_with("data:image/svg");
+    }
+
+    false
+}
+
+fn is_foreign_parent(parent: &Node) -> bool {
+       parent.is_tag_name(ElementId::ForeignObject)
+    || (parent.is_element() && !parent.is_svg_element())
+}
+
+// Non-SVG elements are stored as is, without any attributes processing.
+fn process_foreign_node(
+    ro_doc: &roxmltree::Document,
+    xml_node: roxmltree::Node,
+    opt: &ParseOptions,
+    post_data: &mut PostData,
+    doc: &mut Document,
+    parent: &mut Node,
+) -> Result<(), ParserError> {
+    let mut e = doc.create_element(xml_node.tag_name().name());
+
+    // Preserve the namespace, but only when it differs from the parent one.
+    let ns = xml_node.tag_name().namespace();
+    let parent_ns = xml_node.parent().and_then(|n| n.tag_name().namespace());
+    if let Some(ns) = ns {
+        if parent_ns != Some(ns) {
+            e.set_attribute(("xmlns", ns));
+        }
+    }
+
+    for attr in xml_node.attributes() {
+        // Non-SVG attributes cannot be allowed.
+        if opt.allowed_attributes.is_some() {
+            break;
+        }
+
+        match attr.namespace() {
+            None => {
+                e.set_attribute((attr.name(), attr.value()));
+            }
+            Some("http://www.w3.org/XML/1998/namespace") => {
+                let name = format!("xml:{}", attr.name());
+                e.set_attribute((name.as_str(), attr.value()));
+            }
+            _ => {}
+        }
+    }
+
+    count_node(&e, xml_node, post_data, opt)?;
+    parent.append(e.clone());
+
+    for child in xml_node.children() {
+        process_node(ro_doc, child, opt, post_data, doc, &mut e)?;
+    }
+
+    Ok(())
+}
+
 fn parse_svg_attribute<'a>(
     ro_doc: &roxmltree::Document,
     id: AttributeId,
@@ -221,6 +464,10 @@ fn parse_svg_attribute<'a>(
     node: &mut Node,
     post_data: &mut PostData,
 ) -> Result<(), ParserError> {
+    if !is_allowed_attribute(opt, id) {
+        return Ok(());
+    }
+
     match id {
         AttributeId::Id => {
             node.set_id(value);
@@ -256,6 +503,12 @@ fn parse_svg_attribute<'a>(
         _ => {
             parse_svg_attribute_value(ro_doc, id, value, value_pos, opt,
                                       node, &mut post_data.links)?;
+
+            if opt.preserve_source {
+                if let Some(attr) = node.attributes_mut().get_mut(id) {
+                    attr.set_original(value);
+                }
+            }
         }
     }
 
@@ -271,6 +524,19 @@ pub fn parse_svg_attribute_value<'a>(
     node: &mut Node,
     links: &mut Links,
 ) -> Result<(), ParserError> {
+    if !is_allowed_attribute(opt, id) {
+        return Ok(());
+    }
+
+    if opt.skip_event_attributes && id.as_str().starts_with("on") {
+        return Ok(());
+    }
+
+    if opt.skip_external_references && id == AttributeId::Href && !is_local_reference(value, node) {
+        warn!("External reference '{}' is removed.", value);
+        return Ok(());
+    }
+
     let av = _parse_svg_attribute_value(ro_doc, id, value, value_pos, node, links);
 
     match av {
@@ -280,12 +546,24 @@ pub fn parse_svg_attribute_value<'a>(
                     AttributeValue::NumberList(ref list) if list.is_empty() => {}
                     AttributeValue::LengthList(ref list) if list.is_empty() => {}
                     AttributeValue::Path(ref path) if path.is_empty() => {}
-                    _ => node.set_attribute((id, av)),
+                    _ => {
+                        let attr = Attribute::new(id, av);
+                        if attr.check_keyword() == KeywordCheck::Invalid {
+                            warn!("Attribute '{}' has an unknown keyword: '{}'.", id, value);
+                        }
+
+                        node.set_attribute(attr);
+                    }
                 }
             }
         }
         Err(_) => {
-            if opt.skip_invalid_attributes {
+            if opt.keep_unknown_entities && entities::contains_entity_reference(value) {
+                // Keep an unexpanded entity reference for round-tripping.
+                warn!("Attribute '{}' with an unknown entity is stored as string: '{}'.",
+                      id, value);
+                node.set_attribute((id, value));
+            } else if opt.skip_invalid_attributes {
                 warn!("Attribute '{}' has an invalid value: '{}'.", id, value);
             } else {
                 let pos = ro_doc.text_pos_from(value_pos);
@@ -682,7 +960,9 @@ fn parse_style_attribute(
                 parse_svg_attribute_value(ro_doc, aid, value, value_pos, opt, node, links)?;
             }
             None => {
-                node.set_attribute((name, value));
+                if opt.allowed_attributes.is_none() {
+                    node.set_attribute((name, value));
+                }
             }
         }
     }
@@ -690,7 +970,7 @@ fn parse_style_attribute(
     Ok(())
 }
 
-fn resolve_links(doc: &Document, links: &mut Links) {
+fn resolve_links(doc: &Document, links: &mut Links) -> Result<(), ParserError> {
     for d in &mut links.list {
         match doc.root().descendants().find(|n| *n.id() == d.iri) {
             Some(node) => {
@@ -712,6 +992,7 @@ fn resolve_links(doc: &Document, links: &mut Links) {
                         let attr = Attribute::from((d.attr_id, node.clone()));
                         warn!("Crosslink detected. Attribute {} ignored.", attr);
                     }
+                    Err(e) => return Err(e.into()),
                 }
             }
             None => {
@@ -738,4 +1019,6 @@ fn resolve_links(doc: &Document, links: &mut Links) {
             }
         }
     }
+
+    Ok(())
 }
@@ -6,6 +6,29 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::collections::HashSet;
+
+use {
+    AttributeId,
+    ElementId,
+    ElementId as EId,
+};
+
+/// A duplicated attributes handling policy.
+///
+/// See [`ParseOptions::duplicate_attributes`] for details.
+///
+/// [`ParseOptions::duplicate_attributes`]: struct.ParseOptions.html#structfield.duplicate_attributes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DuplicateAttributes {
+    /// Fail with an XML error.
+    Error,
+    /// Keep the first attribute and ignore the rest.
+    KeepFirst,
+    /// Keep the last attribute and ignore the previous ones.
+    KeepLast,
+}
+
 /// Options that defines SVG parsing.
 #[derive(Debug)]
 pub struct ParseOptions {
@@ -32,6 +55,144 @@ pub struct ParseOptions {
     ///
     /// Default: `false`
     pub skip_invalid_css: bool,
+
+    /// Keep non-SVG elements inside the `foreignObject` element.
+    ///
+    /// Such elements are stored with string tag names and attributes.
+    /// Unlike the SVG text, their whitespaces are preserved as is.
+    ///
+    /// Default: `true`
+    pub keep_foreign_content: bool,
+
+    /// The maximum number of nodes in the document.
+    ///
+    /// All node types are counted: elements, text nodes and comments.
+    /// When the limit is exceeded, the parsing will be aborted
+    /// with the [`ParserError::LimitExceeded`] error.
+    ///
+    /// Elements and comments in the source text are counted before the XML parsing,
+    /// so an input that exceeds the limit is rejected without building an XML tree.
+    /// Elements that will be skipped by the parser are counted too.
+    /// The precise limit is checked again while the DOM is being built.
+    ///
+    /// The input size is not limited by this option.
+    ///
+    /// Useful for processing untrusted input.
+    ///
+    /// Default: `None`
+    ///
+    /// [`ParserError::LimitExceeded`]: enum.ParserError.html#variant.LimitExceeded
+    pub max_nodes: Option<usize>,
+
+    /// A list of allowed attributes.
+    ///
+    /// When set, all other attributes will be removed, including non-SVG ones.
+    /// This applies to attributes defined via `style` and CSS too.
+    /// Useful for sanitizing an untrusted input, like removing event handlers.
+    ///
+    /// Note that `id`, `style` and `class` are attributes too.
+    /// Without `id`, links cannot be resolved and will be stored as strings.
+    /// Without `style` or `class`, the corresponding styles will be ignored.
+    ///
+    /// Default: `None`
+    pub allowed_attributes: Option<HashSet<AttributeId>>,
+
+    /// A list of allowed elements.
+    ///
+    /// When set, all other elements will be removed along with their children,
+    /// including non-SVG ones. The `svg` element must be allowed,
+    /// otherwise the parsing will fail with the `NoSvgElement` error.
+    ///
+    /// Links to removed elements are not resolved and will be stored as strings.
+    ///
+    /// Default: `None`
+    pub allowed_elements: Option<HashSet<ElementId>>,
+
+    /// Skip event attributes, like `onclick` and `onload`.
+    ///
+    /// Default: `false`
+    pub skip_event_attributes: bool,
+
+    /// Skip `xlink:href` attributes that reference external resources.
+    ///
+    /// Only links to elements inside the document are kept,
+    /// like `#id`, and raster images embedded as `data:image/...` URLs
+    /// inside the `image` and `feImage` elements.
+    /// Embedded SVG images are treated as external, since they can contain scripts.
+    ///
+    /// Default: `false`
+    pub skip_external_references: bool,
+
+    /// Keep references to unknown entities as is.
+    ///
+    /// Only entities declared in the internal DTD subset are supported.
+    /// They are expanded in both attribute values and text.
+    /// External DTD is never loaded, so references to other entities,
+    /// like `fill="&myColor;"`, will lead to a parsing error.
+    /// This flag allows converting this error into a warning.
+    ///
+    /// Such references will be preserved as a plain text.
+    /// Attribute values that cannot be parsed because of them are stored as strings
+    /// and will be written back as is.
+    ///
+    /// Limitations:
+    ///
+    /// - References in text nodes don't round-trip, since the `&` char will be escaped
+    ///   on writing, so `&name;` becomes `&amp;name;`.
+    /// - References are escaped before the XML parsing, so error positions,
+    ///   including the [`ParserError::with_source`] output, and [`Node::source_pos`]
+    ///   refer to the escaped text. Each escaped reference shifts the following positions
+    ///   on the same line by 4 columns.
+    ///
+    /// Default: `false`
+    ///
+    /// [`ParserError::with_source`]: enum.ParserError.html#method.with_source
+    /// [`Node::source_pos`]: type.Node.html#method.source_pos
+    pub keep_unknown_entities: bool,
+
+    /// A list of elements which text is never collapsed.
+    ///
+    /// Whitespaces inside such elements and their descendants are preserved,
+    /// as if `xml:space` is set to `preserve`, even when it's set to `default`.
+    ///
+    /// Non-SVG elements are not listed here, since their whitespaces are always preserved.
+    ///
+    /// Default: empty
+    pub preserve_whitespace_elements: HashSet<ElementId>,
+
+    /// Defines how to handle an element with the same attribute set more than once,
+    /// like `<rect fill="red" fill="blue"/>`.
+    ///
+    /// Such documents are invalid XML. With a non-`Error` policy, the ignored attributes
+    /// are removed with a warning, so an element never contains duplicated attributes.
+    ///
+    /// Default: `DuplicateAttributes::Error`
+    pub duplicate_attributes: DuplicateAttributes,
+
+    /// Store source strings of the parsed attribute values.
+    ///
+    /// The source string is written back as is, unless the attribute value was modified,
+    /// so values like `rgb(255, 0, 0)` are not normalized into `#ff0000`.
+    /// Write options that affect values, like `number_format`, are ignored for such attributes.
+    ///
+    /// Links and values resolved from CSS are not affected.
+    ///
+    /// See [`Attribute::original`] for details.
+    ///
+    /// Default: `false`
+    ///
+    /// [`Attribute::original`]: struct.Attribute.html#method.original
+    pub preserve_source: bool,
+
+    /// Store the source text position of each node.
+    ///
+    /// Positions are available via [`Node::source_pos`].
+    /// Disabled by default to avoid the overhead.
+    ///
+    /// Default: `false`
+    ///
+    /// [`Node::source_pos`]: type.Node.html#method.source_pos
+    pub track_positions: bool,
 }
 
 impl Default for ParseOptions {
@@ -40,6 +201,71 @@ impl Default for ParseOptions {
             skip_unresolved_classes: true,
             skip_invalid_attributes: false,
             skip_invalid_css: false,
+            keep_foreign_content: true,
+            max_nodes: None,
+            allowed_attributes: None,
+            allowed_elements: None,
+            skip_event_attributes: false,
+            skip_external_references: false,
+            keep_unknown_entities: false,
+            preserve_whitespace_elements: HashSet::new(),
+            duplicate_attributes: DuplicateAttributes::Error,
+            preserve_source: false,
+            track_positions: false,
         }
     }
 }
+
+impl ParseOptions {
+    /// Returns options for parsing an untrusted input.
+    ///
+    /// The resulting document will not contain:
+    ///
+    /// - `script` and `foreignObject` elements;
+    /// - animation elements: `animate`, `animateColor`, `animateMotion`,
+    ///   `animateTransform`, `mpath` and `set`, since they can modify links;
+    /// - event attributes, like `onclick`;
+    /// - links to external resources, including `javascript:` URLs;
+    /// - non-SVG elements.
+    ///
+    /// Each part can be disabled by modifying the corresponding field.
+    /// All other options have default values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ParseOptions};
+    ///
+    /// let doc = Document::from_str_with_opt(
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' onload='alert(1)'>
+    ///         <script>alert(1)</script>
+    ///      </svg>", &ParseOptions::safe()).unwrap();
+    /// assert_eq!(doc.to_string(), "<svg xmlns=\"http://www.w3.org/2000/svg\"/>\n");
+    /// ```
+    pub fn safe() -> ParseOptions {
+        ParseOptions {
+            keep_foreign_content: false,
+            allowed_elements: Some(SAFE_ELEMENTS.iter().cloned().collect()),
+            skip_event_attributes: true,
+            skip_external_references: true,
+            ..ParseOptions::default()
+        }
+    }
+}
+
+// All elements, except scripts, foreign content and animations.
+static SAFE_ELEMENTS: &[ElementId] = &[
+    EId::A, EId::AltGlyph, EId::AltGlyphDef, EId::AltGlyphItem, EId::Circle, EId::ClipPath,
+    EId::ColorProfile, EId::Cursor, EId::Defs, EId::Desc, EId::Ellipse, EId::FeBlend,
+    EId::FeColorMatrix, EId::FeComponentTransfer, EId::FeComposite, EId::FeConvolveMatrix,
+    EId::FeDiffuseLighting, EId::FeDisplacementMap, EId::FeDistantLight, EId::FeFlood,
+    EId::FeFuncA, EId::FeFuncB, EId::FeFuncG, EId::FeFuncR, EId::FeGaussianBlur, EId::FeImage,
+    EId::FeMerge, EId::FeMergeNode, EId::FeMorphology, EId::FeOffset, EId::FePointLight,
+    EId::FeSpecularLighting, EId::FeSpotLight, EId::FeTile, EId::FeTurbulence, EId::Filter,
+    EId::Font, EId::FontFace, EId::FontFaceFormat, EId::FontFaceName, EId::FontFaceSrc,
+    EId::FontFaceUri, EId::G, EId::Glyph, EId::GlyphRef, EId::Hkern, EId::Image, EId::Line,
+    EId::LinearGradient, EId::Marker, EId::Mask, EId::Metadata, EId::MissingGlyph, EId::Path,
+    EId::Pattern, EId::Polygon, EId::Polyline, EId::RadialGradient, EId::Rect, EId::Stop,
+    EId::Style, EId::Svg, EId::Switch, EId::Symbol, EId::Text, EId::TextPath, EId::Title,
+    EId::Tref, EId::Tspan, EId::Use, EId::View, EId::Vkern,
+];
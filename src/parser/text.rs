@@ -6,12 +6,14 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::collections::HashSet;
 use std::str;
 
 use {
     AttributeId,
     AttributeValue,
     Document,
+    ElementId,
     Node,
 };
 
@@ -42,11 +44,13 @@ enum XmlSpace {
 // - 'xml:space' processing
 // - tabs and newlines removing/replacing
 // - spaces trimming
-pub fn prepare_text(doc: &mut Document) {
+//
+// Text inside the `preserved` elements is processed as if `xml:space` is set to `preserve`.
+pub fn prepare_text(doc: &mut Document, preserved: &HashSet<ElementId>) {
     // Remember nodes that has 'xml:space' changed.
     let mut nodes = Vec::new();
 
-    _prepare_text(&doc.root(), &mut nodes, XmlSpace::Default);
+    _prepare_text(&doc.root(), &mut nodes, preserved, XmlSpace::Default);
 
     // Remove temporary 'xml:space' attributes created during the text processing.
     for mut node in nodes {
@@ -57,22 +61,53 @@ pub fn prepare_text(doc: &mut Document) {
     doc.drain(root, |n| n.is_text() && n.text().is_empty());
 }
 
-fn _prepare_text(parent: &Node, nodes: &mut Vec<Node>, parent_xmlspace: XmlSpace) {
+fn _prepare_text(
+    parent: &Node,
+    nodes: &mut Vec<Node>,
+    preserved: &HashSet<ElementId>,
+    parent_xmlspace: XmlSpace,
+) {
     for mut node in parent.children().filter(|n| n.is_element()) {
-        let xmlspace = get_xmlspace(&mut node, nodes, parent_xmlspace);
+        // Whitespaces inside the foreign content are preserved,
+        // but it still can contain SVG elements.
+        if node.is_tag_name(ElementId::ForeignObject) || !node.is_svg_element() {
+            _prepare_text(&node, nodes, preserved, parent_xmlspace);
+            continue;
+        }
+
+        let xmlspace = get_xmlspace(&mut node, nodes, preserved, parent_xmlspace);
 
         if let Some(child) = node.first_child() {
             if child.is_text() {
-                prepare_text_children(&node, nodes, xmlspace);
+                prepare_text_children(&node, nodes, preserved, xmlspace);
                 continue;
             }
         }
 
-        _prepare_text(&node, nodes, xmlspace);
+        _prepare_text(&node, nodes, preserved, xmlspace);
     }
 }
 
-fn get_xmlspace(node: &mut Node, nodes: &mut Vec<Node>, default: XmlSpace) -> XmlSpace {
+fn get_xmlspace(
+    node: &mut Node,
+    nodes: &mut Vec<Node>,
+    preserved: &HashSet<ElementId>,
+    default: XmlSpace,
+) -> XmlSpace {
+    if !preserved.is_empty() {
+        let is_preserved = node.ancestors().any(|n| {
+            match n.tag_id() {
+                Some(id) => preserved.contains(&id),
+                None => false,
+            }
+        });
+
+        // Overrides 'xml:space' of the element and its descendants.
+        if is_preserved {
+            return XmlSpace::Preserve;
+        }
+    }
+
     {
         let attrs = node.attributes();
         let v = attrs.get_value(AttributeId::Space);
@@ -98,11 +133,17 @@ fn set_xmlspace(node: &mut Node, nodes: &mut Vec<Node>, xmlspace: XmlSpace) {
     nodes.push(node.clone());
 }
 
-fn prepare_text_children(parent: &Node, marked_nodes: &mut Vec<Node>, xmlspace: XmlSpace) {
+fn prepare_text_children(
+    parent: &Node,
+    marked_nodes: &mut Vec<Node>,
+    preserved: &HashSet<ElementId>,
+    xmlspace: XmlSpace,
+) {
     // Trim all descendant text nodes.
     for mut child in parent.descendants() {
         if child.is_text() {
-            let child_xmlspace = get_xmlspace(&mut child.parent().unwrap(), marked_nodes, xmlspace);
+            let child_xmlspace = get_xmlspace(&mut child.parent().unwrap(), marked_nodes, preserved,
+                                              xmlspace);
             let new_text = {
                 let text = child.text();
                 trim(text.as_ref(), child_xmlspace)
@@ -175,8 +216,8 @@ fn prepare_text_children(parent: &Node, marked_nodes: &mut Vec<Node>, xmlspace:
 
             // Parent of the text node is always an element node and always exist,
             // so unwrap is safe.
-            let xmlspace1 = get_xmlspace(&mut node1.parent().unwrap(), marked_nodes, xmlspace);
-            let xmlspace2 = get_xmlspace(&mut node2.parent().unwrap(), marked_nodes, xmlspace);
+            let xmlspace1 = get_xmlspace(&mut node1.parent().unwrap(), marked_nodes, preserved, xmlspace);
+            let xmlspace2 = get_xmlspace(&mut node2.parent().unwrap(), marked_nodes, preserved, xmlspace);
 
             // >text<..>text<
             //  1  2    3  4
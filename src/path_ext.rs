@@ -0,0 +1,1000 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    FuzzyEq,
+    FuzzyZero,
+    Path,
+    PathSegment,
+    Transform,
+};
+
+/// Additional methods for the [`Path`].
+///
+/// [`Path`]: struct.Path.html
+pub trait PathExt {
+    /// Corrects elliptical arcs according to the
+    /// [SVG implementation notes](https://www.w3.org/TR/SVG11/implnote.html#ArcOutOfRangeParameters).
+    ///
+    /// - Arcs with coincident endpoints are removed.
+    /// - Arcs with a zero radius are converted into lines.
+    /// - Negative radii are replaced with their absolute values.
+    /// - Radii that are too small to connect the endpoints are scaled up.
+    ///
+    /// Relative segments stay relative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use svgdom::{Path, PathExt};
+    ///
+    /// let mut path = Path::from_str("M 10 20 A -5 0 0 0 1 30 40 A 5 5 0 0 1 30 40").unwrap();
+    /// path.fix_arcs();
+    /// assert_eq!(path.to_string(), "M 10 20 L 30 40");
+    /// ```
+    fn fix_arcs(&mut self);
+
+    /// Removes redundant line segments.
+    ///
+    /// - Lines shorter than `tolerance` are removed, unless they are the only
+    ///   segment of a subpath or are followed by a smooth curve.
+    /// - Consecutive collinear lines are merged into one, when none of the removed points
+    ///   is farther than `tolerance` from the resulting line.
+    ///
+    /// Subpaths are processed separately and `ClosePath` segments are preserved.
+    /// Relative segments stay relative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use svgdom::{Path, PathExt};
+    ///
+    /// let mut path = Path::from_str("M 10 10 L 20 10 L 30 10 L 30 10 L 30 20 Z").unwrap();
+    /// path.simplify(0.001);
+    /// assert_eq!(path.to_string(), "M 10 10 L 30 10 L 30 20 Z");
+    /// ```
+    fn simplify(&mut self, tolerance: f64);
+
+    /// Reduces the number of points in polylines using the
+    /// [Ramer–Douglas–Peucker](https://en.wikipedia.org/wiki/Ramer%E2%80%93Douglas%E2%80%93Peucker_algorithm)
+    /// algorithm.
+    ///
+    /// Each sequence of consecutive lines is processed separately.
+    /// The first and the last points of a sequence are always preserved.
+    /// Curves and arcs are not affected.
+    ///
+    /// Relative segments stay relative.
+    ///
+    /// Returns the number of removed points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use svgdom::{Path, PathExt};
+    ///
+    /// let mut path = Path::from_str("M 0 0 L 10 0.1 L 20 -0.1 L 30 5 L 40 10").unwrap();
+    /// assert_eq!(path.reduce_points(0.5), 2);
+    /// assert_eq!(path.to_string(), "M 0 0 L 20 -0.1 L 40 10");
+    /// ```
+    fn reduce_points(&mut self, tolerance: f64) -> usize;
+
+    /// Applies a transform to the path.
+    ///
+    /// All segments will be converted into absolute ones.
+    /// `HorizontalLineTo` and `VerticalLineTo` segments are replaced with `LineTo`,
+    /// since they can't be preserved after rotation or skew.
+    /// Arc radii, rotation and sweep direction are recalculated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use svgdom::{Path, PathExt, Transform};
+    ///
+    /// let mut path = Path::from_str("M 10 20 h 10 A 5 5 0 0 1 30 30").unwrap();
+    /// path.transform(&Transform::new(2.0, 0.0, 0.0, 2.0, 5.0, 5.0));
+    /// assert_eq!(path.to_string(), "M 25 45 L 45 45 A 10 10 0 0 1 65 65");
+    /// ```
+    fn transform(&mut self, ts: &Transform);
+
+    /// Checks that the path doesn't have any visible geometry.
+    ///
+    /// A path is degenerate when all of its subpaths are degenerate,
+    /// i.e. contain only coincident points or non-finite numbers.
+    /// An empty path is degenerate too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use svgdom::{Path, PathExt};
+    ///
+    /// assert!(Path::from_str("M 10 20 L 10 20 Z").unwrap().is_degenerate());
+    /// assert!(!Path::from_str("M 10 20 L 10 20 L 30 40").unwrap().is_degenerate());
+    /// ```
+    fn is_degenerate(&self) -> bool;
+
+    /// Removes degenerate subpaths.
+    ///
+    /// A subpath is degenerate when all of its points, including control ones,
+    /// are coincident or when it contains non-finite numbers, like NaN or infinity.
+    ///
+    /// Note that a zero-length subpath can still be rendered when the stroke
+    /// has round or square line caps.
+    ///
+    /// Relative segments stay relative.
+    ///
+    /// Returns indices of the removed subpaths in the original path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use svgdom::{Path, PathExt};
+    ///
+    /// let mut path = Path::from_str("M 10 20 L 10 20 M 30 40 l 10 0 m 5 5").unwrap();
+    /// assert_eq!(path.remove_degenerate_subpaths(), vec![0, 2]);
+    /// assert_eq!(path.to_string(), "M 30 40 l 10 0");
+    /// ```
+    fn remove_degenerate_subpaths(&mut self) -> Vec<usize>;
+
+    /// Converts the path into a canonical form that contains only absolute
+    /// `MoveTo`, `LineTo`, `CurveTo` and `ClosePath` segments.
+    ///
+    /// - `HorizontalLineTo` and `VerticalLineTo` are converted into `LineTo`.
+    /// - `SmoothCurveTo` is converted into `CurveTo` with a reflected control point.
+    /// - `Quadratic` and `SmoothQuadratic` are converted into `CurveTo` without any precision loss.
+    /// - `EllipticalArc` is approximated by up to four `CurveTo` segments,
+    ///   one per each 90 degrees of the arc.
+    ///
+    /// Arcs are corrected the same way as in [`fix_arcs`] before the conversion.
+    ///
+    /// Clone the path beforehand to keep the original.
+    ///
+    /// [`fix_arcs`]: #tymethod.fix_arcs
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use svgdom::{Path, PathExt};
+    ///
+    /// let mut path = Path::from_str("M 10 20 h 30 Q 40 50 10 50 z").unwrap();
+    /// path.to_canonical();
+    /// assert_eq!(path.to_string(), "M 10 20 L 40 20 C 40 40 30 50 10 50 Z");
+    /// ```
+    fn to_canonical(&mut self);
+}
+
+impl PathExt for Path {
+    fn fix_arcs(&mut self) {
+        let mut prev = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        let mut i = 0;
+        while i < self.len() {
+            let seg = self[i];
+            let end = segment_end(&seg, prev, start);
+
+            if let PathSegment::EllipticalArc { abs, rx, ry, x_axis_rotation, large_arc, sweep, x, y } = seg {
+                if end.0.fuzzy_eq(&prev.0) && end.1.fuzzy_eq(&prev.1) {
+                    // An arc with coincident endpoints must be omitted.
+                    self.remove(i);
+                    continue;
+                }
+
+                let mut rx = rx.abs();
+                let mut ry = ry.abs();
+
+                if rx.is_fuzzy_zero() || ry.is_fuzzy_zero() {
+                    // An arc with a zero radius must be treated as a straight line.
+                    self[i] = PathSegment::LineTo { abs, x, y };
+                } else {
+                    let (sin, cos) = x_axis_rotation.to_radians().sin_cos();
+                    let dx = (prev.0 - end.0) / 2.0;
+                    let dy = (prev.1 - end.1) / 2.0;
+                    let x1 =  cos * dx + sin * dy;
+                    let y1 = -sin * dx + cos * dy;
+
+                    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+                    if lambda > 1.0 {
+                        let k = lambda.sqrt();
+                        rx *= k;
+                        ry *= k;
+                    }
+
+                    self[i] = PathSegment::EllipticalArc {
+                        abs, rx, ry, x_axis_rotation, large_arc, sweep, x, y
+                    };
+                }
+            }
+
+            if let PathSegment::MoveTo { .. } = seg {
+                start = end;
+            }
+
+            prev = end;
+            i += 1;
+        }
+    }
+
+    fn simplify(&mut self, tolerance: f64) {
+        // Absolute segment, original `abs` flag and an absolute start point.
+        let mut list: Vec<(PathSegment, bool, (f64, f64))> = Vec::with_capacity(self.len());
+
+        let mut prev = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        // An end point of the last kept segment. Differs from `prev` after a dropped line,
+        // so the next kept segment starts from here and absorbs the dropped offset.
+        let mut kept_end = (0.0, 0.0);
+        // Points that were dropped or merged since the start of the last kept segment.
+        // A merged line must stay within `tolerance` from all of them.
+        let mut dropped: Vec<(f64, f64)> = Vec::new();
+        for (i, seg) in self.iter().enumerate() {
+            let mut abs_seg = to_absolute(seg, prev);
+            let end = segment_end(&abs_seg, prev, start);
+
+            if is_line(&abs_seg) {
+                let is_last_in_subpath = match self.get(i + 1) {
+                    Some(&PathSegment::MoveTo { .. }) | Some(&PathSegment::ClosePath { .. }) => true,
+                    Some(_) => false,
+                    None => true,
+                };
+
+                // Smooth segments reflect the previous control point,
+                // which is the current point after a line.
+                let precedes_smooth = match self.get(i + 1) {
+                    Some(&PathSegment::SmoothCurveTo { .. })
+                    | Some(&PathSegment::SmoothQuadratic { .. }) => true,
+                    _ => false,
+                };
+
+                let follows_move_to = match list.last() {
+                    Some(&(PathSegment::MoveTo { .. }, _, _)) => true,
+                    _ => false,
+                };
+
+                if    distance(kept_end, end) <= tolerance
+                   && !(follows_move_to && is_last_in_subpath)
+                   && !precedes_smooth
+                {
+                    dropped.push(end);
+                    prev = end;
+                    continue;
+                }
+
+                // Horizontal and vertical lines are no longer such
+                // when started from a different point.
+                if kept_end != prev {
+                    abs_seg = PathSegment::LineTo { abs: true, x: end.0, y: end.1 };
+                }
+
+                if let Some(&mut (ref mut prev_seg, _, p0)) = list.last_mut() {
+                    if is_line(prev_seg)
+                        && distance_to_line(kept_end, p0, end) <= tolerance
+                        && (kept_end.0 - p0.0) * (end.0 - kept_end.0)
+                         + (kept_end.1 - p0.1) * (end.1 - kept_end.1) >= 0.0
+                        && dropped.iter().all(|p| distance_to_segment(*p, p0, end) <= tolerance)
+                    {
+                        *prev_seg = match (*prev_seg, abs_seg) {
+                            (PathSegment::HorizontalLineTo { .. },
+                             PathSegment::HorizontalLineTo { .. }) => {
+                                PathSegment::HorizontalLineTo { abs: true, x: end.0 }
+                            }
+                            (PathSegment::VerticalLineTo { .. },
+                             PathSegment::VerticalLineTo { .. }) => {
+                                PathSegment::VerticalLineTo { abs: true, y: end.1 }
+                            }
+                            _ => PathSegment::LineTo { abs: true, x: end.0, y: end.1 },
+                        };
+
+                        dropped.push(kept_end);
+                        prev = end;
+                        kept_end = end;
+                        continue;
+                    }
+                }
+            }
+
+            list.push((abs_seg, seg.is_absolute(), kept_end));
+            dropped.clear();
+
+            if let PathSegment::MoveTo { .. } = abs_seg {
+                start = end;
+            }
+
+            prev = end;
+            kept_end = end;
+        }
+
+        restore_segments(self, list.into_iter().map(|(seg, abs, _)| (seg, abs)));
+    }
+
+    fn reduce_points(&mut self, tolerance: f64) -> usize {
+        // Absolute segment and original `abs` flag.
+        let mut list: Vec<(PathSegment, bool)> = Vec::with_capacity(self.len());
+        // Consecutive lines and their points, including the start one.
+        let mut lines: Vec<(PathSegment, bool)> = Vec::new();
+        let mut points: Vec<(f64, f64)> = Vec::new();
+        let mut removed = 0;
+
+        let mut prev = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        for seg in self.iter() {
+            let abs_seg = to_absolute(seg, prev);
+            let end = segment_end(&abs_seg, prev, start);
+
+            if is_line(&abs_seg) {
+                if points.is_empty() {
+                    points.push(prev);
+                }
+
+                points.push(end);
+                lines.push((abs_seg, seg.is_absolute()));
+            } else {
+                removed += reduce_lines(&lines, &points, tolerance, &mut list);
+                lines.clear();
+                points.clear();
+
+                list.push((abs_seg, seg.is_absolute()));
+            }
+
+            if let PathSegment::MoveTo { .. } = abs_seg {
+                start = end;
+            }
+
+            prev = end;
+        }
+
+        removed += reduce_lines(&lines, &points, tolerance, &mut list);
+
+        restore_segments(self, list.into_iter());
+
+        removed
+    }
+
+    fn transform(&mut self, ts: &Transform) {
+        self.conv_to_absolute();
+
+        let mut prev = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        for seg in self.iter_mut() {
+            let end = segment_end(seg, prev, start);
+
+            match *seg {
+                PathSegment::MoveTo { ref mut x, ref mut y, .. }
+                | PathSegment::LineTo { ref mut x, ref mut y, .. }
+                | PathSegment::SmoothQuadratic { ref mut x, ref mut y, .. } => {
+                    ts.apply_to(x, y);
+                }
+                PathSegment::HorizontalLineTo { .. } | PathSegment::VerticalLineTo { .. } => {
+                    let (x, y) = ts.apply(end.0, end.1);
+                    *seg = PathSegment::LineTo { abs: true, x, y };
+                }
+                PathSegment::CurveTo { ref mut x1, ref mut y1, ref mut x2, ref mut y2,
+                                       ref mut x, ref mut y, .. } => {
+                    ts.apply_to(x1, y1);
+                    ts.apply_to(x2, y2);
+                    ts.apply_to(x, y);
+                }
+                PathSegment::SmoothCurveTo { ref mut x2, ref mut y2, ref mut x, ref mut y, .. } => {
+                    ts.apply_to(x2, y2);
+                    ts.apply_to(x, y);
+                }
+                PathSegment::Quadratic { ref mut x1, ref mut y1, ref mut x, ref mut y, .. } => {
+                    ts.apply_to(x1, y1);
+                    ts.apply_to(x, y);
+                }
+                PathSegment::EllipticalArc { ref mut rx, ref mut ry, ref mut x_axis_rotation,
+                                             ref mut sweep, ref mut x, ref mut y, .. } => {
+                    let (new_rx, new_ry, angle) = transform_arc(*rx, *ry, *x_axis_rotation, ts);
+                    *rx = new_rx;
+                    *ry = new_ry;
+                    *x_axis_rotation = angle;
+
+                    // A mirroring transform changes the arc direction.
+                    if ts.a * ts.d - ts.b * ts.c < 0.0 {
+                        *sweep = !*sweep;
+                    }
+
+                    ts.apply_to(x, y);
+                }
+                PathSegment::ClosePath { .. } => {}
+            }
+
+            if let PathSegment::MoveTo { .. } = *seg {
+                start = end;
+            }
+
+            prev = end;
+        }
+    }
+
+    fn is_degenerate(&self) -> bool {
+        split_subpaths(self).iter().all(|s| s.is_degenerate)
+    }
+
+    fn remove_degenerate_subpaths(&mut self) -> Vec<usize> {
+        let subpaths = split_subpaths(self);
+
+        let removed: Vec<usize> = subpaths.iter().enumerate()
+            .filter(|&(_, s)| s.is_degenerate)
+            .map(|(i, _)| i)
+            .collect();
+
+        if !removed.is_empty() {
+            let list = subpaths.into_iter()
+                .filter(|s| !s.is_degenerate)
+                .flat_map(|s| s.segments.into_iter());
+            restore_segments(self, list);
+        }
+
+        removed
+    }
+
+    fn to_canonical(&mut self) {
+        let old: Vec<PathSegment> = self.drain(..).collect();
+
+        let mut prev = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        // The second control point of the previous cubic curve
+        // and the control point of the previous quadratic curve.
+        let mut prev_cubic: Option<(f64, f64)> = None;
+        let mut prev_quad: Option<(f64, f64)> = None;
+        for seg in &old {
+            let end = segment_end(seg, prev, start);
+            let seg = to_absolute(seg, prev);
+
+            let mut cubic = None;
+            let mut quad = None;
+            match seg {
+                PathSegment::MoveTo { x, y, .. } => {
+                    self.push(PathSegment::MoveTo { abs: true, x, y });
+                    start = end;
+                }
+                PathSegment::LineTo { .. }
+                | PathSegment::HorizontalLineTo { .. }
+                | PathSegment::VerticalLineTo { .. } => {
+                    self.push(PathSegment::LineTo { abs: true, x: end.0, y: end.1 });
+                }
+                PathSegment::CurveTo { x1, y1, x2, y2, x, y, .. } => {
+                    self.push(PathSegment::CurveTo { abs: true, x1, y1, x2, y2, x, y });
+                    cubic = Some((x2, y2));
+                }
+                PathSegment::SmoothCurveTo { x2, y2, x, y, .. } => {
+                    let (x1, y1) = reflect(prev_cubic, prev);
+                    self.push(PathSegment::CurveTo { abs: true, x1, y1, x2, y2, x, y });
+                    cubic = Some((x2, y2));
+                }
+                PathSegment::Quadratic { x1, y1, x, y, .. } => {
+                    self.push(quad_to_curve(prev, (x1, y1), (x, y)));
+                    quad = Some((x1, y1));
+                }
+                PathSegment::SmoothQuadratic { x, y, .. } => {
+                    let p1 = reflect(prev_quad, prev);
+                    self.push(quad_to_curve(prev, p1, (x, y)));
+                    quad = Some(p1);
+                }
+                PathSegment::EllipticalArc { rx, ry, x, y, .. } => {
+                    if end.0.fuzzy_eq(&prev.0) && end.1.fuzzy_eq(&prev.1) {
+                        // An arc with coincident endpoints must be omitted.
+                    } else if rx.is_fuzzy_zero() || ry.is_fuzzy_zero() {
+                        // An arc with a zero radius must be treated as a straight line.
+                        self.push(PathSegment::LineTo { abs: true, x, y });
+                    } else {
+                        arc_to_curves(prev, &seg, self);
+                    }
+                }
+                PathSegment::ClosePath { .. } => {
+                    self.push(PathSegment::ClosePath { abs: true });
+                }
+            }
+
+            prev_cubic = cubic;
+            prev_quad = quad;
+            prev = end;
+        }
+    }
+}
+
+// Reflects the previous control point relative to the current point.
+//
+// The current point itself is used when there is no previous control point.
+fn reflect(control: Option<(f64, f64)>, prev: (f64, f64)) -> (f64, f64) {
+    match control {
+        Some((x, y)) => (2.0 * prev.0 - x, 2.0 * prev.1 - y),
+        None => prev,
+    }
+}
+
+// Converts a quadratic curve into an identical cubic one.
+fn quad_to_curve(p0: (f64, f64), p1: (f64, f64), p: (f64, f64)) -> PathSegment {
+    PathSegment::CurveTo {
+        abs: true,
+        x1: p0.0 + 2.0 / 3.0 * (p1.0 - p0.0),
+        y1: p0.1 + 2.0 / 3.0 * (p1.1 - p0.1),
+        x2: p.0 + 2.0 / 3.0 * (p1.0 - p.0),
+        y2: p.1 + 2.0 / 3.0 * (p1.1 - p.1),
+        x: p.0,
+        y: p.1,
+    }
+}
+
+// Approximates an elliptical arc with cubic curves.
+//
+// Uses the endpoint to center parameterization conversion from the
+// SVG implementation notes, F.6.5. Each curve covers up to 90 degrees.
+//
+// The arc must be absolute and have non-zero radii.
+fn arc_to_curves(prev: (f64, f64), arc: &PathSegment, path: &mut Path) {
+    use std::f64::consts::PI;
+
+    let (rx, ry, x_axis_rotation, large_arc, sweep, end) = match *arc {
+        PathSegment::EllipticalArc { rx, ry, x_axis_rotation, large_arc, sweep, x, y, .. } => {
+            (rx, ry, x_axis_rotation, large_arc, sweep, (x, y))
+        }
+        _ => return,
+    };
+
+    let mut rx = rx.abs();
+    let mut ry = ry.abs();
+    let (sin, cos) = x_axis_rotation.to_radians().sin_cos();
+
+    let dx = (prev.0 - end.0) / 2.0;
+    let dy = (prev.1 - end.1) / 2.0;
+    let x1 =  cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        let k = lambda.sqrt();
+        rx *= k;
+        ry *= k;
+    }
+
+    let rx2 = rx * rx;
+    let ry2 = ry * ry;
+    let num = rx2 * ry2 - rx2 * y1 * y1 - ry2 * x1 * x1;
+    let den = rx2 * y1 * y1 + ry2 * x1 * x1;
+    let mut coef = (num / den).max(0.0).sqrt();
+    if large_arc == sweep {
+        coef = -coef;
+    }
+
+    let cx1 =  coef * rx * y1 / ry;
+    let cy1 = -coef * ry * x1 / rx;
+    let cx = cos * cx1 - sin * cy1 + (prev.0 + end.0) / 2.0;
+    let cy = sin * cx1 + cos * cy1 + (prev.1 + end.1) / 2.0;
+
+    let start_angle = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let end_angle = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+    let mut sweep_angle = end_angle - start_angle;
+    if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= 2.0 * PI;
+    } else if sweep && sweep_angle < 0.0 {
+        sweep_angle += 2.0 * PI;
+    }
+
+    let count = (sweep_angle.abs() / (PI / 2.0) - 0.001).ceil().max(1.0) as usize;
+    let delta = sweep_angle / count as f64;
+    let t = 4.0 / 3.0 * (delta / 4.0).tan();
+
+    // Maps a point on a unit circle onto the ellipse.
+    let map = |ux: f64, uy: f64| {
+        (cx + rx * ux * cos - ry * uy * sin, cy + rx * ux * sin + ry * uy * cos)
+    };
+
+    let mut angle = start_angle;
+    for i in 0..count {
+        let (sin1, cos1) = angle.sin_cos();
+        let (sin2, cos2) = (angle + delta).sin_cos();
+
+        let (x1, y1) = map(cos1 - t * sin1, sin1 + t * cos1);
+        let (x2, y2) = map(cos2 + t * sin2, sin2 - t * cos2);
+        // The last point must match the original one exactly.
+        let (x, y) = if i + 1 == count { end } else { map(cos2, sin2) };
+
+        path.push(PathSegment::CurveTo { abs: true, x1, y1, x2, y2, x, y });
+        angle += delta;
+    }
+}
+
+struct Subpath {
+    // Absolute segments and original `abs` flags.
+    segments: Vec<(PathSegment, bool)>,
+    is_degenerate: bool,
+}
+
+// Splits the path into subpaths and checks which of them are degenerate.
+fn split_subpaths(path: &Path) -> Vec<Subpath> {
+    let mut list: Vec<Subpath> = Vec::new();
+    // The first point of the current subpath.
+    let mut first = (0.0, 0.0);
+
+    let mut prev = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    for seg in path.iter() {
+        let abs_seg = to_absolute(seg, prev);
+        let end = segment_end(&abs_seg, prev, start);
+
+        let is_new = match abs_seg {
+            PathSegment::MoveTo { .. } => true,
+            _ => list.is_empty(),
+        };
+
+        if is_new {
+            first = if list.is_empty() && !is_move_to(&abs_seg) { prev } else { end };
+            list.push(Subpath { segments: Vec::new(), is_degenerate: true });
+        }
+
+        let subpath = list.last_mut().unwrap();
+        subpath.segments.push((abs_seg, seg.is_absolute()));
+
+        if subpath.is_degenerate {
+            let is_coincident = segment_points(&abs_seg, end).iter().all(|p| {
+                p.0.is_finite() && p.1.is_finite() && p.0.fuzzy_eq(&first.0) && p.1.fuzzy_eq(&first.1)
+            });
+
+            if !is_coincident {
+                subpath.is_degenerate = false;
+            }
+        }
+
+        if !is_finite_segment(&abs_seg) {
+            subpath.is_degenerate = true;
+        }
+
+        if let PathSegment::MoveTo { .. } = abs_seg {
+            start = end;
+        }
+
+        prev = end;
+    }
+
+    list
+}
+
+// Returns all points of an absolute segment, including control ones.
+fn segment_points(seg: &PathSegment, end: (f64, f64)) -> Vec<(f64, f64)> {
+    match *seg {
+        PathSegment::CurveTo { x1, y1, x2, y2, .. } => vec![(x1, y1), (x2, y2), end],
+        PathSegment::SmoothCurveTo { x2, y2, .. } => vec![(x2, y2), end],
+        PathSegment::Quadratic { x1, y1, .. } => vec![(x1, y1), end],
+        _ => vec![end],
+    }
+}
+
+fn is_finite_segment(seg: &PathSegment) -> bool {
+    match *seg {
+        PathSegment::MoveTo { x, y, .. }
+        | PathSegment::LineTo { x, y, .. }
+        | PathSegment::SmoothQuadratic { x, y, .. } => x.is_finite() && y.is_finite(),
+        PathSegment::HorizontalLineTo { x, .. } => x.is_finite(),
+        PathSegment::VerticalLineTo { y, .. } => y.is_finite(),
+        PathSegment::CurveTo { x1, y1, x2, y2, x, y, .. } => {
+            [x1, y1, x2, y2, x, y].iter().all(|n| n.is_finite())
+        }
+        PathSegment::SmoothCurveTo { x2, y2, x, y, .. } => {
+            [x2, y2, x, y].iter().all(|n| n.is_finite())
+        }
+        PathSegment::Quadratic { x1, y1, x, y, .. } => {
+            [x1, y1, x, y].iter().all(|n| n.is_finite())
+        }
+        PathSegment::EllipticalArc { rx, ry, x_axis_rotation, x, y, .. } => {
+            [rx, ry, x_axis_rotation, x, y].iter().all(|n| n.is_finite())
+        }
+        PathSegment::ClosePath { .. } => true,
+    }
+}
+
+fn is_move_to(seg: &PathSegment) -> bool {
+    match *seg {
+        PathSegment::MoveTo { .. } => true,
+        _ => false,
+    }
+}
+
+// Appends lines that are left after the RDP reduction.
+//
+// `points` contains the start point of the first line and end points of all lines.
+fn reduce_lines(
+    lines: &[(PathSegment, bool)],
+    points: &[(f64, f64)],
+    tolerance: f64,
+    list: &mut Vec<(PathSegment, bool)>,
+) -> usize {
+    if lines.is_empty() {
+        return 0;
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    rdp(points, 0, points.len() - 1, tolerance, &mut keep);
+
+    let mut prev = 0;
+    for i in 1..points.len() {
+        if !keep[i] {
+            continue;
+        }
+
+        let (seg, abs) = lines[i - 1];
+        if prev + 1 == i {
+            // The previous point is preserved, so the segment can be used as is.
+            list.push((seg, abs));
+        } else {
+            let p = points[i];
+            list.push((PathSegment::LineTo { abs: true, x: p.0, y: p.1 }, abs));
+        }
+
+        prev = i;
+    }
+
+    keep.iter().filter(|k| !**k).count()
+}
+
+fn rdp(points: &[(f64, f64)], first: usize, last: usize, tolerance: f64, keep: &mut [bool]) {
+    if last <= first + 1 {
+        return;
+    }
+
+    let mut max_dist = 0.0;
+    let mut idx = first;
+    for i in (first + 1)..last {
+        let d = distance_to_segment(points[i], points[first], points[last]);
+        if d > max_dist {
+            max_dist = d;
+            idx = i;
+        }
+    }
+
+    if max_dist > tolerance {
+        keep[idx] = true;
+        rdp(points, first, idx, tolerance, keep);
+        rdp(points, idx, last, tolerance, keep);
+    }
+}
+
+// Rebuilds the path from absolute segments, converting them back
+// into relative ones when required.
+fn restore_segments<I>(path: &mut Path, list: I)
+    where I: Iterator<Item = (PathSegment, bool)>
+{
+    let list: Vec<_> = list.collect();
+    path.clear();
+
+    let mut prev = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    for (seg, abs) in list {
+        let end = segment_end(&seg, prev, start);
+
+        if let PathSegment::MoveTo { .. } = seg {
+            start = end;
+        }
+
+        path.push(if abs { seg } else { to_relative(&seg, prev) });
+        prev = end;
+    }
+}
+
+fn is_line(seg: &PathSegment) -> bool {
+    match *seg {
+          PathSegment::LineTo { .. }
+        | PathSegment::HorizontalLineTo { .. }
+        | PathSegment::VerticalLineTo { .. } => true,
+        _ => false,
+    }
+}
+
+fn distance(p1: (f64, f64), p2: (f64, f64)) -> f64 {
+    let dx = p2.0 - p1.0;
+    let dy = p2.1 - p1.1;
+    (dx * dx + dy * dy).sqrt()
+}
+
+// Returns a distance from the point `p` to the line defined by `p1` and `p2`.
+fn distance_to_line(p: (f64, f64), p1: (f64, f64), p2: (f64, f64)) -> f64 {
+    let len = distance(p1, p2);
+    if len.is_fuzzy_zero() {
+        return distance(p, p1);
+    }
+
+    ((p2.0 - p1.0) * (p1.1 - p.1) - (p1.0 - p.0) * (p2.1 - p1.1)).abs() / len
+}
+
+// Returns a distance from the point `p` to the segment defined by `p1` and `p2`.
+fn distance_to_segment(p: (f64, f64), p1: (f64, f64), p2: (f64, f64)) -> f64 {
+    let dx = p2.0 - p1.0;
+    let dy = p2.1 - p1.1;
+    let len2 = dx * dx + dy * dy;
+    if len2.is_fuzzy_zero() {
+        return distance(p, p1);
+    }
+
+    let t = ((p.0 - p1.0) * dx + (p.1 - p1.1) * dy) / len2;
+    if t < 0.0 {
+        distance(p, p1)
+    } else if t > 1.0 {
+        distance(p, p2)
+    } else {
+        distance(p, (p1.0 + t * dx, p1.1 + t * dy))
+    }
+}
+
+// Converts a segment into an absolute one.
+fn to_absolute(seg: &PathSegment, prev: (f64, f64)) -> PathSegment {
+    if seg.is_absolute() {
+        return *seg;
+    }
+
+    let mut seg = shift_segment(seg, prev.0, prev.1);
+    seg.set_absolute(true);
+    seg
+}
+
+// Converts a segment into a relative one.
+fn to_relative(seg: &PathSegment, prev: (f64, f64)) -> PathSegment {
+    if seg.is_relative() {
+        return *seg;
+    }
+
+    let mut seg = shift_segment(seg, -prev.0, -prev.1);
+    seg.set_absolute(false);
+    seg
+}
+
+fn shift_segment(seg: &PathSegment, dx: f64, dy: f64) -> PathSegment {
+    match *seg {
+        PathSegment::MoveTo { abs, x, y } => {
+            PathSegment::MoveTo { abs, x: x + dx, y: y + dy }
+        }
+        PathSegment::LineTo { abs, x, y } => {
+            PathSegment::LineTo { abs, x: x + dx, y: y + dy }
+        }
+        PathSegment::HorizontalLineTo { abs, x } => {
+            PathSegment::HorizontalLineTo { abs, x: x + dx }
+        }
+        PathSegment::VerticalLineTo { abs, y } => {
+            PathSegment::VerticalLineTo { abs, y: y + dy }
+        }
+        PathSegment::CurveTo { abs, x1, y1, x2, y2, x, y } => {
+            PathSegment::CurveTo {
+                abs,
+                x1: x1 + dx, y1: y1 + dy,
+                x2: x2 + dx, y2: y2 + dy,
+                x: x + dx, y: y + dy,
+            }
+        }
+        PathSegment::SmoothCurveTo { abs, x2, y2, x, y } => {
+            PathSegment::SmoothCurveTo { abs, x2: x2 + dx, y2: y2 + dy, x: x + dx, y: y + dy }
+        }
+        PathSegment::Quadratic { abs, x1, y1, x, y } => {
+            PathSegment::Quadratic { abs, x1: x1 + dx, y1: y1 + dy, x: x + dx, y: y + dy }
+        }
+        PathSegment::SmoothQuadratic { abs, x, y } => {
+            PathSegment::SmoothQuadratic { abs, x: x + dx, y: y + dy }
+        }
+        PathSegment::EllipticalArc { abs, rx, ry, x_axis_rotation, large_arc, sweep, x, y } => {
+            PathSegment::EllipticalArc {
+                abs, rx, ry, x_axis_rotation, large_arc, sweep,
+                x: x + dx, y: y + dy,
+            }
+        }
+        PathSegment::ClosePath { abs } => PathSegment::ClosePath { abs },
+    }
+}
+
+// Calculates the radii and the rotation of a transformed ellipse.
+//
+// The ellipse is represented by a matrix that maps a unit circle onto it,
+// so the new radii are the singular values of the transformed matrix.
+fn transform_arc(rx: f64, ry: f64, angle: f64, ts: &Transform) -> (f64, f64, f64) {
+    let (sin, cos) = angle.to_radians().sin_cos();
+
+    // Columns of the transformed ellipse matrix.
+    let m11 = (ts.a * cos + ts.c * sin) * rx;
+    let m21 = (ts.b * cos + ts.d * sin) * rx;
+    let m12 = (ts.a * -sin + ts.c * cos) * ry;
+    let m22 = (ts.b * -sin + ts.d * cos) * ry;
+
+    // M * M^T
+    let p = m11 * m11 + m12 * m12;
+    let q = m11 * m21 + m12 * m22;
+    let r = m21 * m21 + m22 * m22;
+
+    let mid = (p + r) / 2.0;
+    let d = (((p - r) / 2.0).powi(2) + q * q).sqrt();
+
+    let new_rx = (mid + d).sqrt();
+    let new_ry = (mid - d).max(0.0).sqrt();
+    let new_angle = (2.0 * q).atan2(p - r).to_degrees() / 2.0;
+
+    (new_rx, new_ry, new_angle)
+}
+
+// Returns an absolute end point of the segment.
+fn segment_end(seg: &PathSegment, prev: (f64, f64), start: (f64, f64)) -> (f64, f64) {
+    let (x, y) = match *seg {
+        PathSegment::MoveTo { x, y, .. }
+        | PathSegment::LineTo { x, y, .. }
+        | PathSegment::CurveTo { x, y, .. }
+        | PathSegment::SmoothCurveTo { x, y, .. }
+        | PathSegment::Quadratic { x, y, .. }
+        | PathSegment::SmoothQuadratic { x, y, .. }
+        | PathSegment::EllipticalArc { x, y, .. } => (x, y),
+        PathSegment::HorizontalLineTo { abs, x } => {
+            return if abs { (x, prev.1) } else { (prev.0 + x, prev.1) };
+        }
+        PathSegment::VerticalLineTo { abs, y } => {
+            return if abs { (prev.0, y) } else { (prev.0, prev.1 + y) };
+        }
+        PathSegment::ClosePath { .. } => return start,
+    };
+
+    if seg.is_absolute() {
+        (x, y)
+    } else {
+        (prev.0 + x, prev.1 + y)
+    }
+}
+
+/// A kind of a number stored in a path segment.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum SegmentNumber {
+    X,
+    Y,
+    Radius,
+    Angle,
+}
+
+/// Calls `f` for each number of the segment.
+pub(crate) fn visit_segment_numbers<F>(seg: &mut PathSegment, mut f: F)
+    where F: FnMut(&mut f64, SegmentNumber)
+{
+    match *seg {
+        PathSegment::MoveTo { ref mut x, ref mut y, .. }
+        | PathSegment::LineTo { ref mut x, ref mut y, .. }
+        | PathSegment::SmoothQuadratic { ref mut x, ref mut y, .. } => {
+            f(x, SegmentNumber::X);
+            f(y, SegmentNumber::Y);
+        }
+        PathSegment::HorizontalLineTo { ref mut x, .. } => {
+            f(x, SegmentNumber::X);
+        }
+        PathSegment::VerticalLineTo { ref mut y, .. } => {
+            f(y, SegmentNumber::Y);
+        }
+        PathSegment::CurveTo { ref mut x1, ref mut y1, ref mut x2, ref mut y2,
+                               ref mut x, ref mut y, .. } => {
+            f(x1, SegmentNumber::X);
+            f(y1, SegmentNumber::Y);
+            f(x2, SegmentNumber::X);
+            f(y2, SegmentNumber::Y);
+            f(x, SegmentNumber::X);
+            f(y, SegmentNumber::Y);
+        }
+        PathSegment::SmoothCurveTo { ref mut x2, ref mut y2, ref mut x, ref mut y, .. }
+        | PathSegment::Quadratic { x1: ref mut x2, y1: ref mut y2, ref mut x, ref mut y, .. } => {
+            f(x2, SegmentNumber::X);
+            f(y2, SegmentNumber::Y);
+            f(x, SegmentNumber::X);
+            f(y, SegmentNumber::Y);
+        }
+        PathSegment::EllipticalArc { ref mut rx, ref mut ry, ref mut x_axis_rotation,
+                                     ref mut x, ref mut y, .. } => {
+            f(rx, SegmentNumber::Radius);
+            f(ry, SegmentNumber::Radius);
+            f(x_axis_rotation, SegmentNumber::Angle);
+            f(x, SegmentNumber::X);
+            f(y, SegmentNumber::Y);
+        }
+        PathSegment::ClosePath { .. } => {}
+    }
+}
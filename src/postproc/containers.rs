@@ -0,0 +1,58 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    AttributeId,
+    AttributeValue,
+    Document,
+    ElementId,
+    Node,
+};
+
+/// Removes empty `g`, `defs`, `symbol` and `switch` elements.
+///
+/// Elements that are referenced by other elements and groups with a `filter`
+/// attribute will be preserved, since they can still affect the rendering.
+///
+/// Repeats until there is nothing left to remove, since removing an element
+/// can make its parent empty.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, postproc};
+///
+/// let mut doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg'>
+///         <g><g><defs/></g></g>
+///         <rect/>
+///      </svg>").unwrap();
+///
+/// postproc::remove_empty_containers(&mut doc);
+/// assert_eq!(doc.to_string(),
+///     "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    <rect/>\n</svg>\n");
+/// ```
+pub fn remove_empty_containers(doc: &mut Document) {
+    let root = doc.root();
+    while doc.drain(root.clone(), is_empty_container) != 0 {}
+}
+
+fn is_empty_container(node: &Node) -> bool {
+    let is_container = match node.tag_id() {
+        Some(ElementId::G) | Some(ElementId::Defs)
+        | Some(ElementId::Symbol) | Some(ElementId::Switch) => true,
+        _ => false,
+    };
+
+    let has_filter = match node.attributes().get_value(AttributeId::Filter) {
+        Some(&AttributeValue::FuncLink(_)) => true,
+        _ => false,
+    };
+
+    is_container && !node.has_children() && !node.is_used() && !has_filter
+}
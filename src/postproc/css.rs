@@ -0,0 +1,47 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use parser;
+use Document;
+
+/// Applies `style` elements to the document and removes them.
+///
+/// CSS rules are converted into presentation attributes in the cascade order,
+/// respecting the selector specificity and `!important`.
+/// Afterwards, all `style` elements and `class` attributes are removed.
+///
+/// The parser already does this during parsing, so this pass is useful only
+/// for documents with `style` elements created using the DOM API.
+///
+/// Like the parser, only simple selectors are supported: `*`, `type`, `#id` and `.class`.
+/// Unsupported rules and invalid values are skipped with a warning.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, ElementId as EId, AttributeId as AId, NodeType, postproc};
+///
+/// let mut doc = Document::new();
+/// let mut svg = doc.create_element(EId::Svg);
+/// doc.root().append(svg.clone());
+///
+/// let mut style = doc.create_element(EId::Style);
+/// style.append(doc.create_node(NodeType::Text, ".red { fill: red !important } rect { fill: blue }"));
+/// svg.append(style);
+///
+/// let mut rect = doc.create_element(EId::Rect);
+/// rect.set_attribute((AId::Class, "red"));
+/// svg.append(rect);
+///
+/// postproc::flatten_css(&mut doc);
+/// assert_eq!(doc.to_string(),
+///     "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    <rect fill=\"#ff0000\"/>\n</svg>\n");
+/// ```
+pub fn flatten_css(doc: &mut Document) {
+    parser::flatten_css(doc);
+}
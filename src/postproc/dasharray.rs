@@ -0,0 +1,108 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    AttributeId,
+    AttributeValue,
+    FuzzyZero,
+    LengthList,
+    Node,
+};
+
+/// Normalizes `stroke-dasharray` attributes according to the SVG spec.
+///
+/// - A list with a negative value is invalid, so the attribute is removed.
+/// - A list with only zeros is rendered as `none`, so the attribute is removed too.
+///   If an ancestor has a non-`none` dash array, the value is set to `none` instead,
+///   so the inherited one would not be used.
+/// - A list of odd length is repeated to yield an even number of values,
+///   so `5 3 2` becomes `5 3 2 5 3 2`.
+///
+/// Processes `root` and all its descendants.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, postproc};
+///
+/// let doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg'>
+///         <path stroke-dasharray='5 3 2'/>
+///         <path stroke-dasharray='0 0'/>
+///         <path stroke-dasharray='5 -3'/>
+///      </svg>").unwrap();
+/// postproc::normalize_dasharray(&doc.root());
+/// assert_eq!(doc.to_string(),
+/// "<svg xmlns=\"http://www.w3.org/2000/svg\">
+///     <path stroke-dasharray=\"5 3 2 5 3 2\"/>
+///     <path/>
+///     <path/>
+/// </svg>
+/// ");
+/// ```
+pub fn normalize_dasharray(root: &Node) {
+    let aid = AttributeId::StrokeDasharray;
+
+    // Descendants are visited after their ancestors,
+    // so ancestors are always already normalized.
+    for mut node in root.descendants().filter(|n| n.has_attribute(aid)) {
+        let kind = match node.attributes_mut().get_value_mut(aid) {
+            Some(&mut AttributeValue::LengthList(ref mut list)) => normalize_list(list),
+            _ => continue,
+        };
+
+        match kind {
+            DashArray::Valid => {}
+            DashArray::Invalid => {
+                node.remove_attribute(aid);
+            }
+            DashArray::Empty => {
+                if has_inherited_dasharray(&node) {
+                    node.set_attribute((aid, AttributeValue::None));
+                } else {
+                    node.remove_attribute(aid);
+                }
+            }
+        }
+    }
+}
+
+enum DashArray {
+    Valid,
+    Invalid,
+    Empty,
+}
+
+fn normalize_list(list: &mut LengthList) -> DashArray {
+    if list.iter().any(|len| len.num < 0.0) {
+        return DashArray::Invalid;
+    }
+
+    if list.iter().all(|len| len.num.is_fuzzy_zero()) {
+        return DashArray::Empty;
+    }
+
+    if list.len() % 2 == 1 {
+        let copy = list.0.clone();
+        list.0.extend(copy);
+    }
+
+    DashArray::Valid
+}
+
+fn has_inherited_dasharray(node: &Node) -> bool {
+    for n in node.ancestors().skip(1) {
+        match n.attributes().get_value(AttributeId::StrokeDasharray) {
+            Some(&AttributeValue::None) => return false,
+            Some(&AttributeValue::LengthList(_)) => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
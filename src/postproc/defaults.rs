@@ -0,0 +1,69 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    Attribute,
+    AttributeId,
+    AttributeType,
+    Node,
+};
+
+/// Sets default values for the specified attributes when they are not set.
+///
+/// Inheritable attributes are set only on the topmost SVG elements
+/// that don't inherit them from an ancestor, so the inherited values are not overridden.
+/// Non-inheritable attributes are set on each SVG element.
+///
+/// Attributes without a known default value are ignored.
+/// See [`AttributeValue::default_value`] for details.
+///
+/// Processes `root` and all its descendants.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, AttributeId as AId, postproc};
+///
+/// let doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg'>
+///         <g fill='#ff0000'>
+///             <rect/>
+///         </g>
+///         <rect/>
+///      </svg>").unwrap();
+/// postproc::add_missing_defaults(&doc.svg_element().unwrap(), &[AId::Fill, AId::Opacity]);
+/// assert_eq!(doc.to_string(),
+/// "<svg xmlns=\"http://www.w3.org/2000/svg\" fill=\"#000000\" opacity=\"1\">
+///     <g fill=\"#ff0000\" opacity=\"1\">
+///         <rect opacity=\"1\"/>
+///     </g>
+///     <rect opacity=\"1\"/>
+/// </svg>
+/// ");
+/// ```
+///
+/// [`AttributeValue::default_value`]: enum.AttributeValue.html#method.default_value
+pub fn add_missing_defaults(root: &Node, ids: &[AttributeId]) {
+    for mut node in root.descendants().filter(|n| n.is_svg_element()) {
+        for &id in ids {
+            let is_set = if id.is_inheritable() {
+                node.ancestors().any(|n| n.has_attribute(id))
+            } else {
+                node.has_attribute(id)
+            };
+
+            if is_set {
+                continue;
+            }
+
+            if let Some(attr) = Attribute::new_default(id) {
+                node.set_attribute(attr);
+            }
+        }
+    }
+}
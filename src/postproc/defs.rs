@@ -0,0 +1,158 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    Document,
+    ElementId,
+    ElementType,
+    Node,
+};
+
+/// Reorders elements so that referenced definitions appear before their users.
+///
+/// Only non-rendered elements can be moved: `defs` and elements that are rendered
+/// only when referenced, like gradients, patterns, filters, etc.
+/// They are moved within their parent, right before the first sibling
+/// that references them directly or via its children.
+/// Everything else preserves the original order, so the rendering is not affected.
+///
+/// Siblings with cyclic references are left in place and a warning is emitted.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, postproc};
+///
+/// let mut doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+///         <rect fill='url(#lg2)'/>
+///         <linearGradient id='lg2' xlink:href='#lg1'/>
+///         <linearGradient id='lg1'/>
+///      </svg>").unwrap();
+///
+/// postproc::sort_defs_topologically(&mut doc);
+/// assert_eq!(doc.to_string(),
+///     "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">\n    \
+///     <linearGradient id=\"lg1\"/>\n    \
+///     <linearGradient id=\"lg2\" xlink:href=\"#lg1\"/>\n    \
+///     <rect fill=\"url(#lg2)\"/>\n</svg>\n");
+/// ```
+pub fn sort_defs_topologically(doc: &mut Document) {
+    // Each dependency is stored as `(parent, user, definition)`,
+    // where `user` and `definition` are children of `parent`.
+    let mut deps: Vec<(Node, Node, Node)> = Vec::new();
+    for (source, _, target) in doc.links() {
+        let (parent, user, def) = match diverging_children(&source, &target) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        if is_movable(&def) {
+            deps.push((parent, user, def));
+        }
+    }
+
+    let mut parents: Vec<Node> = Vec::new();
+    for &(ref parent, _, _) in &deps {
+        if !parents.contains(parent) {
+            parents.push(parent.clone());
+        }
+    }
+
+    for mut parent in parents {
+        let children: Vec<Node> = parent.children().collect();
+
+        let mut graph = vec![Vec::new(); children.len()];
+        for &(ref p, ref user, ref def) in &deps {
+            if *p != parent {
+                continue;
+            }
+
+            let user_idx = children.iter().position(|n| n == user).unwrap();
+            let def_idx = children.iter().position(|n| n == def).unwrap();
+            if !graph[user_idx].contains(&def_idx) {
+                graph[user_idx].push(def_idx);
+            }
+        }
+
+        let mut order = Vec::with_capacity(children.len());
+        let mut state = vec![State::New; children.len()];
+        let mut has_cycle = false;
+        for idx in 0..children.len() {
+            if !visit(idx, &graph, &mut state, &mut order) {
+                has_cycle = true;
+                break;
+            }
+        }
+
+        if has_cycle {
+            warn!("Cyclic references inside the '{}' element. Elements are left in place.",
+                  parent.tag_name());
+            continue;
+        }
+
+        if order.iter().enumerate().all(|(i, &idx)| i == idx) {
+            continue;
+        }
+
+        for idx in order {
+            let mut node = children[idx].clone();
+            node.detach();
+            parent.append(node);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    New,
+    InProgress,
+    Done,
+}
+
+// Appends dependencies first. Returns `false` on a cycle.
+fn visit(idx: usize, graph: &[Vec<usize>], state: &mut [State], order: &mut Vec<usize>) -> bool {
+    match state[idx] {
+        State::Done => return true,
+        State::InProgress => return false,
+        State::New => {}
+    }
+
+    state[idx] = State::InProgress;
+    for &dep in &graph[idx] {
+        if !visit(dep, graph, state, order) {
+            return false;
+        }
+    }
+    state[idx] = State::Done;
+
+    order.push(idx);
+    true
+}
+
+// Returns the common ancestor of two nodes and its children that contain them.
+//
+// Returns `None` when one node is an ancestor of another.
+fn diverging_children(a: &Node, b: &Node) -> Option<(Node, Node, Node)> {
+    let mut a_path: Vec<Node> = a.ancestors().collect();
+    let mut b_path: Vec<Node> = b.ancestors().collect();
+    a_path.reverse();
+    b_path.reverse();
+
+    for i in 1..a_path.len().min(b_path.len()) {
+        if a_path[i] != b_path[i] {
+            return Some((a_path[i - 1].clone(), a_path[i].clone(), b_path[i].clone()));
+        }
+    }
+
+    None
+}
+
+fn is_movable(node: &Node) -> bool {
+    node.is_referenced() || node.is_tag_name(ElementId::Defs)
+}
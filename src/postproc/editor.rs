@@ -0,0 +1,102 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    Document,
+    ElementId,
+    Node,
+    QName,
+};
+
+/// Namespace prefixes used by the known SVG editors.
+///
+/// Inkscape, Sodipodi, Sketch and Affinity Designer.
+///
+/// Prefixes used by Adobe Illustrator, like `i` or `x`, are too generic
+/// to be matched without checking the namespace URI, so they are not included.
+pub const EDITOR_PREFIXES: &[&str] = &[
+    "inkscape",
+    "sodipodi",
+    "sketch",
+    "serif",
+];
+
+/// Removes editor-specific data from the document.
+///
+/// Removes:
+///
+/// - elements and attributes with [`EDITOR_PREFIXES`]
+/// - `metadata` elements
+/// - empty `defs` elements
+///
+/// Elements that are referenced by other elements will be preserved,
+/// so the rendering will not be affected.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, postproc};
+///
+/// let mut doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg'>
+///         <metadata/>
+///         <defs/>
+///         <rect/>
+///      </svg>").unwrap();
+/// doc.svg_element().unwrap().set_attribute(("inkscape:version", "0.92"));
+///
+/// postproc::remove_editor_data(&mut doc);
+/// assert_eq!(doc.to_string(),
+///     "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    <rect/>\n</svg>\n");
+/// ```
+///
+/// [`EDITOR_PREFIXES`]: constant.EDITOR_PREFIXES.html
+pub fn remove_editor_data(doc: &mut Document) {
+    remove_editor_data_except(doc, &[]);
+}
+
+/// Removes editor-specific data from the document,
+/// except the data with the specified namespace prefixes.
+///
+/// See [`remove_editor_data`] for details.
+///
+/// [`remove_editor_data`]: fn.remove_editor_data.html
+pub fn remove_editor_data_except(doc: &mut Document, keep: &[&str]) {
+    let prefixes: Vec<&str> = EDITOR_PREFIXES.iter()
+        .filter(|p| !keep.contains(p))
+        .cloned()
+        .collect();
+
+    let root = doc.root();
+    doc.drain(root.clone(), |n| {
+        let is_editor_elem = n.is_tag_name(ElementId::Metadata) || has_prefix(n, &prefixes);
+        is_editor_elem && !n.descendants().any(|d| d.is_used())
+    });
+
+    for mut node in root.descendants().filter(|n| n.is_element()) {
+        for prefix in &prefixes {
+            node.remove_attributes_by_prefix(prefix);
+        }
+    }
+
+    doc.drain(root, |n| {
+        n.is_tag_name(ElementId::Defs) && !n.has_children() && !n.is_used()
+    });
+}
+
+fn has_prefix(node: &Node, prefixes: &[&str]) -> bool {
+    match *node.tag_name() {
+        QName::Name(ref name) => {
+            match name.find(':') {
+                Some(idx) => prefixes.contains(&&name[..idx]),
+                None => false,
+            }
+        }
+        QName::Id(_) => false,
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    Attribute,
+    AttributeId,
+    AttributeType,
+    AttributeValue,
+    Document,
+    ElementId,
+    ElementType,
+    Node,
+    QName,
+};
+
+/// Replaces groups with a single child with that child.
+///
+/// The group transform will be prepended to the child one, and the group
+/// presentation attributes will be moved to the child, unless the child
+/// already has them.
+///
+/// Groups that are referenced, that have `clip-path`, `mask`, `filter` or `opacity`
+/// attributes, non-presentation attributes or a presentation attribute
+/// that conflicts with the child one will be preserved.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, postproc};
+///
+/// let mut doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg'>
+///         <g fill='red'><g transform='translate(10)'><rect/></g></g>
+///      </svg>").unwrap();
+///
+/// postproc::collapse_single_child_groups(&mut doc);
+/// assert_eq!(doc.to_string(),
+///     "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    \
+///     <rect fill=\"#ff0000\" transform=\"matrix(1 0 0 1 10 0)\"/>\n</svg>\n");
+/// ```
+pub fn collapse_single_child_groups(doc: &mut Document) {
+    // Process the deepest groups first, so nested groups will be collapsed too.
+    let groups: Vec<Node> = doc.root().descendants().filter(|n| n.is_tag_name(ElementId::G)).collect();
+    for mut g in groups.into_iter().rev() {
+        let mut child = match collapsible_child(&g) {
+            Some(child) => child,
+            None => continue,
+        };
+
+        merge_attributes(&g, &mut child);
+
+        child.detach();
+        g.insert_before(child.clone());
+
+        let id = g.id().clone();
+        doc.remove_node(g);
+
+        if !id.is_empty() {
+            child.set_id(id);
+        }
+    }
+}
+
+fn collapsible_child(g: &Node) -> Option<Node> {
+    let child = try_opt!(g.first_child());
+
+    if child.next_sibling().is_some() {
+        return None;
+    }
+
+    if !(child.is_graphic() || child.is_tag_name(ElementId::G)) {
+        return None;
+    }
+
+    if g.is_used() || (g.has_id() && child.has_id()) {
+        return None;
+    }
+
+    for attr in g.attributes().iter() {
+        let id = match attr.name {
+            QName::Id(id) => id,
+            QName::Name(_) => return None,
+        };
+
+        match id {
+            AttributeId::Transform => continue,
+              AttributeId::ClipPath
+            | AttributeId::Mask
+            | AttributeId::Filter
+            | AttributeId::Opacity => return None,
+            _ => {}
+        }
+
+        if !id.is_presentation() {
+            return None;
+        }
+
+        if !id.is_inheritable() {
+            if let Some(value) = child.attributes().get_value(id) {
+                if *value != attr.value {
+                    return None;
+                }
+            }
+        }
+    }
+
+    Some(child)
+}
+
+fn merge_attributes(g: &Node, child: &mut Node) {
+    for attr in g.attributes().iter() {
+        if attr.has_id(AttributeId::Transform) {
+            if let AttributeValue::Transform(mut ts) = attr.value {
+                if let Some(&AttributeValue::Transform(ref child_ts)) =
+                    child.attributes().get_value(AttributeId::Transform) {
+                    ts.append(child_ts);
+                }
+
+                child.set_attribute((AttributeId::Transform, ts));
+            }
+
+            continue;
+        }
+
+        let is_set = match child.attributes().get_value(attr.name.as_ref()) {
+            Some(&AttributeValue::Inherit) | None => false,
+            Some(_) => true,
+        };
+
+        if !is_set {
+            child.set_attribute(Attribute::new(attr.name.as_ref(), attr.value.clone()));
+        }
+    }
+}
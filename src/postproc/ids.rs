@@ -0,0 +1,252 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{HashMap, HashSet};
+
+use {
+    AttributeValue,
+    Document,
+    Node,
+};
+
+/// Renames all IDs to the shortest unique strings: `a`, `b`, ..., `Z`, `aa`, `ba`, ...
+///
+/// The most referenced elements will get the shortest IDs.
+///
+/// Links are updated automatically, since they are stored as nodes.
+/// Unresolved `url(#id)` and `#id` strings are updated too.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, postproc};
+///
+/// let mut doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg'>
+///         <linearGradient id='gradient'/>
+///         <rect fill='url(#gradient)'/>
+///      </svg>").unwrap();
+///
+/// postproc::minify_ids(&mut doc);
+/// assert_eq!(doc.to_string(),
+///     "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    \
+///     <linearGradient id=\"a\"/>\n    <rect fill=\"url(#a)\"/>\n</svg>\n");
+/// ```
+pub fn minify_ids(doc: &mut Document) {
+    minify_ids_except(doc, &[]);
+}
+
+/// Renames all IDs to the shortest unique strings, except the specified ones.
+///
+/// Useful for IDs that are referenced by an external CSS or JS.
+///
+/// See [`minify_ids`] for details.
+///
+/// [`minify_ids`]: fn.minify_ids.html
+pub fn minify_ids_except(doc: &mut Document, keep: &[&str]) {
+    let mut nodes: Vec<Node> = doc.root().descendants()
+        .filter(|n| n.has_id() && !keep.contains(&n.id().as_str()))
+        .collect();
+
+    // A stable sort, so elements with the same usage count will preserve the document order.
+    nodes.sort_by(|a, b| b.uses_count().cmp(&a.uses_count()));
+
+    let mut renamed = HashMap::new();
+    let mut used_ids: HashSet<String> = keep.iter().map(|s| s.to_string()).collect();
+    let mut idx = 0;
+    for mut node in nodes {
+        let new_id = next_free_id(&mut idx, &used_ids);
+        used_ids.insert(new_id.clone());
+        renamed.insert(node.id().clone(), new_id.clone());
+        node.set_id(new_id);
+    }
+
+    for mut node in doc.root().descendants() {
+        let mut attrs = node.attributes_mut();
+        for attr in attrs.iter_mut() {
+            if let AttributeValue::String(ref mut s) = attr.value {
+                if let Some(new_s) = rename_iri(s, &renamed) {
+                    *s = new_s;
+                }
+            }
+        }
+    }
+}
+
+/// Removes IDs from elements that are not referenced.
+///
+/// An element is referenced when it's linked by another element
+/// or when its IRI is present in a string attribute value, like `url(#id)`.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, postproc};
+///
+/// let mut doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg'>
+///         <linearGradient id='lg1'/>
+///         <rect id='rect1' fill='url(#lg1)'/>
+///      </svg>").unwrap();
+///
+/// postproc::remove_unreferenced_ids(&mut doc);
+/// assert_eq!(doc.to_string(),
+///     "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    \
+///     <linearGradient id=\"lg1\"/>\n    <rect fill=\"url(#lg1)\"/>\n</svg>\n");
+/// ```
+pub fn remove_unreferenced_ids(doc: &mut Document) {
+    remove_unreferenced_ids_except(doc, &[]);
+}
+
+/// Removes IDs from elements that are not referenced, except the specified ones.
+///
+/// A pattern with a trailing `*` matches all IDs with the same prefix,
+/// so `icon-*` will preserve `icon-home`, `icon-search`, etc.
+///
+/// See [`remove_unreferenced_ids`] for details.
+///
+/// [`remove_unreferenced_ids`]: fn.remove_unreferenced_ids.html
+pub fn remove_unreferenced_ids_except(doc: &mut Document, keep: &[&str]) {
+    let mut string_refs = HashSet::new();
+    for node in doc.root().descendants() {
+        for attr in node.attributes().iter() {
+            if let AttributeValue::String(ref s) = attr.value {
+                if let Some(id) = parse_iri(s) {
+                    string_refs.insert(id.to_string());
+                }
+            }
+        }
+    }
+
+    for mut node in doc.root().descendants() {
+        if !node.has_id() || node.is_used() {
+            continue;
+        }
+
+        let is_kept = {
+            let id = node.id();
+               string_refs.contains(id.as_str())
+            || keep.iter().any(|pattern| is_id_match(&id, pattern))
+        };
+
+        if !is_kept {
+            node.set_id(String::new());
+        }
+    }
+}
+
+// Renames elements inside the `roots` which IDs are already present in `used`.
+//
+// New IDs are generated the same way as in `minify_ids`, skipping the IDs from `used`
+// and `roots`. All IDs inside the `roots` are added to `used`.
+// Unresolved `url(#id)` and `#id` strings are updated too.
+pub(crate) fn rename_colliding_ids(roots: &[Node], used: &mut HashSet<String>) {
+    let mut reserved = used.clone();
+    for node in roots.iter().flat_map(|n| n.descendants()).filter(|n| n.has_id()) {
+        reserved.insert(node.id().clone());
+    }
+
+    let mut renamed = HashMap::new();
+    let mut idx = 0;
+    for mut node in roots.iter().flat_map(|n| n.descendants()) {
+        if !node.has_id() {
+            continue;
+        }
+
+        if used.contains(node.id().as_str()) {
+            let new_id = next_free_id(&mut idx, &reserved);
+            reserved.insert(new_id.clone());
+            renamed.insert(node.id().clone(), new_id.clone());
+            node.set_id(new_id);
+        }
+
+        used.insert(node.id().clone());
+    }
+
+    if renamed.is_empty() {
+        return;
+    }
+
+    for mut node in roots.iter().flat_map(|n| n.descendants()) {
+        let mut attrs = node.attributes_mut();
+        for attr in attrs.iter_mut() {
+            if let AttributeValue::String(ref mut s) = attr.value {
+                if let Some(new_s) = rename_iri(s, &renamed) {
+                    *s = new_s;
+                }
+            }
+        }
+    }
+}
+
+fn is_id_match(id: &str, pattern: &str) -> bool {
+    if pattern.ends_with('*') {
+        // `trim_end_matches` requires Rust 1.30.
+        #[allow(deprecated)]
+        let prefix = pattern.trim_right_matches('*');
+        id.starts_with(prefix)
+    } else {
+        id == pattern
+    }
+}
+
+// Returns the first generated ID, starting from `idx`, that is not in `used`.
+fn next_free_id(idx: &mut usize, used: &HashSet<String>) -> String {
+    loop {
+        let id = gen_id(*idx);
+        *idx += 1;
+
+        if !used.contains(&id) {
+            return id;
+        }
+    }
+}
+
+// Generates an ID by index: a..z, A..Z, aa, ba, ...
+fn gen_id(mut idx: usize) -> String {
+    const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const OTHER: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+    let mut id = String::new();
+    id.push(FIRST[idx % FIRST.len()] as char);
+    idx /= FIRST.len();
+
+    while idx != 0 {
+        idx -= 1;
+        id.push(OTHER[idx % OTHER.len()] as char);
+        idx /= OTHER.len();
+    }
+
+    id
+}
+
+fn rename_iri(s: &str, renamed: &HashMap<String, String>) -> Option<String> {
+    let id = try_opt!(parse_iri(s));
+
+    let new_id = try_opt!(renamed.get(id));
+
+    if s.trim().starts_with('#') {
+        Some(format!("#{}", new_id))
+    } else {
+        Some(format!("url(#{})", new_id))
+    }
+}
+
+// Extracts an ID from the `#id` or `url(#id)` string.
+fn parse_iri(s: &str) -> Option<&str> {
+    let s = s.trim();
+    if s.starts_with('#') {
+        return Some(&s[1..]);
+    }
+
+    if s.starts_with("url(#") && s.ends_with(')') {
+        return Some(s[5..s.len() - 1].trim());
+    }
+
+    None
+}
@@ -0,0 +1,38 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Post-processing passes over a parsed document.
+//!
+//! Passes that modify only attributes operate on a subtree, starting from the specified node.
+//! Passes that remove nodes operate on the whole document.
+
+mod containers;
+#[cfg(feature = "parsing")]
+mod css;
+mod dasharray;
+mod defaults;
+mod defs;
+mod editor;
+mod groups;
+mod ids;
+mod paths;
+mod shapes;
+mod units;
+
+pub use self::containers::*;
+#[cfg(feature = "parsing")]
+pub use self::css::*;
+pub use self::dasharray::*;
+pub use self::defaults::*;
+pub use self::defs::*;
+pub use self::editor::*;
+pub use self::groups::*;
+pub use self::ids::*;
+pub use self::paths::*;
+pub use self::shapes::*;
+pub use self::units::*;
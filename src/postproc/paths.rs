@@ -0,0 +1,184 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    AttributeId,
+    AttributeValue,
+    Document,
+    ElementId,
+    FuzzyEq,
+    Path,
+    PathSegment,
+    WriteOptions,
+};
+
+/// Chooses the shortest encoding for each `path` element.
+///
+/// Candidates are:
+///
+/// - absolute and relative segments;
+/// - a mix of them, when each segment uses the shortest form;
+/// - the above with the `H`, `V`, `S` and `T` shorthands, when applicable.
+///
+/// The encoding with the smallest [`approx_byte_size`] under the specified options is kept.
+/// Separators and the compact notation are controlled by `opt` itself,
+/// so they will be the same during the final serialization.
+///
+/// The shape itself is never changed.
+///
+/// Returns the number of bytes saved.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, WriteOptions, postproc};
+///
+/// let mut doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg'><path d='M 100 100 L 110 100 L 110 110'/></svg>").unwrap();
+/// let saved = postproc::optimize_paths(&mut doc, &WriteOptions::default());
+/// assert_eq!(doc.to_string(),
+///            "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    <path d=\"m 100 100 h 10 v 10\"/>\n</svg>\n");
+/// assert_eq!(saved, 10);
+/// ```
+///
+/// [`approx_byte_size`]: ../enum.AttributeValue.html#method.approx_byte_size
+pub fn optimize_paths(doc: &mut Document, opt: &WriteOptions) -> usize {
+    let mut saved = 0;
+    for mut node in doc.root().descendants().filter(|n| n.is_tag_name(ElementId::Path)) {
+        let path = match node.attributes().get_value(AttributeId::D) {
+            Some(&AttributeValue::Path(ref path)) => path.clone(),
+            _ => continue,
+        };
+
+        if path.is_empty() {
+            continue;
+        }
+
+        let mut abs = path.clone();
+        abs.conv_to_absolute();
+
+        let mut short = abs.clone();
+        use_shorthands(&mut short);
+
+        let old_size = AttributeValue::Path(path).approx_byte_size(opt);
+        let mut best = None;
+        let mut best_size = old_size;
+        for candidate in encodings(abs, opt).into_iter().chain(encodings(short, opt)) {
+            let value = AttributeValue::Path(candidate);
+            let size = value.approx_byte_size(opt);
+            if size < best_size {
+                best = Some(value);
+                best_size = size;
+            }
+        }
+
+        if let Some(value) = best {
+            node.set_attribute((AttributeId::D, value));
+            saved += old_size - best_size;
+        }
+    }
+
+    saved
+}
+
+// Returns absolute, relative and mixed variants of an absolute path.
+fn encodings(abs: Path, opt: &WriteOptions) -> Vec<Path> {
+    let mut rel = abs.clone();
+    rel.conv_to_relative();
+
+    let mut mixed = Path::with_capacity(abs.len());
+    for (a, r) in abs.iter().zip(rel.iter()) {
+        let a_size = AttributeValue::Path(Path(vec![*a])).approx_byte_size(opt);
+        let r_size = AttributeValue::Path(Path(vec![*r])).approx_byte_size(opt);
+        mixed.push(if r_size < a_size { *r } else { *a });
+    }
+
+    vec![abs, rel, mixed]
+}
+
+// Replaces segments of an absolute path with shorthands, when they are equivalent.
+fn use_shorthands(path: &mut Path) {
+    // Current point and the start of the current subpath.
+    let (mut px, mut py) = (0.0, 0.0);
+    let (mut mx, mut my) = (0.0, 0.0);
+    // The last control point of the previous cubic or quadratic curve.
+    let mut prev_cubic: Option<(f64, f64)> = None;
+    let mut prev_quad: Option<(f64, f64)> = None;
+
+    for seg in path.iter_mut() {
+        let mut cubic = None;
+        let mut quad = None;
+
+        match *seg {
+            PathSegment::MoveTo { x, y, .. } => {
+                mx = x;
+                my = y;
+            }
+            PathSegment::LineTo { x, y, .. } => {
+                if y.fuzzy_eq(&py) {
+                    *seg = PathSegment::HorizontalLineTo { abs: true, x };
+                } else if x.fuzzy_eq(&px) {
+                    *seg = PathSegment::VerticalLineTo { abs: true, y };
+                }
+            }
+            PathSegment::CurveTo { x1, y1, x2, y2, x, y, .. } => {
+                let (rx, ry) = reflect(prev_cubic, px, py);
+                if x1.fuzzy_eq(&rx) && y1.fuzzy_eq(&ry) {
+                    *seg = PathSegment::SmoothCurveTo { abs: true, x2, y2, x, y };
+                }
+
+                cubic = Some((x2, y2));
+            }
+            PathSegment::SmoothCurveTo { x2, y2, .. } => {
+                cubic = Some((x2, y2));
+            }
+            PathSegment::Quadratic { x1, y1, x, y, .. } => {
+                let (rx, ry) = reflect(prev_quad, px, py);
+                if x1.fuzzy_eq(&rx) && y1.fuzzy_eq(&ry) {
+                    *seg = PathSegment::SmoothQuadratic { abs: true, x, y };
+                }
+
+                quad = Some((x1, y1));
+            }
+            PathSegment::SmoothQuadratic { .. } => {
+                quad = Some(reflect(prev_quad, px, py));
+            }
+            _ => {}
+        }
+
+        match *seg {
+            PathSegment::HorizontalLineTo { x, .. } => {
+                px = x;
+            }
+            PathSegment::VerticalLineTo { y, .. } => {
+                py = y;
+            }
+            PathSegment::ClosePath { .. } => {
+                px = mx;
+                py = my;
+            }
+            _ => {
+                px = seg.x().unwrap();
+                py = seg.y().unwrap();
+            }
+        }
+
+        prev_cubic = cubic;
+        prev_quad = quad;
+    }
+}
+
+// Reflects the previous control point relative to the current point.
+//
+// If there is no previous control point, the current point is used.
+fn reflect(prev: Option<(f64, f64)>, px: f64, py: f64) -> (f64, f64) {
+    match prev {
+        Some((x, y)) => (2.0 * px - x, 2.0 * py - y),
+        None => (px, py),
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use geometry;
+use {
+    AttributeId,
+    Document,
+    ElementId,
+    ElementType,
+    Node,
+    Path,
+    PathSegment,
+};
+
+/// Merges adjacent sibling shapes with identical attributes into a single `path`.
+///
+/// Two shapes are merged when:
+///
+/// - they are direct siblings without any nodes between them;
+/// - all their attributes, including `transform`, are equal,
+///   except the geometry ones, like `d` or `width`, and `id`;
+/// - neither of them is referenced by other elements;
+/// - they don't overlap, including the stroke.
+///
+/// Since the merged shapes don't overlap, the result is rendered the same way
+/// regardless of `fill-rule`, opacity and the painting order.
+///
+/// Shapes that use paint servers, clip paths, masks, filters or markers are never merged,
+/// because they depend on the element's bounding box or segments.
+/// The same goes for shapes with the `pathLength` attribute, children or relative units.
+///
+/// Shapes are converted into a `path` first. The merged element keeps the `id`
+/// of the first shape, if any.
+///
+/// Returns the number of removed elements.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, postproc};
+///
+/// let mut doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg'>
+///         <path fill='#ff0000' d='M 10 10 L 20 10 L 20 20 Z'/>
+///         <rect fill='#ff0000' x='30' y='10' width='10' height='10'/>
+///         <rect fill='#ff0000' x='35' y='15' width='10' height='10'/>
+///      </svg>").unwrap();
+/// assert_eq!(postproc::merge_sibling_shapes(&mut doc), 1);
+/// assert_eq!(doc.to_string(),
+/// "<svg xmlns=\"http://www.w3.org/2000/svg\">
+///     <path d=\"M 10 10 L 20 10 L 20 20 Z M 30 10 L 40 10 L 40 20 L 30 20 Z\" fill=\"#ff0000\"/>
+///     <rect fill=\"#ff0000\" height=\"10\" width=\"10\" x=\"35\" y=\"15\"/>
+/// </svg>
+/// ");
+/// ```
+pub fn merge_sibling_shapes(doc: &mut Document) -> usize {
+    let mut count = 0;
+
+    let shapes: Vec<Node> = doc.root().descendants().filter(|n| n.is_shape()).collect();
+    for mut node in shapes {
+        // Already merged.
+        if node.parent().is_none() {
+            continue;
+        }
+
+        if !is_mergeable(&node) {
+            continue;
+        }
+
+        while let Some(next) = node.next_sibling() {
+            if !next.is_shape() || !is_mergeable(&next) || !has_same_attributes(&node, &next) {
+                break;
+            }
+
+            let path1 = match geometry::shape_to_path(&node) { Some(p) => p, None => break };
+            let mut path2 = match geometry::shape_to_path(&next) { Some(p) => p, None => break };
+            if path2.is_empty() {
+                break;
+            }
+
+            let is_overlapping = match (geometry::stroke_bounding_box(&node),
+                                        geometry::stroke_bounding_box(&next)) {
+                (Some(r1), Some(r2)) => {
+                       r1.x <= r2.x + r2.width && r2.x <= r1.x + r1.width
+                    && r1.y <= r2.y + r2.height && r2.y <= r1.y + r1.height
+                }
+                _ => true,
+            };
+
+            if is_overlapping {
+                break;
+            }
+
+            // The first `moveto` of a path is always absolute.
+            if let PathSegment::MoveTo { ref mut abs, .. } = path2[0] {
+                *abs = true;
+            }
+
+            let mut path = path1;
+            path.extend_from_slice(&path2);
+            set_path(&mut node, path);
+
+            doc.remove_node(next);
+            count += 1;
+        }
+    }
+
+    count
+}
+
+fn is_mergeable(node: &Node) -> bool {
+    if node.has_children() || node.is_used() || node.has_attribute(AttributeId::PathLength) {
+        return false;
+    }
+
+    // Links to clip paths, masks, filters, markers and paint servers.
+    if node.attributes().iter().any(|a| a.value.is_link_container()) {
+        return false;
+    }
+
+    let style = node.computed_style();
+    let has_links = style.values().any(|v| v.is_link_container());
+    !has_links
+}
+
+fn has_same_attributes(node1: &Node, node2: &Node) -> bool {
+    let attrs1 = node1.attributes();
+    let attrs2 = node2.attributes();
+
+    let is_geometry = |node: &Node, id: Option<AttributeId>| {
+        match id {
+            Some(AttributeId::Id) => true,
+            Some(id) => geometry_attributes(node).contains(&id),
+            None => false,
+        }
+    };
+
+    let len1 = attrs1.iter().filter(|a| !is_geometry(node1, a.id())).count();
+    let len2 = attrs2.iter().filter(|a| !is_geometry(node2, a.id())).count();
+    if len1 != len2 {
+        return false;
+    }
+
+    attrs1.iter()
+        .filter(|a| !is_geometry(node1, a.id()))
+        .all(|a| attrs2.get(a.name.as_ref()) == Some(a))
+}
+
+fn geometry_attributes(node: &Node) -> &'static [AttributeId] {
+    match node.tag_id() {
+        Some(ElementId::Path) => &[AttributeId::D],
+        Some(ElementId::Rect) => &[AttributeId::X, AttributeId::Y, AttributeId::Width,
+                                   AttributeId::Height, AttributeId::Rx, AttributeId::Ry],
+        Some(ElementId::Circle) => &[AttributeId::Cx, AttributeId::Cy, AttributeId::R],
+        Some(ElementId::Ellipse) => &[AttributeId::Cx, AttributeId::Cy,
+                                      AttributeId::Rx, AttributeId::Ry],
+        Some(ElementId::Line) => &[AttributeId::X1, AttributeId::Y1,
+                                   AttributeId::X2, AttributeId::Y2],
+        Some(ElementId::Polyline) | Some(ElementId::Polygon) => &[AttributeId::Points],
+        _ => &[],
+    }
+}
+
+fn set_path(node: &mut Node, path: Path) {
+    if !node.is_tag_name(ElementId::Path) {
+        for &id in geometry_attributes(node) {
+            node.remove_attribute(id);
+        }
+
+        node.set_tag_name(ElementId::Path);
+    }
+
+    node.set_attribute((AttributeId::D, path));
+}
@@ -0,0 +1,77 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    AttributeId,
+    AttributeValue,
+    FilterSvgAttrsMut,
+    Length,
+    LengthExt,
+    LengthUnit,
+    Node,
+};
+
+/// Converts all absolute-unit lengths (`pt`, `mm`, `cm`, `in`, `pc`) into user units.
+///
+/// Relative units (`em`, `ex`, `%`) are left untouched.
+///
+/// Processes `root` and all its descendants.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{Document, postproc};
+///
+/// let doc = Document::from_str(
+///     "<svg xmlns='http://www.w3.org/2000/svg' width='1in' height='50%'/>").unwrap();
+/// postproc::normalize_units(&doc.root(), 96.0);
+/// assert_eq!(doc.to_string(), "<svg xmlns=\"http://www.w3.org/2000/svg\" height=\"50%\" width=\"96\"/>\n");
+/// ```
+pub fn normalize_units(root: &Node, dpi: f64) {
+    normalize_units_except(root, dpi, &[]);
+}
+
+/// Converts all absolute-unit lengths into user units,
+/// except the ones in the specified attributes.
+///
+/// See [`normalize_units`] for details.
+///
+/// [`normalize_units`]: fn.normalize_units.html
+pub fn normalize_units_except(root: &Node, dpi: f64, skip: &[AttributeId]) {
+    for mut node in root.descendants() {
+        let mut attrs = node.attributes_mut();
+        for (id, attr) in attrs.iter_mut().svg() {
+            if skip.contains(&id) {
+                continue;
+            }
+
+            match attr.value {
+                AttributeValue::Length(ref mut len) => {
+                    convert_length(len, dpi);
+                }
+                AttributeValue::LengthList(ref mut list) => {
+                    for len in list.iter_mut() {
+                        convert_length(len, dpi);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn convert_length(len: &mut Length, dpi: f64) {
+    match len.unit {
+        LengthUnit::In | LengthUnit::Cm | LengthUnit::Mm | LengthUnit::Pt | LengthUnit::Pc => {
+            if let Some(n) = len.to_px(dpi) {
+                *len = Length::new_number(n);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,418 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// `serde` support.
+//
+// `svgtypes` types don't implement `serde` traits, so all values are converted
+// into mirror types first. Links are stored as element IDs and are resolved
+// after the whole tree is deserialized.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error as DeError;
+
+use {
+    Align,
+    Angle,
+    AngleUnit,
+    AspectRatio,
+    Attribute,
+    AttributeId,
+    AttributeQName,
+    AttributeValue,
+    Color,
+    Document,
+    ElementId,
+    Length,
+    LengthUnit,
+    Node,
+    NodeType,
+    PaintFallback,
+    Path,
+    PathSegment,
+    QName,
+    Transform,
+    ViewBox,
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SNode {
+    Element {
+        tag: SName,
+        id: String,
+        attributes: Vec<SAttribute>,
+        children: Vec<SNode>,
+    },
+    Text(String),
+    Comment(String),
+    ProcessingInstruction(String),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SName {
+    /// An SVG name.
+    Id(String),
+    /// An unknown name.
+    Name(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SAttribute {
+    name: SName,
+    value: SValue,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SValue {
+    None,
+    Inherit,
+    CurrentColor,
+    AspectRatio { defer: bool, align: SAlign, slice: bool },
+    Color(u8, u8, u8),
+    FuncLink(String),
+    Paint(String, Option<SPaintFallback>),
+    Length(f64, SLengthUnit),
+    LengthList(Vec<(f64, SLengthUnit)>),
+    Angle(f64, SAngleUnit),
+    Link(String),
+    Number(f64),
+    NumberList(Vec<f64>),
+    Path(Vec<SPathSegment>),
+    Points(Vec<(f64, f64)>),
+    Transform(f64, f64, f64, f64, f64, f64),
+    ViewBox(f64, f64, f64, f64),
+    String(String),
+}
+
+#[derive(Serialize, Deserialize)]
+enum SPaintFallback {
+    None,
+    CurrentColor,
+    Color(u8, u8, u8),
+}
+
+macro_rules! mirror_enum {
+    ($name:ident, $orig:ident, $($variant:ident),+) => (
+        #[derive(Clone, Copy, Serialize, Deserialize)]
+        enum $name {
+            $($variant),+
+        }
+
+        impl From<$orig> for $name {
+            fn from(v: $orig) -> Self {
+                match v {
+                    $($orig::$variant => $name::$variant),+
+                }
+            }
+        }
+
+        impl From<$name> for $orig {
+            fn from(v: $name) -> Self {
+                match v {
+                    $($name::$variant => $orig::$variant),+
+                }
+            }
+        }
+    )
+}
+
+mirror_enum!(SAlign, Align,
+    None, XMinYMin, XMidYMin, XMaxYMin, XMinYMid, XMidYMid, XMaxYMid, XMinYMax, XMidYMax, XMaxYMax);
+mirror_enum!(SLengthUnit, LengthUnit, None, Em, Ex, Px, In, Cm, Mm, Pt, Pc, Percent);
+mirror_enum!(SAngleUnit, AngleUnit, Degrees, Gradians, Radians);
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum SPathSegment {
+    MoveTo { abs: bool, x: f64, y: f64 },
+    LineTo { abs: bool, x: f64, y: f64 },
+    HorizontalLineTo { abs: bool, x: f64 },
+    VerticalLineTo { abs: bool, y: f64 },
+    CurveTo { abs: bool, x1: f64, y1: f64, x2: f64, y2: f64, x: f64, y: f64 },
+    SmoothCurveTo { abs: bool, x2: f64, y2: f64, x: f64, y: f64 },
+    Quadratic { abs: bool, x1: f64, y1: f64, x: f64, y: f64 },
+    SmoothQuadratic { abs: bool, x: f64, y: f64 },
+    EllipticalArc {
+        abs: bool, rx: f64, ry: f64, x_axis_rotation: f64,
+        large_arc: bool, sweep: bool, x: f64, y: f64,
+    },
+    ClosePath { abs: bool },
+}
+
+macro_rules! convert_segment {
+    ($from:ident, $to:ident, $seg:expr) => (
+        match $seg {
+            $from::MoveTo { abs, x, y } => $to::MoveTo { abs, x, y },
+            $from::LineTo { abs, x, y } => $to::LineTo { abs, x, y },
+            $from::HorizontalLineTo { abs, x } => $to::HorizontalLineTo { abs, x },
+            $from::VerticalLineTo { abs, y } => $to::VerticalLineTo { abs, y },
+            $from::CurveTo { abs, x1, y1, x2, y2, x, y } => {
+                $to::CurveTo { abs, x1, y1, x2, y2, x, y }
+            }
+            $from::SmoothCurveTo { abs, x2, y2, x, y } => $to::SmoothCurveTo { abs, x2, y2, x, y },
+            $from::Quadratic { abs, x1, y1, x, y } => $to::Quadratic { abs, x1, y1, x, y },
+            $from::SmoothQuadratic { abs, x, y } => $to::SmoothQuadratic { abs, x, y },
+            $from::EllipticalArc { abs, rx, ry, x_axis_rotation, large_arc, sweep, x, y } => {
+                $to::EllipticalArc { abs, rx, ry, x_axis_rotation, large_arc, sweep, x, y }
+            }
+            $from::ClosePath { abs } => $to::ClosePath { abs },
+        }
+    )
+}
+
+impl Serialize for Document {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.root().serialize(serializer)
+    }
+}
+
+/// The root node is serialized as a list of its children.
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_root() {
+            let children: Vec<SNode> = self.children().map(|n| to_snode(&n)).collect();
+            children.serialize(serializer)
+        } else {
+            to_snode(self).serialize(serializer)
+        }
+    }
+}
+
+impl Serialize for Attribute {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        to_sattribute(self).serialize(serializer)
+    }
+}
+
+impl Serialize for AttributeValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        to_svalue(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let nodes: Vec<SNode> = Deserialize::deserialize(deserializer)?;
+
+        let mut doc = Document::new();
+        let mut links = Vec::new();
+        let mut root = doc.root();
+        for node in nodes {
+            from_snode(&mut doc, &mut root, node, &mut links)?;
+        }
+
+        let mut ids = HashMap::new();
+        for node in doc.root().descendants() {
+            if node.has_id() {
+                let id = node.id().clone();
+                ids.insert(id, node);
+            }
+        }
+
+        for (mut node, name, value) in links {
+            let link = {
+                let id = match value {
+                    SValue::Link(ref id) | SValue::FuncLink(ref id) | SValue::Paint(ref id, _) => id,
+                    _ => unreachable!(),
+                };
+
+                match ids.get(id) {
+                    Some(link) => link.clone(),
+                    None => return Err(D::Error::custom(format!("unresolved link '#{}'", id))),
+                }
+            };
+
+            let value = match value {
+                SValue::Link(_) => AttributeValue::Link(link),
+                SValue::FuncLink(_) => AttributeValue::FuncLink(link),
+                SValue::Paint(_, fallback) => AttributeValue::Paint(link, fallback.map(from_fallback)),
+                _ => unreachable!(),
+            };
+
+            if let Err(e) = node.set_attribute_checked(Attribute::new(name.as_ref(), value)) {
+                return Err(D::Error::custom(e));
+            }
+        }
+
+        Ok(doc)
+    }
+}
+
+fn to_snode(node: &Node) -> SNode {
+    match node.node_type() {
+        NodeType::Element => {
+            SNode::Element {
+                tag: match *node.tag_name() {
+                    QName::Id(id) => SName::Id(id.as_str().to_string()),
+                    QName::Name(ref name) => SName::Name(name.clone()),
+                },
+                id: node.id().clone(),
+                attributes: node.attributes().iter().map(to_sattribute).collect(),
+                children: node.children().map(|n| to_snode(&n)).collect(),
+            }
+        }
+        NodeType::Text => SNode::Text(node.text().clone()),
+        NodeType::Comment => SNode::Comment(node.text().clone()),
+        NodeType::ProcessingInstruction => SNode::ProcessingInstruction(node.text().clone()),
+        NodeType::Root => unreachable!(),
+    }
+}
+
+fn to_sattribute(attr: &Attribute) -> SAttribute {
+    SAttribute {
+        name: match attr.name {
+            QName::Id(id) => SName::Id(id.as_str().to_string()),
+            QName::Name(ref name) => SName::Name(name.clone()),
+        },
+        value: to_svalue(&attr.value),
+    }
+}
+
+fn to_svalue(value: &AttributeValue) -> SValue {
+    match *value {
+        AttributeValue::None => SValue::None,
+        AttributeValue::Inherit => SValue::Inherit,
+        AttributeValue::CurrentColor => SValue::CurrentColor,
+        AttributeValue::AspectRatio(ratio) => {
+            SValue::AspectRatio { defer: ratio.defer, align: ratio.align.into(), slice: ratio.slice }
+        }
+        AttributeValue::Color(c) => SValue::Color(c.red, c.green, c.blue),
+        AttributeValue::FuncLink(ref link) => SValue::FuncLink(link.id().clone()),
+        AttributeValue::Paint(ref link, fallback) => {
+            let fallback = fallback.map(|v| match v {
+                PaintFallback::None => SPaintFallback::None,
+                PaintFallback::CurrentColor => SPaintFallback::CurrentColor,
+                PaintFallback::Color(c) => SPaintFallback::Color(c.red, c.green, c.blue),
+            });
+
+            SValue::Paint(link.id().clone(), fallback)
+        }
+        AttributeValue::Length(len) => SValue::Length(len.num, len.unit.into()),
+        AttributeValue::LengthList(ref list) => {
+            SValue::LengthList(list.iter().map(|len| (len.num, len.unit.into())).collect())
+        }
+        AttributeValue::Angle(angle) => SValue::Angle(angle.num, angle.unit.into()),
+        AttributeValue::Link(ref link) => SValue::Link(link.id().clone()),
+        AttributeValue::Number(n) => SValue::Number(n),
+        AttributeValue::NumberList(ref list) => SValue::NumberList(list.to_vec()),
+        AttributeValue::Path(ref path) => {
+            SValue::Path(path.iter().map(|seg| convert_segment!(PathSegment, SPathSegment, *seg)).collect())
+        }
+        AttributeValue::Points(ref points) => SValue::Points(points.to_vec()),
+        AttributeValue::Transform(ts) => SValue::Transform(ts.a, ts.b, ts.c, ts.d, ts.e, ts.f),
+        AttributeValue::ViewBox(vb) => SValue::ViewBox(vb.x, vb.y, vb.w, vb.h),
+        AttributeValue::String(ref s) => SValue::String(s.clone()),
+    }
+}
+
+fn from_snode<E: DeError>(
+    doc: &mut Document,
+    parent: &mut Node,
+    node: SNode,
+    links: &mut Vec<(Node, AttributeQName, SValue)>,
+) -> Result<(), E> {
+    match node {
+        SNode::Element { tag, id, attributes, children } => {
+            let mut elem = match tag {
+                SName::Id(name) => {
+                    match ElementId::from_str(&name) {
+                        Some(eid) => doc.create_element(eid),
+                        None => return Err(E::custom(format!("unknown element '{}'", name))),
+                    }
+                }
+                SName::Name(name) => {
+                    if name.is_empty() {
+                        return Err(E::custom("empty element name"));
+                    }
+
+                    doc.create_element(name.as_str())
+                }
+            };
+
+            elem.set_id(id);
+
+            for attr in attributes {
+                let name = match attr.name {
+                    SName::Id(name) => {
+                        match AttributeId::from_str(&name) {
+                            Some(aid) => QName::Id(aid),
+                            None => return Err(E::custom(format!("unknown attribute '{}'", name))),
+                        }
+                    }
+                    SName::Name(name) => QName::Name(name),
+                };
+
+                match attr.value {
+                    SValue::Link(_) | SValue::FuncLink(_) | SValue::Paint(..) => {
+                        links.push((elem.clone(), name, attr.value));
+                    }
+                    value => {
+                        elem.set_attribute(Attribute::new(name.as_ref(), from_svalue(value)));
+                    }
+                }
+            }
+
+            parent.append(elem.clone());
+
+            for child in children {
+                from_snode(doc, &mut elem, child, links)?;
+            }
+        }
+        SNode::Text(text) => {
+            parent.append(doc.create_node(NodeType::Text, text));
+        }
+        SNode::Comment(text) => {
+            parent.append(doc.create_node(NodeType::Comment, text));
+        }
+        SNode::ProcessingInstruction(text) => {
+            parent.append(doc.create_node(NodeType::ProcessingInstruction, text));
+        }
+    }
+
+    Ok(())
+}
+
+// Links are resolved separately.
+fn from_svalue(value: SValue) -> AttributeValue {
+    match value {
+        SValue::None => AttributeValue::None,
+        SValue::Inherit => AttributeValue::Inherit,
+        SValue::CurrentColor => AttributeValue::CurrentColor,
+        SValue::AspectRatio { defer, align, slice } => {
+            AttributeValue::AspectRatio(AspectRatio { defer, align: align.into(), slice })
+        }
+        SValue::Color(r, g, b) => AttributeValue::Color(Color::new(r, g, b)),
+        SValue::Length(num, unit) => AttributeValue::Length(Length::new(num, unit.into())),
+        SValue::LengthList(list) => {
+            let list: Vec<Length> = list.into_iter().map(|(num, unit)| Length::new(num, unit.into())).collect();
+            AttributeValue::LengthList(list.into())
+        }
+        SValue::Angle(num, unit) => AttributeValue::Angle(Angle::new(num, unit.into())),
+        SValue::Number(n) => AttributeValue::Number(n),
+        SValue::NumberList(list) => AttributeValue::NumberList(list.into()),
+        SValue::Path(segments) => {
+            AttributeValue::Path(Path(segments.into_iter()
+                .map(|seg| convert_segment!(SPathSegment, PathSegment, seg)).collect()))
+        }
+        SValue::Points(points) => AttributeValue::Points(points.into()),
+        SValue::Transform(a, b, c, d, e, f) => AttributeValue::Transform(Transform::new(a, b, c, d, e, f)),
+        SValue::ViewBox(x, y, w, h) => AttributeValue::ViewBox(ViewBox::new(x, y, w, h)),
+        SValue::String(s) => AttributeValue::String(s),
+        SValue::Link(_) | SValue::FuncLink(_) | SValue::Paint(..) => unreachable!(),
+    }
+}
+
+fn from_fallback(v: SPaintFallback) -> PaintFallback {
+    match v {
+        SPaintFallback::None => PaintFallback::None,
+        SPaintFallback::CurrentColor => PaintFallback::CurrentColor,
+        SPaintFallback::Color(r, g, b) => PaintFallback::Color(Color::new(r, g, b)),
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for testing code that uses *svgdom*.
+//!
+//! Available only with the `testing` feature.
+
+use {
+    Document,
+    DomChange,
+    ParseOptions,
+    WriteBuffer,
+    WriteOptions,
+};
+
+/// Asserts that two strings are equal and prints them as is on failure.
+///
+/// Unlike `assert_eq!`, which prints strings using `Debug`,
+/// multiline SVG data stays readable.
+#[macro_export]
+macro_rules! assert_eq_text {
+    ($left:expr, $right:expr) => ({
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if *left_val != *right_val {
+                    panic!("assertion failed: `(left == right)`\n\
+                            left:\n{}\nright:\n{}", left_val, right_val);
+                }
+            }
+        }
+    })
+}
+
+/// Returns `true` if documents are semantically equal.
+///
+/// See [`Document::diff`] for details.
+///
+/// [`Document::diff`]: ../struct.Document.html#method.diff
+pub fn is_equivalent(doc1: &Document, doc2: &Document) -> bool {
+    doc1.diff(doc2).is_empty()
+}
+
+/// Asserts that two documents are semantically equal.
+///
+/// # Panics
+///
+/// Panics with a list of differences if documents are not equal.
+pub fn assert_equivalent(doc1: &Document, doc2: &Document) {
+    let changes = doc1.diff(doc2);
+    if !changes.is_empty() {
+        panic!("documents are not equal:\n{}", changes_to_string(&changes));
+    }
+}
+
+/// Asserts that a document will stay the same after writing and parsing it again.
+///
+/// Uses the default options.
+///
+/// # Examples
+///
+/// ```
+/// svgdom::testing::assert_round_trip(
+///     "<svg xmlns='http://www.w3.org/2000/svg'><rect width='10mm'/></svg>");
+/// ```
+///
+/// # Panics
+///
+/// Panics if the text can't be parsed or if the resaved document is not equal to the original.
+pub fn assert_round_trip(text: &str) {
+    assert_round_trip_with_opt(text, &ParseOptions::default(), &WriteOptions::default());
+}
+
+/// Asserts that a document will stay the same after writing and parsing it again
+/// using the specified options.
+///
+/// See [`assert_round_trip`] for details.
+///
+/// [`assert_round_trip`]: fn.assert_round_trip.html
+pub fn assert_round_trip_with_opt(text: &str, parse_opt: &ParseOptions, write_opt: &WriteOptions) {
+    let doc1 = match Document::from_str_with_opt(text, parse_opt) {
+        Ok(doc) => doc,
+        Err(e) => panic!("failed to parse the original document: {}", e),
+    };
+
+    let text2 = doc1.with_write_opt(write_opt).to_string();
+    let doc2 = match Document::from_str_with_opt(&text2, parse_opt) {
+        Ok(doc) => doc,
+        Err(e) => panic!("failed to parse the resaved document: {}\n{}", e, text2),
+    };
+
+    let changes = doc1.diff(&doc2);
+    if !changes.is_empty() {
+        panic!("the resaved document is not equal to the original:\n{}\nresaved:\n{}",
+               changes_to_string(&changes), text2);
+    }
+}
+
+fn changes_to_string(changes: &[DomChange]) -> String {
+    let mut s = String::new();
+    for change in changes {
+        s.push_str(&format!("{:?}\n", change));
+    }
+
+    s
+}
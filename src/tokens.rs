@@ -0,0 +1,186 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use svgtypes::{
+    PathParser,
+    Stream,
+    TransformListParser,
+    TransformListToken,
+};
+
+use {
+    FuzzyEq,
+    PathSegment,
+    TransformItem,
+    ValueError,
+};
+
+/// A streaming tokenizer of the [path data].
+///
+/// Yields segments one by one, without collecting them into a [`Path`].
+/// Stops after the first error.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{PathTokens, PathSegment};
+///
+/// let mut tokens = PathTokens::new("M 10 20 l 30 0 z");
+/// assert_eq!(tokens.next().unwrap().unwrap(), PathSegment::MoveTo { abs: true, x: 10.0, y: 20.0 });
+/// assert_eq!(tokens.next().unwrap().unwrap(), PathSegment::LineTo { abs: false, x: 30.0, y: 0.0 });
+/// assert_eq!(tokens.next().unwrap().unwrap(), PathSegment::ClosePath { abs: false });
+/// assert!(tokens.next().is_none());
+/// ```
+///
+/// [path data]: https://www.w3.org/TR/SVG11/paths.html#PathData
+/// [`Path`]: struct.Path.html
+#[derive(Clone, Copy, Debug)]
+pub struct PathTokens<'a>(PathParser<'a>);
+
+impl<'a> PathTokens<'a> {
+    /// Creates a new tokenizer.
+    pub fn new(text: &'a str) -> Self {
+        PathTokens(PathParser::from(text))
+    }
+}
+
+impl<'a> Iterator for PathTokens<'a> {
+    type Item = Result<PathSegment, ValueError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+/// A streaming tokenizer of the [`<list-of-points>`].
+///
+/// Yields coordinate pairs one by one. Unlike [`Points`] parsing,
+/// an odd number of coordinates is reported as an error.
+/// Stops after the first error.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::PointsTokens;
+///
+/// let mut tokens = PointsTokens::new("10,20 30 40 50");
+/// assert_eq!(tokens.next().unwrap().unwrap(), (10.0, 20.0));
+/// assert_eq!(tokens.next().unwrap().unwrap(), (30.0, 40.0));
+/// assert!(tokens.next().unwrap().is_err());
+/// assert!(tokens.next().is_none());
+/// ```
+///
+/// [`<list-of-points>`]: https://www.w3.org/TR/SVG11/shapes.html#PointsBNF
+/// [`Points`]: struct.Points.html
+#[derive(Clone, Copy, Debug)]
+pub struct PointsTokens<'a>(Stream<'a>);
+
+impl<'a> PointsTokens<'a> {
+    /// Creates a new tokenizer.
+    pub fn new(text: &'a str) -> Self {
+        PointsTokens(Stream::from(text))
+    }
+}
+
+impl<'a> Iterator for PointsTokens<'a> {
+    type Item = Result<(f64, f64), ValueError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.skip_spaces();
+        if self.0.at_end() {
+            return None;
+        }
+
+        let res = parse_point(&mut self.0);
+        if res.is_err() {
+            self.0.jump_to_end();
+        }
+
+        Some(res)
+    }
+}
+
+fn parse_point(s: &mut Stream) -> Result<(f64, f64), ValueError> {
+    let x = s.parse_list_number()?;
+    let y = s.parse_list_number()?;
+    Ok((x, y))
+}
+
+/// A streaming tokenizer of the [`<transform-list>`].
+///
+/// Yields operations one by one, the same way as they are stored in the [`TransformList`].
+/// Stops after the first error.
+///
+/// # Examples
+///
+/// ```
+/// use svgdom::{TransformTokens, TransformItem};
+///
+/// let mut tokens = TransformTokens::new("translate(10 20) rotate(45 5 5)");
+/// assert_eq!(tokens.next().unwrap().unwrap(), TransformItem::Translate { tx: 10.0, ty: 20.0 });
+/// assert_eq!(tokens.next().unwrap().unwrap(), TransformItem::Rotate { angle: 45.0, cx: 5.0, cy: 5.0 });
+/// assert!(tokens.next().is_none());
+/// ```
+///
+/// [`<transform-list>`]: https://www.w3.org/TR/SVG11/coords.html#TransformAttribute
+/// [`TransformList`]: struct.TransformList.html
+#[derive(Clone, Copy, Debug)]
+pub struct TransformTokens<'a>(TransformListParser<'a>);
+
+impl<'a> TransformTokens<'a> {
+    /// Creates a new tokenizer.
+    pub fn new(text: &'a str) -> Self {
+        TransformTokens(TransformListParser::from(text))
+    }
+}
+
+impl<'a> Iterator for TransformTokens<'a> {
+    type Item = Result<TransformItem, ValueError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = match self.0.next() {
+            Some(Ok(token)) => token,
+            Some(Err(e)) => return Some(Err(e)),
+            None => return None,
+        };
+
+        let item = match token {
+            TransformListToken::Matrix { a, b, c, d, e, f } => {
+                TransformItem::Matrix { a, b, c, d, e, f }
+            }
+            TransformListToken::Translate { tx, ty } => {
+                // The parser splits `rotate(a x y)` into `translate(x y) rotate(a) translate(-x -y)`,
+                // so we have to look ahead to join them back.
+                let mut p = self.0;
+                match (p.next(), p.next()) {
+                    (Some(Ok(TransformListToken::Rotate { angle })),
+                     Some(Ok(TransformListToken::Translate { tx: tx2, ty: ty2 })))
+                        if tx.fuzzy_eq(&-tx2) && ty.fuzzy_eq(&-ty2) => {
+                        self.0 = p;
+                        TransformItem::Rotate { angle, cx: tx, cy: ty }
+                    }
+                    _ => TransformItem::Translate { tx, ty },
+                }
+            }
+            TransformListToken::Scale { sx, sy } => {
+                TransformItem::Scale { sx, sy }
+            }
+            TransformListToken::Rotate { angle } => {
+                TransformItem::Rotate { angle, cx: 0.0, cy: 0.0 }
+            }
+            TransformListToken::SkewX { angle } => {
+                TransformItem::SkewX { angle }
+            }
+            TransformListToken::SkewY { angle } => {
+                TransformItem::SkewY { angle }
+            }
+        };
+
+        Some(Ok(item))
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::f64::consts::PI;
+
+use {
+    FuzzyEq,
+    FuzzyZero,
+    Transform,
+    TransformItem,
+    TransformList,
+};
+
+/// Additional methods for the [`Transform`].
+///
+/// [`Transform`]: struct.Transform.html
+pub trait TransformExt {
+    /// Decomposes a matrix into a list of operations.
+    ///
+    /// The result has the `translate rotate skewX scale` order.
+    /// Operations that do nothing will be skipped.
+    ///
+    /// Since the original operations are lost after parsing,
+    /// the result may differ from the authored one, but will produce the same matrix.
+    ///
+    /// A degenerate matrix will be returned as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Transform, TransformExt, TransformItem};
+    ///
+    /// let ts = Transform::new(2.0, 0.0, 0.0, 3.0, 10.0, 20.0);
+    /// let list = ts.as_list();
+    /// assert_eq!(list[0], TransformItem::Translate { tx: 10.0, ty: 20.0 });
+    /// assert_eq!(list[1], TransformItem::Scale { sx: 2.0, sy: 3.0 });
+    /// ```
+    fn as_list(&self) -> TransformList;
+
+    /// Returns the product of two matrices: `self * other`.
+    ///
+    /// The result is the same as the `transform="self other"` attribute,
+    /// so `other` is applied first.
+    ///
+    /// Transforms are stored in `svgtypes`, so the `Mul` trait cannot be implemented here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Transform, TransformExt};
+    ///
+    /// let translate = Transform::new_translate(10.0, 20.0);
+    /// let scale = Transform::new_scale(2.0, 2.0);
+    /// assert_eq!(translate.multiply(&scale), Transform::new(2.0, 0.0, 0.0, 2.0, 10.0, 20.0));
+    /// assert_eq!(scale.multiply(&translate), Transform::new(2.0, 0.0, 0.0, 2.0, 20.0, 40.0));
+    /// ```
+    fn multiply(&self, other: &Transform) -> Transform;
+
+    /// Constructs a rotation around the `cx`, `cy` point.
+    ///
+    /// The same as `rotate(angle cx cy)` or `translate(cx cy) rotate(angle) translate(-cx -cy)`.
+    /// The angle is in degrees.
+    ///
+    /// When [`simplify_rotations`] is enabled, such matrices are written
+    /// in the `rotate(angle cx cy)` form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Transform, TransformExt};
+    ///
+    /// let ts = Transform::rotate_around(90.0, 10.0, 0.0);
+    /// let (x, y) = ts.apply(10.0, 10.0);
+    /// assert_eq!((x.round(), y.round()), (0.0, 0.0));
+    /// ```
+    ///
+    /// [`simplify_rotations`]: struct.WriteOptions.html#structfield.simplify_rotations
+    fn rotate_around(angle: f64, cx: f64, cy: f64) -> Self where Self: Sized;
+
+    /// Applies a transform origin to the transform.
+    ///
+    /// Returns `translate(ox oy) self translate(-ox -oy)`, which is a transform
+    /// that doesn't need an origin to produce the same result.
+    ///
+    /// Use `with_origin(-ox, -oy)` to do the opposite: rewrite a transform so it produces
+    /// the same result when applied with the `ox`, `oy` origin. This is useful when
+    /// an editor rotates or scales an element around a pivot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Transform, TransformExt};
+    ///
+    /// let scale = Transform::new_scale(2.0, 2.0);
+    /// let ts = scale.with_origin(10.0, 20.0);
+    /// assert_eq!(ts, Transform::new(2.0, 0.0, 0.0, 2.0, -10.0, -20.0));
+    /// assert_eq!(ts.with_origin(-10.0, -20.0), scale);
+    /// ```
+    fn with_origin(&self, ox: f64, oy: f64) -> Transform;
+}
+
+impl TransformExt for Transform {
+    fn as_list(&self) -> TransformList {
+        let sx = (self.a * self.a + self.b * self.b).sqrt();
+        let sy = (self.a * self.d - self.b * self.c) / sx;
+        if sx.is_fuzzy_zero() || sy.is_fuzzy_zero() || !sy.is_finite() {
+            return TransformList::from(*self);
+        }
+
+        let angle = self.b.atan2(self.a) * 180.0 / PI;
+        let skew = ((self.a * self.c + self.b * self.d) / (sx * sy)).atan() * 180.0 / PI;
+
+        let mut items = Vec::new();
+
+        if !(self.e.is_fuzzy_zero() && self.f.is_fuzzy_zero()) {
+            items.push(TransformItem::Translate { tx: self.e, ty: self.f });
+        }
+
+        if !is_zero(angle) {
+            items.push(TransformItem::Rotate { angle, cx: 0.0, cy: 0.0 });
+        }
+
+        if !is_zero(skew) {
+            items.push(TransformItem::SkewX { angle: skew });
+        }
+
+        if !(is_zero(sx - 1.0) && is_zero(sy - 1.0)) {
+            items.push(TransformItem::Scale { sx, sy });
+        }
+
+        TransformList(items)
+    }
+
+    fn multiply(&self, other: &Transform) -> Transform {
+        let mut ts = *self;
+        ts.append(other);
+        ts
+    }
+
+    fn rotate_around(angle: f64, cx: f64, cy: f64) -> Transform {
+        TransformItem::Rotate { angle, cx, cy }.to_transform()
+    }
+
+    fn with_origin(&self, ox: f64, oy: f64) -> Transform {
+        let mut ts = Transform::new_translate(ox, oy);
+        ts.append(self);
+        ts.append(&Transform::new_translate(-ox, -oy));
+        ts
+    }
+}
+
+/// Returns a `rotate(angle cx cy)` operation if the matrix is a rotation around a point.
+pub(crate) fn to_rotate_around(ts: &Transform) -> Option<TransformItem> {
+    // A rotation around the origin is already handled by `svgtypes`.
+    if !ts.has_translate() {
+        return None;
+    }
+
+    let angle = ts.b.atan2(ts.a);
+    let (sin, cos) = angle.sin_cos();
+    let is_rotate = ts.a.fuzzy_eq(&cos) && ts.b.fuzzy_eq(&sin)
+                 && ts.c.fuzzy_eq(&-sin) && ts.d.fuzzy_eq(&cos);
+    if !is_rotate || is_zero(angle) {
+        return None;
+    }
+
+    // Solve `(I - R) * c = t` for the center.
+    let k = 1.0 - cos;
+    let det = k * k + sin * sin;
+    let cx = (k * ts.e - sin * ts.f) / det;
+    let cy = (sin * ts.e + k * ts.f) / det;
+
+    Some(TransformItem::Rotate { angle: angle * 180.0 / PI, cx, cy })
+}
+
+// Decomposed values are calculated, so we have to use an absolute tolerance.
+fn is_zero(n: f64) -> bool {
+    n.abs() < 1e-10
+}
@@ -0,0 +1,199 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::f64::consts::PI;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+use {
+    Transform,
+    TransformTokens,
+    ValueError,
+    ValueWriteBuffer,
+    ValueWriteOptions,
+};
+
+/// A single operation of the [`TransformList`].
+///
+/// All angles are in degrees.
+///
+/// [`TransformList`]: struct.TransformList.html
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[allow(missing_docs)]
+pub enum TransformItem {
+    Matrix {
+        a: f64,
+        b: f64,
+        c: f64,
+        d: f64,
+        e: f64,
+        f: f64,
+    },
+    Translate {
+        tx: f64,
+        ty: f64,
+    },
+    Scale {
+        sx: f64,
+        sy: f64,
+    },
+    /// A rotation around the `cx`, `cy` point.
+    Rotate {
+        angle: f64,
+        cx: f64,
+        cy: f64,
+    },
+    SkewX {
+        angle: f64,
+    },
+    SkewY {
+        angle: f64,
+    },
+}
+
+impl TransformItem {
+    /// Converts an operation into a matrix.
+    pub fn to_transform(&self) -> Transform {
+        match *self {
+            TransformItem::Matrix { a, b, c, d, e, f } => {
+                Transform::new(a, b, c, d, e, f)
+            }
+            TransformItem::Translate { tx, ty } => {
+                Transform::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+            }
+            TransformItem::Scale { sx, sy } => {
+                Transform::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+            }
+            TransformItem::Rotate { angle, cx, cy } => {
+                let (sin, cos) = (angle * PI / 180.0).sin_cos();
+                let mut ts = Transform::new(1.0, 0.0, 0.0, 1.0, cx, cy);
+                ts.append(&Transform::new(cos, sin, -sin, cos, 0.0, 0.0));
+                ts.append(&Transform::new(1.0, 0.0, 0.0, 1.0, -cx, -cy));
+                ts
+            }
+            TransformItem::SkewX { angle } => {
+                Transform::new(1.0, 0.0, (angle * PI / 180.0).tan(), 1.0, 0.0, 0.0)
+            }
+            TransformItem::SkewY { angle } => {
+                Transform::new(1.0, (angle * PI / 180.0).tan(), 0.0, 1.0, 0.0, 0.0)
+            }
+        }
+    }
+}
+
+/// An ordered list of transform operations.
+///
+/// Unlike [`Transform`], which is always a matrix, keeps the operations
+/// the way they were authored, so they can be edited separately.
+///
+/// The only exception is `translate(x y) rotate(a) translate(-x -y)`, which will be
+/// parsed as `rotate(a x y)`, since they are indistinguishable.
+///
+/// # Examples
+///
+/// ```
+/// use std::str::FromStr;
+/// use svgdom::{TransformList, TransformItem};
+///
+/// let list = TransformList::from_str("translate(10 20) rotate(45 5 5)").unwrap();
+/// assert_eq!(list[0], TransformItem::Translate { tx: 10.0, ty: 20.0 });
+/// assert_eq!(list[1], TransformItem::Rotate { angle: 45.0, cx: 5.0, cy: 5.0 });
+/// assert_eq!(list.to_string(), "translate(10 20) rotate(45 5 5)");
+/// ```
+///
+/// [`Transform`]: struct.Transform.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct TransformList(pub Vec<TransformItem>);
+
+impl TransformList {
+    /// Collapses all operations into a single matrix.
+    pub fn to_transform(&self) -> Transform {
+        let mut ts = Transform::default();
+        for item in &self.0 {
+            ts.append(&item.to_transform());
+        }
+
+        ts
+    }
+}
+
+impl From<Transform> for TransformList {
+    fn from(ts: Transform) -> Self {
+        TransformList(vec![TransformItem::Matrix {
+            a: ts.a, b: ts.b, c: ts.c, d: ts.d, e: ts.e, f: ts.f,
+        }])
+    }
+}
+
+impl FromStr for TransformList {
+    type Err = ValueError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let items: Result<Vec<_>, _> = TransformTokens::new(text).collect();
+        items.map(TransformList)
+    }
+}
+
+impl ValueWriteBuffer for TransformList {
+    fn write_buf_opt(&self, opt: &ValueWriteOptions, buf: &mut Vec<u8>) {
+        for (i, item) in self.0.iter().enumerate() {
+            if i != 0 {
+                buf.push(b' ');
+            }
+
+            let (name, nums): (&[u8], Vec<f64>) = match *item {
+                TransformItem::Matrix { a, b, c, d, e, f } => {
+                    (b"matrix", vec![a, b, c, d, e, f])
+                }
+                TransformItem::Translate { tx, ty } => {
+                    (b"translate", if ty == 0.0 { vec![tx] } else { vec![tx, ty] })
+                }
+                TransformItem::Scale { sx, sy } => {
+                    (b"scale", if sx == sy { vec![sx] } else { vec![sx, sy] })
+                }
+                TransformItem::Rotate { angle, cx, cy } => {
+                    (b"rotate", if cx == 0.0 && cy == 0.0 { vec![angle] } else { vec![angle, cx, cy] })
+                }
+                TransformItem::SkewX { angle } => (b"skewX", vec![angle]),
+                TransformItem::SkewY { angle } => (b"skewY", vec![angle]),
+            };
+
+            buf.extend_from_slice(name);
+            buf.push(b'(');
+            for (j, n) in nums.iter().enumerate() {
+                if j != 0 {
+                    buf.push(b' ');
+                }
+
+                n.write_buf_opt(opt, buf);
+            }
+            buf.push(b')');
+        }
+    }
+}
+
+impl fmt::Display for TransformList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.with_write_opt(&ValueWriteOptions::default()))
+    }
+}
+
+impl Deref for TransformList {
+    type Target = Vec<TransformItem>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for TransformList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
@@ -62,16 +62,6 @@ impl<T: fmt::Display> fmt::Display for Node<T> {
 }
 
 
-macro_rules! try_opt {
-    ($expr: expr) => {
-        match $expr {
-            Some(value) => value,
-            None => return None
-        }
-    }
-}
-
-
 impl<T> Node<T> {
     /// Creates a new node from its associated data.
     pub(crate) fn new(data: T) -> Node<T> {
@@ -154,6 +144,12 @@ impl<T> Node<T> {
         Ref::map(self.0.borrow(), |v| &v.data)
     }
 
+    /// Returns a shared reference to this node's data
+    /// or `None` if the node is currently mutably borrowed.
+    pub(crate) fn try_borrow(&self) -> Option<Ref<T>> {
+        self.0.try_borrow().ok().map(|v| Ref::map(v, |v| &v.data))
+    }
+
     /// Returns a unique/mutable reference to this node's data
     ///
     /// # Panics
@@ -163,6 +159,12 @@ impl<T> Node<T> {
         RefMut::map(self.0.borrow_mut(), |v| &mut v.data)
     }
 
+    /// Returns a unique/mutable reference to this node's data
+    /// or `None` if the node is currently borrowed.
+    pub(crate) fn try_borrow_mut(&mut self) -> Option<RefMut<T>> {
+        self.0.try_borrow_mut().ok().map(|v| RefMut::map(v, |v| &mut v.data))
+    }
+
     /// Returns an iterator of nodes to this node and its ancestors.
     ///
     /// Includes the current node.
@@ -184,6 +186,30 @@ impl<T> Node<T> {
         FollowingSiblings(Some(self.clone()))
     }
 
+    /// Returns an iterator of nodes before this node in reverse tree order.
+    ///
+    /// Implements the XPath `preceding` axis: all nodes of the tree that end before
+    /// this node starts. Ancestors are excluded, since they contain this node.
+    /// Nodes are yielded starting from the nearest one.
+    ///
+    /// Doesn't include the current node.
+    pub fn preceding(&self) -> Preceding<T> {
+        Preceding {
+            node: self.clone(),
+            ancestor: self.parent(),
+        }
+    }
+
+    /// Returns an iterator of nodes after this node in tree order.
+    ///
+    /// Implements the XPath `following` axis: all nodes of the tree that start after
+    /// this node ends. Descendants are excluded, since they are inside this node.
+    ///
+    /// Doesn't include the current node.
+    pub fn following(&self) -> Following<T> {
+        Following(next_after_subtree(self))
+    }
+
     /// Returns an iterator of nodes to this node's children.
     ///
     /// # Panics
@@ -432,6 +458,8 @@ pub mod iterator {
     pub use super::FollowingSiblings;
     pub use super::Children;
     pub use super::ReverseChildren;
+    pub use super::Preceding;
+    pub use super::Following;
     pub use super::Descendants;
     pub use super::Traverse;
     pub use super::ReverseTraverse;
@@ -479,6 +507,62 @@ impl_node_iterator!(Children, |node: &Node<T>| node.next_sibling());
 pub struct ReverseChildren<T>(Option<Node<T>>);
 impl_node_iterator!(ReverseChildren, |node: &Node<T>| node.previous_sibling());
 
+/// An iterator of nodes before a given node, excluding its ancestors, in reverse tree order.
+pub struct Preceding<T> {
+    node: Node<T>,
+    // The nearest ancestor that was not skipped yet.
+    ancestor: Option<Node<T>>,
+}
+
+impl<T> Iterator for Preceding<T> {
+    type Item = Node<T>;
+
+    /// # Panics
+    ///
+    /// Panics if the node about to be yielded is currently mutability borrowed.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let prev = match self.node.previous_sibling() {
+                Some(sibling) => last_descendant(sibling),
+                None => try_opt!(self.node.parent()),
+            };
+
+            self.node = prev.clone();
+
+            if Some(&prev) == self.ancestor.as_ref() {
+                self.ancestor = prev.parent();
+            } else {
+                return Some(prev);
+            }
+        }
+    }
+}
+
+/// An iterator of nodes after a given node, excluding its descendants, in tree order.
+pub struct Following<T>(Option<Node<T>>);
+impl_node_iterator!(Following, |node: &Node<T>| {
+    node.first_child().or_else(|| next_after_subtree(node))
+});
+
+// Returns the last node of the subtree in tree order.
+fn last_descendant<T>(mut node: Node<T>) -> Node<T> {
+    while let Some(child) = node.last_child() {
+        node = child;
+    }
+
+    node
+}
+
+// Returns the first node after the subtree in tree order.
+fn next_after_subtree<T>(node: &Node<T>) -> Option<Node<T>> {
+    for n in node.ancestors() {
+        if let Some(sibling) = n.next_sibling() {
+            return Some(sibling);
+        }
+    }
+
+    None
+}
 
 /// An iterator of nodes to a given node and its descendants, in tree order.
 pub struct Descendants<T>(Traverse<T>);
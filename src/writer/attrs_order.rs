@@ -7,9 +7,15 @@
 // except according to those terms.
 
 use {
+    Attribute,
+    Attributes,
     AttributeId,
     AttributeQNameRef,
+    AttributeType,
+    AttributesOrder,
     ElementId,
+    FilterSvgAttrs,
+    QName,
     QNameRef,
 };
 
@@ -158,7 +164,7 @@ static FILTER_ATTRIBUTES: &'static [AttributeQNameRef<'static>] = &[
     QNameRef::Id(AttributeId::PrimitiveUnits),
 ];
 
-pub fn attrs_order_by_element(eid: ElementId) -> &'static [AttributeQNameRef<'static>] {
+fn attrs_order_by_element(eid: ElementId) -> &'static [AttributeQNameRef<'static>] {
     match eid {
         ElementId::Svg => SVG_ATTRIBUTES,
         ElementId::Rect => RECT_ATTRIBUTES,
@@ -180,3 +186,90 @@ pub fn attrs_order_by_element(eid: ElementId) -> &'static [AttributeQNameRef<'st
         _ => &[],
     }
 }
+
+/// Returns attributes in the specified order.
+///
+/// Non-SVG attributes are always placed at the end in the original order.
+pub fn sort_attributes(
+    attrs: &Attributes,
+    order: AttributesOrder,
+    eid: Option<ElementId>,
+) -> Vec<&Attribute> {
+    let mut list = Vec::with_capacity(attrs.len());
+
+    match order {
+        AttributesOrder::AsIs => {
+            list.extend(attrs.iter());
+            return list;
+        }
+        AttributesOrder::Alphabetical => {
+            // sort attributes
+            let mut ids: Vec<_> = attrs.iter().svg().map(|(aid, attr)| (aid, attr.name.as_ref()))
+                                       .collect();
+            ids.sort_by_key(|&(x, _)| x as usize);
+
+            for &(_, name) in &ids {
+                list.push(attrs.get(name).unwrap());
+            }
+        }
+        AttributesOrder::Specification => {
+            // sort attributes
+            let mut ids: Vec<_> = attrs.iter().svg().map(|(aid, attr)| (aid, attr.name.as_ref()))
+                                       .collect();
+            ids.sort_by_key(|&(x, _)| x as usize);
+
+            let mut ids2 = Vec::with_capacity(ids.len());
+
+            // collect fill attributes
+            for &(aid, name) in &ids {
+                if aid.is_fill() {
+                    ids2.push((aid, name));
+                }
+            }
+
+            // collect stroke attributes
+            for &(aid, name) in &ids {
+                if aid.is_stroke() {
+                    ids2.push((aid, name));
+                }
+            }
+
+            // collect style attributes
+            for &(aid, name) in &ids {
+                if aid.is_presentation() && !aid.is_fill() && !aid.is_stroke() {
+                    ids2.push((aid, name));
+                }
+            }
+
+            // collect element-specific attributes
+            if let Some(eid) = eid {
+                for name2 in attrs_order_by_element(eid) {
+                    if ids.iter().any(|&(_, name)| name == *name2) {
+                        ids2.push((AttributeId::X, *name2));
+                    }
+                }
+            }
+
+            // sorted
+            for &(_, name) in &ids2 {
+                list.push(attrs.get(name).unwrap());
+            }
+
+            // what is left
+            for &(_, name) in &ids {
+                if !ids2.iter().any(|&(_, name2)| name == name2) {
+                    list.push(attrs.get(name).unwrap());
+                }
+            }
+        }
+    }
+
+    // non-SVG attributes
+    for attr in attrs.iter() {
+        if let QName::Name(_) = attr.name {
+            list.push(attr);
+        }
+    }
+
+    list
+}
@@ -9,18 +9,18 @@
 use std::fmt;
 
 mod attrs_order;
+mod number;
 mod options;
 
 pub use self::options::*;
-use self::attrs_order::attrs_order_by_element;
+pub(crate) use self::number::round_value;
+pub(crate) use self::attrs_order::sort_attributes;
 
 use {
     Attribute,
     AttributeId,
-    AttributeType,
     Document,
     ElementId,
-    FilterSvgAttrs,
     Node,
     NodeData,
     NodeEdge,
@@ -115,6 +115,28 @@ impl Depth {
 
 /// Writes a document into the buffer.
 pub(crate) fn write_dom(doc: &Document, opt: &WriteOptions, out: &mut Vec<u8>) {
+    write_dom_impl(doc, opt, out, |_| {});
+}
+
+/// Returns the size of a serialized document.
+///
+/// The buffer is emptied after each node, so only a single node is kept in memory.
+pub(crate) fn serialized_len(doc: &Document, opt: &WriteOptions) -> usize {
+    let mut len = 0;
+    let mut out = Vec::with_capacity(256);
+    write_dom_impl(doc, opt, &mut out, |out| {
+        len += out.len();
+        out.clear();
+    });
+
+    len + out.len()
+}
+
+fn write_dom_impl<F>(doc: &Document, opt: &WriteOptions, out: &mut Vec<u8>, mut flush: F)
+    where F: FnMut(&mut Vec<u8>)
+{
+    write_prolog(doc, opt, out);
+
     let mut depth = Depth::new(opt.indent);
     let mut attrs_depth = Depth::new(opt.attributes_indent);
     let mut iter = doc.root().traverse();
@@ -137,9 +159,51 @@ pub(crate) fn write_dom(doc: &Document, opt: &WriteOptions, out: &mut Vec<u8>) {
                 write_end_edge(&node, &mut depth, opt, out)
             }
         }
+
+        flush(out);
+    }
+}
+
+/// Writes a BOM and an XML declaration, when enabled.
+fn write_prolog(doc: &Document, opt: &WriteOptions, out: &mut Vec<u8>) {
+    if opt.write_bom {
+        out.extend_from_slice(b"\xEF\xBB\xBF");
+    }
+
+    if opt.write_declaration {
+        let is_ascii =    opt.text_encoding == TextEncoding::Ascii
+                       && !opt.write_bom
+                       && !has_non_ascii_raw_data(doc);
+        let encoding = if is_ascii {
+            "US-ASCII"
+        } else {
+            "UTF-8"
+        };
+
+        let quote = if opt.use_single_quote { '\'' } else { '"' };
+        let decl = format!("<?xml version={q}1.0{q} encoding={q}{}{q}?>", encoding, q = quote);
+        out.extend_from_slice(decl.as_bytes());
+        write_newline(opt.indent, out);
     }
 }
 
+// Checks for non-ASCII characters in data that can't contain character references
+// and is written as is: comments, processing instructions and custom names.
+fn has_non_ascii_raw_data(doc: &Document) -> bool {
+    let is_ascii = |s: &str| s.bytes().all(|c| c < 0x80);
+
+    doc.root().descendants().any(|node| {
+        match node.node_type() {
+            NodeType::Comment | NodeType::ProcessingInstruction => !is_ascii(&node.text()),
+            NodeType::Element => {
+                   !is_ascii(&node.tag_name().to_string())
+                || node.attributes().iter().any(|a| !is_ascii(&a.name.to_string()))
+            }
+            NodeType::Root | NodeType::Text => false,
+        }
+    })
+}
+
 fn is_text_node(node: &Node) -> bool {
        node.is_text()
     || node.is_tag_name(ElementId::Tspan)
@@ -182,27 +246,30 @@ fn write_start_edge(
                 }
             }
         }
-        NodeType::Comment => {
+        NodeType::Comment | NodeType::ProcessingInstruction => {
             depth.write_indent(out);
-            write_non_element_node(node, out);
+            write_non_element_node(node, opt, out);
             write_newline(opt.indent, out);
         }
         NodeType::Text => {
-            write_non_element_node(node, out);
+            write_non_element_node(node, opt, out);
         }
     }
 }
 
 /// Writes a non element node.
 ///
-/// Specifically: Declaration, Comment, Cdata and Text.
-fn write_non_element_node(node: &Node, out: &mut Vec<u8>) {
+/// Specifically: Comment, ProcessingInstruction and Text.
+fn write_non_element_node(node: &Node, opt: &WriteOptions, out: &mut Vec<u8>) {
     match node.node_type() {
         NodeType::Comment => {
             write_node(b"<!--", &node.text(), b"-->", out);
         }
+        NodeType::ProcessingInstruction => {
+            write_node(b"<?", &node.text(), b"?>", out);
+        }
         NodeType::Text => {
-            write_escaped_text(node.text().as_ref(), out);
+            write_escaped_text(node.text().as_ref(), opt, out);
         }
         _ => unreachable!(),
     }
@@ -254,11 +321,28 @@ fn write_attributes(
 ) {
     // Write root SVG node attributes.
     if node.is_tag_name(ElementId::Svg) {
+        // An SVG element inside the foreign content must reset the namespace.
+        if node.parent().map(|v| v.is_element() && !v.is_svg_element()) == Some(true) {
+            let attr = Attribute::new("xmlns", "http://www.w3.org/2000/svg");
+            write_attribute(&attr, depth, attrs_depth, opt, out);
+        }
+
         if node.parent().map(|v| v.is_root()) == Some(true) {
             let attr = Attribute::new("xmlns", "http://www.w3.org/2000/svg");
             write_attribute(&attr, depth, attrs_depth, opt, out);
 
-            let xlink_needed = node.descendants().any(|n| n.has_attribute(AttributeId::Href));
+            let xlink_needed = node.descendants().any(|n| {
+                if n.has_attribute(AttributeId::Href) {
+                    return true;
+                }
+
+                opt.canonicalize_attribute_prefixes && n.attributes().iter().any(|a| {
+                    match a.name {
+                        QName::Name(ref name) => name.starts_with("xlink:"),
+                        QName::Id(_) => false,
+                    }
+                })
+            });
             if xlink_needed {
                 let attr = Attribute::new("xmlns:xlink", "http://www.w3.org/1999/xlink");
                 write_attribute(&attr, depth, attrs_depth, opt, out);
@@ -274,90 +358,34 @@ fn write_attributes(
 
     let attrs = node.attributes();
 
-    match opt.attributes_order {
-        AttributesOrder::AsIs => {
-            for attr in attrs.iter() {
-                write_attribute(attr, depth, attrs_depth, opt, out);
-            }
+    for attr in sort_attributes(&attrs, opt.attributes_order, node.tag_id()) {
+        if opt.canonicalize_attribute_prefixes && is_duplicated_without_prefix(node, attr) {
+            warn!("Attribute {} is skipped, because the element already has \
+                   one with the same name and without the 'svg:' prefix.", attr.name);
+            continue;
         }
-        AttributesOrder::Alphabetical => {
-            // sort attributes
-            let mut ids: Vec<_> = attrs.iter().svg().map(|(aid, attr)| (aid, attr.name.as_ref()))
-                                       .collect();
-            ids.sort_by_key(|&(x, _)| x as usize);
-
-            for &(_, name) in &ids {
-                let attr = attrs.get(name).unwrap();
-                write_attribute(attr, depth, attrs_depth, opt, out);
-            }
 
-            // write non-SVG attributes
-            for attr in attrs.iter() {
-                if let QName::Name(_) = attr.name {
-                    write_attribute(attr, depth, attrs_depth, opt, out);
-                }
-            }
-        }
-        AttributesOrder::Specification => {
-            // sort attributes
-            let mut ids: Vec<_> = attrs.iter().svg().map(|(aid, attr)| (aid, attr.name.as_ref()))
-                                       .collect();
-            ids.sort_by_key(|&(x, _)| x as usize);
-
-            let mut ids2 = Vec::with_capacity(ids.len());
-
-            // collect fill attributes
-            for &(aid, name) in &ids {
-                if aid.is_fill() {
-                    ids2.push((aid, name));
-                }
-            }
-
-            // collect stroke attributes
-            for &(aid, name) in &ids {
-                if aid.is_stroke() {
-                    ids2.push((aid, name));
-                }
-            }
-
-            // collect style attributes
-            for &(aid, name) in &ids {
-                if aid.is_presentation() && !aid.is_fill() && !aid.is_stroke() {
-                    ids2.push((aid, name));
-                }
-            }
-
-            // collect element-specific attributes
-            if let Some(eid) = node.tag_id() {
-                for name2 in attrs_order_by_element(eid) {
-                    if ids.iter().any(|&(_, name)| name == *name2) {
-                        ids2.push((AttributeId::X, *name2));
-                    }
-                }
-            }
-
-            // write sorted
-            for &(_, name) in &ids2 {
-                let attr = attrs.get(name).unwrap();
-                write_attribute(attr, depth, attrs_depth, opt, out);
-            }
+        write_attribute(attr, depth, attrs_depth, opt, out);
+    }
+}
 
-            // write what is left
-            for &(_, name) in &ids {
-                if !ids2.iter().any(|&(_, name2)| name == name2) {
-                    let attr = attrs.get(name).unwrap();
-                    write_attribute(attr, depth, attrs_depth, opt, out);
-                }
-            }
+// Checks that an `svg:`-prefixed attribute will collide with another attribute
+// after the prefix is stripped.
+fn is_duplicated_without_prefix(node: &Node, attr: &Attribute) -> bool {
+    let local = match attr.name {
+        QName::Name(ref name) if name.starts_with("svg:") => &name[4..],
+        _ => return false,
+    };
 
-            // write non-SVG attributes
-            for attr in attrs.iter() {
-                if let QName::Name(_) = attr.name {
-                    write_attribute(attr, depth, attrs_depth, opt, out);
-                }
-            }
-        }
+    if local == "id" {
+        return node.has_id();
     }
+
+    // Names are compared as written, since some SVG attributes get a prefix too.
+    let opt = WriteOptions { canonicalize_attribute_prefixes: true, ..WriteOptions::default() };
+    node.attributes().iter().any(|a| {
+        a.name != attr.name && a.name.with_write_opt(&opt).to_string() == local
+    })
 }
 
 fn write_attribute(
@@ -415,7 +443,10 @@ fn _write_text_elem(
                 _write_text_elem(&child, depth, attrs_depth, opt, out);
             }
             NodeType::Text => {
-                write_escaped_text(child.text().as_ref(), out);
+                write_escaped_text(child.text().as_ref(), opt, out);
+            }
+            NodeType::ProcessingInstruction => {
+                write_non_element_node(&child, opt, out);
             }
             _ => {
                 warn!("'text' element should contain only element and text nodes");
@@ -426,17 +457,36 @@ fn _write_text_elem(
     write_element_end(&root, opt, out);
 }
 
-fn write_escaped_text(text: &str, out: &mut Vec<u8>) {
-    for c in text.as_bytes() {
-        match *c {
-            b'&' => out.extend_from_slice(b"&amp;"),
-            b'<' => out.extend_from_slice(b"&lt;"),
-            b'>' => out.extend_from_slice(b"&gt;"),
-            _    => out.push(*c),
+fn write_escaped_text(text: &str, opt: &WriteOptions, out: &mut Vec<u8>) {
+    for c in text.chars() {
+        match c {
+            '&' => out.extend_from_slice(b"&amp;"),
+            '<' => out.extend_from_slice(b"&lt;"),
+            '>' => out.extend_from_slice(b"&gt;"),
+            _   => write_char(c, opt, out),
         }
     }
 }
 
+/// Writes a char according to `WriteOptions::text_encoding`.
+pub(crate) fn write_char(c: char, opt: &WriteOptions, out: &mut Vec<u8>) {
+    if (c as u32) < 0x80 || opt.text_encoding == TextEncoding::Utf8 {
+        let mut buf = [0; 4];
+        out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+    } else {
+        write_char_ref(c, out);
+    }
+}
+
+/// Writes a char as a hexadecimal character reference, like `&#x20ac;`.
+pub(crate) fn write_char_ref(c: char, out: &mut Vec<u8>) {
+    use std::io::Write;
+
+    out.extend_from_slice(b"&#x");
+    write!(out, "{:x}", c as u32).unwrap();
+    out.push(b';');
+}
+
 /// Writes an element closing tag.
 fn write_element_end(node: &Node, opt: &WriteOptions, out: &mut Vec<u8>) {
     if node.has_children() {
@@ -486,6 +536,7 @@ impl fmt::Debug for NodeData {
                 write!(f, ")")
             }
             NodeType::Comment => write!(f, "Comment({})", self.text),
+            NodeType::ProcessingInstruction => write!(f, "ProcessingInstruction({})", self.text),
             NodeType::Text => write!(f, "Text({})", self.text),
         }
     }
@@ -501,6 +552,7 @@ impl fmt::Display for NodeData {
                 write!(f, ">")
             }
             NodeType::Comment => write!(f, "<!--{}-->", self.text),
+            NodeType::ProcessingInstruction => write!(f, "<?{}?>", self.text),
             NodeType::Text => write!(f, "{}", self.text),
         }
     }
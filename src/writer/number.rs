@@ -0,0 +1,77 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use {
+    AttributeValue,
+};
+use path_ext::visit_segment_numbers;
+
+/// Returns a copy of the value with all numbers rounded to the specified precision.
+///
+/// Returns `None` when the value doesn't contain any numbers.
+pub(crate) fn round_value(value: &AttributeValue, precision: u8) -> Option<AttributeValue> {
+    let mut value = match *value {
+        AttributeValue::Angle(_)
+        | AttributeValue::Length(_)
+        | AttributeValue::LengthList(_)
+        | AttributeValue::Number(_)
+        | AttributeValue::NumberList(_)
+        | AttributeValue::Path(_)
+        | AttributeValue::Points(_)
+        | AttributeValue::Transform(_)
+        | AttributeValue::ViewBox(_) => value.clone(),
+        _ => return None,
+    };
+
+    let k = 10f64.powi(precision as i32);
+    let round = |n: &mut f64| *n = (*n * k).round() / k;
+
+    match value {
+        AttributeValue::Angle(ref mut a) => round(&mut a.num),
+        AttributeValue::Length(ref mut l) => round(&mut l.num),
+        AttributeValue::LengthList(ref mut list) => {
+            for l in list.iter_mut() {
+                round(&mut l.num);
+            }
+        }
+        AttributeValue::Number(ref mut n) => round(n),
+        AttributeValue::NumberList(ref mut list) => {
+            for n in list.iter_mut() {
+                round(n);
+            }
+        }
+        AttributeValue::Path(ref mut path) => {
+            for seg in path.iter_mut() {
+                visit_segment_numbers(seg, |n, _| round(n));
+            }
+        }
+        AttributeValue::Points(ref mut points) => {
+            for p in points.iter_mut() {
+                round(&mut p.0);
+                round(&mut p.1);
+            }
+        }
+        AttributeValue::Transform(ref mut ts) => {
+            round(&mut ts.a);
+            round(&mut ts.b);
+            round(&mut ts.c);
+            round(&mut ts.d);
+            round(&mut ts.e);
+            round(&mut ts.f);
+        }
+        AttributeValue::ViewBox(ref mut vb) => {
+            round(&mut vb.x);
+            round(&mut vb.y);
+            round(&mut vb.w);
+            round(&mut vb.h);
+        }
+        _ => {}
+    }
+
+    Some(value)
+}
@@ -6,7 +6,11 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::collections::HashMap;
+use std::fmt;
+
 use {
+    AttributeValue,
     ListSeparator,
     ValueWriteOptions,
 };
@@ -42,6 +46,81 @@ pub enum AttributesOrder {
     Specification,
 }
 
+/// A numbers format.
+///
+/// Numbers are never written using the scientific notation,
+/// since some SVG parsers do not support it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NumberFormat {
+    /// Write numbers using the shortest representation.
+    ///
+    /// Numbers are still rounded to 11 digits after the decimal point
+    /// to prevent writing numbers like `29.999999999999996`.
+    Shortest,
+    /// Round numbers to the specified number of digits after the decimal point.
+    ///
+    /// Trailing zeros are not written.
+    Fixed(u8),
+}
+
+/// A text encoding.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextEncoding {
+    /// Write non-ASCII characters as is, using UTF-8.
+    Utf8,
+    /// Write non-ASCII characters as hexadecimal character references, like `&#x20ac;`.
+    ///
+    /// Affects text nodes and attribute values.
+    /// Comments, processing instructions and custom element and attribute names
+    /// can't contain references, so they are written as is. In this case the output
+    /// is not pure ASCII and the XML declaration will specify `UTF-8`.
+    Ascii,
+}
+
+/// A function that converts a custom attribute value into a string.
+pub type AttributeFormatter = fn(&AttributeValue) -> String;
+
+/// A registry of value formatters for custom attributes.
+///
+/// See [`WriteOptions::attribute_formatters`] for details.
+///
+/// [`WriteOptions::attribute_formatters`]: struct.WriteOptions.html#structfield.attribute_formatters
+#[derive(Clone, Default)]
+pub struct AttributeFormatters(HashMap<String, AttributeFormatter>);
+
+impl AttributeFormatters {
+    /// Registers a formatter for a custom attribute with the specified name,
+    /// like `data-points` or `inkscape:label`.
+    ///
+    /// Replaces the previous formatter for the same name.
+    pub fn register(&mut self, name: &str, f: AttributeFormatter) {
+        self.0.insert(name.to_string(), f);
+    }
+
+    /// Removes a formatter for a custom attribute with the specified name.
+    pub fn remove(&mut self, name: &str) {
+        self.0.remove(name);
+    }
+
+    /// Returns a formatter for a custom attribute with the specified name.
+    pub fn get(&self, name: &str) -> Option<AttributeFormatter> {
+        self.0.get(name).cloned()
+    }
+
+    /// Returns `true` if there are no registered formatters.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for AttributeFormatters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut names: Vec<&String> = self.0.keys().collect();
+        names.sort();
+        write!(f, "AttributeFormatters({:?})", names)
+    }
+}
+
 /// Options that defines SVG writing.
 #[derive(Debug)]
 pub struct WriteOptions {
@@ -121,7 +200,176 @@ pub struct WriteOptions {
     /// Default: `AttributesOrder::Alphabetical`
     pub attributes_order: AttributesOrder,
 
+    /// Canonicalize attribute prefixes.
+    ///
+    /// - Non-SVG attributes with a redundant `svg:` prefix will be written without it.
+    ///   When the element already has an attribute with the same name, the prefixed one
+    ///   is skipped with a warning.
+    /// - `xml:base` and `xml:lang` will be written with the `xml:` prefix.
+    /// - The `xmlns:xlink` declaration will be added when any attribute
+    ///   has the `xlink:` prefix and not only `xlink:href`.
+    ///
+    /// Only the attribute name text is affected.
+    ///
+    /// # Examples
+    ///
+    /// Before:
+    ///
+    /// ```text
+    /// <svg xmlns="http://www.w3.org/2000/svg">
+    ///     <a svg:target="_blank" xlink:title="Title" lang="en"/>
+    /// </svg>
+    /// ```
+    ///
+    /// After:
+    ///
+    /// ```text
+    /// <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+    ///     <a target="_blank" xlink:title="Title" xml:lang="en"/>
+    /// </svg>
+    /// ```
+    ///
+    /// Default: disabled
+    pub canonicalize_attribute_prefixes: bool,
+
+    /// Set numbers format.
+    ///
+    /// Affects all numeric attribute values, including paths and transforms.
+    ///
+    /// # Examples
+    ///
+    /// `NumberFormat::Fixed(2)`
+    ///
+    /// Before:
+    ///
+    /// ```text
+    /// <rect x="10.3333" y="0.0000001" width="20.005"/>
+    /// ```
+    ///
+    /// After:
+    ///
+    /// ```text
+    /// <rect x="10.33" y="0" width="20.01"/>
+    /// ```
+    ///
+    /// Default: `NumberFormat::Shortest`
+    pub number_format: NumberFormat,
+
+    /// Set the encoding of non-ASCII characters.
+    ///
+    /// # Examples
+    ///
+    /// Before:
+    ///
+    /// ```text
+    /// <text font-family="Café">€</text>
+    /// ```
+    ///
+    /// After:
+    ///
+    /// ```text
+    /// <text font-family="Caf&#xe9;">&#x20ac;</text>
+    /// ```
+    ///
+    /// Default: `TextEncoding::Utf8`
+    pub text_encoding: TextEncoding,
+
+    /// Write an XML declaration.
+    ///
+    /// The declared encoding matches [`text_encoding`]: `UTF-8` or `US-ASCII`.
+    /// When [`write_bom`] is enabled, the encoding is always `UTF-8`,
+    /// since a BOM must not contradict the declaration. The same goes for
+    /// documents with non-ASCII comments or custom names, which can't be escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```text
+    /// <?xml version="1.0" encoding="UTF-8"?>
+    /// <svg xmlns="http://www.w3.org/2000/svg"/>
+    /// ```
+    ///
+    /// Default: disabled
+    ///
+    /// [`text_encoding`]: #structfield.text_encoding
+    /// [`write_bom`]: #structfield.write_bom
+    pub write_declaration: bool,
+
+    /// Write a UTF-8 byte order mark (`EF BB BF`) at the start of the output.
+    ///
+    /// Some Windows tools expect it.
+    ///
+    /// Default: disabled
+    pub write_bom: bool,
+
+    /// Write rotations around a point as `rotate(angle cx cy)`.
+    ///
+    /// Only a transform that is exactly a rotation around a point is affected.
+    /// Other transforms are written as before.
+    ///
+    /// # Examples
+    ///
+    /// Before:
+    ///
+    /// ```text
+    /// <rect transform="matrix(0 1 -1 0 30 10)"/>
+    /// ```
+    ///
+    /// After:
+    ///
+    /// ```text
+    /// <rect transform="rotate(90 10 20)"/>
+    /// ```
+    ///
+    /// Default: disabled
+    pub simplify_rotations: bool,
+
+    /// Value formatters for custom attributes.
+    ///
+    /// Custom, non-SVG, attributes are written as is by default.
+    /// A registered formatter controls how the value of such an attribute is written,
+    /// which is useful when a structured value, like a `Points`, is stored in a custom attribute.
+    ///
+    /// The returned string is escaped by the writer. A formatter takes precedence
+    /// over the original text preserved by [`ParseOptions::preserve_source`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use svgdom::{Document, ElementId, AttributeValue, Points, WriteBuffer, WriteOptions};
+    ///
+    /// fn format_points(value: &AttributeValue) -> String {
+    ///     match *value {
+    ///         AttributeValue::Points(ref points) => {
+    ///             let list: Vec<String> = points.iter().map(|p| format!("{},{}", p.0, p.1)).collect();
+    ///             list.join(";")
+    ///         }
+    ///         ref v => v.to_string(),
+    ///     }
+    /// }
+    ///
+    /// let mut doc = Document::new();
+    /// let mut svg = doc.create_element(ElementId::Svg);
+    /// svg.set_attribute(("data-points", Points(vec![(10.0, 20.0), (30.0, 40.0)])));
+    /// doc.root().append(svg);
+    ///
+    /// let mut opt = WriteOptions::default();
+    /// opt.use_single_quote = true;
+    /// opt.attribute_formatters.register("data-points", format_points);
+    /// assert_eq!(doc.with_write_opt(&opt).to_string(),
+    ///     "<svg xmlns='http://www.w3.org/2000/svg' data-points='10,20;30,40'/>\n");
+    /// ```
+    ///
+    /// Default: empty
+    ///
+    /// [`ParseOptions::preserve_source`]: struct.ParseOptions.html#structfield.preserve_source
+    pub attribute_formatters: AttributeFormatters,
+
     /// `svgtypes` options.
+    ///
+    /// Leading zeros removal (`0.5` -> `.5`) is controlled by `values.remove_leading_zero`.
+    /// It takes separators into account, so the output stays unambiguous even with
+    /// `values.use_compact_path_notation` enabled.
+    /// A plus sign is never written.
     pub values: ValueWriteOptions,
 }
 
@@ -132,6 +380,13 @@ impl Default for WriteOptions {
             attributes_indent: Indent::None,
             use_single_quote: false,
             attributes_order: AttributesOrder::Alphabetical,
+            canonicalize_attribute_prefixes: false,
+            number_format: NumberFormat::Shortest,
+            text_encoding: TextEncoding::Utf8,
+            write_declaration: false,
+            write_bom: false,
+            simplify_rotations: false,
+            attribute_formatters: AttributeFormatters::default(),
             values: ValueWriteOptions {
                 trim_hex_colors: false,
                 remove_leading_zero: false,
@@ -0,0 +1,127 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt::Write;
+
+use {
+    Document,
+    Node,
+    NodeType,
+};
+
+pub fn node_path(node: &Node) -> String {
+    PathCache::default().path(node)
+}
+
+// Keeps the paths of the last node and its ancestors, so the paths of nearby nodes,
+// like the ones from a diff, don't recompute the common part.
+#[derive(Default)]
+pub struct PathCache {
+    // Starts with the root element. The root node itself is not stored.
+    stack: Vec<(Node, String)>,
+}
+
+impl PathCache {
+    pub fn path(&mut self, node: &Node) -> String {
+        let mut ancestors: Vec<Node> = node.ancestors().filter(|n| n.parent().is_some()).collect();
+        ancestors.reverse();
+
+        let common = self.stack.iter().zip(ancestors.iter()).take_while(|&(a, b)| a.0 == *b).count();
+        self.stack.truncate(common);
+
+        for n in &ancestors[common..] {
+            let mut path = self.stack.last().map(|v| v.1.clone()).unwrap_or_default();
+
+            path.push('/');
+            path.push_str(&node_step(n));
+            self.stack.push((n.clone(), path));
+        }
+
+        self.stack.last().map(|v| v.1.clone()).unwrap_or_else(|| "/".to_string())
+    }
+}
+
+fn node_step(node: &Node) -> String {
+    let parent = node.parent().unwrap();
+    let mut step = step_name(node);
+
+    // The root element is unique, so it doesn't need a predicate.
+    let is_root_elem = node.is_element()
+                    && parent.node_type() == NodeType::Root
+                    && parent.children().filter(|c| c.is_element()).count() == 1;
+
+    if !is_root_elem {
+        let idx = parent.children().take_while(|c| c != node)
+                        .filter(|c| is_same_step(c, &step)).count();
+        write!(step, "[{}]", idx + 1).unwrap();
+    }
+
+    step
+}
+
+pub fn resolve(doc: &Document, path: &str) -> Option<Node> {
+    if !path.starts_with('/') {
+        return None;
+    }
+
+    let mut node = doc.root();
+    for step in path[1..].split('/') {
+        if step.is_empty() {
+            // Only a plain `/` is allowed.
+            if path.len() == 1 {
+                break;
+            }
+
+            return None;
+        }
+
+        let (name, idx) = try_opt!(parse_step(step));
+
+        node = try_opt!(node.children().filter(|c| is_same_step(c, name)).nth(idx - 1));
+    }
+
+    Some(node)
+}
+
+fn step_name(node: &Node) -> String {
+    match node.node_type() {
+        NodeType::Element => node.tag_name().to_string(),
+        NodeType::Text => "text()".to_string(),
+        NodeType::Comment => "comment()".to_string(),
+        NodeType::ProcessingInstruction => "processing-instruction()".to_string(),
+        NodeType::Root => unreachable!(),
+    }
+}
+
+fn is_same_step(node: &Node, name: &str) -> bool {
+    match node.node_type() {
+        NodeType::Element => node.tag_name().to_string() == name,
+        NodeType::Text => name == "text()",
+        NodeType::Comment => name == "comment()",
+        NodeType::ProcessingInstruction => name == "processing-instruction()",
+        NodeType::Root => false,
+    }
+}
+
+// Splits `name[n]` into a name and a 1-based index.
+// A step without a predicate selects the first node.
+fn parse_step(step: &str) -> Option<(&str, usize)> {
+    let start = match step.find('[') {
+        Some(idx) => idx,
+        None => return Some((step, 1)),
+    };
+
+    if !step.ends_with(']') || start == 0 {
+        return None;
+    }
+
+    match step[start + 1..step.len() - 1].parse::<usize>() {
+        Ok(idx) if idx > 0 => Some((&step[..start], idx)),
+        _ => None,
+    }
+}
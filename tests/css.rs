@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![cfg(feature = "parsing")]
+
 #[macro_use] extern crate pretty_assertions;
 
 extern crate svgdom;
@@ -362,3 +364,74 @@ test_resave!(parse_css_24,
     <g fill='#0000ff'/>
 </svg>
 ");
+
+// selectors with a higher specificity win
+test_resave!(parse_css_25,
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <style>
+    <![CDATA[
+        #rect1 {fill:green}
+        .fil1 {fill:blue}
+        rect {fill:red}
+        * {fill:yellow}
+    ]]>
+    </style>
+    <rect id='rect1' class='fil1'/>
+    <rect class='fil1'/>
+    <rect/>
+    <g/>
+</svg>",
+"<svg xmlns='http://www.w3.org/2000/svg' fill='#ffff00'>
+    <rect id='rect1' fill='#008000'/>
+    <rect fill='#0000ff'/>
+    <rect fill='#ff0000'/>
+    <g fill='#ffff00'/>
+</svg>
+");
+
+// the latest rule wins when specificity is the same
+test_resave!(parse_css_26,
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <style>
+    <![CDATA[
+        .fil1 {fill:blue}
+        .fil2 {fill:red}
+    ]]>
+    </style>
+    <rect class='fil2 fil1'/>
+</svg>",
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect fill='#ff0000'/>
+</svg>
+");
+
+#[test]
+fn important_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <style>
+        #rect1 { fill:green }
+        rect { fill:red !important; stroke:blue }
+    </style>
+    <rect id='rect1'/>
+</svg>").unwrap();
+
+    assert_eq!(doc.to_string(),
+"<svg xmlns=\"http://www.w3.org/2000/svg\">
+    <rect id=\"rect1\" fill=\"#ff0000\" stroke=\"#0000ff\"/>
+</svg>
+");
+}
+
+// important declarations override style attributes
+test_resave!(important_2,
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <style>
+        .red { fill:red !important; /* stroke:red !important; */ stroke:green }
+    </style>
+    <rect class='red' style='fill:blue; stroke:blue'/>
+</svg>",
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect fill='#ff0000' stroke='#0000ff'/>
+</svg>
+");
@@ -0,0 +1,376 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "parsing")]
+
+extern crate svgdom;
+
+use svgdom::{
+    Attribute,
+    AttributeId as AId,
+    AttributeValue,
+    Document,
+    DomChange,
+    ElementId as EId,
+    Error,
+    Length,
+    QName,
+    WriteOptions,
+};
+
+#[test]
+fn diff_equal_1() {
+    let doc1 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#lg1)' width='10' transform='matrix(1 0 0 1 10 20)'/>
+    <path d='M 10 20 L 30 40'/>
+    <text>Text</text>
+</svg>").unwrap();
+
+    let doc2 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#lg1)' width='10.0' transform='translate(10 20)'/>
+    <path d='M10,20 L30,40'/>
+    <text>Text</text>
+</svg>").unwrap();
+
+    assert_eq!(doc1.diff(&doc2), Vec::new());
+}
+
+#[test]
+fn diff_attributes_1() {
+    let doc1 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='10' height='20'/>
+</svg>").unwrap();
+
+    let doc2 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='15' x='5'/>
+</svg>").unwrap();
+
+    let rect = doc1.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+
+    let changes = doc1.diff(&doc2);
+    assert_eq!(changes.len(), 3);
+
+    match changes[0] {
+        DomChange::AttributeChanged { ref node, ref old, ref new } => {
+            assert_eq!(*node, rect);
+            assert_eq!(old.name, QName::Id(AId::Width));
+            assert_eq!(new.value, AttributeValue::Length(Length::new_number(15.0)));
+        }
+        _ => panic!("invalid change"),
+    }
+
+    match changes[1] {
+        DomChange::AttributeRemoved { ref node, ref name } => {
+            assert_eq!(*node, rect);
+            assert_eq!(*name, QName::Id(AId::Height));
+        }
+        _ => panic!("invalid change"),
+    }
+
+    match changes[2] {
+        DomChange::AttributeAdded { ref node, ref attribute } => {
+            assert_eq!(*node, rect);
+            assert_eq!(attribute.name, QName::Id(AId::X));
+        }
+        _ => panic!("invalid change"),
+    }
+}
+
+#[test]
+fn diff_nodes_1() {
+    let doc1 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect id='rect1'/>
+    <circle/>
+</svg>").unwrap();
+
+    let doc2 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <ellipse/>
+    <rect id='rect1'/>
+</svg>").unwrap();
+
+    let circle = doc1.root().descendants().find(|n| n.is_tag_name(EId::Circle)).unwrap();
+    let ellipse = doc2.root().descendants().find(|n| n.is_tag_name(EId::Ellipse)).unwrap();
+
+    let changes = doc1.diff(&doc2);
+    assert_eq!(changes, vec![
+        DomChange::NodeRemoved { node: circle },
+        DomChange::NodeAdded {
+            parent: doc1.svg_element().unwrap(),
+            index: 0,
+            node: ellipse,
+        },
+    ]);
+}
+
+#[test]
+fn diff_text_1() {
+    let doc1 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text>Text</text>
+</svg>").unwrap();
+
+    let doc2 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text>Other</text>
+</svg>").unwrap();
+
+    let changes = doc1.diff(&doc2);
+    assert_eq!(changes.len(), 1);
+
+    match changes[0] {
+        DomChange::TextChanged { ref old, ref new, .. } => {
+            assert_eq!(old, "Text");
+            assert_eq!(new, "Other");
+        }
+        _ => panic!("invalid change"),
+    }
+}
+
+#[test]
+fn diff_links_1() {
+    let doc1 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <linearGradient id='lg2'/>
+    <rect fill='url(#lg1)'/>
+</svg>").unwrap();
+
+    let doc2 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <linearGradient id='lg2'/>
+    <rect fill='url(#lg2)'/>
+</svg>").unwrap();
+
+    let changes = doc1.diff(&doc2);
+    assert_eq!(changes.len(), 1);
+
+    match changes[0] {
+        DomChange::AttributeChanged { ref old, .. } => {
+            assert_eq!(old.name, QName::Id(AId::Fill));
+        }
+        _ => panic!("invalid change"),
+    }
+}
+
+macro_rules! test_patch {
+    ($name:ident, $in1:expr, $in2:expr) => (
+        #[test]
+        fn $name() {
+            let mut doc1 = Document::from_str($in1).unwrap();
+            let doc2 = Document::from_str($in2).unwrap();
+
+            let changes = doc1.diff(&doc2);
+            doc1.apply_patch(&changes).unwrap();
+
+            assert_eq!(doc1.to_string(), doc2.to_string());
+            assert_eq!(doc1.diff(&doc2), Vec::new());
+        }
+    )
+}
+
+test_patch!(patch_1,
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='10' height='20'/>
+    <text>Text</text>
+</svg>",
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='15' x='5'/>
+    <text>Other</text>
+</svg>");
+
+test_patch!(patch_2,
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect id='rect1'/>
+    <circle/>
+    <g>
+        <path/>
+    </g>
+</svg>",
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <ellipse/>
+    <rect id='rect1'/>
+    <g>
+        <path/>
+        <g>
+            <line/>
+        </g>
+    </g>
+</svg>");
+
+test_patch!(patch_links_1,
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#lg1)'/>
+</svg>",
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <linearGradient id='lg2' xlink:href='#lg1'/>
+    <rect fill='url(#lg2)'/>
+</svg>");
+
+// A node with the same ID but a different tag name.
+test_patch!(patch_links_2,
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <rect id='shape'/>
+    <use xlink:href='#shape'/>
+</svg>",
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <circle id='shape'/>
+    <use xlink:href='#shape'/>
+</svg>");
+
+test_patch!(patch_id_1,
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect id='rect1'/>
+    <rect/>
+</svg>",
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect/>
+    <rect id='rect2'/>
+</svg>");
+
+#[test]
+fn patch_invalid_1() {
+    let mut doc1 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='10'/>
+</svg>").unwrap();
+
+    let doc2 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='20'/>
+</svg>").unwrap();
+
+    let changes = doc1.diff(&doc2);
+
+    // Patch must be applied only to the original document.
+    let mut doc3 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='10'/>
+</svg>").unwrap();
+    assert!(doc3.apply_patch(&changes).is_err());
+
+    // Value doesn't match.
+    let mut rect = doc1.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    rect.set_attribute((AId::Width, 30.0));
+    assert!(doc1.apply_patch(&changes).is_err());
+
+    // Attribute is missing.
+    rect.remove_attribute(AId::Width);
+    assert!(doc1.apply_patch(&changes).is_err());
+}
+
+#[test]
+fn patch_invalid_2() {
+    let mut doc1 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='10' fill='red'/>
+</svg>").unwrap();
+
+    let doc2 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <rect width='20' fill='url(#lg1)'/>
+</svg>").unwrap();
+
+    // A link to the gradient can't be resolved without the added node.
+    let changes: Vec<DomChange> = doc1.diff(&doc2).into_iter().filter(|c| {
+        match *c {
+            DomChange::NodeAdded { .. } => false,
+            _ => true,
+        }
+    }).collect();
+
+    let text = doc1.to_string();
+    assert_eq!(doc1.apply_patch(&changes), Err(Error::InvalidPatch));
+    assert_eq!(doc1.to_string(), text);
+}
+
+#[test]
+fn patch_invalid_3() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1' x1='5'/>
+    <linearGradient id='lg2' xlink:href='#lg1'/>
+</svg>").unwrap();
+
+    let lg1 = doc.root().descendants().find(|n| *n.id() == "lg1").unwrap();
+    let lg2 = doc.root().descendants().find(|n| *n.id() == "lg2").unwrap();
+    let x1 = lg1.attributes().get(AId::X1).unwrap().clone();
+    let changes = vec![
+        DomChange::AttributeRemoved { node: lg1.clone(), name: QName::Id(AId::X1) },
+        DomChange::AttributeAdded {
+            node: lg1.clone(),
+            attribute: Attribute::new(AId::Href, AttributeValue::Link(lg2.clone())),
+        },
+    ];
+
+    assert_eq!(doc.apply_patch(&changes), Err(Error::ElementCrosslink));
+    assert_eq!(lg1.attributes().get(AId::X1), Some(&x1));
+}
+
+#[test]
+fn diff_report_1() {
+    let doc1 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <g id='g1'>
+        <rect/>
+        <rect fill='red' stroke='black'/>
+    </g>
+    <text>Text</text>
+    <!--comment-->
+</svg>").unwrap();
+
+    let doc2 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <g id='g1'>
+        <rect/>
+        <rect fill='rgb(0, 0, 255)'>
+            <title>Title</title>
+        </rect>
+    </g>
+    <text>New text</text>
+    <!--comment-->
+</svg>").unwrap();
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc1.diff_text(&doc2, &opt),
+"--- original
++++ modified
+@@ /svg/g[1]/rect[2] @@
+- fill='#ff0000'
++ fill='#0000ff'
+- stroke='#000000'
++ <title>...</title>
+@@ /svg/text[1]/text()[1] @@
+- \"Text\"
++ \"New text\"
+");
+
+    // Reordered attributes are not a change.
+    let doc3 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <g id='g1'>
+        <rect/>
+        <rect stroke='#000' fill='#ff0000'/>
+    </g>
+    <text>Text</text>
+    <!--comment-->
+</svg>").unwrap();
+    assert_eq!(doc1.diff_text(&doc3, &opt), "");
+}
@@ -6,15 +6,22 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+// Most of the tests require parsing.
+#![cfg_attr(not(feature = "parsing"), allow(unused_imports))]
+
 #[macro_use] extern crate pretty_assertions;
 
-extern crate svgdom;
+#[macro_use] extern crate svgdom;
 
 use svgdom::{
     AttributeId as AId,
     AttributeValue,
     Document,
     ElementId as EId,
+    ExternalReference,
+    FeatureSet,
+    Indent,
+    TranslateMode,
     WriteOptions,
     WriteBuffer,
 };
@@ -218,6 +225,7 @@ fn remove_attribute_1() {
     assert_eq!(n.has_attribute(AId::StrokeWidth), false);
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn drain_1() {
     let mut doc = Document::from_str(
@@ -234,6 +242,7 @@ fn drain_1() {
                "<svg xmlns='http://www.w3.org/2000/svg'/>\n");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn drain_2() {
     let mut doc = Document::from_str(
@@ -259,6 +268,7 @@ fn drain_2() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn drain_3() {
     let mut doc = Document::from_str(
@@ -283,6 +293,7 @@ fn drain_3() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn drain_4() {
     let mut doc = Document::from_str(
@@ -306,6 +317,7 @@ fn drain_4() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn deep_copy_1() {
     let mut doc = Document::from_str(
@@ -335,6 +347,7 @@ fn deep_copy_1() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn deep_copy_2() {
     let mut doc = Document::from_str(
@@ -372,6 +385,7 @@ fn deep_copy_2() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn deep_copy_3() {
     let mut doc = Document::from_str(
@@ -425,3 +439,1836 @@ fn set_attr_1() {
     rect.set_attribute((AId::Href, rect2));
     assert_eq!(rect.attributes().get(AId::Href).unwrap().to_string(), "xlink:href=\"#rect2\"");
 }
+
+#[cfg(feature = "parsing")]
+#[test]
+fn computed_style_1() {
+    use svgdom::{Color, Length};
+
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <style>
+        .red {fill:red}
+    </style>
+    <g class='red' stroke='green' opacity='0.5'>
+        <g style='stroke:blue' stroke-width='inherit'>
+            <rect fill='inherit' opacity='inherit' stroke-width='2'/>
+        </g>
+    </g>
+</svg>").unwrap();
+
+    let rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    let style = rect.computed_style();
+
+    assert_eq!(style[&AId::Fill], AttributeValue::Color(Color::new(255, 0, 0)));
+    assert_eq!(style[&AId::Stroke], AttributeValue::Color(Color::new(0, 0, 255)));
+    assert_eq!(style[&AId::StrokeWidth], AttributeValue::from(Length::new_number(2.0)));
+    // `opacity` is not inheritable, so the parent doesn't have a computed value.
+    assert!(!style.contains_key(&AId::Opacity));
+    assert_eq!(style.len(), 3);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn normalize_units_1() {
+    use svgdom::postproc;
+
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' width='25.4mm' height='1in'>
+    <rect x='72pt' y='1pc' width='2.54cm' height='10%' font-size='12pt' stroke-dasharray='1in 5em 3'/>
+</svg>").unwrap();
+
+    postproc::normalize_units_except(&doc.root(), 96.0, &[AId::FontSize]);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' height='96' width='96'>
+    <rect font-size='12pt' height='10%' stroke-dasharray='96 5em 3' width='96' x='96' y='16'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn nodes_with_attribute_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect id='r1' clip-path='none'/>
+    <g visibility='hidden'>
+        <rect id='r2' clip-path='none'/>
+        <rect id='r3' clip-path='none' visibility='visible'/>
+        <rect id='r4' clip-path='none' visibility='inherit'/>
+    </g>
+    <g display='none'>
+        <rect id='r5' clip-path='none'/>
+    </g>
+    <rect/>
+</svg>").unwrap();
+
+    let ids: Vec<_> = doc.nodes_with_attribute(AId::ClipPath).iter()
+                         .map(|n| n.id().clone()).collect();
+    assert_eq!(ids, vec!["r1", "r2", "r3", "r4", "r5"]);
+
+    let ids: Vec<_> = doc.nodes_with_attribute_iter(AId::ClipPath).only_visible()
+                         .map(|n| n.id().clone()).collect();
+    assert_eq!(ids, vec!["r1", "r3"]);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn find_nodes_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect id='r1' width='10'/>
+    <rect id='r2' width='20'/>
+    <g id='g1'>
+        <rect id='r3' width='30'/>
+    </g>
+</svg>").unwrap();
+
+    let nodes = doc.find_nodes(|n| n.is_tag_name(EId::Rect));
+    assert_eq!(nodes.len(), 3);
+
+    let first = doc.find_nodes_iter(|n| n.has_id()).next().unwrap();
+    assert_eq!(*first.id(), "r1");
+
+    let wide = doc.find_nodes_iter(|n| {
+        match n.attributes().get_value(AId::Width) {
+            Some(&AttributeValue::Length(len)) => len.num > 15.0,
+            _ => false,
+        }
+    }).count();
+    assert_eq!(wide, 2);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn image_data_1() {
+    use svgdom::ImageData;
+
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='data:image/svg+xml;charset=utf-8,%3Csvg%2F%3E'/>
+    <image xlink:href='data:;base64,SGVs
+                                  bG8='/>
+    <image xlink:href='image.png'/>
+    <image xlink:href='data:image/png;base64'/>
+    <image/>
+</svg>").unwrap();
+
+    let images: Vec<_> = doc.svg_element().unwrap().children().map(|n| n.image_data()).collect();
+    assert_eq!(images, vec![
+        Some(ImageData::Embedded { mime: "image/svg+xml".to_string(), data: b"<svg/>".to_vec() }),
+        Some(ImageData::Embedded { mime: "text/plain".to_string(), data: b"Hello".to_vec() }),
+        Some(ImageData::External("image.png".to_string())),
+        None,
+        None,
+    ]);
+}
+
+#[test]
+fn image_data_2() {
+    let mut doc = Document::new();
+    let mut image = doc.create_element(EId::Image);
+
+    for data in &[&b""[..], b"a", b"ab", b"abc", b"abcd"] {
+        image.set_image_data("image/png", data);
+        match image.image_data() {
+            Some(svgdom::ImageData::Embedded { mime, data: data2 }) => {
+                assert_eq!(mime, "image/png");
+                assert_eq!(data2, data.to_vec());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    image.set_image_data("image/png", b"abcd");
+    assert_eq!(image.attributes().get(AId::Href).unwrap().to_string(),
+               "xlink:href=\"data:image/png;base64,YWJjZA==\"");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn apply_transform_to_1() {
+    use svgdom::Transform;
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1' gradientTransform='translate(10 20)'/>
+    <g transform='rotate(90)'/>
+    <!--comment-->
+    <path transform='translate(5 5)'/>
+</svg>").unwrap();
+
+    let nodes: Vec<_> = doc.svg_element().unwrap().children().collect();
+    doc.apply_transform_to(&nodes[..3], &Transform::new_scale(2.0, 2.0));
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1' gradientTransform='matrix(2 0 0 2 20 40)'/>
+    <g transform='matrix(0 2 -2 0 0 0)'/>
+    <!--comment-->
+    <path transform='matrix(1 0 0 1 5 5)'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn clip_path_and_mask_1() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <clipPath id='clip1'/>
+    <mask id='mask1'/>
+    <rect clip-path='url(#clip1)' mask='url(#mask1)'/>
+    <rect clip-path='none' mask='none'/>
+</svg>").unwrap();
+
+    let clip = doc.root().descendants().find(|n| n.is_tag_name(EId::ClipPath)).unwrap();
+    let mask = doc.root().descendants().find(|n| n.is_tag_name(EId::Mask)).unwrap();
+    let rect1 = mask.next_sibling().unwrap();
+    let rect2 = rect1.next_sibling().unwrap();
+
+    assert_eq!(rect1.clip_path(), Some(clip.clone()));
+    assert_eq!(rect1.mask(), Some(mask.clone()));
+    assert_eq!(rect2.clip_path(), None);
+    assert_eq!(rect2.mask(), None);
+
+    doc.remove_node(mask);
+    assert_eq!(rect1.mask(), None);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn gradient_stops_1() {
+    use svgdom::{Color, GradientStop};
+
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <radialGradient id='rg1' color='#00ff00' stop-color='#0000ff'>
+        <stop offset='-1' stop-color='currentColor'/>
+        <stop offset='50%' stop-color='inherit' stop-opacity='0.3'/>
+        <stop offset='2'/>
+    </radialGradient>
+    <linearGradient id='lg1' xlink:href='#rg1'/>
+    <linearGradient id='lg2' xlink:href='#lg1'/>
+    <rect/>
+</svg>").unwrap();
+
+    let stops = vec![
+        GradientStop { offset: 0.0, color: Color::new(0, 255, 0), opacity: 1.0 },
+        GradientStop { offset: 0.5, color: Color::new(0, 0, 255), opacity: 0.3 },
+        GradientStop { offset: 1.0, color: Color::new(0, 0, 0), opacity: 1.0 },
+    ];
+
+    let svg = doc.svg_element().unwrap();
+    let mut iter = svg.children();
+    assert_eq!(iter.next().unwrap().gradient_stops(), stops);
+    assert_eq!(iter.next().unwrap().gradient_stops(), stops);
+    assert_eq!(iter.next().unwrap().gradient_stops(), stops);
+    assert_eq!(iter.next().unwrap().gradient_stops(), vec![]);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn remove_attributes_by_prefix_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <rect id='rect1'/>
+    <use xlink:href='#rect1' xml:space='preserve'/>
+</svg>").unwrap();
+
+    let rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    let mut use_elem = doc.root().descendants().find(|n| n.is_tag_name(EId::Use)).unwrap();
+    use_elem.set_attribute(("xlink:title", "Title"));
+    use_elem.set_attribute(("inkscape:label", "Label"));
+    assert!(rect.is_used());
+
+    assert_eq!(use_elem.attributes().iter_by_prefix("xlink:").count(), 2);
+
+    use_elem.remove_attributes_by_prefix("xlink:");
+    assert!(!rect.is_used());
+
+    assert_eq!(use_elem.attributes().iter().map(|a| a.name.to_string()).collect::<Vec<_>>(),
+               vec!["xml:space", "inkscape:label"]);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn remove_editor_data_1() {
+    use svgdom::postproc;
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <metadata id='meta1'/>
+    <metadata id='meta2'>
+        <rect id='rect1'/>
+    </metadata>
+    <defs>
+        <metadata/>
+    </defs>
+    <use xlink:href='#rect1'/>
+</svg>").unwrap();
+
+    let mut svg = doc.svg_element().unwrap();
+    svg.set_attribute(("inkscape:version", "0.92"));
+    svg.set_attribute(("sodipodi:docname", "test.svg"));
+
+    let mut namedview = doc.create_element("sodipodi:namedview");
+    namedview.set_attribute(("pagecolor", "#ffffff"));
+    svg.prepend(namedview);
+
+    postproc::remove_editor_data_except(&mut doc, &["sodipodi"]);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink' sodipodi:docname='test.svg'>
+    <sodipodi:namedview pagecolor='#ffffff'/>
+    <metadata id='meta2'>
+        <rect id='rect1'/>
+    </metadata>
+    <use xlink:href='#rect1'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn remove_empty_containers_1() {
+    use svgdom::postproc;
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <symbol/>
+        <g id='g1'/>
+    </defs>
+    <switch>
+        <g>
+            <g/>
+        </g>
+    </switch>
+    <filter id='f1'/>
+    <g filter='url(#f1)'/>
+    <g filter='none'/>
+    <use xlink:href='#g1'/>
+</svg>").unwrap();
+
+    postproc::remove_empty_containers(&mut doc);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <g id='g1'/>
+    </defs>
+    <filter id='f1'/>
+    <g filter='url(#f1)'/>
+    <use xlink:href='#g1'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn collapse_single_child_groups_1() {
+    use svgdom::postproc;
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <g id='g1' fill='red' stroke='inherit'>
+        <g transform='translate(10 20)'>
+            <rect fill='green' stroke='inherit' transform='scale(2)'/>
+        </g>
+    </g>
+    <g id='g2' opacity='0.5'>
+        <rect/>
+    </g>
+    <g display='none'>
+        <rect display='inline'/>
+    </g>
+    <g>
+        <rect/>
+        <rect/>
+    </g>
+    <g id='g3'>
+        <rect id='rect1'/>
+    </g>
+    <use xlink:href='#g2'/>
+</svg>").unwrap();
+
+    postproc::collapse_single_child_groups(&mut doc);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <rect id='g1' fill='#008000' stroke='inherit' transform='matrix(2 0 0 2 10 20)'/>
+    <g id='g2' opacity='0.5'>
+        <rect/>
+    </g>
+    <g display='none'>
+        <rect display='inline'/>
+    </g>
+    <g>
+        <rect/>
+        <rect/>
+    </g>
+    <g id='g3'>
+        <rect id='rect1'/>
+    </g>
+    <use xlink:href='#g2'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn minify_ids_1() {
+    use svgdom::postproc;
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <linearGradient id='a'/>
+    <linearGradient id='lg3' xlink:href='#a'/>
+    <rect id='external' fill='url(#lg1)' stroke='url(#a)'/>
+    <use xlink:href='#a'/>
+</svg>").unwrap();
+
+    let mut svg = doc.svg_element().unwrap();
+    svg.set_attribute(("data-target", "url(#lg3)"));
+
+    postproc::minify_ids_except(&mut doc, &["external", "b"]);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink' data-target='url(#d)'>
+    <linearGradient id='c'/>
+    <linearGradient id='a'/>
+    <linearGradient id='d' xlink:href='#a'/>
+    <rect id='external' fill='url(#c)' stroke='url(#a)'/>
+    <use xlink:href='#a'/>
+</svg>
+");
+
+    // All links are still resolvable.
+    let doc = Document::from_str(&doc.to_string()).unwrap();
+    let rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    assert!(rect.attributes().get_value(AId::Fill).unwrap().is_link_container());
+    assert!(rect.attributes().get_value(AId::Stroke).unwrap().is_link_container());
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn remove_unreferenced_ids_1() {
+    use svgdom::postproc;
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink' id='svg1'>
+    <linearGradient id='lg1'/>
+    <linearGradient id='lg2'/>
+    <linearGradient id='lg3' xlink:href='#lg1'/>
+    <rect id='icon-home'/>
+    <rect id='icon-search'/>
+    <rect id='rect1'/>
+</svg>").unwrap();
+
+    let mut svg = doc.svg_element().unwrap();
+    svg.set_attribute(("data-target", "url(#lg2)"));
+
+    postproc::remove_unreferenced_ids_except(&mut doc, &["icon-*", "rect"]);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink' data-target='url(#lg2)'>
+    <linearGradient id='lg1'/>
+    <linearGradient id='lg2'/>
+    <linearGradient xlink:href='#lg1'/>
+    <rect id='icon-home'/>
+    <rect id='icon-search'/>
+    <rect/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn group_nodes_1() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect/>
+    <circle/>
+    <ellipse/>
+    <line/>
+</svg>").unwrap();
+
+    let svg = doc.svg_element().unwrap();
+    let nodes: Vec<_> = svg.children().skip(1).take(2).collect();
+    let g = doc.group_nodes(&nodes);
+    assert_eq!(g.parent(), Some(svg.clone()));
+    assert_eq!(g.children().count(), 2);
+
+    let line = svg.last_child().unwrap();
+    doc.wrap_node(line, EId::A);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect/>
+    <g>
+        <circle/>
+        <ellipse/>
+    </g>
+    <a>
+        <line/>
+    </a>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+#[should_panic]
+fn group_nodes_2() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect/>
+    <circle/>
+    <ellipse/>
+</svg>").unwrap();
+
+    let svg = doc.svg_element().unwrap();
+    let nodes = vec![svg.first_child().unwrap(), svg.last_child().unwrap()];
+    doc.group_nodes(&nodes);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn ctm_1() {
+    use svgdom::Transform;
+
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' width='200' height='100' viewBox='10 10 100 100'>
+    <g transform='translate(10 20)'>
+        <g transform='scale(2)'>
+            <svg x='5' y='5' width='20' height='20' viewBox='0 0 10 10' preserveAspectRatio='none'>
+                <rect transform='translate(1 1)'/>
+            </svg>
+        </g>
+    </g>
+</svg>").unwrap();
+
+    let svg = doc.svg_element().unwrap();
+    assert_eq!(svg.ctm(), Transform::default());
+
+    let g1 = svg.first_child().unwrap();
+    // The viewBox is scaled by 1 and centered horizontally.
+    assert_eq!(g1.ctm(), Transform::new(1.0, 0.0, 0.0, 1.0, 50.0, 10.0));
+
+    let g2 = g1.first_child().unwrap();
+    assert_eq!(g2.ctm(), Transform::new(2.0, 0.0, 0.0, 2.0, 50.0, 10.0));
+
+    let nested_svg = g2.first_child().unwrap();
+    assert_eq!(nested_svg.ctm(), g2.ctm());
+
+    let rect = nested_svg.first_child().unwrap();
+    assert_eq!(rect.ctm(), Transform::new(4.0, 0.0, 0.0, 4.0, 64.0, 24.0));
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn to_root_coordinates_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 50 50' width='100' height='100'>
+    <g transform='translate(10 10)'>
+        <circle cx='5' cy='5' r='5'/>
+        <polygon points='0 0 10 0 10 10'/>
+        <rect width='50%' height='10'/>
+        <text>Text</text>
+    </g>
+</svg>").unwrap();
+
+    let g = doc.svg_element().unwrap().first_child().unwrap();
+    let mut iter = g.children();
+
+    let circle = iter.next().unwrap();
+    assert_eq!(circle.to_root_coordinates().unwrap().to_string(),
+               "M 40 30 A 10 10 0 0 1 30 40 A 10 10 0 0 1 20 30 \
+                A 10 10 0 0 1 30 20 A 10 10 0 0 1 40 30 Z");
+
+    let polygon = iter.next().unwrap();
+    assert_eq!(polygon.to_root_coordinates().unwrap().to_string(),
+               "M 20 20 L 40 20 L 40 40 Z");
+
+    let rect = iter.next().unwrap();
+    assert_eq!(rect.to_root_coordinates(), None);
+
+    let text = iter.next().unwrap();
+    assert_eq!(text.to_root_coordinates(), None);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn sort_defs_topologically_1() {
+    use svgdom::postproc;
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <use xlink:href='#rect2'/>
+    <g>
+        <rect fill='url(#lg2)'/>
+    </g>
+    <rect id='rect2'/>
+    <defs>
+        <linearGradient id='lg2' xlink:href='#lg1'/>
+    </defs>
+    <defs>
+        <linearGradient id='lg1'/>
+        <linearGradient id='lg3'/>
+    </defs>
+</svg>").unwrap();
+
+    postproc::sort_defs_topologically(&mut doc);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <use xlink:href='#rect2'/>
+    <defs>
+        <linearGradient id='lg1'/>
+        <linearGradient id='lg3'/>
+    </defs>
+    <defs>
+        <linearGradient id='lg2' xlink:href='#lg1'/>
+    </defs>
+    <g>
+        <rect fill='url(#lg2)'/>
+    </g>
+    <rect id='rect2'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn viewport_size_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' width='2in' height='100'>
+    <svg width='50%' x='10'>
+        <symbol>
+            <rect/>
+        </symbol>
+    </svg>
+</svg>").unwrap();
+
+    let svg = doc.svg_element().unwrap();
+    let nested_svg = svg.first_child().unwrap();
+    let symbol = nested_svg.first_child().unwrap();
+    let rect = symbol.first_child().unwrap();
+
+    assert_eq!(svg.viewport_size(), Some((192.0, 100.0)));
+    assert_eq!(nested_svg.viewport_size(), Some((96.0, 100.0)));
+    assert_eq!(symbol.viewport_size(), None);
+    assert_eq!(rect.nearest_viewport(), Some(nested_svg.clone()));
+
+    let doc = Document::from_str("<svg xmlns='http://www.w3.org/2000/svg' width='50%'/>").unwrap();
+    assert_eq!(doc.svg_element().unwrap().viewport_size(), None);
+}
+
+#[test]
+fn element_builder_1() {
+    let mut doc = Document::new();
+    let svg = doc.element(EId::Svg)
+        .attr((AId::Width, 100.0))
+        .child(EId::G, |g| {
+            g.id("g1")
+             .child(EId::Rect, |rect| rect.attr((AId::Height, 10.0)))
+             .child(EId::Text, |text| text.text("Text"))
+        })
+        .child("custom", |e| e.attr(("data", "1")))
+        .finish();
+    doc.root().append(svg);
+
+    assert_eq!(doc.to_string(),
+"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\">
+    <g id=\"g1\">
+        <rect height=\"10\"/>
+        <text>Text</text>
+    </g>
+    <custom data=\"1\"/>
+</svg>
+");
+}
+
+#[test]
+#[should_panic]
+fn element_builder_2() {
+    let mut doc = Document::new();
+    let lg = doc.create_element(EId::LinearGradient);
+    // A linked element must have an ID.
+    doc.element(EId::Rect).attr((AId::Fill, lg));
+}
+
+#[test]
+fn svg_macro_1() {
+    let mut doc = Document::new();
+    let mut svg = svg!(doc, Svg {
+        LinearGradient(id = "lg1")
+    });
+    let lg = svg.first_child().unwrap();
+    let rect = svg!(doc, Rect(Fill = lg.clone(), "class" = "rect"));
+    svg.append(rect);
+    doc.root().append(svg);
+
+    assert_eq!(lg.uses_count(), 1);
+    assert_eq!(doc.to_string(),
+"<svg xmlns=\"http://www.w3.org/2000/svg\">
+    <linearGradient id=\"lg1\"/>
+    <rect fill=\"url(#lg1)\" class=\"rect\"/>
+</svg>
+");
+}
+
+#[test]
+fn visit_values_mut_1() {
+    let mut doc = Document::new();
+    let svg = svg!(doc, Svg {
+        LinearGradient(id = "lg1")
+        Rect(Fill = "red", Stroke = "blue")
+    });
+    let lg = svg.first_child().unwrap();
+    let mut rect = svg.last_child().unwrap();
+    rect.set_attribute((AId::Fill, lg.clone()));
+    doc.root().append(svg.clone());
+
+    let mut visited = Vec::new();
+    doc.visit_values_mut(|node, aid, value| {
+        // The node can be accessed inside the closure.
+        visited.push((node.tag_id().unwrap(), aid));
+        *value = AttributeValue::from("green");
+    });
+
+    assert_eq!(visited, vec![(EId::Rect, AId::Stroke)]);
+    assert_eq!(lg.uses_count(), 1);
+    assert_eq!(doc.to_string(),
+"<svg xmlns=\"http://www.w3.org/2000/svg\">
+    <linearGradient id=\"lg1\"/>
+    <rect fill=\"url(#lg1)\" stroke=\"green\"/>
+</svg>
+");
+}
+
+#[test]
+#[should_panic]
+fn visit_values_mut_2() {
+    let mut doc = Document::new();
+    let svg = svg!(doc, Svg {
+        LinearGradient(id = "lg1")
+        Rect(Fill = "red")
+    });
+    let lg = svg.first_child().unwrap();
+    doc.root().append(svg);
+
+    // Links cannot be set via the visitor.
+    doc.visit_values_mut(|_, _, value| *value = AttributeValue::FuncLink(lg.clone()));
+}
+
+#[test]
+fn update_values_1() {
+    let mut doc = Document::new();
+    let svg = svg!(doc, Svg {
+        LinearGradient(id = "lg1")
+        Rect(Fill = "red", Stroke = "blue", Opacity = 0.5)
+    });
+    let lg = svg.first_child().unwrap();
+    let mut rect = svg.last_child().unwrap();
+    rect.set_attribute((AId::Fill, lg.clone()));
+    rect.set_attribute(("data-x", "1"));
+    doc.root().append(svg.clone());
+
+    let mut visited = Vec::new();
+    rect.update_values(|aid, value| {
+        visited.push(aid);
+        match *value {
+            AttributeValue::Number(ref mut n) => *n *= 2.0,
+            _ => *value = AttributeValue::from("green"),
+        }
+    });
+
+    assert_eq!(visited, vec![AId::Stroke, AId::Opacity]);
+    assert_eq!(lg.uses_count(), 1);
+    assert_eq!(doc.to_string(),
+"<svg xmlns=\"http://www.w3.org/2000/svg\">
+    <linearGradient id=\"lg1\"/>
+    <rect fill=\"url(#lg1)\" opacity=\"1\" stroke=\"green\" data-x=\"1\"/>
+</svg>
+");
+}
+
+#[test]
+#[should_panic]
+fn update_values_2() {
+    let mut doc = Document::new();
+    let svg = svg!(doc, Svg {
+        LinearGradient(id = "lg1")
+        Rect(Fill = "red")
+    });
+    let lg = svg.first_child().unwrap();
+    let mut rect = svg.last_child().unwrap();
+    doc.root().append(svg);
+
+    // Links cannot be set via the visitor.
+    rect.update_values(|_, value| *value = AttributeValue::FuncLink(lg.clone()));
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn scale_1() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1' x1='0.5'/>
+    <linearGradient id='lg2' x1='10' gradientUnits='userSpaceOnUse'/>
+    <clipPath id='clip1' clipPathUnits='objectBoundingBox'>
+        <rect width='0.5'/>
+    </clipPath>
+    <text font-size='10' x='5 10' dy='1em'>Text</text>
+    <path d='M 10 20 a 5 5 45 0 1 10 0 H 30 Z'/>
+</svg>").unwrap();
+
+    doc.scale(2.0);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1' x1='0.5'/>
+    <linearGradient id='lg2' gradientUnits='userSpaceOnUse' x1='20'/>
+    <clipPath id='clip1' clipPathUnits='objectBoundingBox'>
+        <rect width='0.5'/>
+    </clipPath>
+    <text dy='1em' font-size='20' x='10 20'>Text</text>
+    <path d='M 20 40 a 10 10 45 0 1 20 0 H 60 Z'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn scale_2() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <marker id='m1' markerWidth='3'>
+        <rect width='1'/>
+    </marker>
+    <marker id='m2' markerUnits='userSpaceOnUse' markerWidth='3'>
+        <rect width='1'/>
+    </marker>
+    <filter id='f1'>
+        <feOffset dx='2' dy='3'/>
+        <feGaussianBlur stdDeviation='1 2'/>
+        <feMorphology radius='4'/>
+        <feDisplacementMap scale='5'/>
+    </filter>
+</svg>").unwrap();
+
+    doc.scale(2.0);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <marker id='m1' markerWidth='3'>
+        <rect width='1'/>
+    </marker>
+    <marker id='m2' markerUnits='userSpaceOnUse' markerWidth='6'>
+        <rect width='2'/>
+    </marker>
+    <filter id='f1'>
+        <feOffset dx='4' dy='6'/>
+        <feGaussianBlur stdDeviation='2 4'/>
+        <feMorphology radius='8'/>
+        <feDisplacementMap scale='10'/>
+    </filter>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn translate_1() {
+    let text = "\
+<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <g>
+        <path d='m 10 20 l 10 0 H 40 Z'/>
+        <circle cx='5mm' r='5'/>
+        <text x='5 10'>Text<tspan y='20'>Text</tspan></text>
+    </g>
+    <rect fill='url(#lg1)' width='10'/>
+</svg>";
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+
+    let mut doc = Document::from_str(text).unwrap();
+    doc.translate(5.0, 10.0, TranslateMode::Rewrite);
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <g>
+        <path d='m 15 30 l 10 0 H 45 Z'/>
+        <circle cx='5mm' r='5' transform='matrix(1 0 0 1 5 10)'/>
+        <text x='10 15' y='10'>Text<tspan y='30'>Text</tspan></text>
+    </g>
+    <rect fill='url(#lg1)' transform='matrix(1 0 0 1 5 10)' width='10'/>
+</svg>
+");
+
+    let mut doc = Document::from_str(text).unwrap();
+    doc.translate(5.0, 10.0, TranslateMode::Wrap);
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <g transform='matrix(1 0 0 1 5 10)'>
+        <linearGradient id='lg1'/>
+        <g>
+            <path d='m 10 20 l 10 0 H 40 Z'/>
+            <circle cx='5mm' r='5'/>
+            <text x='5 10'>Text<tspan y='20'>Text</tspan></text>
+        </g>
+        <rect fill='url(#lg1)' width='10'/>
+    </g>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn translate_2() {
+    // `use` elements must not be moved twice
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <rect id='rect2' width='10'/>
+        <use id='use1' xlink:href='#rect1'/>
+    </defs>
+    <rect id='rect1' width='10'/>
+    <use xlink:href='#rect1' x='50'/>
+    <use xlink:href='#rect1' x='50' transform='scale(2)'/>
+    <use xlink:href='#rect1' x='10%'/>
+    <use xlink:href='#rect2' x='50'/>
+</svg>").unwrap();
+
+    doc.translate(100.0, 0.0, TranslateMode::Rewrite);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <rect id='rect2' width='10'/>
+        <use id='use1' xlink:href='#rect1' x='-100' y='0'/>
+    </defs>
+    <rect id='rect1' width='10' x='100' y='0'/>
+    <use xlink:href='#rect1' x='50'/>
+    <use xlink:href='#rect1' transform='matrix(2 0 0 2 100 0)' x='-50' y='0'/>
+    <use xlink:href='#rect1' x='10%'/>
+    <use xlink:href='#rect2' x='150' y='0'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn merge_1() {
+    let mut doc = Document::new();
+
+    for _ in 0..2 {
+        let other = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <linearGradient id='lg2' xlink:href='#lg1'/>
+    <rect fill='url(#lg2)'/>
+</svg>").unwrap();
+
+        doc.merge(other, None);
+    }
+
+    // All links must point to the nodes of the current document.
+    let links: Vec<_> = doc.links().collect();
+    assert_eq!(links.len(), 4);
+    for (src, _, dst) in links {
+        assert!(doc.root().descendants().any(|n| n == src));
+        assert!(doc.root().descendants().any(|n| n == dst));
+        assert_eq!(src.parent(), dst.parent());
+    }
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <g>
+        <linearGradient id='lg1'/>
+        <linearGradient id='lg2' xlink:href='#lg1'/>
+        <rect fill='url(#lg2)'/>
+    </g>
+    <g>
+        <linearGradient id='a'/>
+        <linearGradient id='b' xlink:href='#a'/>
+        <rect fill='url(#b)'/>
+    </g>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn add_as_symbol_1() {
+    let icon = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 10 10' preserveAspectRatio='xMinYMin'>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#lg1)' width='10' height='10'/>
+    <rect id='rect1' filter='url(#missing)'/>
+</svg>").unwrap();
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect id='rect1'/>
+</svg>").unwrap();
+
+    doc.add_as_symbol(&icon, "icon1");
+    doc.add_as_symbol(&icon, "icon2");
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <defs>
+        <symbol id='icon1' preserveAspectRatio='xMinYMin' viewBox='0 0 10 10'>
+            <linearGradient id='lg1'/>
+            <rect fill='url(#lg1)' height='10' width='10'/>
+            <rect id='a' filter='url(#missing)'/>
+        </symbol>
+        <symbol id='icon2' preserveAspectRatio='xMinYMin' viewBox='0 0 10 10'>
+            <linearGradient id='b'/>
+            <rect fill='url(#b)' height='10' width='10'/>
+            <rect id='c' filter='url(#missing)'/>
+        </symbol>
+    </defs>
+    <rect id='rect1'/>
+</svg>
+");
+}
+
+#[test]
+fn get_property_1() {
+    let mut doc = Document::new();
+    let mut rect = doc.create_element(EId::Rect);
+    rect.set_attribute((AId::StrokeWidth, 2.0));
+    rect.set_attribute((AId::Width, 10.0));
+
+    assert_eq!(rect.get_property("stroke-width"), Some(AttributeValue::Number(2.0)));
+    assert_eq!(rect.get_property("stroke-opacity"), None);
+    // Not a presentation attribute.
+    assert_eq!(rect.get_property("width"), None);
+    assert_eq!(rect.get_property("strokeWidth"), None);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn external_references_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <linearGradient xlink:href='#lg1'/>
+    <image xlink:href='http://example.com/image.png'/>
+    <image xlink:href='DATA:image/svg+xml,%3Csvg/%3E'/>
+    <image xlink:href='http://example.com/image.png'/>
+    <rect filter='url(#missing)' cursor=\"url('a.cur'), url(b.cur), auto\"/>
+</svg>").unwrap();
+
+    assert_eq!(doc.external_references(), vec![
+        ExternalReference::Url("http://example.com/image.png".to_string()),
+        ExternalReference::Data("image/svg+xml".to_string()),
+        ExternalReference::Url("a.cur".to_string()),
+        ExternalReference::Url("b.cur".to_string()),
+    ]);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn inline_references_1() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <rect id='icon1'/>
+    <use xlink:href='icons.svg#icon1'/>
+    <use xlink:href='icons.svg#icon1' x='10'/>
+    <use xlink:href='icons.svg#missing'/>
+    <use xlink:href='other.svg#icon1'/>
+    <image xlink:href='image.svg'/>
+</svg>").unwrap();
+
+    let mut requests = Vec::new();
+    doc.inline_references(|url| {
+        requests.push(url.to_string());
+        match url {
+            "icons.svg" => Some(b"<svg xmlns='http://www.w3.org/2000/svg'>
+                <linearGradient id='lg1'/>
+                <g id='icon1'><rect fill='url(#lg1)'/></g>
+            </svg>".to_vec()),
+            "image.svg" => Some(b"<svg/>".to_vec()),
+            _ => None,
+        }
+    });
+
+    assert_eq!(requests, vec!["icons.svg", "other.svg", "image.svg"]);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <linearGradient id='lg1'/>
+        <g id='a'>
+            <rect fill='url(#lg1)'/>
+        </g>
+    </defs>
+    <rect id='icon1'/>
+    <use xlink:href='#a'/>
+    <use xlink:href='#a' x='10'/>
+    <use xlink:href='icons.svg#missing'/>
+    <use xlink:href='other.svg#icon1'/>
+    <image xlink:href='data:image/svg+xml;base64,PHN2Zy8+'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn polygon_to_polyline_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <polygon points='10 20 30 40 50 60'/>
+    <polygon points='10 20 30 40 10 20'/>
+    <polyline points='10 20 30 40'/>
+    <rect/>
+</svg>").unwrap();
+
+    for mut node in doc.svg_element().unwrap().children() {
+        node.polygon_to_polyline();
+    }
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <polyline points='10 20 30 40 50 60 10 20'/>
+    <polyline points='10 20 30 40 10 20'/>
+    <polyline points='10 20 30 40'/>
+    <rect/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn validate_content_model_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:x='http://www.example.com'>
+    <tspan/>
+    <text>Text<tspan><tspan/></tspan></text>
+    <filter><feMerge><feMergeNode/><rect/></feMerge></filter>
+    <rect><desc>Text</desc><g/></rect>
+    <x:data><stop/></x:data>
+</svg>").unwrap();
+
+    let errors: Vec<_> = doc.validate_content_model().iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors, vec![
+        "element 'tspan' is not allowed inside 'svg'",
+        "element 'rect' is not allowed inside 'feMerge'",
+        "element 'g' is not allowed inside 'rect'",
+    ]);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn features_used_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <path id='path1' d='M 10 20 L 30 40'/>
+    <mask id='mask1'/>
+    <text mask='url(#mask1)'><textPath xlink:href='#path1'>Text</textPath></text>
+    <rect><set attributeName='width' to='10'/></rect>
+</svg>").unwrap();
+
+    let mut expected = FeatureSet::default();
+    expected.masking = true;
+    expected.text = true;
+    expected.text_on_path = true;
+    expected.animation = true;
+    assert_eq!(doc.features_used(), expected);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn add_missing_defaults_1() {
+    use svgdom::postproc;
+
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <g stroke-width='2'>
+        <rect fill-rule='inherit'/>
+    </g>
+    <rect stroke-opacity='0.5' clip-rule='evenodd'/>
+</svg>").unwrap();
+
+    // Only the children are processed.
+    let children: Vec<_> = doc.svg_element().unwrap().children().collect();
+    for child in children {
+        postproc::add_missing_defaults(&child, &[AId::StrokeWidth, AId::StrokeOpacity,
+                                                 AId::FillRule, AId::Width]);
+    }
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <g fill-rule='nonzero' stroke-opacity='1' stroke-width='2'>
+        <rect fill-rule='inherit'/>
+    </g>
+    <rect clip-rule='evenodd' fill-rule='nonzero' stroke-opacity='0.5' stroke-width='1'/>
+</svg>
+");
+}
+
+#[test]
+fn iter_sorted_1() {
+    use svgdom::AttributesOrder;
+
+    let mut doc = Document::new();
+    let mut svg = doc.create_element(EId::Svg);
+    doc.root().append(svg.clone());
+    let mut rect = doc.create_element(EId::Rect);
+    svg.append(rect.clone());
+    rect.set_attribute((AId::Height, 10.0));
+    rect.set_attribute(("data-name", "rect"));
+    rect.set_attribute((AId::StrokeWidth, 2.0));
+    rect.set_attribute((AId::Transform, "translate(10)"));
+    rect.set_attribute((AId::Fill, "red"));
+    rect.set_attribute((AId::Opacity, 0.5));
+
+    let names = |order| {
+        rect.attributes().iter_sorted(order, rect.tag_id())
+            .map(|a| a.name.to_string()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(names(AttributesOrder::AsIs),
+               ["height", "data-name", "stroke-width", "transform", "fill", "opacity"]);
+    assert_eq!(names(AttributesOrder::Alphabetical),
+               ["fill", "height", "opacity", "stroke-width", "transform", "data-name"]);
+    assert_eq!(names(AttributesOrder::Specification),
+               ["fill", "stroke-width", "opacity", "transform", "height", "data-name"]);
+
+    // The storage order is not affected.
+    assert_eq!(rect.attributes().iter().next().unwrap().name.to_string(), "height");
+
+    // The writer uses the same order.
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    opt.attributes_order = AttributesOrder::Specification;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect fill='red' stroke-width='2' opacity='0.5' transform='translate(10)' height='10' \
+data-name='rect'/>
+</svg>
+");
+}
+
+#[test]
+fn node_id_1() {
+    let mut doc = Document::new();
+    let rect = doc.create_element(EId::Rect);
+    let id = rect.node_id().unwrap();
+    assert_eq!(doc.root().node_id().map(|id| doc.node(id)), Some(Some(doc.root())));
+
+    // Detached nodes are still valid.
+    let mut g = doc.create_element(EId::G);
+    g.append(rect.clone());
+    rect.clone().detach();
+    assert_eq!(doc.node(id), Some(rect.clone()));
+
+    doc.remove_node(rect);
+    assert_eq!(doc.node(id), None);
+
+    // A storage slot can be reused, but the handle must stay invalid.
+    let circle = doc.create_element(EId::Circle);
+    assert_eq!(doc.node(id), None);
+    assert_ne!(circle.node_id(), Some(id));
+    assert_eq!(doc.node(circle.node_id().unwrap()), Some(circle));
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn remove_nodes_1() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <g>
+        <linearGradient id='lg1'/>
+        <rect id='rect1' fill='url(#lg1)'/>
+    </g>
+    <use xlink:href='#rect1'/>
+    <circle fill='url(#lg1)'/>
+    <ellipse/>
+</svg>").unwrap();
+
+    let g = doc.root().descendants().find(|n| n.is_tag_name(EId::G)).unwrap();
+    let rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    let lg = doc.root().descendants().find(|n| n.is_tag_name(EId::LinearGradient)).unwrap();
+    let ellipse = doc.root().descendants().find(|n| n.is_tag_name(EId::Ellipse)).unwrap();
+
+    // Descendants are listed before the ancestor.
+    assert_eq!(doc.remove_nodes(&[rect.clone(), lg, g, ellipse, rect]), 4);
+
+    assert_eq!(doc.to_string(),
+"<svg xmlns=\"http://www.w3.org/2000/svg\">
+    <use/>
+    <circle/>
+</svg>
+");
+}
+
+#[test]
+fn set_text_checked_1() {
+    use svgdom::{Error, NodeType};
+
+    let mut doc = Document::new();
+    let mut comment = doc.create_node(NodeType::Comment, "text");
+    assert_eq!(comment.set_text_checked("comment"), Ok(()));
+    assert_eq!(*comment.text(), "comment");
+
+    let mut rect = doc.create_element(EId::Rect);
+    assert_eq!(rect.set_text_checked("text"), Err(Error::InvalidNodeType));
+    assert_eq!(*rect.text(), "");
+
+    assert_eq!(doc.root().set_text_checked("text"), Err(Error::InvalidNodeType));
+}
+
+#[test]
+fn set_tag_name_checked_1() {
+    use svgdom::{Error, NodeType};
+
+    let mut doc = Document::new();
+    let mut rect = doc.create_element(EId::Rect);
+    assert_eq!(rect.set_tag_name_checked("custom"), Ok(()));
+    assert!(rect.is_tag_name("custom"));
+
+    let mut comment = doc.create_node(NodeType::Comment, "text");
+    assert_eq!(comment.set_tag_name_checked(EId::Rect), Err(Error::InvalidNodeType));
+    assert_eq!(doc.root().set_tag_name_checked(EId::Rect), Err(Error::InvalidNodeType));
+    assert!(!comment.is_tag_name(EId::Rect));
+}
+
+#[test]
+#[should_panic]
+fn set_tag_name_non_element_1() {
+    use svgdom::NodeType;
+
+    let mut doc = Document::new();
+    let mut comment = doc.create_node(NodeType::Comment, "text");
+    comment.set_tag_name(EId::Rect);
+}
+
+#[test]
+fn preceding_following_1() {
+    let mut doc = Document::new();
+    let mut svg = doc.create_element(EId::Svg);
+    doc.root().append(svg.clone());
+
+    // svg
+    //   g1
+    //     rect1
+    //     rect2
+    //   g2
+    //     g3
+    //       rect3
+    //   rect4
+    let mut add = |parent: &mut svgdom::Node, id: &str| {
+        let mut node = doc.create_element(EId::G);
+        node.set_id(id);
+        parent.append(node.clone());
+        node
+    };
+
+    let mut g1 = add(&mut svg, "g1");
+    let rect1 = add(&mut g1, "rect1");
+    let rect2 = add(&mut g1, "rect2");
+    let mut g2 = add(&mut svg, "g2");
+    let mut g3 = add(&mut g2, "g3");
+    let rect3 = add(&mut g3, "rect3");
+    let rect4 = add(&mut svg, "rect4");
+
+    fn ids<I: Iterator<Item = svgdom::Node>>(iter: I) -> Vec<String> {
+        iter.map(|n| n.id().clone()).collect()
+    }
+
+    assert_eq!(ids(g3.preceding()), ["rect2", "rect1", "g1"]);
+    assert_eq!(ids(rect3.preceding()), ["rect2", "rect1", "g1"]);
+    assert_eq!(ids(rect4.preceding()), ["rect3", "g3", "g2", "rect2", "rect1", "g1"]);
+    assert_eq!(ids(rect1.preceding()), Vec::<String>::new());
+
+    assert_eq!(ids(g1.following()), ["g2", "g3", "rect3", "rect4"]);
+    assert_eq!(ids(rect2.following()), ["g2", "g3", "rect3", "rect4"]);
+    assert_eq!(ids(rect3.following()), ["rect4"]);
+    assert_eq!(ids(rect4.following()), Vec::<String>::new());
+    assert_eq!(doc.root().following().count(), 0);
+    assert_eq!(doc.root().preceding().count(), 0);
+}
+
+#[test]
+fn name_validator_1() {
+    use svgdom::{Error, is_valid_xml_name};
+
+    assert!(is_valid_xml_name("_a-b.c:d"));
+    assert!(is_valid_xml_name("élément"));
+    assert!(!is_valid_xml_name("-a"));
+    assert!(!is_valid_xml_name("a<b"));
+    assert!(!is_valid_xml_name("a\"b"));
+
+    // Any name is allowed by default.
+    let mut doc = Document::new();
+    let mut node = doc.create_element("my element");
+    assert_eq!(node.set_attribute_checked(("1 attr", "value")), Ok(()));
+
+    doc.set_name_validator(Some(is_valid_xml_name));
+    // Existing nodes use the new validator too.
+    assert_eq!(node.set_attribute_checked(("2 attr", "value")), Err(Error::InvalidName));
+
+    let mut node = doc.create_element("my:element");
+    assert_eq!(node.set_tag_name_checked("my element"), Err(Error::InvalidName));
+    assert!(node.is_tag_name("my:element"));
+    assert_eq!(node.set_attribute_checked(("1 attr", "value")), Err(Error::InvalidName));
+    assert!(node.attributes().is_empty());
+
+    // SVG names are not checked.
+    fn reject_all(_: &str) -> bool { false }
+    doc.set_name_validator(Some(reject_all));
+    let mut node = doc.create_element(EId::Rect);
+    node.set_tag_name(EId::Circle);
+    node.set_attribute((AId::Fill, "red"));
+    assert_eq!(node.set_attribute_checked(("fill2", "red")), Err(Error::InvalidName));
+}
+
+#[test]
+#[should_panic]
+fn name_validator_2() {
+    let mut doc = Document::new();
+    doc.set_name_validator(Some(svgdom::is_valid_xml_name));
+    doc.create_element("1rect");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn to_document_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <linearGradient id='lg1'/>
+        <linearGradient id='lg2' xlink:href='#lg1'/>
+        <clipPath id='clip1'/>
+    </defs>
+    <g fill='url(#lg2)' opacity='0.5'>
+        <g id='g1' clip-path='url(#clip1)' stroke='red'>
+            <rect id='rect1' fill='green'/>
+        </g>
+    </g>
+</svg>").unwrap();
+
+    let g = doc.root().descendants().find(|n| *n.id() == "g1").unwrap();
+    let new_doc = g.to_document();
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(new_doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <clipPath id='clip1'/>
+        <linearGradient id='lg2' xlink:href='#lg1'/>
+        <linearGradient id='lg1'/>
+    </defs>
+    <g id='g1' clip-path='url(#clip1)' fill='url(#lg2)' stroke='#ff0000'>
+        <rect id='rect1' fill='#008000'/>
+    </g>
+</svg>
+");
+
+    // The original document is unchanged.
+    assert_eq!(doc.root().descendants().filter(|n| n.is_element()).count(), 8);
+
+    // The root node is copied as is.
+    let copy = doc.root().to_document();
+    assert_eq!(copy.with_write_opt(&opt).to_string(), doc.with_write_opt(&opt).to_string());
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn len_bytes_1() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <!--comment-->
+    <g fill='red'>
+        <rect width='10' height='10'/>
+    </g>
+    <text>Text <tspan>text</tspan></text>
+</svg>").unwrap();
+
+    let mut opt = WriteOptions::default();
+    assert_eq!(doc.len_bytes(&opt), doc.with_write_opt(&opt).to_string().len());
+
+    opt.indent = Indent::None;
+    opt.use_single_quote = true;
+    assert_eq!(doc.len_bytes(&opt), doc.with_write_opt(&opt).to_string().len());
+
+    assert_eq!(doc.node_count(), 8);
+    assert_eq!(doc.attribute_count(), 3);
+
+    // Detached nodes are not counted.
+    let rect = doc.create_element(EId::Rect);
+    assert_eq!(doc.node_count(), 8);
+    doc.svg_element().unwrap().append(rect);
+    assert_eq!(doc.node_count(), 9);
+}
+
+#[test]
+fn try_accessors_1() {
+    let mut doc = Document::new();
+    let mut rect = doc.create_element(EId::Rect);
+    rect.set_attribute((AId::Width, 10.0));
+
+    assert_eq!(rect.try_attributes().unwrap().len(), 1);
+    assert_eq!(rect.try_text().unwrap().as_str(), "");
+
+    {
+        let mut rect2 = rect.clone();
+        let _attrs = rect2.attributes_mut();
+        assert!(rect.try_attributes().is_none());
+        assert!(rect.try_text().is_none());
+    }
+
+    assert!(rect.try_attributes().is_some());
+}
+
+#[test]
+fn try_accessors_2() {
+    let mut doc = Document::new();
+    let mut rect = doc.create_element(EId::Rect);
+    rect.set_id("rect1");
+    rect.set_attribute((AId::Width, 10.0));
+
+    assert_eq!(rect.try_node_type(), Some(svgdom::NodeType::Element));
+    assert_eq!(rect.try_id().unwrap().as_str(), "rect1");
+    assert!(rect.try_tag_name().is_some());
+
+    {
+        let _id = rect.id();
+        let mut rect2 = rect.clone();
+        assert!(rect2.try_attributes_mut().is_none());
+        assert!(rect2.try_text_mut().is_none());
+        // Shared borrows are allowed.
+        assert!(rect2.try_tag_name().is_some());
+    }
+
+    {
+        let mut rect2 = rect.clone();
+        let _text = rect2.try_text_mut().unwrap();
+        assert_eq!(rect.try_node_type(), None);
+        assert!(rect.try_id().is_none());
+        assert!(rect.try_tag_name().is_none());
+    }
+
+    *rect.try_attributes_mut().unwrap().get_value_mut(AId::Width).unwrap() = 20.0.into();
+    assert_eq!(rect.attributes().get_value(AId::Width), Some(&AttributeValue::from(20.0)));
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn xpath_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <!--comment-->
+    <g>
+        <rect/>
+        <rect/>
+    </g>
+    <g>
+        <text>Text<tspan>Span</tspan>Text</text>
+    </g>
+</svg>").unwrap();
+
+    // Every node must be resolved back to itself.
+    for node in doc.root().descendants() {
+        assert_eq!(doc.node_at_xpath(&node.xpath()), Some(node.clone()));
+    }
+
+    let rect = doc.node_at_xpath("/svg/g[1]/rect[2]").unwrap();
+    assert!(rect.is_tag_name(EId::Rect));
+    assert_eq!(rect.previous_sibling().unwrap().xpath(), "/svg/g[1]/rect[1]");
+
+    assert_eq!(doc.node_at_xpath("/svg/comment()").unwrap().xpath(), "/svg/comment()[1]");
+    assert_eq!(doc.node_at_xpath("/svg/g[2]/text/text()[2]").unwrap().text().as_str(), "Text");
+    assert_eq!(doc.root().xpath(), "/");
+    assert_eq!(doc.node_at_xpath("/"), Some(doc.root()));
+
+    assert_eq!(doc.node_at_xpath(""), None);
+    assert_eq!(doc.node_at_xpath("svg"), None);
+    assert_eq!(doc.node_at_xpath("//svg"), None);
+    assert_eq!(doc.node_at_xpath("/svg/g[0]"), None);
+    assert_eq!(doc.node_at_xpath("/svg/g[x]"), None);
+    assert_eq!(doc.node_at_xpath("/svg/g[1"), None);
+    assert_eq!(doc.node_at_xpath("/svg/circle"), None);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn normalize_dasharray_1() {
+    use svgdom::postproc;
+
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <path stroke-dasharray='5'/>
+    <path stroke-dasharray='5 3 2'/>
+    <path stroke-dasharray='5mm 3%'/>
+    <path stroke-dasharray='0 0 0'/>
+    <path stroke-dasharray='5 -1 2'/>
+    <path stroke-dasharray='none'/>
+    <g stroke-dasharray='4 2'>
+        <path stroke-dasharray='0'/>
+        <path stroke-dasharray='-4'/>
+    </g>
+</svg>").unwrap();
+
+    postproc::normalize_dasharray(&doc.root());
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <path stroke-dasharray='5 5'/>
+    <path stroke-dasharray='5 3 2 5 3 2'/>
+    <path stroke-dasharray='5mm 3%'/>
+    <path/>
+    <path/>
+    <path stroke-dasharray='none'/>
+    <g stroke-dasharray='4 2'>
+        <path stroke-dasharray='none'/>
+        <path/>
+    </g>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn merge_sibling_shapes_1() {
+    use svgdom::postproc;
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <g fill='#ff0000'>
+        <path id='p1' d='m 10 10 h 10 v 10 z'/>
+        <path d='m 30 10 h 10 v 10 z'/>
+        <circle cx='60' cy='15' r='5'/>
+    </g>
+    <rect id='rect1' width='10' height='10'/>
+    <rect x='20' width='10' height='10'/>
+    <rect x='40' width='10' height='10' fill='url(#lg1)'/>
+    <rect x='60' width='10' height='10' fill='url(#lg1)'/>
+    <rect y='20' width='10' height='10' stroke='#000000' stroke-width='4'/>
+    <rect x='12' y='20' width='10' height='10' stroke='#000000' stroke-width='4'/>
+    <rect y='40' width='10' height='10' opacity='0.5'/>
+    <rect x='20' y='40' width='10' height='10'/>
+    <use xlink:href='#rect1'/>
+</svg>").unwrap();
+
+    assert_eq!(postproc::merge_sibling_shapes(&mut doc), 2);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <g fill='#ff0000'>
+        <path id='p1' d='m 10 10 h 10 v 10 z M 30 10 h 10 v 10 z M 65 15 A 5 5 0 0 1 60 20 A 5 5 0 0 1 55 15 \
+A 5 5 0 0 1 60 10 A 5 5 0 0 1 65 15 Z'/>
+    </g>
+    <rect id='rect1' height='10' width='10'/>
+    <rect height='10' width='10' x='20'/>
+    <rect fill='url(#lg1)' height='10' width='10' x='40'/>
+    <rect fill='url(#lg1)' height='10' width='10' x='60'/>
+    <rect height='10' stroke='#000000' stroke-width='4' width='10' y='20'/>
+    <rect height='10' stroke='#000000' stroke-width='4' width='10' x='12' y='20'/>
+    <rect height='10' opacity='0.5' width='10' y='40'/>
+    <rect height='10' width='10' x='20' y='40'/>
+    <use xlink:href='#rect1'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn merge_sibling_shapes_2() {
+    use svgdom::postproc;
+
+    // Round joins don't extend the stroke further than the half of its width.
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='10' height='10' stroke='#000000' stroke-width='4' stroke-linejoin='round'/>
+    <rect x='15' width='10' height='10' stroke='#000000' stroke-width='4' stroke-linejoin='round'/>
+</svg>").unwrap();
+
+    assert_eq!(postproc::merge_sibling_shapes(&mut doc), 1);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn stroke_bounding_box_1() {
+    use svgdom::Rect;
+
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+    <rect x='10' y='10' width='10' height='10' transform='scale(2)'/>
+    <rect x='10' y='10' width='10' height='10' stroke='#000000' stroke-width='2'/>
+    <path d='M 0 10 L 10 0 L 20 10' stroke='#000000' stroke-width='2'/>
+    <path d='M 0 10 L 10 0 L 20 10' stroke='#000000' stroke-width='2' stroke-miterlimit='1.2'/>
+    <path d='M 0 10 L 10 0 L 20 10' stroke='#000000' stroke-width='2' stroke-linejoin='round'/>
+    <path d='M 10 10 L 30 10' stroke='#000000' stroke-width='10%'/>
+    <path d='M 10 10 L 30 10' stroke='#000000' stroke-width='1em'/>
+    <g/>
+</svg>").unwrap();
+
+    let round = |r: Rect| {
+        let f = |n: f64| (n * 1000.0).round() / 1000.0;
+        (f(r.x), f(r.y), f(r.width), f(r.height))
+    };
+
+    let nodes: Vec<_> = doc.svg_element().unwrap().children().collect();
+
+    // No stroke.
+    assert_eq!(nodes[0].bounding_box(), Some(Rect { x: 10.0, y: 10.0, width: 10.0, height: 10.0 }));
+    assert_eq!(nodes[0].stroke_bounding_box(), nodes[0].bounding_box());
+
+    // Right angle miters are the same as the stroke offset.
+    assert_eq!(nodes[1].stroke_bounding_box(), Some(Rect { x: 9.0, y: 9.0, width: 12.0, height: 12.0 }));
+
+    // A miter tip.
+    assert_eq!(round(nodes[2].stroke_bounding_box().unwrap()), (-1.0, -1.414, 22.0, 12.414));
+    // The miter limit is exceeded, so a bevel join is used.
+    assert_eq!(round(nodes[3].stroke_bounding_box().unwrap()), (-1.0, -1.0, 22.0, 12.0));
+    assert_eq!(round(nodes[4].stroke_bounding_box().unwrap()), (-1.0, -1.0, 22.0, 12.0));
+
+    // Relative to the viewport diagonal.
+    assert_eq!(nodes[5].stroke_bounding_box(), Some(Rect { x: 5.0, y: 5.0, width: 30.0, height: 10.0 }));
+
+    assert_eq!(nodes[6].bounding_box(), Some(Rect { x: 10.0, y: 10.0, width: 20.0, height: 0.0 }));
+    assert_eq!(nodes[6].stroke_bounding_box(), None);
+
+    assert_eq!(nodes[7].bounding_box(), None);
+    assert_eq!(nodes[7].stroke_bounding_box(), None);
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn resolve_size_1() {
+    let size = |text: &str| Document::from_str(text).unwrap().resolve_size((300.0, 150.0));
+
+    assert_eq!(size("<svg xmlns='http://www.w3.org/2000/svg' width='20' height='10'/>"), (20.0, 10.0));
+    assert_eq!(size("<svg xmlns='http://www.w3.org/2000/svg' width='20mm' height='1in'/>").1, 96.0);
+    assert_eq!(size("<svg xmlns='http://www.w3.org/2000/svg' width='10%' height='100%'/>"), (30.0, 150.0));
+    assert_eq!(size("<svg xmlns='http://www.w3.org/2000/svg' height='40' viewBox='0 0 20 10'/>"), (80.0, 40.0));
+    assert_eq!(size("<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 20 10'/>"), (20.0, 10.0));
+    assert_eq!(size("<svg xmlns='http://www.w3.org/2000/svg' width='20'/>"), (20.0, 150.0));
+    assert_eq!(size("<svg xmlns='http://www.w3.org/2000/svg' width='2em' height='-5'/>"), (300.0, 150.0));
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn is_reachable_from_root_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <linearGradient id='lg1'>
+            <stop id='stop1' offset='0'/>
+        </linearGradient>
+        <clipPath id='clip1'>
+            <rect id='rect1'/>
+        </clipPath>
+        <g id='g1'>
+            <rect id='rect2'/>
+            <use id='use1' xlink:href='#symbol1'/>
+        </g>
+        <g id='g2'>
+            <rect id='rect3'/>
+        </g>
+        <symbol id='symbol1'>
+            <rect id='rect4'/>
+        </symbol>
+        <symbol id='symbol2'>
+            <rect id='rect5'/>
+        </symbol>
+        <use id='use2' xlink:href='#g2'/>
+        <use id='use3' xlink:href='#lg1'/>
+    </defs>
+    <g id='g3'>
+        <rect id='rect6' fill='url(#lg1)' clip-path='url(#clip1)'/>
+        <use id='use4' xlink:href='#g1'/>
+    </g>
+    <g id='g4'>
+        <symbol id='symbol3'>
+            <rect id='rect7'/>
+        </symbol>
+    </g>
+</svg>").unwrap();
+
+    let is_reachable = |id: &str| {
+        doc.root().descendants().find(|n| *n.id() == id).unwrap().is_reachable_from_root()
+    };
+
+    assert!(!doc.root().is_reachable_from_root());
+    assert!(doc.svg_element().unwrap().is_reachable_from_root());
+
+    // Referenced elements are not rendered by themselves.
+    assert!(!is_reachable("lg1"));
+    assert!(!is_reachable("stop1"));
+    assert!(!is_reachable("rect1"));
+
+    // Rendered via a `use` element.
+    assert!(is_reachable("g1"));
+    assert!(is_reachable("rect2"));
+    // A `use` inside a reachable `use` tree.
+    assert!(is_reachable("symbol1"));
+    assert!(is_reachable("rect4"));
+
+    // A `use` element inside `defs` doesn't make anything reachable.
+    assert!(!is_reachable("use2"));
+    assert!(!is_reachable("rect3"));
+    assert!(!is_reachable("symbol2"));
+    assert!(!is_reachable("rect5"));
+
+    assert!(is_reachable("g3"));
+    assert!(is_reachable("rect6"));
+    assert!(is_reachable("use4"));
+
+    // A `symbol` is never rendered directly.
+    assert!(is_reachable("g4"));
+    assert!(!is_reachable("symbol3"));
+    assert!(!is_reachable("rect7"));
+}
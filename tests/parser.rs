@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![cfg(feature = "parsing")]
+
 #[macro_use] extern crate pretty_assertions;
 
 extern crate svgdom;
@@ -17,6 +19,7 @@ use svgdom::{
     AttributeValue,
     Document,
     ElementId as EId,
+    KeywordCheck,
     TagNameRef,
     NodeType,
     ParseOptions,
@@ -92,6 +95,45 @@ fn parse_comment_1() {
     assert_eq!(doc.root().children().count(), 2);
 }
 
+#[test]
+fn parse_pi_1() {
+    let text = "<?xml version='1.0'?>
+<?xml-stylesheet href='style.css' type='text/css'?>
+<svg xmlns='http://www.w3.org/2000/svg'>
+    <?target?>
+    <rect/>
+    <text>Text<?target data?></text>
+</svg>
+<?end?>
+";
+
+    let doc = Document::from_str(text).unwrap();
+
+    let mut pi = doc.root().first_child().unwrap();
+    assert_eq!(pi.node_type(), NodeType::ProcessingInstruction);
+    assert_eq!(&*pi.pi_target().unwrap(), "xml-stylesheet");
+    assert_eq!(&*pi.pi_data().unwrap(), "href='style.css' type='text/css'");
+    assert_eq!(doc.root().children().count(), 3);
+
+    pi.set_pi_data("href='new.css'").unwrap();
+    assert!(doc.svg_element().unwrap().set_pi_data("data").is_err());
+
+    let svg = doc.svg_element().unwrap();
+    let pi = svg.first_child().unwrap();
+    assert_eq!(&*pi.pi_target().unwrap(), "target");
+    assert_eq!(&*pi.pi_data().unwrap(), "");
+
+    assert_eq!(TStr(doc.with_write_opt(&write_options()).to_string().as_str()), TStr(
+"<?xml-stylesheet href='new.css'?>
+<svg xmlns='http://www.w3.org/2000/svg'>
+    <?target?>
+    <rect/>
+    <text>Text<?target data?></text>
+</svg>
+<?end?>
+"));
+}
+
 #[test]
 fn parse_text_1() {
     let doc = Document::from_str("<svg xmlns='http://www.w3.org/2000/svg'>text</svg>").unwrap();
@@ -517,3 +559,377 @@ fn attr_value_error_6() {
 
 // TODO: this
 // p { font-family: "Font 1", "Font 2", Georgia, Times, serif; }
+
+#[test]
+fn parse_fragment_1() {
+    let doc = Document::from_fragment_str(
+"<linearGradient id='lg1'/>
+<!--comment-->
+<rect fill='url(#lg1)'/>
+<use xlink:href='#lg1'/>").unwrap();
+
+    assert_eq!(doc.svg_element(), None);
+    assert_eq!(doc.root().children().count(), 4);
+
+    let lg = doc.root().first_child().unwrap();
+    assert_eq!(lg.uses_count(), 2);
+
+    assert_eq!(TStr(doc.with_write_opt(&write_options()).to_string().as_str()), TStr(
+"<linearGradient id='lg1'/>
+<!--comment-->
+<rect fill='url(#lg1)'/>
+<use xlink:href='#lg1'/>
+"));
+}
+
+#[test]
+fn parse_fragment_2() {
+    let doc = Document::from_fragment_str("<rect/> text <svg/>").unwrap();
+
+    let mut iter = doc.root().children();
+    assert!(iter.next().unwrap().is_tag_name(EId::Rect));
+    assert_eq!(iter.next().unwrap().node_type(), NodeType::Text);
+    assert!(iter.next().unwrap().is_tag_name(EId::Svg));
+}
+
+#[test]
+fn parse_fragment_3() {
+    assert!(Document::from_fragment_str("<rect>").is_err());
+}
+
+#[test]
+fn parse_error_with_source_1() {
+    let text = "<svg xmlns='http://www.w3.org/2000/svg'>\n\t<rect fill='qwe'/>\n</svg>";
+    let err = Document::from_str(text).err().unwrap();
+    assert_eq!(err.with_source(text).to_string(),
+               "invalid attribute value at 2:14\n\t<rect fill='qwe'/>\n\t            ^");
+}
+
+#[test]
+fn parse_error_with_source_2() {
+    let text = "<svg/>";
+    let err = Document::from_str(text).err().unwrap();
+    assert_eq!(err.pos(), None);
+    assert_eq!(err.with_source(text).to_string(), err.to_string());
+}
+
+#[test]
+fn parse_max_nodes_1() {
+    let text = "<svg xmlns='http://www.w3.org/2000/svg'><!--comment--><text>text</text></svg>";
+
+    let mut opt = ParseOptions::default();
+    opt.max_nodes = Some(4);
+    assert!(Document::from_str_with_opt(text, &opt).is_ok());
+
+    opt.max_nodes = Some(3);
+    assert_eq!(Document::from_str_with_opt(text, &opt).err().unwrap().to_string(),
+               "the document has too many nodes");
+}
+
+#[test]
+fn parse_max_nodes_2() {
+    let mut text = String::from("<svg xmlns='http://www.w3.org/2000/svg'>");
+    for _ in 0..100000 {
+        text.push_str("<g/>");
+    }
+    text.push_str("</svg>");
+
+    let mut opt = ParseOptions::default();
+    opt.max_nodes = Some(1000);
+    assert!(Document::from_str_with_opt(&text, &opt).is_err());
+}
+
+#[test]
+fn parse_max_nodes_3() {
+    // The limit is checked before the XML parsing, so the missing end tag is not reported.
+    let mut text = String::from("<svg xmlns='http://www.w3.org/2000/svg'>");
+    for _ in 0..100000 {
+        text.push_str("<g/>");
+    }
+
+    let mut opt = ParseOptions::default();
+    opt.max_nodes = Some(1000);
+    assert_eq!(Document::from_str_with_opt(&text, &opt).err().unwrap().to_string(),
+               "the document has too many nodes");
+
+    // Markup inside comments is not counted.
+    let text = "<svg xmlns='http://www.w3.org/2000/svg'><!--<g/><g/>--></svg>";
+    opt.max_nodes = Some(2);
+    assert!(Document::from_str_with_opt(text, &opt).is_ok());
+}
+
+#[test]
+fn parse_allowed_attributes_1() {
+    let mut opt = ParseOptions::default();
+    opt.allowed_attributes = Some([AId::Id, AId::Fill, AId::Href, AId::Style].iter().cloned().collect());
+    opt.allowed_elements = Some([EId::Svg, EId::Rect, EId::LinearGradient, EId::Use]
+                                    .iter().cloned().collect());
+
+    let doc = Document::from_str_with_opt(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <script>alert(1)</script>
+    <linearGradient id='lg1'/>
+    <pattern id='patt1'/>
+    <rect fill='url(#lg1)' stroke='url(#lg1)' onclick='alert(1)' data-test='1'/>
+    <rect style='stroke:red;fill:green;custom:1'/>
+    <use xlink:href='#patt1'/>
+    <g>
+        <rect/>
+    </g>
+</svg>", &opt).unwrap();
+
+    assert_eq!(TStr(doc.with_write_opt(&write_options()).to_string().as_str()), TStr(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#lg1)'/>
+    <rect fill='#008000'/>
+    <use xlink:href='#patt1'/>
+</svg>
+"));
+}
+
+#[test]
+fn parse_safe_1() {
+    let doc = Document::from_str_with_opt(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'
+     onload='alert(1)'>
+    <script>alert(1)</script>
+    <script xlink:href='https://example.com/evil.js'/>
+    <a xlink:href='javascript:alert(1)'>
+        <rect id='rect1' onclick='alert(1)' onmouseover='alert(1)'/>
+    </a>
+    <a xlink:href=' JavaScript:alert(1)'/>
+    <use xlink:href='#rect1'/>
+    <use xlink:href='https://example.com/image.svg#rect1'/>
+    <image xlink:href='data:image/png;base64,AAAA'/>
+    <image xlink:href='data:image/svg+xml;base64,AAAA'/>
+    <image xlink:href='image.png'/>
+    <set attributeName='xlink:href' to='javascript:alert(1)'/>
+    <animate attributeName='xlink:href' values='javascript:alert(1)'/>
+    <foreignObject>
+        <iframe xmlns='http://www.w3.org/1999/xhtml' src='javascript:alert(1)'/>
+    </foreignObject>
+</svg>", &ParseOptions::safe()).unwrap();
+
+    assert_eq!(TStr(doc.with_write_opt(&write_options()).to_string().as_str()), TStr(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <a>
+        <rect id='rect1'/>
+    </a>
+    <a/>
+    <use xlink:href='#rect1'/>
+    <use/>
+    <image xlink:href='data:image/png;base64,AAAA'/>
+    <image/>
+    <image/>
+</svg>
+"));
+}
+
+#[test]
+fn parse_safe_2() {
+    // Each part can be disabled.
+    let mut opt = ParseOptions::safe();
+    opt.skip_event_attributes = false;
+
+    let doc = Document::from_str_with_opt(
+        "<svg xmlns='http://www.w3.org/2000/svg' onload='init()'/>", &opt).unwrap();
+    assert_eq!(doc.to_string(), "<svg xmlns=\"http://www.w3.org/2000/svg\" onload=\"init()\"/>\n");
+}
+
+#[test]
+fn parse_keyword_1() {
+    let doc = Document::from_str(
+        "<svg xmlns='http://www.w3.org/2000/svg' fill-rule='evenodd ' clip-rule='even-odd' \
+              display='none' font-family='Arial'/>").unwrap();
+
+    let svg = doc.svg_element().unwrap();
+    let attrs = svg.attributes();
+    assert_eq!(attrs.get(AId::FillRule).unwrap().check_keyword(), KeywordCheck::Valid);
+    // An unknown keyword is preserved as a string.
+    assert_eq!(attrs.get(AId::ClipRule).unwrap().check_keyword(), KeywordCheck::Invalid);
+    assert_eq!(attrs.get(AId::Display).unwrap().check_keyword(), KeywordCheck::Valid);
+    assert_eq!(attrs.get(AId::FontFamily).unwrap().check_keyword(), KeywordCheck::NotKeyword);
+}
+
+test_resave!(parse_entity_1,
+"<!DOCTYPE svg [
+    <!ENTITY color 'red'>
+    <!ENTITY title 'Title'>
+]>
+<svg xmlns='http://www.w3.org/2000/svg'>
+    <text fill='&color;'>&title;</text>
+</svg>
+",
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text fill='#ff0000'>Title</text>
+</svg>
+");
+
+#[test]
+fn parse_entity_2() {
+    let mut opt = ParseOptions::default();
+    opt.keep_unknown_entities = true;
+
+    let doc = Document::from_str_with_opt(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text fill='&color;' font-family='&font;'>&title; &amp; &#65;</text>
+</svg>
+", &opt).unwrap();
+
+    assert_eq!(doc.with_write_opt(&write_options()).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text fill='&color;' font-family='&font;'>&amp;title; &amp; A</text>
+</svg>
+");
+}
+
+#[test]
+fn parse_entity_3() {
+    // disabled by default
+    let res = Document::from_str("<svg xmlns='http://www.w3.org/2000/svg' fill='&c;'/>");
+    assert!(res.is_err());
+}
+
+#[test]
+fn parse_duplicated_attributes_1() {
+    use svgdom::DuplicateAttributes;
+
+    let text = "<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect fill='red' x='5' fill = 'blue' stroke='black' fill='green'/>
+</svg>";
+
+    // XML doesn't allow duplicated attributes.
+    assert!(Document::from_str(text).is_err());
+
+    let mut opt = ParseOptions::default();
+    opt.duplicate_attributes = DuplicateAttributes::KeepFirst;
+    let doc = Document::from_str_with_opt(text, &opt).unwrap();
+    let rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    assert_eq!(rect.attributes().len(), 3);
+    assert_eq!(rect.attributes().iter().filter(|a| a.has_id(AId::Fill)).count(), 1);
+    assert_eq!(rect.attributes().get_value(AId::Fill).unwrap().to_string(), "#ff0000");
+
+    opt.duplicate_attributes = DuplicateAttributes::KeepLast;
+    let doc = Document::from_str_with_opt(text, &opt).unwrap();
+    let rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    assert_eq!(rect.attributes().len(), 3);
+    assert_eq!(rect.attributes().get_value(AId::Fill).unwrap().to_string(), "#008000");
+    assert_eq!(rect.attributes().get_value(AId::Stroke).unwrap().to_string(), "#000000");
+}
+
+#[test]
+fn parse_duplicated_attributes_2() {
+    use svgdom::DuplicateAttributes;
+
+    let mut text = "<svg xmlns='http://www.w3.org/2000/svg'>
+    <!-- <rect x='1' x='2'/> -->
+    <text x='1'>x='2'</text>
+    <rect".to_string();
+    for i in 0..1000 {
+        text.push_str(&format!(" x='{}'", i));
+    }
+    text.push_str("/>\n    <circle r='1' r='2'/>\n</svg>");
+
+    let mut opt = ParseOptions::default();
+    opt.duplicate_attributes = DuplicateAttributes::KeepLast;
+    let doc = Document::from_str_with_opt(&text, &opt).unwrap();
+    assert_eq!(doc.with_write_opt(&write_options()).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <!-- <rect x='1' x='2'/> -->
+    <text x='1'>x='2'</text>
+    <rect x='999'/>
+    <circle r='2'/>
+</svg>
+");
+}
+
+#[test]
+fn parse_preserve_source_1() {
+    let text = "<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <rect fill='rgb(255, 0, 0)' width='10.50' height='1e1' stroke='url(#lg1)' style='opacity:0.50'/>
+</svg>";
+
+    let mut opt = ParseOptions::default();
+    opt.preserve_source = true;
+    let doc = Document::from_str_with_opt(text, &opt).unwrap();
+
+    let mut wopt = WriteOptions::default();
+    wopt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&wopt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <rect fill='rgb(255, 0, 0)' height='1e1' opacity='0.5' stroke='url(#lg1)' width='10.50'/>
+</svg>
+");
+
+    // A modified value is written as usual.
+    let mut rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    assert_eq!(rect.attributes().get(AId::Width).unwrap().original(), Some("10.50"));
+    if let Some(&mut AttributeValue::Length(ref mut len)) = rect.attributes_mut().get_value_mut(AId::Width) {
+        len.num = 20.0;
+    }
+    assert_eq!(rect.attributes().get(AId::Width).unwrap().original(), None);
+    assert_eq!(rect.attributes().get(AId::Height).unwrap().original(), Some("1e1"));
+    assert_eq!(rect.attributes().get(AId::Opacity).unwrap().original(), None);
+
+    // Disabled by default.
+    let doc = Document::from_str(text).unwrap();
+    assert_eq!(doc.with_write_opt(&wopt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <rect fill='#ff0000' height='10' opacity='0.5' stroke='url(#lg1)' width='10.5'/>
+</svg>
+");
+}
+
+#[test]
+fn parse_track_positions_1() {
+    let text = "<svg xmlns='http://www.w3.org/2000/svg'>
+    <!--комментарий--><rect/>
+  <text>Text</text>
+</svg>";
+
+    let mut opt = ParseOptions::default();
+    opt.track_positions = true;
+    let doc = Document::from_str_with_opt(text, &opt).unwrap();
+
+    let positions: Vec<(u32, u32)> = doc.root().descendants().skip(1)
+        .map(|n| n.source_pos().unwrap())
+        .map(|p| (p.row, p.col))
+        .collect();
+    assert_eq!(positions, vec![(1, 1), (2, 5), (2, 23), (3, 3), (3, 9)]);
+
+    assert_eq!(doc.root().source_pos(), None);
+
+    // Disabled by default.
+    let doc = Document::from_str(text).unwrap();
+    assert_eq!(doc.svg_element().unwrap().source_pos(), None);
+
+    // Fragments.
+    let doc = Document::from_fragment_str_with_opt("<rect/>\n<circle/>", &opt).unwrap();
+    let positions: Vec<(u32, u32)> = doc.root().children()
+        .filter(|n| n.is_element())
+        .map(|n| n.source_pos().unwrap())
+        .map(|p| (p.row, p.col))
+        .collect();
+    assert_eq!(positions, vec![(1, 1), (2, 1)]);
+}
+
+#[test]
+fn parse_track_positions_2() {
+    // nodes from entities are located in the DTD
+    let text = "<!DOCTYPE svg [<!ENTITY r \"<rect/>\">]>
+<svg xmlns='http://www.w3.org/2000/svg'>&r;<circle/></svg>";
+
+    let mut opt = ParseOptions::default();
+    opt.track_positions = true;
+    let doc = Document::from_str_with_opt(text, &opt).unwrap();
+
+    let positions: Vec<(u32, u32)> = doc.svg_element().unwrap().descendants()
+        .map(|n| n.source_pos().unwrap())
+        .map(|p| (p.row, p.col))
+        .collect();
+    assert_eq!(positions, vec![(2, 1), (1, 28), (2, 44)]);
+}
@@ -0,0 +1,339 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[macro_use] extern crate pretty_assertions;
+
+extern crate svgdom;
+
+use std::str::FromStr;
+
+use svgdom::{
+    Path,
+    PathExt,
+    PathSegment,
+    PathTokens,
+};
+
+macro_rules! test_path {
+    ($name:ident, $method:ident, $in_text:expr, $out_text:expr) => (
+        #[test]
+        fn $name() {
+            let mut path = Path::from_str($in_text).unwrap();
+            path.$method();
+            assert_eq!(path.to_string(), $out_text);
+        }
+    )
+}
+
+// negative radii
+test_path!(fix_arcs_1, fix_arcs,
+    "M 10 20 A -5 -10 0 0 1 15 30",
+    "M 10 20 A 5 10 0 0 1 15 30");
+
+// zero radius
+test_path!(fix_arcs_2, fix_arcs,
+    "M 10 20 A 0 10 0 0 1 15 30 a 10 0 0 0 1 5 5",
+    "M 10 20 L 15 30 l 5 5");
+
+// coincident endpoints
+test_path!(fix_arcs_3, fix_arcs,
+    "M 10 20 A 5 10 0 0 1 10 20 a 5 10 0 0 1 0 0 L 30 40",
+    "M 10 20 L 30 40");
+
+// radii are too small
+test_path!(fix_arcs_4, fix_arcs,
+    "M 0 0 A 1 1 0 0 1 10 0",
+    "M 0 0 A 5 5 0 0 1 10 0");
+
+// radii are big enough
+test_path!(fix_arcs_5, fix_arcs,
+    "M 0 0 A 10 10 0 0 1 10 0",
+    "M 0 0 A 10 10 0 0 1 10 0");
+
+// relative arc after ClosePath
+test_path!(fix_arcs_6, fix_arcs,
+    "M 10 10 L 20 20 Z a 1 1 0 0 1 0 0",
+    "M 10 10 L 20 20 Z");
+
+macro_rules! test_simplify {
+    ($name:ident, $tolerance:expr, $in_text:expr, $out_text:expr) => (
+        #[test]
+        fn $name() {
+            let mut path = Path::from_str($in_text).unwrap();
+            path.simplify($tolerance);
+            assert_eq!(path.to_string(), $out_text);
+        }
+    )
+}
+
+// zero-length lines
+test_simplify!(simplify_1, 0.001,
+    "M 10 10 L 10 10 L 20 20 h 0 v 0 L 30 10",
+    "M 10 10 L 20 20 L 30 10");
+
+// collinear lines
+test_simplify!(simplify_2, 0.001,
+    "M 10 10 L 20 20 L 30 30 l 10 10",
+    "M 10 10 L 40 40");
+
+// relative lines
+test_simplify!(simplify_3, 0.001,
+    "m 10 10 l 10 0 l 10 0 h 10 l 0 10",
+    "m 10 10 l 30 0 l 0 10");
+
+// horizontal and vertical lines
+test_simplify!(simplify_4, 0.001,
+    "M 10 10 H 20 H 30 V 20 V 30",
+    "M 10 10 H 30 V 30");
+
+// lines in the opposite direction should not be merged
+test_simplify!(simplify_5, 0.001,
+    "M 10 10 L 30 10 L 20 10",
+    "M 10 10 L 30 10 L 20 10");
+
+// subpaths are preserved
+test_simplify!(simplify_6, 0.001,
+    "M 10 10 L 20 10 Z M 20 10 L 30 10 L 40 10",
+    "M 10 10 L 20 10 Z M 20 10 L 40 10");
+
+// a single zero-length line is preserved
+test_simplify!(simplify_7, 0.001,
+    "M 10 10 L 10 10 M 20 20 L 20 20 Z",
+    "M 10 10 L 10 10 M 20 20 L 20 20 Z");
+
+// near-duplicate points
+test_simplify!(simplify_8, 0.1,
+    "M 10 10 L 10.05 10 L 20 20 L 30 29.95 L 40 40",
+    "M 10 10 L 40 40");
+
+// curves are not affected
+test_simplify!(simplify_9, 0.001,
+    "M 10 10 L 20 10 C 30 10 40 20 50 50 L 60 60",
+    "M 10 10 L 20 10 C 30 10 40 20 50 50 L 60 60");
+
+#[test]
+fn simplify_10() {
+    // dropped short lines must not shift the following relative segments
+    let mut text = "M 0 0".to_string();
+    for _ in 0..100 {
+        text.push_str(" l 0.5 0.5");
+    }
+    text.push_str(" l 0 10");
+
+    let mut path = Path::from_str(&text).unwrap();
+    path.simplify(1.0);
+    assert_eq!(path.to_string(), "M 0 0 l 50 50 l 0 10");
+}
+
+#[test]
+fn simplify_11() {
+    // the error must not accumulate along a long run of merged lines
+    let points: Vec<(f64, f64)> = (0..200).map(|i| {
+        let a = i as f64 * 0.005;
+        (1000.0 * a.cos(), 1000.0 * a.sin())
+    }).collect();
+
+    let mut path = Path::new();
+    path.push(PathSegment::MoveTo { abs: true, x: points[0].0, y: points[0].1 });
+    for p in &points[1..] {
+        path.push(PathSegment::LineTo { abs: true, x: p.0, y: p.1 });
+    }
+
+    path.simplify(0.05);
+    assert!(path.len() > 2);
+
+    let mut vertices = Vec::new();
+    for seg in path.iter() {
+        match *seg {
+            PathSegment::MoveTo { x, y, .. } | PathSegment::LineTo { x, y, .. } => vertices.push((x, y)),
+            _ => unreachable!(),
+        }
+    }
+
+    for p in &points {
+        let d = vertices.windows(2).map(|v| distance_to_segment(*p, v[0], v[1]))
+                        .fold(::std::f64::INFINITY, f64::min);
+        assert!(d <= 0.05 + 1e-9, "{:?} is {} away", p, d);
+    }
+}
+
+fn distance_to_segment(p: (f64, f64), p1: (f64, f64), p2: (f64, f64)) -> f64 {
+    let (dx, dy) = (p2.0 - p1.0, p2.1 - p1.1);
+    let t = (((p.0 - p1.0) * dx + (p.1 - p1.1) * dy) / (dx * dx + dy * dy)).max(0.0).min(1.0);
+    let (x, y) = (p1.0 + t * dx - p.0, p1.1 + t * dy - p.1);
+    (x * x + y * y).sqrt()
+}
+
+// lines before smooth curves are preserved
+test_simplify!(simplify_12, 0.1,
+    "M 10 10 C 20 0 30 0 40 10 L 40.05 10 S 60 20 70 10 L 70 10.05 T 90 10",
+    "M 10 10 C 20 0 30 0 40 10 L 40.05 10 S 60 20 70 10 L 70 10.05 T 90 10");
+
+#[test]
+fn reduce_points_1() {
+    let mut path = Path::from_str("M 0 0 L 1 0.01 L 2 -0.01 L 3 0.02 L 4 0 L 4 10 Z").unwrap();
+    assert_eq!(path.reduce_points(0.1), 3);
+    assert_eq!(path.to_string(), "M 0 0 L 4 0 L 4 10 Z");
+}
+
+#[test]
+fn reduce_points_2() {
+    // relative lines
+    let mut path = Path::from_str("m 10 10 l 1 0.01 l 1 -0.02 l 1 0.01 h 10 v 10").unwrap();
+    assert_eq!(path.reduce_points(0.1), 3);
+    assert_eq!(path.to_string(), "m 10 10 l 13 0 v 10");
+}
+
+#[test]
+fn reduce_points_3() {
+    // curves split line sequences
+    let mut path = Path::from_str("M 0 0 L 1 0 L 2 0 C 3 1 4 1 5 0 L 6 0 L 7 0").unwrap();
+    assert_eq!(path.reduce_points(0.1), 2);
+    assert_eq!(path.to_string(), "M 0 0 L 2 0 C 3 1 4 1 5 0 L 7 0");
+}
+
+#[test]
+fn reduce_points_4() {
+    // nothing to reduce
+    let mut path = Path::from_str("M 0 0 L 10 0 L 10 10 L 0 10 Z").unwrap();
+    assert_eq!(path.reduce_points(0.1), 0);
+    assert_eq!(path.to_string(), "M 0 0 L 10 0 L 10 10 L 0 10 Z");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn optimize_paths_1() {
+    use svgdom::{AttributeId as AId, Document, ElementId as EId, WriteOptions, postproc};
+
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <path d='M 100 100 C 100 100 110 90 120 100 C 130 110 140 90 150 100 Q 160 110 170 100 \
+             Q 180 90 190 100 L 190 150 Z M 10 10 L 20 20'/>
+</svg>").unwrap();
+
+    let opt = WriteOptions::default();
+    let old_len = doc.to_string().len();
+    let saved = postproc::optimize_paths(&mut doc, &opt);
+    assert_eq!(old_len - doc.to_string().len(), saved);
+
+    let path = doc.root().descendants().find(|n| n.is_tag_name(EId::Path)).unwrap();
+    assert_eq!(path.attributes().get_value(AId::D).unwrap().to_string(),
+               "M 100 100 s 10 -10 20 0 s 20 -10 30 0 q 10 10 20 0 t 20 0 v 50 Z M 10 10 L 20 20");
+
+    // The path is already the shortest one.
+    let saved = postproc::optimize_paths(&mut doc, &opt);
+    assert_eq!(saved, 0);
+}
+
+#[test]
+fn transform_1() {
+    use svgdom::Transform;
+
+    // Swaps x and y, which mirrors the path.
+    let mut path = Path::from_str("M 10 20 v 10 A 10 5 0 1 0 30 40 z").unwrap();
+    path.transform(&Transform::new(0.0, 1.0, 1.0, 0.0, 0.0, 0.0));
+    assert_eq!(path.to_string(), "M 20 10 L 30 10 A 10 5 90 1 1 40 30 Z");
+}
+
+#[test]
+fn is_degenerate_1() {
+    assert!(Path::new().is_degenerate());
+    assert!(Path::from_str("M 10 20").unwrap().is_degenerate());
+    assert!(Path::from_str("M 10 20 C 10 20 10 20 10 20 m 0 0 h 0 z").unwrap().is_degenerate());
+    assert!(!Path::from_str("M 10 20 C 10 30 10 20 10 20").unwrap().is_degenerate());
+}
+
+#[test]
+fn remove_degenerate_subpaths_1() {
+    use svgdom::PathSegment;
+
+    let mut path = Path::from_str("M 10 20 L 20 20 m 5 5 l 0 0 z m 5 5 l 10 10").unwrap();
+    assert_eq!(path.remove_degenerate_subpaths(), vec![1]);
+    assert_eq!(path.to_string(), "M 10 20 L 20 20 m 10 10 l 10 10");
+
+    let mut path = Path::from_str("M 10 20 L 20 20 M 10 10 L 20 20").unwrap();
+    path[1] = PathSegment::LineTo { abs: true, x: ::std::f64::NAN, y: 20.0 };
+    assert_eq!(path.remove_degenerate_subpaths(), vec![0]);
+    assert_eq!(path.to_string(), "M 10 10 L 20 20");
+    assert!(!path.is_degenerate());
+
+    path[1] = PathSegment::LineTo { abs: true, x: ::std::f64::INFINITY, y: 20.0 };
+    assert!(path.is_degenerate());
+}
+
+test_path!(to_canonical_1, to_canonical,
+    "M 10 20 h 10 v 10 H 0 V 0 l 5 5 z m 10 10 L 20 20",
+    "M 10 20 L 20 20 L 20 30 L 0 30 L 0 0 L 5 5 Z M 20 30 L 20 20"
+);
+
+test_path!(to_canonical_2, to_canonical,
+    "M 0 0 C 0 10 10 10 10 0 s 10 -10 10 0 L 30 0 S 40 10 50 0",
+    "M 0 0 C 0 10 10 10 10 0 C 10 -10 20 -10 20 0 L 30 0 C 30 0 40 10 50 0"
+);
+
+test_path!(to_canonical_3, to_canonical,
+    "M 0 0 Q 30 30 60 0 t 60 0 L 150 0 T 180 0",
+    "M 0 0 C 20 20 40 20 60 0 C 80 -20 100 -20 120 0 L 150 0 C 150 0 160 0 180 0"
+);
+
+test_path!(to_canonical_4, to_canonical,
+    "M 0 0 A 10 10 0 0 1 20 0 A 10 10 0 0 1 0 0",
+    "M 0 0 C 0 -5.52284749831 4.47715250169 -10 10 -10 \
+     C 15.52284749831 -10 20 -5.52284749831 20 0 \
+     C 20 5.52284749831 15.52284749831 10 10 10 \
+     C 4.47715250169 10 0 5.52284749831 0 0"
+);
+
+// Large arc and radii scaling.
+test_path!(to_canonical_5, to_canonical,
+    "M 0 0 A 10 10 0 1 0 0 20 a 1 1 0 0 0 20 0",
+    "M 0 0 C -5.52284749831 0 -10 4.47715250169 -10 10 \
+     C -10 15.52284749831 -5.52284749831 20 0 20 \
+     C 0 25.52284749831 4.47715250169 30 10 30 \
+     C 15.52284749831 30 20 25.52284749831 20 20"
+);
+
+// Degenerate arcs.
+test_path!(to_canonical_6, to_canonical,
+    "M 10 20 A 0 5 0 0 1 30 40 A 5 5 0 0 1 30 40 Z",
+    "M 10 20 L 30 40 Z"
+);
+
+#[test]
+fn to_canonical_7() {
+    use svgdom::PathSegment;
+
+    // A large rotated arc.
+    let path = Path::from_str("M 0 0 A 20 10 45 1 1 10 10").unwrap();
+    let mut canonical = path.clone();
+    canonical.to_canonical();
+    assert_eq!(path.len(), 2);
+    assert_eq!(canonical.len(), 5);
+
+    // Arcs can't be represented exactly, but the end point must be preserved.
+    match canonical[4] {
+        PathSegment::CurveTo { abs, x, y, .. } => {
+            assert!(abs);
+            assert_eq!((x, y), (10.0, 10.0));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn tokens_1() {
+    let text = "M 10 20 L 30 40 Q 50 60 70 80 Z";
+    let segments: Vec<PathSegment> = PathTokens::new(text).map(|s| s.unwrap()).collect();
+    assert_eq!(segments, Path::from_str(text).unwrap().0);
+
+    // Valid segments before an error are still yielded.
+    let mut tokens = PathTokens::new("M 10 20 L 30 x");
+    assert_eq!(tokens.next().unwrap().unwrap(), PathSegment::MoveTo { abs: true, x: 10.0, y: 20.0 });
+    assert!(tokens.next().unwrap().is_err());
+    assert!(tokens.next().is_none());
+}
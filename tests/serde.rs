@@ -0,0 +1,68 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(all(feature = "serde", feature = "parsing"))]
+
+extern crate serde_json;
+extern crate svgdom;
+
+use svgdom::{
+    AttributeId as AId,
+    Document,
+    ElementId as EId,
+};
+
+#[test]
+fn round_trip_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink' \
+      viewBox='0 0 10 20' preserveAspectRatio='xMinYMax slice'>
+    <!--comment-->
+    <?target data?>
+    <linearGradient id='lg1'>
+        <stop offset='0.5' stop-color='#ff0000'/>
+    </linearGradient>
+    <linearGradient id='lg2' xlink:href='#lg1' gradientTransform='matrix(1 0 0 1 10 20)'/>
+    <path d='M 10 20 L 30 40 A 5 5 0 1 0 10 10 Z' fill='url(#lg2) none' stroke-dasharray='1 2mm'
+          custom='value'/>
+    <text rotate='10 20' x='5%'>Text</text>
+</svg>").unwrap();
+
+    let json = serde_json::to_string(&doc).unwrap();
+    let doc2: Document = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(doc.to_string(), doc2.to_string());
+
+    let lg1 = doc2.root().descendants().find(|n| n.is_tag_name(EId::LinearGradient)).unwrap();
+    assert_eq!(lg1.uses_count(), 1);
+}
+
+#[test]
+fn node_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect id='rect1' width='10'/>
+</svg>").unwrap();
+
+    let rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+    assert_eq!(serde_json::to_string(&rect).unwrap(),
+        "{\"element\":{\"tag\":{\"id\":\"rect\"},\"id\":\"rect1\",\"attributes\":\
+         [{\"name\":{\"id\":\"width\"},\"value\":{\"Length\":[10.0,\"None\"]}}],\"children\":[]}}");
+
+    let attrs = rect.attributes();
+    assert_eq!(serde_json::to_string(attrs.get(AId::Width).unwrap()).unwrap(),
+        "{\"name\":{\"id\":\"width\"},\"value\":{\"Length\":[10.0,\"None\"]}}");
+}
+
+#[test]
+fn unresolved_link_1() {
+    let json = "[{\"element\":{\"tag\":{\"id\":\"use\"},\"id\":\"\",\"attributes\":\
+                [{\"name\":{\"id\":\"href\"},\"value\":{\"Link\":\"rect1\"}}],\"children\":[]}}]";
+    let res: Result<Document, _> = serde_json::from_str(json);
+    assert!(res.is_err());
+}
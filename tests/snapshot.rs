@@ -0,0 +1,133 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "parsing")]
+
+extern crate svgdom;
+
+use std::sync::Arc;
+use std::thread;
+
+use svgdom::{
+    AttributeId as AId,
+    Document,
+    ElementId as EId,
+    FlatValue,
+    WriteBuffer,
+    NodeType,
+    QName,
+    Snapshot,
+};
+
+fn is_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn send_sync_1() {
+    is_send_sync::<Snapshot>();
+}
+
+#[test]
+fn structure_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <g id='g1'>
+        <rect/>
+    </g>
+    <text>Text</text>
+</svg>").unwrap();
+
+    let s = doc.snapshot();
+    assert_eq!(s.len(), 6);
+    assert_eq!(s.root().node_type, NodeType::Root);
+    assert_eq!(s.root().children, vec![1]);
+    assert_eq!(s[1].tag_name, QName::Id(EId::Svg));
+    assert_eq!(s[1].children, vec![2, 4]);
+    assert_eq!(s[2].id, "g1");
+    assert_eq!(s[3].parent, Some(2));
+    assert_eq!(s[5].node_type, NodeType::Text);
+    assert_eq!(s[5].text, "Text");
+    assert_eq!(s.index_by_id("g1"), Some(2));
+}
+
+#[test]
+fn links_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#lg1)'/>
+</svg>").unwrap();
+
+    let s = doc.snapshot();
+    assert_eq!(s[3].attributes[0].name, QName::Id(AId::Fill));
+    assert_eq!(s[3].attributes[0].value, FlatValue::Paint(2, None));
+}
+
+#[test]
+fn threads_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect/>
+    <rect/>
+    <circle/>
+</svg>").unwrap();
+
+    let s = Arc::new(doc.snapshot());
+
+    let handles: Vec<_> = [EId::Rect, EId::Circle].iter().map(|id| {
+        let s = s.clone();
+        let id = *id;
+        thread::spawn(move || {
+            s.nodes().iter().filter(|n| n.tag_name == QName::Id(id)).count()
+        })
+    }).collect();
+
+    let counts: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(counts, vec![2, 1]);
+}
+
+#[test]
+fn flat_round_trip_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <!--comment-->
+    <linearGradient id='lg1'/>
+    <rect id='rect1' fill='url(#lg1)' stroke='#ff0000'/>
+    <use xlink:href='#rect1'/>
+    <text>Text</text>
+</svg>").unwrap();
+
+    let flat = doc.to_flat();
+    assert_eq!(flat.root().node_type, NodeType::Root);
+
+    let doc2 = Document::from_flat(&flat).unwrap();
+    assert_eq!(doc.to_string(), doc2.to_string());
+    assert_eq!(doc2.svg_element().unwrap().children().nth(1).unwrap().uses_count(), 1);
+    assert_eq!(flat, doc2.to_flat());
+}
+
+#[test]
+fn flat_round_trip_2() {
+    let doc = Document::new();
+    let flat = doc.to_flat();
+    assert_eq!(flat.len(), 1);
+
+    let doc2 = Document::from_flat(&flat).unwrap();
+    assert!(!doc2.root().has_children());
+    assert_eq!(doc2.with_write_opt(&Default::default()).to_string(), "");
+}
+
+#[test]
+fn flat_invalid_root_1() {
+    use svgdom::Error;
+
+    let doc = Document::from_str("<svg xmlns='http://www.w3.org/2000/svg'/>").unwrap();
+
+    let mut flat = doc.to_flat();
+    flat.nodes.swap(0, 1);
+    assert_eq!(Document::from_flat(&flat).err(), Some(Error::InvalidNodeType));
+}
@@ -0,0 +1,61 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "testing")]
+
+#[macro_use]
+extern crate svgdom;
+
+use svgdom::{
+    testing,
+    Document,
+    WriteBuffer,
+    WriteOptions,
+};
+
+#[test]
+fn round_trip_1() {
+    testing::assert_round_trip(
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#lg1)' transform='translate(10 20)' style='stroke:red'/>
+    <text>Text</text>
+</svg>");
+}
+
+#[test]
+fn equivalent_1() {
+    let doc1 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='10.0' fill='#ff0000'/>
+</svg>").unwrap();
+
+    let doc2 = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect width='10' fill='red'/>
+</svg>").unwrap();
+
+    assert!(testing::is_equivalent(&doc1, &doc2));
+    testing::assert_equivalent(&doc1, &doc2);
+}
+
+#[test]
+#[should_panic]
+fn equivalent_2() {
+    let doc1 = Document::from_str("<svg xmlns='http://www.w3.org/2000/svg'><rect/></svg>").unwrap();
+    let doc2 = Document::from_str("<svg xmlns='http://www.w3.org/2000/svg'><circle/></svg>").unwrap();
+    testing::assert_equivalent(&doc1, &doc2);
+}
+
+#[test]
+fn eq_text_1() {
+    let doc = Document::from_str("<svg xmlns='http://www.w3.org/2000/svg'/>").unwrap();
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq_text!(doc.with_write_opt(&opt).to_string(), "<svg xmlns='http://www.w3.org/2000/svg'/>\n");
+}
@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![cfg(feature = "parsing")]
+
 #[macro_use] extern crate pretty_assertions;
 
 extern crate svgdom;
@@ -16,6 +18,7 @@ use svgdom::{
     Document,
     ElementId as EId,
     NodeType,
+    ParseOptions,
     WriteOptions,
     WriteBuffer,
 };
@@ -144,6 +147,21 @@ fn text_content_5() {
     assert_eq!(text, "'Text'");
 }
 
+#[test]
+fn text_content_6() {
+    let mut opt = ParseOptions::default();
+    opt.preserve_whitespace_elements.insert(EId::TextPath);
+
+    let doc = Document::from_str_with_opt(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text>  Text  <textPath xml:space='default'>  a  <tspan>  b  </tspan></textPath></text>
+</svg>
+", &opt).unwrap();
+
+    let text: String = doc.root().descendants().map(|n| n.text().to_owned()).collect();
+    assert_eq!(text, "Text   a    b  ");
+}
+
 // Manually created text.
 #[test]
 fn text_1() {
@@ -499,3 +517,122 @@ test_resave!(text_space_preserve_7,
     <text id='text2'>Text</text>
 </svg>
 ");
+
+test_resave!(foreign_object_1,
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <foreignObject>
+        <p xmlns='http://www.w3.org/1999/xhtml' class='note'>  Some   <b>bold</b>  text  </p>
+    </foreignObject>
+    <text>  Some   text  </text>
+</svg>
+",
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <foreignObject>
+        <p xmlns='http://www.w3.org/1999/xhtml' class='note'>  Some   <b>bold</b>  text  </p>
+    </foreignObject>
+    <text>Some text</text>
+</svg>
+");
+
+// SVG inside the foreign content still follows the SVG rules.
+test_resave!(foreign_object_2,
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <foreignObject>
+        <div xmlns='http://www.w3.org/1999/xhtml'>
+            <svg xmlns='http://www.w3.org/2000/svg'><text>  Text  </text></svg>
+        </div>
+    </foreignObject>
+</svg>
+",
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <foreignObject>
+        <div xmlns='http://www.w3.org/1999/xhtml'>
+            <svg xmlns='http://www.w3.org/2000/svg'><text>Text</text></svg>
+        </div>
+    </foreignObject>
+</svg>
+");
+
+#[test]
+fn foreign_object_3() {
+    let mut parse_opt = ParseOptions::default();
+    parse_opt.keep_foreign_content = false;
+    let doc = Document::from_str_with_opt(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <foreignObject>
+        <p xmlns='http://www.w3.org/1999/xhtml'>Text</p>
+    </foreignObject>
+</svg>
+", &parse_opt).unwrap();
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+
+    assert_eq!(TStr(&doc.with_write_opt(&opt).to_string()), TStr(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <foreignObject/>
+</svg>
+"));
+}
+
+#[test]
+fn split_text_1() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text>Text <tspan>ёжик</tspan></text>
+</svg>").unwrap();
+
+    let tspan = doc.root().descendants().find(|n| n.is_tag_name(EId::Tspan)).unwrap();
+    let text = tspan.first_child().unwrap();
+
+    let end = doc.split_text(text.clone(), 4);
+    assert_eq!(*end.text(), "");
+    let start = doc.split_text(text.clone(), 0);
+    assert_eq!(*text.text(), "");
+    let mid = doc.split_text(start.clone(), 1);
+
+    let parts: Vec<String> = tspan.children().map(|n| n.text().clone()).collect();
+    assert_eq!(parts, vec!["", "ё", "жик", ""]);
+    assert_eq!(mid.parent(), Some(tspan));
+}
+
+#[test]
+#[should_panic]
+fn split_text_2() {
+    let mut doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text>ёжик</text>
+</svg>").unwrap();
+
+    let text = doc.root().descendants().find(|n| n.node_type() == NodeType::Text).unwrap();
+    doc.split_text(text, 5);
+}
+
+#[test]
+fn normalize_1() {
+    let text =
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text>Text <tspan>ёжик</tspan> text</text>
+    <text xml:space='preserve'>  Text  </text>
+</svg>";
+
+    let mut doc = Document::from_str(text).unwrap();
+    let orig = doc.to_string();
+
+    // Nothing to merge yet.
+    let root = doc.root();
+    doc.normalize(root.clone());
+    assert_eq!(doc.to_string(), orig);
+
+    let texts: Vec<_> = doc.root().descendants().filter(|n| n.node_type() == NodeType::Text).collect();
+    for text in texts {
+        let start = doc.split_text(text.clone(), 0);
+        doc.split_text(start, 2);
+        let empty = doc.create_node(NodeType::Text, "");
+        text.clone().insert_before(empty);
+    }
+
+    doc.normalize(root);
+    assert_eq!(doc.to_string(), orig);
+    assert_eq!(doc.root().descendants().filter(|n| n.node_type() == NodeType::Text).count(), 4);
+}
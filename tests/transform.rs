@@ -0,0 +1,139 @@
+// Copyright 2018 Evgeniy Reizner
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate svgdom;
+
+use std::str::FromStr;
+
+use svgdom::{
+    FuzzyEq,
+    Transform,
+    TransformExt,
+    TransformItem,
+    TransformList,
+    TransformTokens,
+};
+
+fn approx_eq(ts1: &Transform, ts2: &Transform) -> bool {
+    let nums1 = [ts1.a, ts1.b, ts1.c, ts1.d, ts1.e, ts1.f];
+    let nums2 = [ts2.a, ts2.b, ts2.c, ts2.d, ts2.e, ts2.f];
+    nums1.iter().zip(nums2.iter()).all(|(n1, n2)| (n1 - n2).abs() < 1e-9)
+}
+
+macro_rules! test_resave {
+    ($name:ident, $in_text:expr, $out_text:expr) => (
+        #[test]
+        fn $name() {
+            let list = TransformList::from_str($in_text).unwrap();
+            assert_eq!(list.to_string(), $out_text);
+        }
+    )
+}
+
+test_resave!(resave_1, "translate(10, 20)", "translate(10 20)");
+test_resave!(resave_2, "translate(10) scale(2) scale(2, 3)", "translate(10) scale(2) scale(2 3)");
+test_resave!(resave_3, "rotate(30) rotate(30, 10, 20)", "rotate(30) rotate(30 10 20)");
+test_resave!(resave_4, "skewX(10)skewY(20)", "skewX(10) skewY(20)");
+test_resave!(resave_5, "matrix(1 0 0 1 10 20)", "matrix(1 0 0 1 10 20)");
+test_resave!(resave_6, "", "");
+
+#[test]
+fn to_transform_1() {
+    let list = TransformList::from_str("translate(10 20) scale(2)").unwrap();
+    assert!(list.to_transform().fuzzy_eq(&Transform::new(2.0, 0.0, 0.0, 2.0, 10.0, 20.0)));
+}
+
+#[test]
+fn to_transform_2() {
+    let list = TransformList::from_str("rotate(90 10 10)").unwrap();
+    assert!(approx_eq(&list.to_transform(), &Transform::new(0.0, 1.0, -1.0, 0.0, 20.0, 0.0)));
+}
+
+#[test]
+fn as_list_1() {
+    let ts = Transform::new(0.0, 1.0, -1.0, 0.0, 0.0, 0.0);
+    let list = ts.as_list();
+    assert_eq!(list.len(), 1);
+    match list[0] {
+        TransformItem::Rotate { angle, .. } => assert!(angle.fuzzy_eq(&90.0)),
+        _ => panic!("invalid item"),
+    }
+}
+
+#[test]
+fn as_list_2() {
+    let list = TransformList::from_str("translate(10 20) rotate(30) skewX(15) scale(2 3)").unwrap();
+    let ts = list.to_transform();
+    assert!(approx_eq(&ts.as_list().to_transform(), &ts));
+    assert_eq!(ts.as_list().len(), 4);
+}
+
+#[test]
+fn as_list_3() {
+    // A degenerate matrix.
+    let ts = Transform::new(0.0, 0.0, 0.0, 0.0, 10.0, 20.0);
+    assert_eq!(ts.as_list(), TransformList::from(ts));
+}
+
+#[test]
+fn as_list_4() {
+    assert!(Transform::default().as_list().is_empty());
+}
+
+#[test]
+fn multiply_1() {
+    // Same as `transform="translate(10 20) rotate(90)"`.
+    let list = TransformList::from_str("translate(10 20) rotate(90)").unwrap();
+    let ts = Transform::new_translate(10.0, 20.0).multiply(&Transform::new_rotate(90.0));
+    assert!(approx_eq(&ts, &list.to_transform()));
+
+    let (x, y) = ts.apply(1.0, 0.0);
+    assert!((x - 10.0).abs() < 1e-9 && (y - 21.0).abs() < 1e-9);
+}
+
+#[test]
+fn rotate_around_1() {
+    let expanded = Transform::new_translate(10.0, 20.0)
+        .multiply(&Transform::new_rotate(30.0))
+        .multiply(&Transform::new_translate(-10.0, -20.0));
+    assert!(approx_eq(&Transform::rotate_around(30.0, 10.0, 20.0), &expanded));
+
+    let list = TransformList::from_str("rotate(30 10 20)").unwrap();
+    assert!(approx_eq(&Transform::rotate_around(30.0, 10.0, 20.0), &list.to_transform()));
+}
+
+#[test]
+fn with_origin_1() {
+    let ts = Transform::new_rotate(-45.0).with_origin(5.0, 15.0);
+    assert!(approx_eq(&ts, &Transform::rotate_around(-45.0, 5.0, 15.0)));
+
+    // Reverse rewrite.
+    assert!(approx_eq(&ts.with_origin(-5.0, -15.0), &Transform::new_rotate(-45.0)));
+}
+
+#[test]
+fn tokens_1() {
+    let text = "translate(10 20) rotate(30) translate(-10 -20) rotate(45, 1, 2) scale(2) translate(5)";
+    let items: Vec<TransformItem> = TransformTokens::new(text).map(|t| t.unwrap()).collect();
+    assert_eq!(items, TransformList::from_str(text).unwrap().0);
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0], TransformItem::Rotate { angle: 30.0, cx: 10.0, cy: 20.0 });
+    assert_eq!(items[3], TransformItem::Translate { tx: 5.0, ty: 0.0 });
+}
+
+#[test]
+fn tokens_2() {
+    // Valid operations before an error are still yielded.
+    let mut tokens = TransformTokens::new("translate(10 20) rotate(30) scale(");
+    assert_eq!(tokens.next().unwrap().unwrap(), TransformItem::Translate { tx: 10.0, ty: 20.0 });
+    assert_eq!(tokens.next().unwrap().unwrap(), TransformItem::Rotate { angle: 30.0, cx: 0.0, cy: 0.0 });
+    assert!(tokens.next().unwrap().is_err());
+    assert!(tokens.next().is_none());
+
+    assert!(TransformList::from_str("translate(10 20) rotate(30) scale(").is_err());
+}
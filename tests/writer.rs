@@ -6,12 +6,16 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+// Most of the tests require parsing.
+#![cfg_attr(not(feature = "parsing"), allow(unused_imports))]
+
 #[macro_use] extern crate pretty_assertions;
 
 extern crate svgdom;
 
 use svgdom::{
     AttributeId as AId,
+    AttributeValue,
     AttributesOrder,
     Color,
     Document,
@@ -20,6 +24,9 @@ use svgdom::{
     Length,
     LengthUnit,
     NodeType,
+    NumberFormat,
+    Path,
+    TextEncoding,
     Transform,
     ViewBox,
     WriteOptions,
@@ -30,6 +37,7 @@ use svgdom::{
 
 macro_rules! test_resave {
     ($name:ident, $in_text:expr, $out_text:expr) => (
+        #[cfg(feature = "parsing")]
         #[test]
         fn $name() {
             let doc = Document::from_str($in_text).unwrap();
@@ -232,6 +240,7 @@ test_resave!(cdata_4,
 </svg>
 ");
 
+#[cfg(feature = "parsing")]
 #[test]
 fn indent_1() {
     // default indent is 4
@@ -253,6 +262,7 @@ fn indent_1() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn indent_2() {
     let doc = Document::from_str(
@@ -275,6 +285,7 @@ fn indent_2() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn indent_3() {
     let doc = Document::from_str(
@@ -297,6 +308,7 @@ fn indent_3() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn indent_4() {
     let doc = Document::from_str(
@@ -314,6 +326,7 @@ fn indent_4() {
 "<svg xmlns='http://www.w3.org/2000/svg'><g><rect/></g></svg>");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn indent_5() {
     let doc = Document::from_str(
@@ -336,6 +349,7 @@ fn indent_5() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn attrs_indent_1() {
     let doc = Document::from_str(
@@ -366,6 +380,7 @@ fn attrs_indent_1() {
 ");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn single_quote_1() {
     let doc = Document::from_str("<svg xmlns='http://www.w3.org/2000/svg' id=\"svg1\"/>").unwrap();
@@ -389,6 +404,7 @@ test_resave!(escape_2,
 ");
 
 // Escape attribute values according to the current quote type.
+#[cfg(feature = "parsing")]
 #[test]
 fn escape_3() {
     let doc = Document::from_str(
@@ -406,6 +422,7 @@ fn escape_3() {
 }
 
 // Escape attribute values according to the current quote type.
+#[cfg(feature = "parsing")]
 #[test]
 fn escape_4() {
     let doc = Document::from_str(
@@ -422,6 +439,7 @@ fn escape_4() {
                "<svg xmlns='http://www.w3.org/2000/svg' font-family='\"Noto Sans\"'/>");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn attrs_order_1() {
     let doc = Document::from_str(
@@ -440,6 +458,7 @@ fn attrs_order_1() {
         "<svg xmlns='http://www.w3.org/2000/svg' id='svg1' fill='#ff0000' height='100' stroke='#0000ff' width='100'/>");
 }
 
+#[cfg(feature = "parsing")]
 #[test]
 fn attrs_order_2() {
     let doc = Document::from_str(
@@ -505,3 +524,289 @@ test_resave!(non_svg_1,
     <rect/>
 </svg>
 ");
+
+#[test]
+fn canonicalize_attribute_prefixes_1() {
+    let mut doc = Document::new();
+    let mut svg = doc.create_element(EId::Svg);
+    doc.root().append(svg.clone());
+
+    let mut a = doc.create_element(EId::A);
+    svg.append(a.clone());
+    a.set_attribute(("svg:target", "_blank"));
+    a.set_attribute(("xlink:title", "Title"));
+    a.set_attribute((AId::Lang, "en"));
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <a lang='en' svg:target='_blank' xlink:title='Title'/>
+</svg>
+");
+
+    opt.canonicalize_attribute_prefixes = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <a xml:lang='en' target='_blank' xlink:title='Title'/>
+</svg>
+");
+}
+
+#[test]
+fn canonicalize_attribute_prefixes_2() {
+    let mut doc = Document::new();
+    let mut svg = doc.create_element(EId::Svg);
+    doc.root().append(svg.clone());
+
+    // The attribute without a prefix is preserved.
+    let mut rect = doc.create_element(EId::Rect);
+    svg.append(rect.clone());
+    rect.set_id("rect1");
+    rect.set_attribute(("svg:fill", "red"));
+    rect.set_attribute((AId::Fill, "blue"));
+    rect.set_attribute(("svg:id", "rect2"));
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    opt.canonicalize_attribute_prefixes = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect id='rect1' fill='blue'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn approx_byte_size_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#lg1)' stroke='#ff0000' width='10.5mm' font-family='\"Noto Sans\"'
+          transform='translate(10 20)'/>
+</svg>").unwrap();
+
+    let rect = doc.root().descendants().find(|n| n.is_tag_name(EId::Rect)).unwrap();
+
+    let mut opt = WriteOptions::default();
+    for &use_single_quote in &[false, true] {
+        opt.use_single_quote = use_single_quote;
+        for attr in rect.attributes().iter() {
+            let len = attr.value.with_write_opt(&opt).to_string().len();
+            assert_eq!(attr.value.approx_byte_size(&opt), len);
+        }
+    }
+
+    assert_eq!(AttributeValue::None.approx_byte_size(&opt), 4);
+}
+
+#[test]
+fn number_format_1() {
+    let mut doc = Document::new();
+    let mut svg = doc.create_element(EId::Svg);
+    doc.root().append(svg.clone());
+
+    let mut path = doc.create_element(EId::Path);
+    svg.append(path.clone());
+    let d: Path = "M 0.0000001 1234567890.25 L 0.00000000000123 0.5".parse().unwrap();
+    path.set_attribute((AId::D, d));
+    path.set_attribute((AId::Opacity, 0.0000007));
+    path.set_attribute((AId::Transform, Transform::new(1.0, 0.0, 0.0, 1.0, 1234567.123456, 0.0)));
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <path d='M 0.0000001 1234567890.25 L 0 0.5' opacity='0.0000007' \
+transform='matrix(1 0 0 1 1234567.123456 0)'/>
+</svg>
+");
+
+    opt.number_format = NumberFormat::Fixed(3);
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <path d='M 0 1234567890.25 L 0 0.5' opacity='0' transform='matrix(1 0 0 1 1234567.123 0)'/>
+</svg>
+");
+}
+
+#[cfg(feature = "parsing")]
+#[test]
+fn remove_leading_zero_1() {
+    let doc = Document::from_str(
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <path d='M 0.5 0.5 L -0.5 0.25 1.5 0.5'/>
+    <polyline points='0.5 0.5 1 -0.5'/>
+</svg>").unwrap();
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    opt.values.remove_leading_zero = true;
+    opt.values.use_compact_path_notation = true;
+
+    let text = doc.with_write_opt(&opt).to_string();
+    assert_eq!(text,
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <path d='M.5.5L-.5.25L1.5.5'/>
+    <polyline points='.5 .5 1 -.5'/>
+</svg>
+");
+
+    // The output must be parsed back to the same values.
+    let doc2 = Document::from_str(&text).unwrap();
+    assert_eq!(doc2.to_string(), doc.to_string());
+}
+
+#[test]
+fn text_encoding_1() {
+    let mut doc = Document::new();
+    let mut svg = doc.create_element(EId::Svg);
+    doc.root().append(svg.clone());
+
+    let mut text = doc.create_element(EId::Text);
+    text.set_attribute((AId::FontFamily, "Café"));
+    text.set_attribute(("data-x", "€ ✓"));
+    svg.append(text.clone());
+
+    let mut glyph = doc.create_element(EId::Glyph);
+    glyph.set_attribute((AId::Unicode, "é"));
+    svg.append(glyph.clone());
+
+    text.append(doc.create_node(NodeType::Text, "€ < 𝄞"));
+    svg.append(doc.create_node(NodeType::Comment, "é"));
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text font-family='Café' data-x='€ ✓'>€ &lt; 𝄞</text>
+    <glyph unicode='&#xe9;'/>
+    <!--é-->
+</svg>
+");
+
+    opt.text_encoding = TextEncoding::Ascii;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <text font-family='Caf&#xe9;' data-x='&#x20ac; &#x2713;'>&#x20ac; &lt; &#x1d11e;</text>
+    <glyph unicode='&#xe9;'/>
+    <!--é-->
+</svg>
+");
+}
+
+#[test]
+fn declaration_and_bom_1() {
+    let mut doc = Document::new();
+    let mut svg = doc.create_element(EId::Svg);
+    doc.root().append(svg.clone());
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    opt.write_declaration = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<?xml version='1.0' encoding='UTF-8'?>
+<svg xmlns='http://www.w3.org/2000/svg'/>
+");
+
+    opt.text_encoding = TextEncoding::Ascii;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<?xml version='1.0' encoding='US-ASCII'?>
+<svg xmlns='http://www.w3.org/2000/svg'/>
+");
+
+    // Comments can't be escaped.
+    let comment = doc.create_node(NodeType::Comment, "é");
+    svg.append(comment.clone());
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<?xml version='1.0' encoding='UTF-8'?>
+<svg xmlns='http://www.w3.org/2000/svg'>
+    <!--é-->
+</svg>
+");
+    doc.remove_node(comment);
+
+    // A BOM always implies UTF-8.
+    opt.write_bom = true;
+    let mut buf = Vec::new();
+    doc.write_buf_opt(&opt, &mut buf);
+    assert!(buf.starts_with(b"\xEF\xBB\xBF<?xml version='1.0' encoding='UTF-8'?>"));
+
+    // No BOM by default.
+    let mut buf = Vec::new();
+    doc.write_buf_opt(&WriteOptions::default(), &mut buf);
+    assert!(buf.starts_with(b"<svg"));
+}
+
+#[test]
+fn rotate_around_1() {
+    use svgdom::TransformExt;
+
+    let mut doc = Document::new();
+    let mut svg = doc.create_element(EId::Svg);
+    doc.root().append(svg.clone());
+
+    let mut rect = doc.create_element(EId::Rect);
+    rect.set_attribute((AId::Transform, Transform::rotate_around(30.0, 10.0, 20.0)));
+    svg.append(rect.clone());
+
+    let mut rect = doc.create_element(EId::Rect);
+    rect.set_attribute((AId::Transform, Transform::rotate_around(-90.0, -5.5, 0.0)));
+    svg.append(rect.clone());
+
+    let mut rect = doc.create_element(EId::Rect);
+    rect.set_attribute((AId::Transform, Transform::new(2.0, 0.0, 0.0, 2.0, 10.0, 20.0)));
+    svg.append(rect.clone());
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    opt.values.simplify_transform_matrices = true;
+
+    // Disabled by default.
+    assert!(!doc.with_write_opt(&opt).to_string().contains("rotate"));
+
+    opt.simplify_rotations = true;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg'>
+    <rect transform='rotate(30 10 20)'/>
+    <rect transform='rotate(-90 -5.5 0)'/>
+    <rect transform='matrix(2 0 0 2 10 20)'/>
+</svg>
+");
+}
+
+#[test]
+fn attribute_formatters_1() {
+    fn upper(value: &AttributeValue) -> String {
+        match *value {
+            AttributeValue::String(ref s) => s.to_uppercase(),
+            ref v => v.to_string(),
+        }
+    }
+
+    let mut doc = Document::new();
+    let mut svg = doc.create_element(EId::Svg);
+    svg.set_attribute((AId::Fill, Color::new(255, 0, 0)));
+    svg.set_attribute(("data-a", "a\"b"));
+    svg.set_attribute(("data-b", "  b  "));
+    doc.root().append(svg);
+
+    let mut opt = WriteOptions::default();
+    opt.use_single_quote = true;
+    opt.attribute_formatters.register("fill", upper);
+    opt.attribute_formatters.register("data-a", upper);
+    assert_eq!(format!("{:?}", opt.attribute_formatters), "AttributeFormatters([\"data-a\", \"fill\"])");
+
+    // SVG attributes are not affected.
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns='http://www.w3.org/2000/svg' fill='#ff0000' data-a='A\"B' data-b='  b  '/>
+");
+
+    opt.attribute_formatters.register("data-b", upper);
+    opt.attribute_formatters.remove("data-a");
+    opt.use_single_quote = false;
+    assert_eq!(doc.with_write_opt(&opt).to_string(),
+"<svg xmlns=\"http://www.w3.org/2000/svg\" fill=\"#ff0000\" data-a=\"a&quot;b\" data-b=\"  B  \"/>
+");
+}